@@ -1,4 +1,4 @@
-use std::{collections::HashSet, sync::Arc, time::Duration};
+use std::{sync::Arc, time::Duration};
 
 use axum::{
     extract::{Query, State},
@@ -16,19 +16,23 @@ use tokio::{
 use tracing::{error, info, warn};
 
 use twi_overlay_core::policy::{PolicyEngine, PolicyOutcome};
-use twi_overlay_core::types::{Command, NormalizedEvent, NormalizedReward, NormalizedUser, Patch};
+use twi_overlay_core::types::{
+    Command, CommandSource, NormalizedEvent, NormalizedReward, NormalizedUser, Patch,
+};
 use twi_overlay_storage::{
     Database, HelixBackfillCheckpoint, HelixBackfillError, HelixBackfillStatus, OauthFailure,
     OauthLink, OauthLinkError, QueueError, SettingsError,
 };
 use twi_overlay_twitch::{
-    HelixClient, HelixError, HelixRedemption, HelixRedemptionStatus, ListRedemptionsParams,
+    HelixClient, HelixError, HelixRedemption, HelixRedemptionSort, HelixRedemptionStatus,
+    ListRedemptionsParams,
 };
 
 use crate::command::{
     classify_helix_error, has_required_scopes, CommandExecutor, CommandExecutorError,
     ERR_OAUTH_EXPIRED, ERR_OAUTH_MISSING_SCOPE, ERR_OAUTH_NOT_LINKED, ERR_OAUTH_REAUTH,
 };
+use crate::policy_trace::PolicyTrace;
 use crate::problem::ProblemResponse;
 use crate::router::AppState;
 use crate::sse::{SseError, SseHub};
@@ -64,6 +68,7 @@ impl BackfillService {
             receiver,
             interval,
             page_size,
+            policy_trace: None,
         };
         (Self { sender }, worker)
     }
@@ -110,9 +115,16 @@ pub struct BackfillWorker {
     receiver: mpsc::Receiver<BackfillCommand>,
     interval: Duration,
     page_size: u32,
+    policy_trace: Option<PolicyTrace>,
 }
 
 impl BackfillWorker {
+    /// Records each backfill policy evaluation into `trace`.
+    pub fn with_policy_trace(mut self, trace: PolicyTrace) -> Self {
+        self.policy_trace = Some(trace);
+        self
+    }
+
     pub fn spawn(self) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move { self.run().await })
     }
@@ -254,14 +266,7 @@ impl BackfillWorker {
             .await
             .map_err(BackfillError::Settings)?;
 
-        let target_rewards: HashSet<_> = profile
-            .settings
-            .policy
-            .target_rewards
-            .iter()
-            .cloned()
-            .collect();
-        if target_rewards.is_empty() {
+        if !profile.settings.policy.has_reward_targets() {
             self.update_checkpoint_status(
                 &broadcaster_id,
                 HelixBackfillStatus::Idle,
@@ -274,19 +279,35 @@ impl BackfillWorker {
             return Ok(());
         }
 
+        // Pages are fetched newest first, so everything at or before the previous run's
+        // `last_seen_at` has already been applied and pagination can stop there. The watermark
+        // only advances after a clean run: older redemptions on a page that failed would
+        // otherwise fall behind it and never be fetched again.
+        let previous = self
+            .database
+            .helix_backfill()
+            .fetch(&broadcaster_id)
+            .await
+            .map_err(BackfillError::Checkpoint)?;
+        let known_until = previous
+            .as_ref()
+            .and_then(|checkpoint| checkpoint.last_seen_at);
+        let known_redemption_id = previous.and_then(|checkpoint| checkpoint.last_redemption_id);
+        let mut last_redemption_id = known_redemption_id.clone();
+        let mut last_seen_at = known_until;
+        let mut failed = false;
+
         self.update_checkpoint_status(
             &broadcaster_id,
             HelixBackfillStatus::Running,
             None,
-            None,
-            None,
+            last_redemption_id.clone(),
+            last_seen_at,
             None,
         )
         .await?;
 
         let mut after: Option<String> = None;
-        let mut last_redemption_id: Option<String> = None;
-        let mut last_seen_at: Option<DateTime<Utc>> = None;
         let timezone = profile.timezone;
         let settings = profile.settings;
 
@@ -298,9 +319,11 @@ impl BackfillWorker {
                     &ListRedemptionsParams {
                         broadcaster_id: &broadcaster_id,
                         reward_id: None,
+                        ids: &[],
                         status: HelixRedemptionStatus::Unfulfilled,
                         after: after.as_deref(),
                         first: Some(self.page_size),
+                        sort: Some(HelixRedemptionSort::Newest),
                     },
                 )
                 .await;
@@ -309,14 +332,19 @@ impl BackfillWorker {
                 Ok(page) => page,
                 Err(err) => {
                     let (code, requires_reauth) = classify_helix_error(&err);
-                    self.record_oauth_failure(&link, code, requires_reauth)
-                        .await;
+                    if err.is_retryable() {
+                        // Transient failures say nothing about the link; the next tick retries.
+                        warn!(stage = "oauth", broadcaster = %broadcaster_id, error = %err, "helix backfill page failed transiently");
+                    } else {
+                        self.record_oauth_failure(&link, code, requires_reauth)
+                            .await;
+                    }
                     self.update_checkpoint_status(
                         &broadcaster_id,
                         HelixBackfillStatus::Error,
                         after,
-                        last_redemption_id,
-                        last_seen_at,
+                        known_redemption_id,
+                        known_until,
                         Some(code.to_string()),
                     )
                     .await?;
@@ -325,30 +353,41 @@ impl BackfillWorker {
             };
 
             if page.data.is_empty() {
-                self.update_checkpoint_status(
-                    &broadcaster_id,
-                    HelixBackfillStatus::Idle,
-                    page.cursor.clone(),
-                    last_redemption_id,
-                    last_seen_at,
-                    None,
-                )
-                .await?;
+                after = page.cursor;
                 break;
             }
 
+            let mut reached_known = false;
             for redemption in page.data {
-                if !target_rewards.contains(&redemption.reward.id) {
+                if known_until.is_some_and(|known| redemption.redeemed_at <= known) {
+                    reached_known = true;
+                    break;
+                }
+                if !settings
+                    .policy
+                    .is_reward_enabled(&redemption.reward.id, Some(&redemption.reward.title))
+                {
                     continue;
                 }
+                if redemption.status.is_unknown() {
+                    warn!(
+                        stage = "backfill",
+                        broadcaster_id = %broadcaster_id,
+                        redemption_id = %redemption.id,
+                        status = redemption.status.as_str(),
+                        "unrecognized Helix redemption status"
+                    );
+                }
 
                 match self
                     .apply_redemption(&settings, &timezone, &broadcaster_id, &redemption)
                     .await
                 {
                     RedemptionApply::Processed => {
-                        last_redemption_id = Some(redemption.id.clone());
-                        last_seen_at = Some(redemption.redeemed_at);
+                        if last_seen_at.is_none_or(|seen| redemption.redeemed_at > seen) {
+                            last_redemption_id = Some(redemption.id.clone());
+                            last_seen_at = Some(redemption.redeemed_at);
+                        }
                         self.publish_backfill_event(&broadcaster_id, &redemption, "ok", None);
                     }
                     RedemptionApply::Duplicate => {
@@ -369,6 +408,7 @@ impl BackfillWorker {
                         );
                     }
                     RedemptionApply::Failed(err_code) => {
+                        failed = true;
                         self.publish_backfill_event(
                             &broadcaster_id,
                             &redemption,
@@ -380,20 +420,37 @@ impl BackfillWorker {
             }
 
             after = page.cursor;
+            if reached_known {
+                after = None;
+                break;
+            }
             if after.is_none() {
-                self.update_checkpoint_status(
-                    &broadcaster_id,
-                    HelixBackfillStatus::Idle,
-                    None,
-                    last_redemption_id,
-                    last_seen_at,
-                    None,
-                )
-                .await?;
                 break;
             }
         }
 
+        if failed {
+            self.update_checkpoint_status(
+                &broadcaster_id,
+                HelixBackfillStatus::Error,
+                None,
+                known_redemption_id,
+                known_until,
+                Some("command:failed".to_string()),
+            )
+            .await?;
+        } else {
+            self.update_checkpoint_status(
+                &broadcaster_id,
+                HelixBackfillStatus::Idle,
+                after,
+                last_redemption_id,
+                last_seen_at,
+                None,
+            )
+            .await?;
+        }
+
         Ok(())
     }
 
@@ -421,7 +478,23 @@ impl BackfillWorker {
         };
 
         let issued_at = self.now();
-        let outcome = self.policy.evaluate(settings, &normalized, issued_at);
+        let mut outcome = match self
+            .policy
+            .evaluate(settings, timezone, &normalized, issued_at)
+        {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                error!(stage = "policy", broadcaster = %broadcaster_id, error = %err, "backfill policy evaluation failed");
+                return RedemptionApply::Failed("policy:invalid_timezone");
+            }
+        };
+        for command in &mut outcome.commands {
+            command.set_source(CommandSource::Backfill);
+        }
+
+        if let Some(trace) = &self.policy_trace {
+            trace.record(broadcaster_id, issued_at, &normalized, &outcome);
+        }
 
         if outcome.commands.is_empty() {
             let reason = outcome
@@ -433,9 +506,11 @@ impl BackfillWorker {
 
         self.publish_policy_tap(broadcaster_id, &normalized, &outcome);
 
+        // Held until the patches are broadcast so subscribers see them in version order.
+        let guard = self.command_executor.lock(broadcaster_id).await;
         match self
             .command_executor
-            .execute(broadcaster_id, timezone, &outcome.commands)
+            .execute_locked(&guard, timezone, &outcome.commands)
             .await
         {
             Ok(patches) => {
@@ -454,8 +529,8 @@ impl BackfillWorker {
                 {
                     match self
                         .command_executor
-                        .execute(
-                            broadcaster_id,
+                        .execute_locked(
+                            &guard,
                             timezone,
                             &[Command::RedemptionUpdate(update_command)],
                         )
@@ -807,6 +882,7 @@ mod tests {
     use twi_overlay_core::policy::PolicyEngine;
     use twi_overlay_core::types::{
         Command, CommandSource, EnqueueCommand, NormalizedReward, NormalizedUser,
+        QueueRemovalReason, QueueRemoveCommand,
     };
     use twi_overlay_storage::{
         Database, HelixBackfillCheckpoint, HelixBackfillStatus, NewOauthLink,
@@ -814,9 +890,9 @@ mod tests {
     use twi_overlay_twitch::{HelixClient, TwitchOAuthClient};
     use url::Url;
 
-    use crate::command::{CommandExecutor, ERR_HELIX_UNAUTHORIZED};
+    use crate::command::{CommandExecutor, ERR_HELIX_ERROR, ERR_HELIX_UNAUTHORIZED};
     use crate::router::{app_router, AppState};
-    use crate::sse::SseHub;
+    use crate::sse::{Audience, SseHub};
     use crate::tap::TapHub;
     use crate::telemetry;
 
@@ -905,6 +981,13 @@ mod tests {
                 .await
                 .expect("queue count");
         assert_eq!(queue_count.0, 1);
+        let source: (String,) =
+            sqlx::query_as("SELECT enqueued_source FROM queue_entries WHERE broadcaster_id = ?")
+                .bind(BROADCASTER_ID)
+                .fetch_one(database.pool())
+                .await
+                .expect("enqueued source");
+        assert_eq!(source.0, "backfill");
 
         let checkpoint = database
             .helix_backfill()
@@ -916,6 +999,124 @@ mod tests {
         assert_eq!(checkpoint.last_redemption_id.as_deref(), Some("red-1"));
     }
 
+    #[tokio::test]
+    async fn backfill_worker_stops_paginating_at_known_redemptions() {
+        let database = Database::connect("sqlite::memory:?cache=shared")
+            .await
+            .expect("connect");
+        database.run_migrations().await.expect("migrations");
+
+        provision_broadcaster(&database).await;
+        insert_state_index(&database).await;
+        insert_oauth_link(&database, ChronoDuration::hours(1), false).await;
+        // Previous run saw everything up to 2024-01-01T00:00:00Z.
+        insert_checkpoint(&database, HelixBackfillStatus::Idle).await;
+
+        let tap = TapHub::new();
+        let http = Client::builder().build().expect("client");
+        let helix_server = MockServer::start();
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse(&format!("{}/", helix_server.base_url())).expect("url"),
+            http.clone(),
+        );
+        let policy = Arc::new(PolicyEngine::new());
+        let clock_now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 30, 0).unwrap();
+        let clock = Arc::new(move || clock_now);
+        let command_executor = CommandExecutor::new(
+            database.clone(),
+            tap.clone(),
+            clock.clone(),
+            helix_client.clone(),
+        );
+        let sse = SseHub::new(database.clone(), 64, StdDuration::from_secs(60));
+        let (_service, mut worker) = BackfillService::new(
+            database.clone(),
+            tap,
+            policy,
+            command_executor,
+            sse,
+            helix_client.clone(),
+            clock,
+            StdDuration::from_secs(60),
+            50,
+        );
+
+        let redemption = |id: &str, user: &str, redeemed_at: &str| {
+            json!({
+                "id": id,
+                "broadcaster_id": BROADCASTER_ID,
+                "broadcaster_login": "example",
+                "broadcaster_name": "Example",
+                "user_id": user,
+                "user_login": user,
+                "user_name": user,
+                "user_input": "",
+                "status": "UNFULFILLED",
+                "reward": {
+                    "id": "reward-1",
+                    "title": "Managed reward",
+                    "prompt": null,
+                    "cost": 1000
+                },
+                "redeemed_at": redeemed_at
+            })
+        };
+        let next_page = helix_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/channel_points/custom_rewards/redemptions")
+                .query_param("after", "page-2");
+            then.status(200).json_body(json!({
+                "data": [redemption("red-older", "user-3", "2023-12-31T23:00:00Z")],
+                "pagination": {"cursor": null}
+            }));
+        });
+        let first_page = helix_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/channel_points/custom_rewards/redemptions")
+                .query_param("broadcaster_id", BROADCASTER_ID)
+                .query_param("status", "UNFULFILLED")
+                .query_param("sort", "NEWEST");
+            then.status(200).json_body(json!({
+                "data": [
+                    redemption("red-new", "user-2", "2024-01-01T00:10:00Z"),
+                    redemption("red-1", "user-1", "2024-01-01T00:00:00Z")
+                ],
+                "pagination": {"cursor": "page-2"}
+            }));
+        });
+
+        worker
+            .run_single(BROADCASTER_ID)
+            .await
+            .expect("backfill run");
+
+        assert_eq!(first_page.hits(), 1);
+        assert_eq!(next_page.hits(), 0);
+
+        let queued: Vec<(String,)> =
+            sqlx::query_as("SELECT redemption_id FROM queue_entries WHERE broadcaster_id = ?")
+                .bind(BROADCASTER_ID)
+                .fetch_all(database.pool())
+                .await
+                .expect("queue rows");
+        assert_eq!(queued, vec![("red-new".to_string(),)]);
+
+        let checkpoint = database
+            .helix_backfill()
+            .fetch(BROADCASTER_ID)
+            .await
+            .expect("fetch checkpoint")
+            .expect("checkpoint present");
+        assert_eq!(checkpoint.status, HelixBackfillStatus::Idle);
+        assert_eq!(checkpoint.last_redemption_id.as_deref(), Some("red-new"));
+        assert_eq!(
+            checkpoint.last_seen_at,
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 10, 0).unwrap())
+        );
+        assert!(checkpoint.cursor.is_none());
+    }
+
     #[tokio::test]
     async fn backfill_worker_retries_helix_when_queue_entry_exists() {
         let database = Database::connect("sqlite::memory:?cache=shared")
@@ -1044,6 +1245,288 @@ mod tests {
         assert_eq!(checkpoint.last_redemption_id.as_deref(), Some("red-1"));
     }
 
+    #[tokio::test]
+    async fn backfill_replay_does_not_reenqueue_removed_entry() {
+        let database = Database::connect("sqlite::memory:?cache=shared")
+            .await
+            .expect("connect");
+        database.run_migrations().await.expect("migrations");
+
+        provision_broadcaster(&database).await;
+        insert_state_index(&database).await;
+        insert_oauth_link(&database, ChronoDuration::hours(1), false).await;
+
+        let tap = TapHub::new();
+        let http = Client::builder().build().expect("client");
+        let helix_server = MockServer::start();
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse(&format!("{}/", helix_server.base_url())).expect("url"),
+            http.clone(),
+        );
+        let policy = Arc::new(PolicyEngine::new());
+        let clock_now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let clock = Arc::new(move || clock_now);
+        let command_executor = CommandExecutor::new(
+            database.clone(),
+            tap.clone(),
+            clock.clone(),
+            helix_client.clone(),
+        );
+        let sse = SseHub::new(database.clone(), 64, StdDuration::from_secs(60));
+        let (_service, mut worker) = BackfillService::new(
+            database.clone(),
+            tap,
+            policy,
+            command_executor.clone(),
+            sse,
+            helix_client.clone(),
+            clock,
+            StdDuration::from_secs(60),
+            50,
+        );
+
+        helix_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/channel_points/custom_rewards/redemptions")
+                .query_param("broadcaster_id", BROADCASTER_ID)
+                .query_param("status", "UNFULFILLED");
+            then.status(200).json_body(json!({
+                "data": [{
+                    "id": "red-1",
+                    "broadcaster_id": BROADCASTER_ID,
+                    "broadcaster_login": "example",
+                    "broadcaster_name": "Example",
+                    "user_id": "user-1",
+                    "user_login": "user1",
+                    "user_name": "User 1",
+                    "user_input": "",
+                    "status": "UNFULFILLED",
+                    "reward": {
+                        "id": "reward-1",
+                        "title": "Managed reward",
+                        "prompt": null,
+                        "cost": 1000
+                    },
+                    "redeemed_at": "2024-01-01T00:00:00Z"
+                }],
+                "pagination": {"cursor": null}
+            }));
+        });
+        helix_server.mock(|when, then| {
+            when.method(httpmock::Method::PATCH)
+                .path("/channel_points/custom_rewards/redemptions");
+            then.status(200);
+        });
+
+        worker
+            .run_single(BROADCASTER_ID)
+            .await
+            .expect("first backfill run");
+
+        let (entry_id,): (String,) =
+            sqlx::query_as("SELECT id FROM queue_entries WHERE redemption_id = ?")
+                .bind("red-1")
+                .fetch_one(database.pool())
+                .await
+                .expect("entry id");
+        command_executor
+            .execute_admin_command(
+                BROADCASTER_ID,
+                "UTC",
+                Audience::Admin,
+                Command::QueueRemove(QueueRemoveCommand {
+                    broadcaster_id: BROADCASTER_ID.to_string(),
+                    issued_at: clock_now,
+                    source: CommandSource::Admin,
+                    entry_id,
+                    reason: QueueRemovalReason::ExplicitRemove,
+                    op_id: "remove-1".to_string(),
+                }),
+            )
+            .await
+            .expect("remove entry");
+
+        worker
+            .run_single(BROADCASTER_ID)
+            .await
+            .expect("replayed backfill run");
+
+        let (entries, status): (i64, String) = sqlx::query_as(
+            "SELECT COUNT(*), MAX(status) FROM queue_entries WHERE redemption_id = ?",
+        )
+        .bind("red-1")
+        .fetch_one(database.pool())
+        .await
+        .expect("queue entries");
+        assert_eq!(entries, 1);
+        assert_eq!(status, "REMOVED");
+
+        let (enqueues,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM command_log WHERE type = 'enqueue' AND op_id = ?")
+                .bind(crate::command::enqueue_op_id(BROADCASTER_ID, "red-1"))
+                .fetch_one(database.pool())
+                .await
+                .expect("enqueue commands");
+        assert_eq!(enqueues, 1);
+    }
+
+    #[tokio::test]
+    async fn backfill_worker_keeps_watermark_until_pagination_succeeds() {
+        let database = Database::connect("sqlite::memory:?cache=shared")
+            .await
+            .expect("connect");
+        database.run_migrations().await.expect("migrations");
+
+        provision_broadcaster(&database).await;
+        insert_state_index(&database).await;
+        insert_oauth_link(&database, ChronoDuration::hours(1), false).await;
+        // Previous run saw everything up to 2024-01-01T00:00:00Z.
+        insert_checkpoint(&database, HelixBackfillStatus::Idle).await;
+
+        let tap = TapHub::new();
+        let http = Client::builder().build().expect("client");
+        let helix_server = MockServer::start();
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse(&format!("{}/", helix_server.base_url())).expect("url"),
+            http.clone(),
+        );
+        let policy = Arc::new(PolicyEngine::new());
+        let clock_now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 30, 0).unwrap();
+        let clock = Arc::new(move || clock_now);
+        let command_executor = CommandExecutor::new(
+            database.clone(),
+            tap.clone(),
+            clock.clone(),
+            helix_client.clone(),
+        );
+        let sse = SseHub::new(database.clone(), 64, StdDuration::from_secs(60));
+        let (_service, mut worker) = BackfillService::new(
+            database.clone(),
+            tap,
+            policy,
+            command_executor,
+            sse,
+            helix_client.clone(),
+            clock,
+            StdDuration::from_secs(60),
+            50,
+        );
+
+        let redemption = |id: &str, user: &str, redeemed_at: &str| {
+            json!({
+                "id": id,
+                "broadcaster_id": BROADCASTER_ID,
+                "broadcaster_login": "example",
+                "broadcaster_name": "Example",
+                "user_id": user,
+                "user_login": user,
+                "user_name": user,
+                "user_input": "",
+                "status": "UNFULFILLED",
+                "reward": {
+                    "id": "reward-1",
+                    "title": "Managed reward",
+                    "prompt": null,
+                    "cost": 1000
+                },
+                "redeemed_at": redeemed_at
+            })
+        };
+        let mut failing_page = helix_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/channel_points/custom_rewards/redemptions")
+                .query_param("after", "page-2");
+            then.status(500)
+                .json_body(json!({"error": "Internal Server Error", "status": 500}));
+        });
+        let first_page_body = json!({
+            "data": [redemption("red-new", "user-2", "2024-01-01T00:20:00Z")],
+            "pagination": {"cursor": "page-2"}
+        });
+        let mut first_page = helix_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/channel_points/custom_rewards/redemptions")
+                .query_param("broadcaster_id", BROADCASTER_ID)
+                .query_param("status", "UNFULFILLED")
+                .query_param("sort", "NEWEST");
+            then.status(200).json_body(first_page_body.clone());
+        });
+
+        let result = worker.run_single(BROADCASTER_ID).await;
+        assert!(matches!(result, Err(BackfillError::Helix(_))));
+        assert_eq!(failing_page.hits(), 1);
+
+        let checkpoint = database
+            .helix_backfill()
+            .fetch(BROADCASTER_ID)
+            .await
+            .expect("fetch checkpoint")
+            .expect("checkpoint present");
+        assert_eq!(checkpoint.status, HelixBackfillStatus::Error);
+        assert_eq!(checkpoint.last_redemption_id.as_deref(), Some("red-1"));
+        assert_eq!(
+            checkpoint.last_seen_at,
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+        );
+
+        // Mocks match in registration order, so the second page has to be registered again
+        // ahead of the first page for `after=page-2` to reach it.
+        failing_page.delete();
+        first_page.delete();
+        failing_page = helix_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/channel_points/custom_rewards/redemptions")
+                .query_param("after", "page-2");
+            then.status(200).json_body(json!({
+                "data": [redemption("red-older", "user-3", "2024-01-01T00:10:00Z")],
+                "pagination": {"cursor": null}
+            }));
+        });
+
+        first_page = helix_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/channel_points/custom_rewards/redemptions")
+                .query_param("broadcaster_id", BROADCASTER_ID)
+                .query_param("status", "UNFULFILLED")
+                .query_param("sort", "NEWEST");
+            then.status(200).json_body(first_page_body.clone());
+        });
+
+        worker
+            .run_single(BROADCASTER_ID)
+            .await
+            .expect("second backfill run");
+        assert_eq!(first_page.hits(), 1);
+        assert_eq!(failing_page.hits(), 1);
+
+        let mut queued: Vec<(String,)> =
+            sqlx::query_as("SELECT redemption_id FROM queue_entries WHERE broadcaster_id = ?")
+                .bind(BROADCASTER_ID)
+                .fetch_all(database.pool())
+                .await
+                .expect("queue rows");
+        queued.sort();
+        assert_eq!(
+            queued,
+            vec![("red-new".to_string(),), ("red-older".to_string(),)]
+        );
+
+        let checkpoint = database
+            .helix_backfill()
+            .fetch(BROADCASTER_ID)
+            .await
+            .expect("fetch checkpoint")
+            .expect("checkpoint present");
+        assert_eq!(checkpoint.status, HelixBackfillStatus::Idle);
+        assert_eq!(checkpoint.last_redemption_id.as_deref(), Some("red-new"));
+        assert_eq!(
+            checkpoint.last_seen_at,
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 20, 0).unwrap())
+        );
+    }
+
     #[tokio::test]
     async fn backfill_worker_marks_error_on_helix_failure() {
         let database = Database::connect("sqlite::memory:?cache=shared")
@@ -1123,6 +1606,80 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn backfill_worker_leaves_link_untouched_on_transient_helix_failure() {
+        let database = Database::connect("sqlite::memory:?cache=shared")
+            .await
+            .expect("connect");
+        database.run_migrations().await.expect("migrations");
+
+        provision_broadcaster(&database).await;
+        insert_state_index(&database).await;
+        insert_oauth_link(&database, ChronoDuration::hours(1), false).await;
+
+        let tap = TapHub::new();
+        let http = Client::builder().build().expect("client");
+        let helix_server = MockServer::start();
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse(&format!("{}/", helix_server.base_url())).expect("url"),
+            http.clone(),
+        );
+        let policy = Arc::new(PolicyEngine::new());
+        let clock_now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let clock = Arc::new(move || clock_now);
+        let command_executor = CommandExecutor::new(
+            database.clone(),
+            tap.clone(),
+            clock.clone(),
+            helix_client.clone(),
+        );
+        let sse = SseHub::new(database.clone(), 64, StdDuration::from_secs(60));
+        let (_service, mut worker) = BackfillService::new(
+            database.clone(),
+            tap,
+            policy,
+            command_executor,
+            sse,
+            helix_client.clone(),
+            clock,
+            StdDuration::from_secs(60),
+            50,
+        );
+
+        helix_server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/channel_points/custom_rewards/redemptions")
+                .query_param("broadcaster_id", BROADCASTER_ID)
+                .query_param("status", "UNFULFILLED")
+                .query_param("first", "50");
+            then.status(503).json_body(
+                json!({"error": "Service Unavailable", "status": 503, "message": "busy"}),
+            );
+        });
+
+        let result = worker.run_single(BROADCASTER_ID).await;
+        assert!(matches!(result, Err(BackfillError::Helix(_))));
+
+        let checkpoint = database
+            .helix_backfill()
+            .fetch(BROADCASTER_ID)
+            .await
+            .expect("fetch checkpoint")
+            .expect("checkpoint present");
+        assert_eq!(checkpoint.status, HelixBackfillStatus::Error);
+        assert_eq!(checkpoint.error_message.as_deref(), Some(ERR_HELIX_ERROR));
+
+        let link = database
+            .oauth_links()
+            .fetch_by_broadcaster(BROADCASTER_ID)
+            .await
+            .expect("fetch link")
+            .expect("link present");
+        assert!(!link.requires_reauth);
+        assert!(link.last_failure_reason.is_none());
+    }
+
     #[tokio::test]
     async fn debug_helix_returns_status_payload() {
         let metrics = telemetry::init_metrics().expect("metrics");
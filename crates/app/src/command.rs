@@ -1,35 +1,49 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex as StdMutex,
+    },
+    time::Instant,
+};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
 use chrono_tz::Tz;
 use metrics::{counter, histogram};
 use serde_json::{to_string, to_value, Value};
 use sqlx::{Sqlite, Transaction};
 use thiserror::Error;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
 use uuid::Uuid;
 
 use twi_overlay_core::projector::Projector;
 use twi_overlay_core::types::{
-    Command, CommandResult, EnqueueCommand, Patch, QueueCompleteCommand, QueueEntry,
-    QueueEntryStatus, QueueRemovalReason, QueueRemoveCommand, RedemptionUpdateCommand,
-    RedemptionUpdateMode, Settings, SettingsUpdateCommand,
+    Command, CommandResult, CommandSource, CounterCorrectCommand, EnqueueCommand, FulfillOn,
+    NoopCommand, Patch, QueueAddCommand, QueueCompleteCommand, QueueEntryStatus,
+    QueueRemovalReason, QueueRemoveCommand, RedemptionUpdateCommand, RedemptionUpdateMode,
+    Settings, SettingsUpdateCommand, TimezoneUpdateCommand,
 };
 use twi_overlay_storage::{
     BroadcasterRepository, CommandLogError, DailyCounterError, DailyCounterRepository, Database,
     NewCommandLog, NewDailyCounter, NewQueueEntry, OauthFailure, OauthLink, OauthLinkError,
-    QueueError, QueueRepository, SettingsError, SettingsUpdateError,
+    QueueError, QueueRepository, SettingsError, SettingsUpdateError, TimezoneUpdateError,
 };
 
 use reqwest::StatusCode;
 use twi_overlay_twitch::{HelixClient, HelixError, HelixRedemptionStatus, UpdateRedemptionRequest};
 
+use crate::sse::Audience;
+use crate::state::VersionCache;
 use crate::tap::{StageEvent, StageKind, StageMetadata, StagePayload, TapHub};
 use tracing::{error, warn};
 
 pub(crate) const REQUIRED_OAUTH_SCOPES: &[&str] =
     &["channel:read:redemptions", "channel:manage:redemptions"];
+/// Reward identifier recorded on entries added manually from the admin interface.
+pub const MANUAL_REWARD_ID: &str = "manual";
 const ERR_QUEUE_NOT_FOUND: &str = "queue:not-found";
 const ERR_QUEUE_NO_REDEMPTION: &str = "queue:no-redemption";
+pub(crate) const ERR_REWARD_NOT_MANAGED: &str = "reward:not-managed";
 pub(crate) const ERR_OAUTH_NOT_LINKED: &str = "oauth:not-linked";
 pub(crate) const ERR_OAUTH_REAUTH: &str = "oauth:reauth-required";
 pub(crate) const ERR_OAUTH_MISSING_SCOPE: &str = "oauth:missing-scope";
@@ -61,6 +75,14 @@ pub enum CommandApplyResult {
     SettingsUpdated {
         applied: bool,
     },
+    QueueAdded {
+        entry_id: String,
+        user_today_count: u32,
+    },
+    CounterCorrected {
+        user_id: String,
+        count: u32,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -85,6 +107,49 @@ pub struct CommandExecutor {
     tap: TapHub,
     clock: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
     helix: HelixClient,
+    versions: VersionCache,
+    coalesce_threshold: Arc<AtomicUsize>,
+    locks: BroadcasterLocks,
+}
+
+/// In-process write locks serializing command application per broadcaster.
+///
+/// Mutations for one broadcaster apply in arrival order while different broadcasters
+/// proceed in parallel. Entries are never evicted; the set is bounded by provisioned broadcasters.
+#[derive(Clone, Default)]
+struct BroadcasterLocks {
+    entries: Arc<StdMutex<HashMap<String, Arc<AsyncMutex<()>>>>>,
+}
+
+impl BroadcasterLocks {
+    async fn acquire(&self, broadcaster_id: &str) -> BroadcasterGuard {
+        let lock = {
+            let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+            entries
+                .entry(broadcaster_id.to_string())
+                .or_default()
+                .clone()
+        };
+        BroadcasterGuard {
+            broadcaster_id: broadcaster_id.to_string(),
+            _guard: lock.lock_owned().await,
+        }
+    }
+}
+
+/// Held write lock for one broadcaster, released on drop.
+///
+/// Callers that broadcast the resulting patches keep the guard until broadcasting finishes so
+/// SSE subscribers receive them in version order.
+pub struct BroadcasterGuard {
+    broadcaster_id: String,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl BroadcasterGuard {
+    pub fn broadcaster_id(&self) -> &str {
+        &self.broadcaster_id
+    }
 }
 
 impl CommandExecutor {
@@ -99,23 +164,77 @@ impl CommandExecutor {
             tap,
             clock,
             helix,
+            versions: VersionCache::default(),
+            coalesce_threshold: Arc::new(AtomicUsize::new(0)),
+            locks: BroadcasterLocks::default(),
         }
     }
 
+    /// Coalesces a batch's patches into one `patch.batch` when it yields more than `threshold`.
+    ///
+    /// The setting is shared by every clone of this executor; zero disables coalescing.
+    pub fn set_coalesce_threshold(&self, threshold: usize) {
+        self.coalesce_threshold.store(threshold, Ordering::Relaxed);
+    }
+
+    /// Returns the version cache kept in sync with committed commands.
+    pub fn versions(&self) -> &VersionCache {
+        &self.versions
+    }
+
     fn now(&self) -> DateTime<Utc> {
         (self.clock)()
     }
 
+    /// Unprovisioned broadcasters fall back to midnight so that callers see the usual
+    /// not-found errors from the commands themselves.
+    async fn day_rollover_hour(&self, broadcaster_id: &str) -> Result<u8, CommandExecutorError> {
+        match self
+            .database
+            .broadcasters()
+            .fetch_settings(broadcaster_id)
+            .await
+        {
+            Ok(profile) => Ok(profile.settings.day_rollover_hour),
+            Err(SettingsError::NotFound) => Ok(0),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// With `policy.verify_reward_ownership` enabled, only rewards the policy targets (by id or
+    /// by the title stored on the entry) may be fulfilled or refunded through Helix.
+    async fn reward_owned(
+        &self,
+        broadcaster_id: &str,
+        reward_id: &str,
+        reward_title: Option<&str>,
+    ) -> Result<bool, CommandExecutorError> {
+        let profile = match self
+            .database
+            .broadcasters()
+            .fetch_settings(broadcaster_id)
+            .await
+        {
+            Ok(profile) => profile,
+            Err(SettingsError::NotFound) => return Ok(true),
+            Err(err) => return Err(err.into()),
+        };
+        let policy = &profile.settings.policy;
+        Ok(!policy.verify_reward_ownership || policy.is_reward_enabled(reward_id, reward_title))
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn apply_command(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
         broadcaster_id: &str,
         timezone: &str,
+        rollover_hour: u8,
         command: &Command,
         queue_repo: &QueueRepository,
         counter_repo: &DailyCounterRepository,
         broadcaster_repo: &BroadcasterRepository,
+        follow_ups: &mut Vec<RedemptionUpdateCommand>,
     ) -> Result<CommandApplication, CommandExecutorError> {
         match command {
             Command::Enqueue(enqueue) => {
@@ -123,6 +242,7 @@ impl CommandExecutor {
                     tx,
                     broadcaster_id,
                     timezone,
+                    rollover_hour,
                     enqueue,
                     queue_repo,
                     counter_repo,
@@ -138,9 +258,11 @@ impl CommandExecutor {
                     tx,
                     broadcaster_id,
                     timezone,
+                    rollover_hour,
                     complete,
                     queue_repo,
                     counter_repo,
+                    follow_ups,
                 )
                 .await
             }
@@ -149,9 +271,11 @@ impl CommandExecutor {
                     tx,
                     broadcaster_id,
                     timezone,
+                    rollover_hour,
                     remove,
                     queue_repo,
                     counter_repo,
+                    follow_ups,
                 )
                 .await
             }
@@ -159,10 +283,39 @@ impl CommandExecutor {
                 self.handle_settings_update(tx, broadcaster_id, update, broadcaster_repo)
                     .await
             }
+            Command::TimezoneUpdate(update) => {
+                self.handle_timezone_update(tx, broadcaster_id, update, broadcaster_repo)
+                    .await
+            }
+            Command::CounterCorrect(correct) => {
+                self.handle_counter_correct(
+                    tx,
+                    broadcaster_id,
+                    timezone,
+                    rollover_hour,
+                    correct,
+                    counter_repo,
+                )
+                .await
+            }
+            Command::Noop(noop) => self.handle_noop(tx, broadcaster_id, noop).await,
+            Command::QueueAdd(add) => {
+                self.handle_queue_add(
+                    tx,
+                    broadcaster_id,
+                    timezone,
+                    rollover_hour,
+                    add,
+                    queue_repo,
+                    counter_repo,
+                )
+                .await
+            }
         }
     }
 
-    /// Executes a batch of commands for the provided broadcaster, returning generated patches.
+    /// Locks the broadcaster and runs [`Self::execute_locked`].
+    #[cfg(test)]
     pub async fn execute(
         &self,
         broadcaster_id: &str,
@@ -173,86 +326,266 @@ impl CommandExecutor {
             return Ok(Vec::new());
         }
 
+        let guard = self.lock(broadcaster_id).await;
+        self.execute_locked(&guard, timezone, commands).await
+    }
+
+    /// Acquires the broadcaster's write lock shared by every command entry point.
+    pub async fn lock(&self, broadcaster_id: &str) -> BroadcasterGuard {
+        self.locks.acquire(broadcaster_id).await
+    }
+
+    /// Executes a batch of commands for the provided broadcaster, returning generated patches.
+    ///
+    /// The caller holds the broadcaster's write lock (see [`Self::lock`]) until the batch's
+    /// patches are broadcast, so subscribers see versions in order.
+    pub async fn execute_locked(
+        &self,
+        guard: &BroadcasterGuard,
+        timezone: &str,
+        commands: &[Command],
+    ) -> Result<Vec<Patch>, CommandExecutorError> {
+        if commands.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let broadcaster_id = guard.broadcaster_id();
         let command_log_repo = self.database.command_log();
-        let mut tx = command_log_repo.begin().await?;
+        let mut tx = command_log_repo.begin_immediate().await?;
         let queue_repo = self.database.queue();
         let counter_repo = self.database.daily_counters();
         let broadcaster_repo = self.database.broadcasters();
+        let rollover_hour = self.day_rollover_hour(broadcaster_id).await?;
         let mut patches = Vec::with_capacity(commands.len());
 
+        let mut follow_ups = Vec::new();
+
+        let mut latest_version = None;
         for command in commands {
             let application = self
                 .apply_command(
                     &mut tx,
                     broadcaster_id,
                     timezone,
+                    rollover_hour,
                     command,
                     &queue_repo,
                     &counter_repo,
                     &broadcaster_repo,
+                    &mut follow_ups,
                 )
                 .await?;
+            latest_version = latest_version.max(Some(application.version));
             patches.extend(application.patches);
         }
 
         tx.commit().await?;
+        if let Some(version) = latest_version {
+            self.versions.record(broadcaster_id, version).await;
+        }
+        if let Some((_, follow_up_patches)) =
+            self.apply_follow_ups(broadcaster_id, &follow_ups).await
+        {
+            patches.extend(follow_up_patches);
+        }
+
+        let threshold = self.coalesce_threshold.load(Ordering::Relaxed);
+        if threshold > 0 && patches.len() > threshold {
+            return Ok(vec![Projector::batch(self.now(), patches)]);
+        }
         Ok(patches)
     }
 
-    /// Executes a single admin command, returning its application details.
+    /// Locks the broadcaster and runs [`Self::execute_admin_command_locked`].
+    #[cfg(test)]
     pub async fn execute_admin_command(
         &self,
         broadcaster_id: &str,
         timezone: &str,
+        audience: Audience,
         command: Command,
     ) -> Result<CommandApplication, CommandExecutorError> {
-        match command {
-            Command::QueueComplete(_) | Command::QueueRemove(_) | Command::SettingsUpdate(_) => {}
-            _ => {
-                return Err(CommandExecutorError::UnsupportedCommand(
-                    command.metric_kind(),
-                ))
-            }
+        let guard = self.lock(broadcaster_id).await;
+        self.execute_admin_command_locked(&guard, timezone, audience, command)
+            .await
+    }
+
+    /// Executes a single admin command under the broadcaster's write lock, returning its
+    /// application details.
+    pub async fn execute_admin_command_locked(
+        &self,
+        guard: &BroadcasterGuard,
+        timezone: &str,
+        audience: Audience,
+        command: Command,
+    ) -> Result<CommandApplication, CommandExecutorError> {
+        let broadcaster_id = guard.broadcaster_id();
+        if !is_admin_command(&command) {
+            return Err(CommandExecutorError::UnsupportedCommand {
+                kind: command.metric_kind(),
+                allowed: ADMIN_COMMAND_KINDS,
+            });
+        }
+        if permitted_source(audience) != Some(command.source()) {
+            return Err(CommandExecutorError::SourceNotPermitted {
+                command_source: command.source().as_str(),
+                audience: audience.as_str(),
+            });
         }
 
         let command_log_repo = self.database.command_log();
-        let mut tx = command_log_repo.begin().await?;
+        let mut tx = command_log_repo.begin_immediate().await?;
         let queue_repo = self.database.queue();
         let counter_repo = self.database.daily_counters();
         let broadcaster_repo = self.database.broadcasters();
+        let rollover_hour = self.day_rollover_hour(broadcaster_id).await?;
+        let mut follow_ups = Vec::new();
 
-        let application = self
+        let mut application = self
             .apply_command(
                 &mut tx,
                 broadcaster_id,
                 timezone,
+                rollover_hour,
                 &command,
                 &queue_repo,
                 &counter_repo,
                 &broadcaster_repo,
+                &mut follow_ups,
             )
             .await?;
 
         tx.commit().await?;
+        self.versions
+            .record(broadcaster_id, application.version)
+            .await;
+        if let Some((version, patches)) = self.apply_follow_ups(broadcaster_id, &follow_ups).await {
+            application.version = version;
+            application.patches.extend(patches);
+        }
         Ok(application)
     }
 
+    /// Applies the redemption updates deferred by queue mutations once their transaction has
+    /// committed, so a slow or failing Helix call never holds back the queue change itself.
+    ///
+    /// Failures are logged rather than returned: the queue mutation is already durable and the
+    /// redemption update records its own Helix outcome.
+    async fn apply_follow_ups(
+        &self,
+        broadcaster_id: &str,
+        follow_ups: &[RedemptionUpdateCommand],
+    ) -> Option<(u64, Vec<Patch>)> {
+        if follow_ups.is_empty() {
+            return None;
+        }
+
+        let result: Result<(u64, Vec<Patch>), CommandExecutorError> = async {
+            let command_log_repo = self.database.command_log();
+            let mut tx = command_log_repo.begin_immediate().await?;
+            let mut latest_version = 0;
+            let mut patches = Vec::with_capacity(follow_ups.len());
+            for update in follow_ups {
+                let application = self
+                    .handle_redemption_update(&mut tx, broadcaster_id, update)
+                    .await?;
+                latest_version = latest_version.max(application.version);
+                patches.extend(application.patches);
+            }
+            tx.commit().await?;
+            Ok((latest_version, patches))
+        }
+        .await;
+
+        match result {
+            Ok((version, patches)) => {
+                self.versions.record(broadcaster_id, version).await;
+                Some((version, patches))
+            }
+            Err(err) => {
+                error!(
+                    stage = "command",
+                    broadcaster = %broadcaster_id,
+                    error = %err,
+                    "failed to apply deferred redemption updates"
+                );
+                None
+            }
+        }
+    }
+
+    /// Under `policy.fulfill_on = "complete"` a queued redemption stays unfulfilled on Twitch,
+    /// so the entry leaving the queue settles it: completion fulfills it while UNDO and expiry
+    /// cancel (refund) it.
+    async fn settle_redemption(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+        redemption_id: Option<&str>,
+        issued_at: DateTime<Utc>,
+        source: CommandSource,
+        mode: RedemptionUpdateMode,
+    ) -> Result<Option<RedemptionUpdateCommand>, CommandExecutorError> {
+        let Some(redemption_id) = redemption_id else {
+            return Ok(None);
+        };
+        let profile = self
+            .database
+            .broadcasters()
+            .fetch_settings_for_update(tx, broadcaster_id)
+            .await?;
+        if profile.settings.policy.fulfill_on != FulfillOn::Complete {
+            return Ok(None);
+        }
+        Ok(Some(RedemptionUpdateCommand {
+            broadcaster_id: broadcaster_id.to_string(),
+            issued_at,
+            source,
+            redemption_id: redemption_id.to_string(),
+            mode,
+            applicable: false,
+            result: CommandResult::Skipped,
+            managed: None,
+            error: None,
+        }))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn handle_enqueue(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
         broadcaster_id: &str,
         timezone: &str,
+        rollover_hour: u8,
         command: &EnqueueCommand,
         queue_repo: &QueueRepository,
         counter_repo: &DailyCounterRepository,
     ) -> Result<CommandApplication, CommandExecutorError> {
+        // Replays of the same redemption (backfill retries, EventSub redelivery) resolve to the
+        // original command even if the queue entry has since been removed.
+        let op_id = enqueue_op_id(broadcaster_id, &command.redemption_id);
+        if let Some(existing) = self
+            .database
+            .command_log()
+            .find_by_op_id(tx, broadcaster_id, &op_id)
+            .await?
+        {
+            return Ok(CommandApplication {
+                version: existing.version,
+                patches: Vec::new(),
+                result: CommandApplyResult::None,
+                duplicate: true,
+            });
+        }
+
         let serialized = to_string(command)?;
         let inserted_at = self.now();
         let version = self
             .append_command(
                 tx,
                 broadcaster_id,
-                None,
+                Some(&op_id),
+                command.source,
                 "enqueue",
                 &serialized,
                 inserted_at,
@@ -260,27 +593,23 @@ impl CommandExecutor {
             .await?;
 
         let command_enum = Command::Enqueue(command.clone());
-        self.emit_command_event(broadcaster_id, version, "enqueue", &command_enum, None);
-
-        let entry = self.build_queue_entry(command);
-        let new_entry = NewQueueEntry {
-            id: entry.id.clone(),
+        self.emit_command_event(
             broadcaster_id,
-            user_id: &command.user.id,
-            user_login: entry.user_login.clone(),
-            user_display_name: entry.user_display_name.clone(),
-            user_avatar: entry.user_avatar.clone(),
-            reward_id: &command.reward.id,
-            redemption_id: Some(command.redemption_id.clone()),
-            enqueued_at: entry.enqueued_at,
-            status: entry.status,
-            status_reason: entry.status_reason.clone(),
-            managed: entry.managed,
-            last_updated_at: entry.last_updated_at,
-        };
+            version,
+            "enqueue",
+            &command_enum,
+            Some(&op_id),
+        );
+
+        let new_entry = NewQueueEntry::from_enqueue_command(
+            command,
+            Uuid::new_v4().to_string(),
+            command.issued_at,
+        );
         queue_repo.insert_entry(tx, &new_entry).await?;
+        let entry = new_entry.to_domain();
 
-        let day = compute_local_day(command.issued_at, timezone)?;
+        let day = compute_local_day(command.issued_at, timezone, rollover_hour)?;
         let user_today_count = counter_repo
             .increment(
                 tx,
@@ -294,7 +623,7 @@ impl CommandExecutor {
             .await?;
 
         let patch = Projector::queue_enqueued(version, command.issued_at, entry, user_today_count);
-        self.emit_projector_event(broadcaster_id, version, &patch, &command_enum, None);
+        self.emit_projector_event(broadcaster_id, version, &patch, &command_enum, Some(&op_id));
         counter!("projector_patches_total", "type" => patch.kind_str()).increment(1);
 
         Ok(CommandApplication {
@@ -342,6 +671,25 @@ impl CommandExecutor {
                     redemption = %command.redemption_id,
                     "queue entry missing redemption id, skipping helix update"
                 );
+            } else if !self
+                .reward_owned(
+                    broadcaster_id,
+                    &entry.reward_id,
+                    entry.reward_title.as_deref(),
+                )
+                .await?
+            {
+                error_code = Some(ERR_REWARD_NOT_MANAGED.to_string());
+                if target_managed {
+                    target_managed = false;
+                }
+                warn!(
+                    stage = "oauth",
+                    broadcaster = %broadcaster_id,
+                    redemption = %command.redemption_id,
+                    reward = %entry.reward_id,
+                    "reward is not a configured target, refusing helix update"
+                );
             } else {
                 let oauth_link = oauth_repo
                     .fetch_by_broadcaster_for_update(tx, broadcaster_id)
@@ -505,6 +853,7 @@ impl CommandExecutor {
                 tx,
                 broadcaster_id,
                 None,
+                enriched.source,
                 "redemption.update",
                 &serialized,
                 inserted_at,
@@ -540,14 +889,143 @@ impl CommandExecutor {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_queue_add(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+        timezone: &str,
+        rollover_hour: u8,
+        command: &QueueAddCommand,
+        queue_repo: &QueueRepository,
+        counter_repo: &DailyCounterRepository,
+    ) -> Result<CommandApplication, CommandExecutorError> {
+        // The op_id doubles as the entry id so that retries resolve to the same entry.
+        let entry_id = command.op_id.clone();
+        let serialized = to_string(command)?;
+        let existing_version = self
+            .ensure_unique_op_id(tx, broadcaster_id, &command.op_id, "queue.add", &serialized)
+            .await?;
+
+        if let Some(version) = existing_version {
+            let Some(entry) = queue_repo
+                .find_entry_for_update(tx, broadcaster_id, &entry_id)
+                .await?
+            else {
+                return Err(QueueError::NotFound.into());
+            };
+            let day = compute_local_day(entry.enqueued_at, timezone, rollover_hour)?;
+            let user_today_count = counter_repo
+                .fetch_value(tx, &day, broadcaster_id, &entry.user_id)
+                .await?
+                .unwrap_or(0);
+            return Ok(CommandApplication {
+                version,
+                patches: Vec::new(),
+                result: CommandApplyResult::QueueAdded {
+                    entry_id,
+                    user_today_count,
+                },
+                duplicate: true,
+            });
+        }
+
+        let inserted_at = self.now();
+        let version = self
+            .append_command(
+                tx,
+                broadcaster_id,
+                Some(&command.op_id),
+                command.source,
+                "queue.add",
+                &serialized,
+                inserted_at,
+            )
+            .await?;
+
+        let command_enum = Command::QueueAdd(command.clone());
+        self.emit_command_event(
+            broadcaster_id,
+            version,
+            "queue.add",
+            &command_enum,
+            Some(&command.op_id),
+        );
+
+        let user_login = command
+            .user
+            .login
+            .clone()
+            .unwrap_or_else(|| command.user.id.clone());
+        let new_entry = NewQueueEntry {
+            id: entry_id.clone(),
+            broadcaster_id,
+            user_id: &command.user.id,
+            user_display_name: command
+                .user
+                .display_name
+                .clone()
+                .unwrap_or_else(|| user_login.clone()),
+            user_login,
+            user_avatar: None,
+            reward_id: MANUAL_REWARD_ID,
+            reward_title: None,
+            redemption_id: None,
+            enqueued_at: command.issued_at,
+            status: QueueEntryStatus::Queued,
+            status_reason: None,
+            managed: false,
+            last_updated_at: command.issued_at,
+            enqueued_source: command.source.into(),
+        };
+        queue_repo.insert_entry(tx, &new_entry).await?;
+        let entry = new_entry.to_domain();
+
+        let day = compute_local_day(command.issued_at, timezone, rollover_hour)?;
+        let user_today_count = counter_repo
+            .increment(
+                tx,
+                &NewDailyCounter {
+                    day,
+                    broadcaster_id,
+                    user_id: &command.user.id,
+                    updated_at: inserted_at,
+                },
+            )
+            .await?;
+
+        let patch = Projector::queue_enqueued(version, command.issued_at, entry, user_today_count);
+        self.emit_projector_event(
+            broadcaster_id,
+            version,
+            &patch,
+            &command_enum,
+            Some(&command.op_id),
+        );
+        counter!("projector_patches_total", "type" => patch.kind_str()).increment(1);
+
+        Ok(CommandApplication {
+            version,
+            patches: vec![patch],
+            result: CommandApplyResult::QueueAdded {
+                entry_id,
+                user_today_count,
+            },
+            duplicate: false,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn handle_queue_complete(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
         broadcaster_id: &str,
         timezone: &str,
+        rollover_hour: u8,
         command: &QueueCompleteCommand,
         queue_repo: &QueueRepository,
         counter_repo: &DailyCounterRepository,
+        follow_ups: &mut Vec<RedemptionUpdateCommand>,
     ) -> Result<CommandApplication, CommandExecutorError> {
         let Some(entry) = queue_repo
             .find_entry_for_update(tx, broadcaster_id, &command.entry_id)
@@ -567,7 +1045,7 @@ impl CommandExecutor {
             )
             .await?;
 
-        let day = compute_local_day(entry.enqueued_at, timezone)?;
+        let day = compute_local_day(entry.enqueued_at, timezone, rollover_hour)?;
         let user_today_count = counter_repo
             .fetch_value(tx, &day, broadcaster_id, &entry.user_id)
             .await?
@@ -596,6 +1074,7 @@ impl CommandExecutor {
                 tx,
                 broadcaster_id,
                 Some(&command.op_id),
+                command.source,
                 "queue.complete",
                 &serialized,
                 updated_at,
@@ -621,6 +1100,20 @@ impl CommandExecutor {
         );
         counter!("projector_patches_total", "type" => patch.kind_str()).increment(1);
 
+        if let Some(fulfill) = self
+            .settle_redemption(
+                tx,
+                broadcaster_id,
+                entry.redemption_id.as_deref(),
+                command.issued_at,
+                command.source,
+                RedemptionUpdateMode::Consume,
+            )
+            .await?
+        {
+            follow_ups.push(fulfill);
+        }
+
         Ok(CommandApplication {
             version,
             patches: vec![patch],
@@ -633,14 +1126,17 @@ impl CommandExecutor {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn handle_queue_remove(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
         broadcaster_id: &str,
         timezone: &str,
+        rollover_hour: u8,
         command: &QueueRemoveCommand,
         queue_repo: &QueueRepository,
         counter_repo: &DailyCounterRepository,
+        follow_ups: &mut Vec<RedemptionUpdateCommand>,
     ) -> Result<CommandApplication, CommandExecutorError> {
         let Some(entry) = queue_repo
             .find_entry_for_update(tx, broadcaster_id, &command.entry_id)
@@ -660,7 +1156,7 @@ impl CommandExecutor {
             )
             .await?;
 
-        let day = compute_local_day(entry.enqueued_at, timezone)?;
+        let day = compute_local_day(entry.enqueued_at, timezone, rollover_hour)?;
         let mode = queue_mode_from_reason(command.reason);
 
         let user_today_count = counter_repo
@@ -692,7 +1188,19 @@ impl CommandExecutor {
             )
             .await?;
 
-        let new_count = if matches!(command.reason, QueueRemovalReason::Undo) {
+        let decrement = match command.reason {
+            QueueRemovalReason::Undo => true,
+            QueueRemovalReason::Expired => {
+                self.database
+                    .broadcasters()
+                    .fetch_settings_for_update(tx, broadcaster_id)
+                    .await?
+                    .settings
+                    .expire_decrement_counts
+            }
+            QueueRemovalReason::ExplicitRemove | QueueRemovalReason::StreamStartClear => false,
+        };
+        let new_count = if decrement {
             counter_repo
                 .decrement(tx, &day, broadcaster_id, &entry.user_id, updated_at)
                 .await?
@@ -706,6 +1214,7 @@ impl CommandExecutor {
                 tx,
                 broadcaster_id,
                 Some(&command.op_id),
+                command.source,
                 "queue.remove",
                 &serialized,
                 updated_at,
@@ -739,9 +1248,14 @@ impl CommandExecutor {
         counter!("projector_patches_total", "type" => queue_patch.kind_str()).increment(1);
         patches.push(queue_patch);
 
-        if matches!(command.reason, QueueRemovalReason::Undo) {
-            let counter_patch =
-                Projector::counter_updated(version, command.issued_at, &entry.user_id, new_count);
+        if decrement {
+            let counter_patch = Projector::counter_updated(
+                version,
+                command.issued_at,
+                &entry.user_id,
+                new_count,
+                updated_at,
+            );
             self.emit_projector_event(
                 broadcaster_id,
                 version,
@@ -753,9 +1267,28 @@ impl CommandExecutor {
             patches.push(counter_patch);
         }
 
-        Ok(CommandApplication {
-            version,
-            patches,
+        if matches!(
+            command.reason,
+            QueueRemovalReason::Undo | QueueRemovalReason::Expired
+        ) {
+            if let Some(refund) = self
+                .settle_redemption(
+                    tx,
+                    broadcaster_id,
+                    entry.redemption_id.as_deref(),
+                    command.issued_at,
+                    command.source,
+                    RedemptionUpdateMode::Refund,
+                )
+                .await?
+            {
+                follow_ups.push(refund);
+            }
+        }
+
+        Ok(CommandApplication {
+            version,
+            patches,
             result: CommandApplyResult::QueueMutation {
                 entry_id: command.entry_id.clone(),
                 mode,
@@ -765,6 +1298,102 @@ impl CommandExecutor {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_counter_correct(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+        timezone: &str,
+        rollover_hour: u8,
+        command: &CounterCorrectCommand,
+        counter_repo: &DailyCounterRepository,
+    ) -> Result<CommandApplication, CommandExecutorError> {
+        let serialized = to_string(command)?;
+        let existing_version = self
+            .ensure_unique_op_id(
+                tx,
+                broadcaster_id,
+                &command.op_id,
+                "counter.correct",
+                &serialized,
+            )
+            .await?;
+
+        if let Some(version) = existing_version {
+            return Ok(CommandApplication {
+                version,
+                patches: Vec::new(),
+                result: CommandApplyResult::CounterCorrected {
+                    user_id: command.user_id.clone(),
+                    count: command.count,
+                },
+                duplicate: true,
+            });
+        }
+
+        let updated_at = self.now();
+        let day = compute_local_day(command.issued_at, timezone, rollover_hour)?;
+        let count = counter_repo
+            .set(
+                tx,
+                &NewDailyCounter {
+                    day,
+                    broadcaster_id,
+                    user_id: &command.user_id,
+                    updated_at,
+                },
+                command.count,
+            )
+            .await?;
+
+        let version = self
+            .append_command(
+                tx,
+                broadcaster_id,
+                Some(&command.op_id),
+                command.source,
+                "counter.correct",
+                &serialized,
+                updated_at,
+            )
+            .await?;
+
+        let command_enum = Command::CounterCorrect(command.clone());
+        self.emit_command_event(
+            broadcaster_id,
+            version,
+            "counter.correct",
+            &command_enum,
+            Some(&command.op_id),
+        );
+
+        let patch = Projector::counter_updated(
+            version,
+            command.issued_at,
+            &command.user_id,
+            count,
+            updated_at,
+        );
+        self.emit_projector_event(
+            broadcaster_id,
+            version,
+            &patch,
+            &command_enum,
+            Some(&command.op_id),
+        );
+        counter!("projector_patches_total", "type" => patch.kind_str()).increment(1);
+
+        Ok(CommandApplication {
+            version,
+            patches: vec![patch],
+            result: CommandApplyResult::CounterCorrected {
+                user_id: command.user_id.clone(),
+                count,
+            },
+            duplicate: false,
+        })
+    }
+
     async fn handle_settings_update(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
@@ -804,6 +1433,7 @@ impl CommandExecutor {
                 tx,
                 broadcaster_id,
                 Some(&command.op_id),
+                command.source,
                 "settings.update",
                 &serialized,
                 updated_at,
@@ -837,6 +1467,129 @@ impl CommandExecutor {
         })
     }
 
+    async fn handle_timezone_update(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+        command: &TimezoneUpdateCommand,
+        broadcaster_repo: &BroadcasterRepository,
+    ) -> Result<CommandApplication, CommandExecutorError> {
+        let serialized = to_string(command)?;
+        let existing_version = self
+            .ensure_unique_op_id(
+                tx,
+                broadcaster_id,
+                &command.op_id,
+                "settings.timezone",
+                &serialized,
+            )
+            .await?;
+
+        if let Some(version) = existing_version {
+            return Ok(CommandApplication {
+                version,
+                patches: Vec::new(),
+                result: CommandApplyResult::SettingsUpdated { applied: true },
+                duplicate: true,
+            });
+        }
+
+        let updated_at = self.now();
+        broadcaster_repo
+            .update_timezone(tx, broadcaster_id, &command.timezone, updated_at)
+            .await?;
+
+        let version = self
+            .append_command(
+                tx,
+                broadcaster_id,
+                Some(&command.op_id),
+                command.source,
+                "settings.timezone",
+                &serialized,
+                updated_at,
+            )
+            .await?;
+
+        let command_enum = Command::TimezoneUpdate(command.clone());
+        self.emit_command_event(
+            broadcaster_id,
+            version,
+            "settings.timezone",
+            &command_enum,
+            Some(&command.op_id),
+        );
+
+        let patch = Projector::settings_updated(
+            version,
+            command.issued_at,
+            &serde_json::json!({ "timezone": command.timezone }),
+        );
+        self.emit_projector_event(
+            broadcaster_id,
+            version,
+            &patch,
+            &command_enum,
+            Some(&command.op_id),
+        );
+        counter!("projector_patches_total", "type" => patch.kind_str()).increment(1);
+
+        Ok(CommandApplication {
+            version,
+            patches: vec![patch],
+            result: CommandApplyResult::SettingsUpdated { applied: true },
+            duplicate: false,
+        })
+    }
+
+    async fn handle_noop(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+        command: &NoopCommand,
+    ) -> Result<CommandApplication, CommandExecutorError> {
+        let serialized = to_string(command)?;
+        let existing_version = self
+            .ensure_unique_op_id(tx, broadcaster_id, &command.op_id, "noop", &serialized)
+            .await?;
+
+        if let Some(version) = existing_version {
+            return Ok(CommandApplication {
+                version,
+                patches: Vec::new(),
+                result: CommandApplyResult::None,
+                duplicate: true,
+            });
+        }
+
+        let version = self
+            .append_command(
+                tx,
+                broadcaster_id,
+                Some(&command.op_id),
+                command.source,
+                "noop",
+                &serialized,
+                self.now(),
+            )
+            .await?;
+
+        self.emit_command_event(
+            broadcaster_id,
+            version,
+            "noop",
+            &Command::Noop(command.clone()),
+            Some(&command.op_id),
+        );
+
+        Ok(CommandApplication {
+            version,
+            patches: Vec::new(),
+            result: CommandApplyResult::None,
+            duplicate: false,
+        })
+    }
+
     async fn ensure_unique_op_id(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
@@ -872,11 +1625,13 @@ impl CommandExecutor {
         Ok(Some(existing.version))
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn append_command(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
         broadcaster_id: &str,
         op_id: Option<&str>,
+        source: CommandSource,
         command_type: &str,
         payload_json: &str,
         created_at: DateTime<Utc>,
@@ -884,6 +1639,7 @@ impl CommandExecutor {
         let record = NewCommandLog {
             broadcaster_id,
             op_id,
+            source,
             command_type,
             payload_json,
             created_at,
@@ -985,45 +1741,14 @@ impl CommandExecutor {
         };
         self.tap.publish(event);
     }
-
-    fn build_queue_entry(&self, command: &EnqueueCommand) -> QueueEntry {
-        let issued_at = command.issued_at;
-        let user_login = command
-            .user
-            .login
-            .clone()
-            .unwrap_or_else(|| command.user.id.clone());
-        let user_display_name = command
-            .user
-            .display_name
-            .clone()
-            .or(command.user.login.clone())
-            .unwrap_or_else(|| command.user.id.clone());
-
-        QueueEntry {
-            id: Uuid::new_v4().to_string(),
-            broadcaster_id: command.broadcaster_id.clone(),
-            user_id: command.user.id.clone(),
-            user_login,
-            user_display_name,
-            user_avatar: None,
-            reward_id: command.reward.id.clone(),
-            redemption_id: Some(command.redemption_id.clone()),
-            enqueued_at: issued_at,
-            status: QueueEntryStatus::Queued,
-            status_reason: None,
-            managed: command.managed.unwrap_or(false),
-            last_updated_at: issued_at,
-        }
-    }
 }
 
 fn queue_mode_from_reason(reason: QueueRemovalReason) -> QueueMutationMode {
     match reason {
         QueueRemovalReason::Undo => QueueMutationMode::Undo,
-        QueueRemovalReason::ExplicitRemove | QueueRemovalReason::StreamStartClear => {
-            QueueMutationMode::Undo
-        }
+        QueueRemovalReason::ExplicitRemove
+        | QueueRemovalReason::StreamStartClear
+        | QueueRemovalReason::Expired => QueueMutationMode::Undo,
     }
 }
 
@@ -1039,7 +1764,32 @@ fn merge_settings_patch(
 
     let mut merged = to_value(current)?;
     merge_value(&mut merged, patch);
-    let settings: Settings = serde_json::from_value(merged)?;
+    let mut settings: Settings = serde_json::from_value(merged)?;
+
+    // Only keys the patch sets are validated, so stored values never block unrelated updates.
+    let patched = |path: &[&str]| {
+        path.iter()
+            .try_fold(patch, |value, key| value.get(key))
+            .is_some_and(|value| !value.is_null())
+    };
+    if patched(&["day_rollover_hour"]) && settings.day_rollover_hour > 23 {
+        return Err(CommandExecutorError::InvalidSettingsPatch(
+            "day_rollover_hour must be between 0 and 23".to_string(),
+        ));
+    }
+    if patched(&["policy", "target_rewards"]) {
+        let target_rewards = &mut settings.policy.target_rewards;
+        for reward in target_rewards.iter_mut() {
+            *reward = reward.trim().to_string();
+        }
+        if target_rewards.iter().any(String::is_empty) {
+            return Err(CommandExecutorError::InvalidSettingsPatch(
+                "policy.target_rewards must not contain empty reward ids".to_string(),
+            ));
+        }
+        let mut seen = HashSet::with_capacity(target_rewards.len());
+        target_rewards.retain(|reward| seen.insert(reward.clone()));
+    }
     Ok(settings)
 }
 
@@ -1066,6 +1816,37 @@ fn merge_value(target: &mut Value, patch: &Value) {
     *target = patch.clone();
 }
 
+/// Command kinds (as reported by `Command::metric_kind`) accepted by `execute_admin_command`.
+pub(crate) const ADMIN_COMMAND_KINDS: &[&str] = &[
+    "complete", "undo", "settings", "timezone", "add", "counter", "noop",
+];
+
+/// Command source an authenticated token audience may submit mutations as.
+fn permitted_source(audience: Audience) -> Option<CommandSource> {
+    match audience {
+        Audience::Admin => Some(CommandSource::Admin),
+        Audience::Overlay => None,
+    }
+}
+
+fn is_admin_command(command: &Command) -> bool {
+    match command {
+        Command::QueueComplete(_)
+        | Command::QueueRemove(_)
+        | Command::SettingsUpdate(_)
+        | Command::TimezoneUpdate(_)
+        | Command::QueueAdd(_)
+        | Command::CounterCorrect(_)
+        | Command::Noop(_) => true,
+        Command::Enqueue(_) | Command::RedemptionUpdate(_) => false,
+    }
+}
+
+/// Deterministic op_id for an enqueue, keyed by the redemption it originates from.
+pub(crate) fn enqueue_op_id(broadcaster_id: &str, redemption_id: &str) -> String {
+    format!("enqueue:{broadcaster_id}:{redemption_id}")
+}
+
 fn normalize_idempotent_payload(payload_json: &str) -> Result<Value, CommandExecutorError> {
     let mut value: Value = serde_json::from_str(payload_json)?;
 
@@ -1099,19 +1880,56 @@ pub(crate) fn classify_helix_error(err: &HelixError) -> (&'static str, bool) {
             _ => (ERR_HELIX_ERROR, false),
         },
         HelixError::Http(_) => (ERR_NETWORK_ERROR, false),
-        HelixError::Url(_) => (ERR_INTERNAL_ERROR, false),
+        HelixError::Url(_) | HelixError::UnsupportedStatus(_) => (ERR_INTERNAL_ERROR, false),
     }
 }
 
+/// Returns the `YYYY-MM-DD` day key for `occurred_at`.
+///
+/// Days start at `rollover_hour` local time, so with a rollover of 6 an event at 03:00 still
+/// belongs to the previous date.
 pub fn compute_local_day(
     occurred_at: DateTime<Utc>,
     timezone: &str,
+    rollover_hour: u8,
 ) -> Result<String, CommandExecutorError> {
     let tz: Tz = timezone
         .parse()
         .map_err(|_| CommandExecutorError::InvalidTimezone(timezone.to_string()))?;
-    let local_time = occurred_at.with_timezone(&tz);
-    Ok(local_time.format("%Y-%m-%d").to_string())
+    Ok(local_date(occurred_at, &tz, rollover_hour)
+        .format("%Y-%m-%d")
+        .to_string())
+}
+
+fn local_date(occurred_at: DateTime<Utc>, tz: &Tz, rollover_hour: u8) -> NaiveDate {
+    // Shift the wall clock rather than the instant so DST transitions don't move the rollover.
+    (occurred_at.with_timezone(tz).naive_local()
+        - chrono::Duration::hours(i64::from(rollover_hour)))
+    .date()
+}
+
+/// Returns the UTC bounds `[start, end)` of the local day containing `occurred_at`.
+pub fn local_day_bounds(
+    occurred_at: DateTime<Utc>,
+    timezone: &str,
+    rollover_hour: u8,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), CommandExecutorError> {
+    let tz: Tz = timezone
+        .parse()
+        .map_err(|_| CommandExecutorError::InvalidTimezone(timezone.to_string()))?;
+    let date = local_date(occurred_at, &tz, rollover_hour);
+    let start_of = |date: NaiveDate| {
+        // The rollover can fall inside a DST gap; the first valid instant after it is used then.
+        (u32::from(rollover_hour)..24)
+            .filter_map(|hour| date.and_hms_opt(hour, 0, 0))
+            .find_map(|local| tz.from_local_datetime(&local).earliest())
+            .map(|start| start.with_timezone(&Utc))
+            .ok_or_else(|| CommandExecutorError::InvalidTimezone(timezone.to_string()))
+    };
+    let next = date
+        .succ_opt()
+        .ok_or_else(|| CommandExecutorError::InvalidTimezone(timezone.to_string()))?;
+    Ok((start_of(date)?, start_of(next)?))
 }
 
 #[derive(Debug, Error)]
@@ -1128,6 +1946,8 @@ pub enum CommandExecutorError {
     Settings(#[from] SettingsError),
     #[error("settings update error: {0}")]
     SettingsUpdate(#[from] SettingsUpdateError),
+    #[error("timezone update error: {0}")]
+    TimezoneUpdate(#[from] TimezoneUpdateError),
     #[error("database error: {0}")]
     Database(#[from] sqlx::Error),
     #[error("oauth link error: {0}")]
@@ -1138,8 +1958,16 @@ pub enum CommandExecutorError {
     OpConflict { op_id: String },
     #[error("invalid settings patch: {0}")]
     InvalidSettingsPatch(String),
-    #[error("unsupported command type: {0}")]
-    UnsupportedCommand(&'static str),
+    #[error("command source {command_source} is not permitted for {audience} tokens")]
+    SourceNotPermitted {
+        command_source: &'static str,
+        audience: &'static str,
+    },
+    #[error("unsupported command type: {kind} (allowed: {})", allowed.join(", "))]
+    UnsupportedCommand {
+        kind: &'static str,
+        allowed: &'static [&'static str],
+    },
 }
 
 #[cfg(test)]
@@ -1151,8 +1979,10 @@ mod tests {
     use httpmock::Method::PATCH;
     use reqwest::Client;
     use serde_json::json;
+    use twi_overlay_core::policy::PolicyEngine;
     use twi_overlay_core::types::{
-        CommandResult, CommandSource, NormalizedReward, NormalizedUser, RedemptionUpdateMode,
+        CommandResult, CommandSource, CounterCorrectCommand, NormalizedEvent, NormalizedReward,
+        NormalizedUser, RedemptionUpdateMode,
     };
     use twi_overlay_storage::NewOauthLink;
     use twi_overlay_twitch::HelixClient;
@@ -1227,6 +2057,48 @@ mod tests {
         assert_eq!(row.0, 1);
     }
 
+    #[tokio::test]
+    async fn large_batch_is_coalesced_above_threshold() {
+        let executor = setup_executor().await;
+        executor.set_coalesce_threshold(3);
+        let commands: Vec<Command> = (0..5)
+            .map(|index| {
+                let mut command = enqueue_command();
+                if let Command::Enqueue(enqueue) = &mut command {
+                    enqueue.redemption_id = format!("red-{index}");
+                }
+                command
+            })
+            .collect();
+
+        let patches = executor
+            .execute("b-1", "UTC", &commands)
+            .await
+            .expect("execute");
+        assert_eq!(patches.len(), 1);
+        let batch = &patches[0];
+        assert_eq!(batch.kind_str(), "patch.batch");
+        assert_eq!(batch.version, 5);
+        assert_eq!(batch.data["from_version"].as_u64(), Some(1));
+        let inner = batch.data["patches"].as_array().expect("patches");
+        assert_eq!(inner.len(), 5);
+        assert!(inner
+            .iter()
+            .all(|patch| patch["type"].as_str() == Some("queue.enqueued")));
+
+        // At or below the threshold, patches are emitted individually.
+        let mut small = enqueue_command();
+        if let Command::Enqueue(enqueue) = &mut small {
+            enqueue.redemption_id = "red-small".to_string();
+        }
+        let patches = executor
+            .execute("b-1", "UTC", &[small])
+            .await
+            .expect("execute small");
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].kind_str(), "queue.enqueued");
+    }
+
     #[tokio::test]
     async fn redemption_update_generates_patch() {
         let executor = setup_executor().await;
@@ -1359,7 +2231,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn redemption_update_skips_without_oauth_link() {
+    async fn fulfill_on_complete_defers_helix_update_until_completion() {
         let server = MockServer::start_async().await;
         let helix_client = HelixClient::new(
             "client",
@@ -1367,9 +2239,489 @@ mod tests {
             Client::builder().build().expect("helix client"),
         );
         let executor = setup_executor_with_helix(helix_client).await;
+        let database = executor.database.clone();
+        sqlx::query("UPDATE broadcasters SET settings_json = ?1 WHERE id = 'b-1'")
+            .bind(r#"{"policy":{"fulfill_on":"complete","target_rewards":["r-join"]}}"#)
+            .execute(database.pool())
+            .await
+            .expect("update settings");
 
-        let enqueue = Command::Enqueue(EnqueueCommand {
-            broadcaster_id: "b-1".to_string(),
+        let now = Utc::now();
+        let command_log = database.command_log();
+        let mut tx = command_log.begin().await.expect("begin oauth tx");
+        database
+            .oauth_links()
+            .upsert_link(
+                &mut tx,
+                &NewOauthLink {
+                    id: Uuid::new_v4().to_string(),
+                    broadcaster_id: "b-1",
+                    twitch_user_id: "t-1".to_string(),
+                    scopes: REQUIRED_OAUTH_SCOPES
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    managed_scopes: REQUIRED_OAUTH_SCOPES
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    access_token: "access-token".into(),
+                    refresh_token: "refresh-token".into(),
+                    expires_at: now + ChronoDuration::hours(1),
+                    created_at: now,
+                    updated_at: now,
+                },
+            )
+            .await
+            .expect("insert oauth link");
+        tx.commit().await.expect("commit oauth link");
+
+        let patch_mock = server
+            .mock_async(|when, then| {
+                when.method(PATCH)
+                    .path("/channel_points/custom_rewards/redemptions")
+                    .query_param("broadcaster_id", "b-1")
+                    .query_param("reward_id", "r-join")
+                    .query_param("id", "red-1")
+                    .json_body(json!({ "status": "FULFILLED" }));
+                then.status(200);
+            })
+            .await;
+
+        let settings = database
+            .broadcasters()
+            .fetch_settings("b-1")
+            .await
+            .expect("settings")
+            .settings;
+        let event = NormalizedEvent::RedemptionAdd {
+            broadcaster_id: "b-1".to_string(),
+            occurred_at: now,
+            redemption_id: "red-1".to_string(),
+            user: NormalizedUser {
+                id: "u-1".to_string(),
+                login: Some("alice".to_string()),
+                display_name: Some("Alice".to_string()),
+            },
+            reward: NormalizedReward {
+                id: "r-join".to_string(),
+                title: Some("Join".to_string()),
+                cost: Some(1),
+            },
+        };
+        let outcome = PolicyEngine::new()
+            .evaluate(&settings, "UTC", &event, now)
+            .expect("evaluate");
+        let patches = executor
+            .execute("b-1", "UTC", &outcome.commands)
+            .await
+            .expect("enqueue");
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patch_mock.hits_async().await, 0);
+
+        let entry_id = patches[0].data["entry"]["id"]
+            .as_str()
+            .expect("entry id")
+            .to_string();
+        let result = executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::QueueComplete(QueueCompleteCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: now,
+                    source: CommandSource::Admin,
+                    entry_id,
+                    op_id: Uuid::new_v4().to_string(),
+                }),
+            )
+            .await
+            .expect("queue complete");
+
+        assert_eq!(patch_mock.hits_async().await, 1);
+        let kinds: Vec<_> = result.patches.iter().map(|p| p.kind_str()).collect();
+        assert_eq!(kinds, vec!["queue.completed", "redemption.updated"]);
+        assert_eq!(result.version, 3);
+    }
+
+    #[tokio::test]
+    async fn fulfill_on_complete_refunds_redemption_on_undo_and_expiry() {
+        let server = MockServer::start_async().await;
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse(&(server.base_url() + "/")).expect("helix url"),
+            Client::builder().build().expect("helix client"),
+        );
+        let executor = setup_executor_with_helix(helix_client).await;
+        let database = executor.database.clone();
+        sqlx::query("UPDATE broadcasters SET settings_json = ?1 WHERE id = 'b-1'")
+            .bind(r#"{"policy":{"fulfill_on":"complete","target_rewards":["r-join"]}}"#)
+            .execute(database.pool())
+            .await
+            .expect("update settings");
+
+        let now = Utc::now();
+        let command_log = database.command_log();
+        let mut tx = command_log.begin().await.expect("begin oauth tx");
+        database
+            .oauth_links()
+            .upsert_link(
+                &mut tx,
+                &NewOauthLink {
+                    id: Uuid::new_v4().to_string(),
+                    broadcaster_id: "b-1",
+                    twitch_user_id: "t-1".to_string(),
+                    scopes: REQUIRED_OAUTH_SCOPES
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    managed_scopes: REQUIRED_OAUTH_SCOPES
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    access_token: "access-token".into(),
+                    refresh_token: "refresh-token".into(),
+                    expires_at: now + ChronoDuration::hours(1),
+                    created_at: now,
+                    updated_at: now,
+                },
+            )
+            .await
+            .expect("insert oauth link");
+        tx.commit().await.expect("commit oauth link");
+
+        let cancel_mock = server
+            .mock_async(|when, then| {
+                when.method(PATCH)
+                    .path("/channel_points/custom_rewards/redemptions")
+                    .query_param("broadcaster_id", "b-1")
+                    .query_param("reward_id", "r-join")
+                    .json_body(json!({ "status": "CANCELED" }));
+                then.status(200);
+            })
+            .await;
+
+        let settings = database
+            .broadcasters()
+            .fetch_settings("b-1")
+            .await
+            .expect("settings")
+            .settings;
+        let mut entry_ids = Vec::new();
+        for (redemption_id, user_id) in [("red-undo", "u-1"), ("red-expire", "u-2")] {
+            let event = NormalizedEvent::RedemptionAdd {
+                broadcaster_id: "b-1".to_string(),
+                occurred_at: now,
+                redemption_id: redemption_id.to_string(),
+                user: NormalizedUser {
+                    id: user_id.to_string(),
+                    login: None,
+                    display_name: None,
+                },
+                reward: NormalizedReward {
+                    id: "r-join".to_string(),
+                    title: Some("Join".to_string()),
+                    cost: Some(1),
+                },
+            };
+            let outcome = PolicyEngine::new()
+                .evaluate(&settings, "UTC", &event, now)
+                .expect("evaluate");
+            let patches = executor
+                .execute("b-1", "UTC", &outcome.commands)
+                .await
+                .expect("enqueue");
+            entry_ids.push(
+                patches[0].data["entry"]["id"]
+                    .as_str()
+                    .expect("entry id")
+                    .to_string(),
+            );
+        }
+        assert_eq!(cancel_mock.hits_async().await, 0);
+
+        let undo = executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::QueueRemove(QueueRemoveCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: now,
+                    source: CommandSource::Admin,
+                    entry_id: entry_ids[0].clone(),
+                    reason: QueueRemovalReason::Undo,
+                    op_id: Uuid::new_v4().to_string(),
+                }),
+            )
+            .await
+            .expect("undo");
+        let kinds: Vec<_> = undo.patches.iter().map(|p| p.kind_str()).collect();
+        assert_eq!(
+            kinds,
+            vec!["queue.removed", "counter.updated", "redemption.updated"]
+        );
+        assert_eq!(undo.version, 4);
+        assert_eq!(cancel_mock.hits_async().await, 1);
+
+        let patches = executor
+            .execute(
+                "b-1",
+                "UTC",
+                &[Command::QueueRemove(QueueRemoveCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: now,
+                    source: CommandSource::Policy,
+                    entry_id: entry_ids[1].clone(),
+                    reason: QueueRemovalReason::Expired,
+                    op_id: format!("expire:{}", entry_ids[1]),
+                })],
+            )
+            .await
+            .expect("expire");
+        let kinds: Vec<_> = patches.iter().map(|p| p.kind_str()).collect();
+        assert_eq!(kinds, vec!["queue.removed", "redemption.updated"]);
+        assert_eq!(cancel_mock.hits_async().await, 2);
+
+        let modes: Vec<String> = sqlx::query_scalar(
+            "SELECT json_extract(payload_json, '$.mode') FROM command_log \
+             WHERE broadcaster_id = 'b-1' AND type = 'redemption.update' ORDER BY version",
+        )
+        .fetch_all(database.pool())
+        .await
+        .expect("redemption updates");
+        assert_eq!(modes, vec!["refund", "refund"]);
+    }
+
+    #[tokio::test]
+    async fn redemption_update_rejects_non_target_reward_when_verified() {
+        let server = MockServer::start_async().await;
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse(&(server.base_url() + "/")).expect("helix url"),
+            Client::builder().build().expect("helix client"),
+        );
+        let executor = setup_executor_with_helix(helix_client).await;
+        let database = executor.database.clone();
+        sqlx::query("UPDATE broadcasters SET settings_json = ?1 WHERE id = 'b-1'")
+            .bind(r#"{"policy":{"target_rewards":["r-join"],"verify_reward_ownership":true}}"#)
+            .execute(database.pool())
+            .await
+            .expect("update settings");
+
+        let now = Utc::now();
+        let command_log = database.command_log();
+        let mut tx = command_log.begin().await.expect("begin oauth tx");
+        database
+            .oauth_links()
+            .upsert_link(
+                &mut tx,
+                &NewOauthLink {
+                    id: Uuid::new_v4().to_string(),
+                    broadcaster_id: "b-1",
+                    twitch_user_id: "t-1".to_string(),
+                    scopes: REQUIRED_OAUTH_SCOPES
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    managed_scopes: REQUIRED_OAUTH_SCOPES
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    access_token: "access-token".into(),
+                    refresh_token: "refresh-token".into(),
+                    expires_at: now + ChronoDuration::hours(1),
+                    created_at: now,
+                    updated_at: now,
+                },
+            )
+            .await
+            .expect("insert oauth link");
+        tx.commit().await.expect("commit oauth link");
+
+        let enqueue = Command::Enqueue(EnqueueCommand {
+            broadcaster_id: "b-1".to_string(),
+            issued_at: now,
+            source: CommandSource::Policy,
+            user: NormalizedUser {
+                id: "u-1".to_string(),
+                login: Some("alice".to_string()),
+                display_name: Some("Alice".to_string()),
+            },
+            reward: NormalizedReward {
+                id: "reward-other".to_string(),
+                title: Some("Other".to_string()),
+                cost: Some(1),
+            },
+            redemption_id: "red-other".to_string(),
+            managed: Some(true),
+        });
+        executor
+            .execute("b-1", "UTC", &[enqueue])
+            .await
+            .expect("enqueue");
+
+        let patch_mock = server
+            .mock_async(|when, then| {
+                when.method(PATCH)
+                    .path("/channel_points/custom_rewards/redemptions");
+                then.status(200);
+            })
+            .await;
+
+        let update = Command::RedemptionUpdate(RedemptionUpdateCommand {
+            broadcaster_id: "b-1".to_string(),
+            issued_at: now,
+            source: CommandSource::Policy,
+            redemption_id: "red-other".to_string(),
+            mode: RedemptionUpdateMode::Consume,
+            applicable: true,
+            result: CommandResult::Skipped,
+            managed: None,
+            error: None,
+        });
+        let patches = executor
+            .execute("b-1", "UTC", &[update])
+            .await
+            .expect("execute");
+
+        patch_mock.assert_hits_async(0).await;
+
+        let patch = &patches[0];
+        assert_eq!(patch.data["applicable"].as_bool(), Some(false));
+        assert_eq!(patch.data["managed"].as_bool(), Some(false));
+        assert_eq!(patch.data["result"].as_str(), Some("skipped"));
+        assert_eq!(patch.data["error"].as_str(), Some(ERR_REWARD_NOT_MANAGED));
+    }
+
+    #[tokio::test]
+    async fn redemption_update_accepts_title_targeted_reward_when_verified() {
+        let server = MockServer::start_async().await;
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse(&(server.base_url() + "/")).expect("helix url"),
+            Client::builder().build().expect("helix client"),
+        );
+        let executor = setup_executor_with_helix(helix_client).await;
+        let database = executor.database.clone();
+        sqlx::query("UPDATE broadcasters SET settings_json = ?1 WHERE id = 'b-1'")
+            .bind(
+                r#"{"policy":{"target_rewards":[],"target_reward_titles":["Join Queue"],"verify_reward_ownership":true}}"#,
+            )
+            .execute(database.pool())
+            .await
+            .expect("update settings");
+
+        let now = Utc::now();
+        let command_log = database.command_log();
+        let mut tx = command_log.begin().await.expect("begin oauth tx");
+        database
+            .oauth_links()
+            .upsert_link(
+                &mut tx,
+                &NewOauthLink {
+                    id: Uuid::new_v4().to_string(),
+                    broadcaster_id: "b-1",
+                    twitch_user_id: "t-1".to_string(),
+                    scopes: REQUIRED_OAUTH_SCOPES
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    managed_scopes: REQUIRED_OAUTH_SCOPES
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    access_token: "access-token".into(),
+                    refresh_token: "refresh-token".into(),
+                    expires_at: now + ChronoDuration::hours(1),
+                    created_at: now,
+                    updated_at: now,
+                },
+            )
+            .await
+            .expect("insert oauth link");
+        tx.commit().await.expect("commit oauth link");
+
+        let enqueue = Command::Enqueue(EnqueueCommand {
+            broadcaster_id: "b-1".to_string(),
+            issued_at: now,
+            source: CommandSource::Policy,
+            user: NormalizedUser {
+                id: "u-1".to_string(),
+                login: Some("alice".to_string()),
+                display_name: Some("Alice".to_string()),
+            },
+            reward: NormalizedReward {
+                id: "reward-titled".to_string(),
+                title: Some("Join Queue".to_string()),
+                cost: Some(1),
+            },
+            redemption_id: "red-titled".to_string(),
+            managed: Some(true),
+        });
+        executor
+            .execute("b-1", "UTC", &[enqueue])
+            .await
+            .expect("enqueue");
+
+        let mut tx = command_log.begin().await.expect("begin verify");
+        let entry = database
+            .queue()
+            .find_entry_by_redemption_for_update(&mut tx, "b-1", "red-titled")
+            .await
+            .expect("find entry")
+            .expect("entry present");
+        assert_eq!(entry.reward_title.as_deref(), Some("Join Queue"));
+        drop(tx);
+
+        let patch_mock = server
+            .mock_async(|when, then| {
+                when.method(PATCH)
+                    .path("/channel_points/custom_rewards/redemptions")
+                    .query_param("reward_id", "reward-titled")
+                    .query_param("id", "red-titled")
+                    .json_body(json!({ "status": "FULFILLED" }));
+                then.status(200);
+            })
+            .await;
+
+        let update = Command::RedemptionUpdate(RedemptionUpdateCommand {
+            broadcaster_id: "b-1".to_string(),
+            issued_at: now,
+            source: CommandSource::Policy,
+            redemption_id: "red-titled".to_string(),
+            mode: RedemptionUpdateMode::Consume,
+            applicable: true,
+            result: CommandResult::Skipped,
+            managed: None,
+            error: None,
+        });
+        let patches = executor
+            .execute("b-1", "UTC", &[update])
+            .await
+            .expect("execute");
+
+        patch_mock.assert_hits_async(1).await;
+
+        let patch = &patches[0];
+        assert_eq!(patch.data["applicable"].as_bool(), Some(true));
+        assert_eq!(patch.data["result"].as_str(), Some("ok"));
+        assert!(patch.data.get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn redemption_update_skips_without_oauth_link() {
+        let server = MockServer::start_async().await;
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse(&(server.base_url() + "/")).expect("helix url"),
+            Client::builder().build().expect("helix client"),
+        );
+        let executor = setup_executor_with_helix(helix_client).await;
+
+        let enqueue = Command::Enqueue(EnqueueCommand {
+            broadcaster_id: "b-1".to_string(),
             issued_at: Utc::now(),
             source: CommandSource::Policy,
             user: NormalizedUser {
@@ -1523,6 +2875,323 @@ mod tests {
         assert_eq!(patch.data["error"].as_str(), Some(ERR_HELIX_UNAUTHORIZED));
     }
 
+    #[test]
+    fn day_rollover_hour_shifts_local_day_key() {
+        // 03:00 in Tokyo on 2024-01-02.
+        let at = Utc.with_ymd_and_hms(2024, 1, 1, 18, 0, 0).unwrap();
+        assert_eq!(
+            compute_local_day(at, "Asia/Tokyo", 0).unwrap(),
+            "2024-01-02"
+        );
+        assert_eq!(
+            compute_local_day(at, "Asia/Tokyo", 6).unwrap(),
+            "2024-01-01"
+        );
+
+        let (start, end) = local_day_bounds(at, "Asia/Tokyo", 6).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2023, 12, 31, 21, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2024, 1, 1, 21, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn day_rollover_hour_follows_wall_clock_across_dst() {
+        // 04:30 EDT on 2024-03-10, the morning clocks in New York jumped from 02:00 to 03:00.
+        let at = Utc.with_ymd_and_hms(2024, 3, 10, 8, 30, 0).unwrap();
+        assert_eq!(
+            compute_local_day(at, "America/New_York", 4).unwrap(),
+            "2024-03-10"
+        );
+        // 03:30 EDT is still before the 04:00 rollover.
+        let before = Utc.with_ymd_and_hms(2024, 3, 10, 7, 30, 0).unwrap();
+        assert_eq!(
+            compute_local_day(before, "America/New_York", 4).unwrap(),
+            "2024-03-09"
+        );
+
+        let (start, end) = local_day_bounds(at, "America/New_York", 4).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 3, 10, 8, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2024, 3, 11, 8, 0, 0).unwrap());
+    }
+
+    #[tokio::test]
+    async fn enqueue_before_rollover_counts_toward_previous_day() {
+        let executor = setup_executor().await;
+        sqlx::query("UPDATE broadcasters SET settings_json = ? WHERE id = 'b-1'")
+            .bind(r#"{"day_rollover_hour":6}"#)
+            .execute(executor.database.pool())
+            .await
+            .expect("update settings");
+
+        let Command::Enqueue(mut enqueue) = enqueue_command() else {
+            unreachable!()
+        };
+        enqueue.issued_at = Utc.with_ymd_and_hms(2024, 1, 2, 3, 0, 0).unwrap();
+        executor
+            .execute("b-1", "UTC", &[Command::Enqueue(enqueue)])
+            .await
+            .expect("execute");
+
+        let days: Vec<(String,)> =
+            sqlx::query_as("SELECT day FROM daily_counters WHERE broadcaster_id = 'b-1'")
+                .fetch_all(executor.database.pool())
+                .await
+                .expect("counters");
+        assert_eq!(days, vec![("2024-01-01".to_string(),)]);
+    }
+
+    #[tokio::test]
+    async fn settings_update_rejects_out_of_range_rollover_hour() {
+        let executor = setup_executor().await;
+        let err = executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::SettingsUpdate(SettingsUpdateCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: Utc::now(),
+                    source: CommandSource::Admin,
+                    patch: json!({ "day_rollover_hour": 24 }),
+                    op_id: "op-rollover".to_string(),
+                }),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CommandExecutorError::InvalidSettingsPatch(_)));
+    }
+
+    #[tokio::test]
+    async fn settings_update_validates_target_rewards() {
+        let executor = setup_executor().await;
+        let err = executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::SettingsUpdate(SettingsUpdateCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: Utc::now(),
+                    source: CommandSource::Admin,
+                    patch: json!({ "policy": { "target_rewards": ["r-join", " "] } }),
+                    op_id: "op-empty-reward".to_string(),
+                }),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CommandExecutorError::InvalidSettingsPatch(_)));
+
+        executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::SettingsUpdate(SettingsUpdateCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: Utc::now(),
+                    source: CommandSource::Admin,
+                    patch: json!({ "policy": { "target_rewards": ["r-join", "r-other", " r-join "] } }),
+                    op_id: "op-duplicate-reward".to_string(),
+                }),
+            )
+            .await
+            .expect("apply settings");
+        let profile = executor
+            .database
+            .broadcasters()
+            .fetch_settings("b-1")
+            .await
+            .expect("settings");
+        assert_eq!(
+            profile.settings.policy.target_rewards,
+            vec!["r-join".to_string(), "r-other".to_string()]
+        );
+
+        // A stored empty id predating the validation does not block patches to other keys.
+        sqlx::query("UPDATE broadcasters SET settings_json = ? WHERE id = 'b-1'")
+            .bind(json!({ "policy": { "target_rewards": ["r-join", ""] } }).to_string())
+            .execute(executor.database.pool())
+            .await
+            .expect("store legacy settings");
+        executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::SettingsUpdate(SettingsUpdateCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: Utc::now(),
+                    source: CommandSource::Admin,
+                    patch: json!({ "group_size": 3 }),
+                    op_id: "op-unrelated".to_string(),
+                }),
+            )
+            .await
+            .expect("unrelated patch applies");
+    }
+
+    #[tokio::test]
+    async fn enqueued_source_follows_command_source() {
+        let executor = setup_executor().await;
+        let mut backfill = enqueue_command();
+        if let Command::Enqueue(enqueue) = &mut backfill {
+            enqueue.redemption_id = "red-backfill".to_string();
+        }
+        backfill.set_source(CommandSource::Backfill);
+        let patches = executor
+            .execute("b-1", "UTC", &[enqueue_command(), backfill])
+            .await
+            .expect("execute");
+        assert_eq!(
+            patches[0].data["entry"]["enqueued_source"].as_str(),
+            Some("policy")
+        );
+        assert_eq!(
+            patches[1].data["entry"]["enqueued_source"].as_str(),
+            Some("backfill")
+        );
+
+        executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::QueueAdd(QueueAddCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: Utc::now(),
+                    source: CommandSource::Admin,
+                    user: NormalizedUser {
+                        id: "u-2".to_string(),
+                        login: Some("bob".to_string()),
+                        display_name: None,
+                    },
+                    op_id: "op-manual".to_string(),
+                }),
+            )
+            .await
+            .expect("queue add");
+
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT redemption_id, enqueued_source FROM queue_entries WHERE redemption_id IS NOT NULL ORDER BY redemption_id",
+        )
+        .fetch_all(executor.database.pool())
+        .await
+        .expect("sources");
+        assert_eq!(
+            rows,
+            vec![
+                ("red-1".to_string(), "policy".to_string()),
+                ("red-backfill".to_string(), "backfill".to_string()),
+            ]
+        );
+        let manual: (String,) =
+            sqlx::query_as("SELECT enqueued_source FROM queue_entries WHERE id = 'op-manual'")
+                .fetch_one(executor.database.pool())
+                .await
+                .expect("manual source");
+        assert_eq!(manual.0, "manual");
+
+        let logged: (String,) = sqlx::query_as(
+            "SELECT source FROM command_log WHERE broadcaster_id = 'b-1' AND version = 2",
+        )
+        .fetch_one(executor.database.pool())
+        .await
+        .expect("command log source");
+        assert_eq!(logged.0, "backfill");
+    }
+
+    #[tokio::test]
+    async fn counter_correct_emits_only_counter_patch() {
+        let executor = setup_executor().await;
+        executor
+            .execute("b-1", "UTC", &[enqueue_command()])
+            .await
+            .expect("enqueue");
+
+        let issued_at = Utc::now();
+        let application = executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::CounterCorrect(CounterCorrectCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at,
+                    source: CommandSource::Admin,
+                    user_id: "u-1".to_string(),
+                    count: 5,
+                    op_id: "op-counter".to_string(),
+                }),
+            )
+            .await
+            .expect("counter correct");
+
+        assert!(matches!(
+            application.result,
+            CommandApplyResult::CounterCorrected { ref user_id, count: 5 } if user_id == "u-1"
+        ));
+        assert_eq!(application.patches.len(), 1);
+        let patch = &application.patches[0];
+        assert_eq!(patch.kind_str(), "counter.updated");
+        assert_eq!(patch.data["user_id"], "u-1");
+        assert_eq!(patch.data["count"], 5);
+        assert!(patch.data["updated_at"].is_string());
+        assert_eq!(patch.at, issued_at);
+    }
+
+    #[tokio::test]
+    async fn admin_command_rejects_mismatched_source() {
+        let executor = setup_executor().await;
+        let settings_update = |source| {
+            Command::SettingsUpdate(SettingsUpdateCommand {
+                broadcaster_id: "b-1".to_string(),
+                issued_at: Utc::now(),
+                source,
+                patch: json!({ "group_size": 2 }),
+                op_id: "op-source".to_string(),
+            })
+        };
+
+        let err = executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                settings_update(CommandSource::Policy),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CommandExecutorError::SourceNotPermitted {
+                command_source: "policy",
+                audience: "admin",
+            }
+        ));
+
+        let err = executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Overlay,
+                settings_update(CommandSource::Admin),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CommandExecutorError::SourceNotPermitted {
+                audience: "overlay",
+                ..
+            }
+        ));
+
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM command_log")
+            .fetch_one(executor.database.pool())
+            .await
+            .expect("command log count");
+        assert_eq!(count, 0);
+    }
+
     #[tokio::test]
     async fn invalid_timezone_is_reported() {
         let executor = setup_executor().await;
@@ -1533,6 +3202,68 @@ mod tests {
         assert!(matches!(err, CommandExecutorError::InvalidTimezone(_)));
     }
 
+    #[tokio::test]
+    async fn admin_command_gate_rejects_enqueue_with_allowed_kinds() {
+        let executor = setup_executor().await;
+        let err = executor
+            .execute_admin_command("b-1", "UTC", Audience::Admin, enqueue_command())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CommandExecutorError::UnsupportedCommand {
+                kind: "enqueue",
+                allowed: ADMIN_COMMAND_KINDS,
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "unsupported command type: enqueue (allowed: complete, undo, settings, timezone, add, counter, noop)"
+        );
+
+        let now = Utc::now();
+        let admin_commands = [
+            Command::QueueComplete(QueueCompleteCommand {
+                broadcaster_id: "b-1".to_string(),
+                issued_at: now,
+                source: CommandSource::Admin,
+                entry_id: "e-1".to_string(),
+                op_id: "op-1".to_string(),
+            }),
+            Command::QueueRemove(QueueRemoveCommand {
+                broadcaster_id: "b-1".to_string(),
+                issued_at: now,
+                source: CommandSource::Admin,
+                entry_id: "e-1".to_string(),
+                reason: QueueRemovalReason::Undo,
+                op_id: "op-2".to_string(),
+            }),
+            Command::SettingsUpdate(SettingsUpdateCommand {
+                broadcaster_id: "b-1".to_string(),
+                issued_at: now,
+                source: CommandSource::Admin,
+                patch: json!({}),
+                op_id: "op-3".to_string(),
+            }),
+            Command::QueueAdd(QueueAddCommand {
+                broadcaster_id: "b-1".to_string(),
+                issued_at: now,
+                source: CommandSource::Admin,
+                user: NormalizedUser {
+                    id: "u-1".to_string(),
+                    login: None,
+                    display_name: None,
+                },
+                op_id: "op-4".to_string(),
+            }),
+        ];
+        for command in &admin_commands {
+            assert!(is_admin_command(command));
+            assert!(ADMIN_COMMAND_KINDS.contains(&command.metric_kind()));
+        }
+        assert!(!is_admin_command(&enqueue_command()));
+    }
+
     #[tokio::test]
     async fn queue_complete_marks_entry_completed_and_emits_patch() {
         let executor = setup_executor().await;
@@ -1554,7 +3285,7 @@ mod tests {
         });
 
         let result = executor
-            .execute_admin_command("b-1", "UTC", command)
+            .execute_admin_command("b-1", "UTC", Audience::Admin, command)
             .await
             .expect("queue complete");
 
@@ -1605,7 +3336,7 @@ mod tests {
         });
 
         let result = executor
-            .execute_admin_command("b-1", "UTC", command)
+            .execute_admin_command("b-1", "UTC", Audience::Admin, command)
             .await
             .expect("queue remove");
 
@@ -1653,6 +3384,7 @@ mod tests {
             .execute_admin_command(
                 "b-1",
                 "UTC",
+                Audience::Admin,
                 Command::QueueRemove(QueueRemoveCommand {
                     broadcaster_id: "b-1".to_string(),
                     issued_at: Utc::now(),
@@ -1682,7 +3414,7 @@ mod tests {
         });
 
         let result = executor
-            .execute_admin_command("b-1", "UTC", command)
+            .execute_admin_command("b-1", "UTC", Audience::Admin, command)
             .await
             .expect("settings update");
 
@@ -1701,6 +3433,7 @@ mod tests {
             .execute_admin_command(
                 "b-1",
                 "UTC",
+                Audience::Admin,
                 Command::SettingsUpdate(SettingsUpdateCommand {
                     broadcaster_id: "b-1".to_string(),
                     issued_at: Utc::now(),
@@ -1714,4 +3447,134 @@ mod tests {
         assert!(duplicate.duplicate);
         assert!(duplicate.patches.is_empty());
     }
+
+    #[tokio::test]
+    async fn concurrent_mutations_for_one_broadcaster_apply_sequentially() {
+        let executor = setup_executor().await;
+        let mut tasks = tokio::task::JoinSet::new();
+        for idx in 0..8 {
+            let executor = executor.clone();
+            let mut command = enqueue_command();
+            if let Command::Enqueue(enqueue) = &mut command {
+                enqueue.user.id = format!("u-{idx}");
+                enqueue.redemption_id = format!("red-{idx}");
+            }
+            tasks.spawn(async move { executor.execute("b-1", "UTC", &[command]).await });
+        }
+
+        let mut versions = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            let patches = result.expect("join").expect("execute");
+            versions.extend(patches.iter().map(|patch| patch.version));
+        }
+        versions.sort_unstable();
+        assert_eq!(versions, (1..=8).collect::<Vec<u64>>());
+
+        let current: i64 = sqlx::query_scalar(
+            "SELECT current_version FROM state_index WHERE broadcaster_id = 'b-1'",
+        )
+        .fetch_one(executor.database.pool())
+        .await
+        .expect("state index");
+        assert_eq!(current, 8);
+        let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM queue_entries")
+            .fetch_one(executor.database.pool())
+            .await
+            .expect("count queue");
+        assert_eq!(queued, 8);
+    }
+
+    #[tokio::test]
+    async fn noop_reserves_op_id_without_side_effects() {
+        let executor = setup_executor().await;
+        let noop = Command::Noop(NoopCommand {
+            broadcaster_id: "b-1".to_string(),
+            issued_at: Utc::now(),
+            source: CommandSource::Admin,
+            op_id: "op-noop".to_string(),
+        });
+
+        let application = executor
+            .execute_admin_command("b-1", "UTC", Audience::Admin, noop.clone())
+            .await
+            .expect("noop");
+        assert_eq!(application.version, 1);
+        assert!(application.patches.is_empty());
+        assert!(!application.duplicate);
+
+        let replay = executor
+            .execute_admin_command("b-1", "UTC", Audience::Admin, noop)
+            .await
+            .expect("noop replay");
+        assert_eq!(replay.version, 1);
+        assert!(replay.duplicate);
+
+        let command_log = executor.database.command_log();
+        let mut tx = command_log.begin().await.expect("begin");
+        let logged = command_log
+            .find_by_op_id(&mut tx, "b-1", "op-noop")
+            .await
+            .expect("find op_id")
+            .expect("logged noop");
+        tx.commit().await.expect("commit");
+        assert_eq!(logged.version, 1);
+        assert_eq!(logged.command_type, "noop");
+
+        let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM queue_entries")
+            .fetch_one(executor.database.pool())
+            .await
+            .expect("count queue");
+        let counters: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM daily_counters")
+            .fetch_one(executor.database.pool())
+            .await
+            .expect("count counters");
+        assert_eq!((queued, counters), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn command_log_records_command_source() {
+        let executor = setup_executor().await;
+        let enqueue_patch = executor
+            .execute("b-1", "UTC", &[enqueue_command()])
+            .await
+            .expect("enqueue");
+        let entry_id = enqueue_patch[0].data["entry"]["id"]
+            .as_str()
+            .expect("entry id")
+            .to_string();
+
+        executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::QueueComplete(QueueCompleteCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: Utc::now(),
+                    source: CommandSource::Admin,
+                    entry_id,
+                    op_id: Uuid::new_v4().to_string(),
+                }),
+            )
+            .await
+            .expect("queue complete");
+
+        let logged = executor
+            .database
+            .command_log()
+            .list_recent("b-1", 10)
+            .await
+            .expect("list command log");
+        let sources: Vec<_> = logged
+            .iter()
+            .map(|entry| (entry.command_type.as_str(), entry.source.as_deref()))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                ("queue.complete", Some("admin")),
+                ("enqueue", Some("policy")),
+            ]
+        );
+    }
 }
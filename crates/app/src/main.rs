@@ -2,7 +2,10 @@ mod backfill;
 mod command;
 mod maintenance;
 mod oauth;
+mod policy_trace;
 mod problem;
+#[cfg(unix)]
+mod reload;
 mod router;
 mod sse;
 mod state;
@@ -13,8 +16,8 @@ mod webhook;
 use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use reqwest::Client;
-use tracing::info;
-use twi_overlay_storage::Database;
+use tracing::{info, warn};
+use twi_overlay_storage::{Database, DatabaseOptions};
 use twi_overlay_twitch::{HelixClient, TwitchOAuthClient};
 use twi_overlay_util::{load_env_file, AppConfig};
 use url::Url;
@@ -32,12 +35,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tap_hub.spawn_mock_publisher();
     }
 
-    let database = Database::connect(&config.database_url).await?;
+    let database = Database::connect_with(
+        &config.database_url,
+        DatabaseOptions {
+            wal_autocheckpoint: config.sqlite_wal_autocheckpoint,
+            ..DatabaseOptions::default()
+        },
+    )
+    .await?;
     database.run_migrations().await?;
 
-    let _maintenance_handle =
-        maintenance::MaintenanceWorker::new(database.clone(), tap_hub.clone()).spawn();
-
     let webhook_secret: Arc<[u8]> = Arc::from(
         config
             .webhook_secret
@@ -75,18 +82,86 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Duration::from_secs(config.helix_backfill_interval_secs),
         config.helix_backfill_page_size,
     );
+    let state = state
+        .with_environment(config.environment)
+        .with_snapshot_queue_limit(config.state_snapshot_max_queue)
+        .with_oauth_login_cooldown(Duration::from_secs(config.oauth_login_cooldown_secs))
+        .with_patch_coalesce_threshold(config.patch_coalesce_threshold)
+        .with_eventsub_transport(config.eventsub_transport)
+        .with_eventsub_persist_types(&config.eventsub_persist_types)
+        .with_policy_trace_capacity(config.policy_trace_capacity)
+        .with_sse_max_filter_types(config.sse_max_filter_types)
+        .with_sse_max_lag(config.sse_max_lag)
+        .with_enabled_endpoints(&config.enabled_endpoints)
+        .with_sse_heartbeat(
+            &config.sse_heartbeat_text,
+            config.sse_heartbeat_event.as_deref(),
+        )
+        .with_single_use_sse_audiences(
+            config
+                .sse_single_use_audiences
+                .iter()
+                .filter_map(|value| sse::Audience::parse(value)),
+        );
+
+    let _maintenance_handle =
+        maintenance::MaintenanceWorker::new(state.storage().clone(), tap_hub.clone())
+            .with_queue_expiry(state.command_executor().clone(), state.sse().clone())
+            .with_checkpoint_threshold(config.sqlite_wal_checkpoint_pages)
+            .spawn();
 
     let _backfill_handle = backfill_worker.spawn();
 
+    #[cfg(unix)]
+    let _reload_handle = reload::spawn(state.clone());
+
     let addr: SocketAddr = config.bind_addr;
     info!(stage = "app", %addr, env = %config.environment.as_str(), "starting HTTP server");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
+    let sse = state.sse().clone();
     axum::serve(listener, router::app_router(state))
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            info!(stage = "app", "shutdown requested; draining connections");
+            // Open SSE streams never complete on their own and would hold the drain forever.
+            sse.shutdown();
+        })
         .await
         .map_err(|err| err.into())
 }
 
+/// Resolves on Ctrl-C, or on SIGTERM where the platform has it.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            warn!(stage = "app", error = %err, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                warn!(stage = "app", error = %err, "failed to install SIGTERM handler");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
 fn ensure_trailing_slash(value: &str) -> String {
     if value.ends_with('/') {
         value.to_string()
@@ -6,22 +6,34 @@ use serde_json::json;
 use sqlx::Error as SqlxError;
 use thiserror::Error;
 use tokio::time::{interval, MissedTickBehavior};
-use tracing::{error, info, warn};
-use twi_overlay_storage::Database;
+use tracing::{debug, error, info, warn};
+use twi_overlay_core::types::{Command, CommandSource, QueueRemovalReason, QueueRemoveCommand};
+use twi_overlay_storage::{Database, SettingsError, SseTokenError};
 
+use crate::command::CommandExecutor;
+use crate::sse::SseHub;
 use crate::tap::{StageEvent, StageKind, StageMetadata, StagePayload, TapHub};
 
 const TTL_HOURS: i64 = 72;
 const BATCH_LIMIT: i64 = 1000;
 const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
 
-/// Background worker responsible for TTL deletion and WAL checkpoints.
+/// Background worker responsible for TTL deletion, queue expiry, and WAL checkpoints.
 #[derive(Clone)]
 pub struct MaintenanceWorker {
     database: Database,
     tap: TapHub,
     clock: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
     interval: Duration,
+    queue_expiry: Option<QueueExpiry>,
+    checkpoint_threshold_pages: Option<u64>,
+}
+
+/// Dependencies needed to expire queue entries through the command pipeline.
+#[derive(Clone)]
+struct QueueExpiry {
+    executor: CommandExecutor,
+    sse: SseHub,
 }
 
 impl MaintenanceWorker {
@@ -32,9 +44,23 @@ impl MaintenanceWorker {
             tap,
             clock: Arc::new(Utc::now),
             interval: DEFAULT_INTERVAL,
+            queue_expiry: None,
+            checkpoint_threshold_pages: None,
         }
     }
 
+    /// Only checkpoints once the WAL holds more than `pages` frames; `None` checkpoints every cycle.
+    pub fn with_checkpoint_threshold(mut self, pages: Option<u64>) -> Self {
+        self.checkpoint_threshold_pages = pages;
+        self
+    }
+
+    /// Enables `queue_entry_ttl_secs` expiry, issuing removals via `executor` and broadcasting over `sse`.
+    pub fn with_queue_expiry(mut self, executor: CommandExecutor, sse: SseHub) -> Self {
+        self.queue_expiry = Some(QueueExpiry { executor, sse });
+        self
+    }
+
     /// Overrides the clock used for determining TTL thresholds.
     #[cfg(test)]
     pub fn with_clock(mut self, clock: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>) -> Self {
@@ -60,7 +86,7 @@ impl MaintenanceWorker {
         }
     }
 
-    /// Executes one maintenance cycle (TTL + checkpoint).
+    /// Executes one maintenance cycle (TTL + queue expiry + checkpoint).
     pub async fn run_once(&self) -> Result<(), MaintenanceError> {
         let now = (self.clock)();
         let threshold = now - ChronoDuration::hours(TTL_HOURS);
@@ -119,11 +145,123 @@ impl MaintenanceWorker {
             }),
         );
 
+        let (token_deleted, token_busy) = self
+            .delete_expired_rows("sse_token_uses", now, |repo_now| async move {
+                self.database
+                    .sse_tokens()
+                    .purge_expired(repo_now, BATCH_LIMIT)
+                    .await
+                    .map_err(|SseTokenError::Database(err)| err)
+            })
+            .await?;
+
+        info!(
+            stage = "storage",
+            table = "sse_token_uses",
+            deleted = token_deleted,
+            busy = token_busy,
+            "sse_token_uses expiry sweep completed"
+        );
+
+        self.expire_queue_entries(now).await?;
+
         self.run_checkpoint().await?;
 
         Ok(())
     }
 
+    async fn expire_queue_entries(&self, now: DateTime<Utc>) -> Result<(), MaintenanceError> {
+        let Some(expiry) = &self.queue_expiry else {
+            return Ok(());
+        };
+
+        let profiles = self
+            .database
+            .broadcasters()
+            .list_settings()
+            .await
+            .map_err(|source| MaintenanceError::QueueExpiry { source })?;
+
+        for (broadcaster_id, profile) in profiles {
+            let Some(ttl_secs) = profile.settings.queue_entry_ttl_secs.filter(|ttl| *ttl > 0)
+            else {
+                continue;
+            };
+            let threshold = now - ChronoDuration::seconds(ttl_secs as i64);
+            let entry_ids = match self
+                .database
+                .queue()
+                .list_queued_before(&broadcaster_id, threshold, BATCH_LIMIT)
+                .await
+            {
+                Ok(ids) if ids.is_empty() => continue,
+                Ok(ids) => ids,
+                Err(err) => {
+                    warn!(stage = "storage", broadcaster = %broadcaster_id, error = %err, "failed to list stale queue entries");
+                    continue;
+                }
+            };
+
+            // Each entry is expired on its own so one failing entry does not hold back the rest.
+            let mut expired = 0u64;
+            for entry_id in &entry_ids {
+                let command = Command::QueueRemove(QueueRemoveCommand {
+                    broadcaster_id: broadcaster_id.clone(),
+                    issued_at: now,
+                    source: CommandSource::Policy,
+                    entry_id: entry_id.clone(),
+                    reason: QueueRemovalReason::Expired,
+                    op_id: format!("expire:{entry_id}"),
+                });
+                let guard = expiry.executor.lock(&broadcaster_id).await;
+                let patches = match expiry
+                    .executor
+                    .execute_locked(&guard, &profile.timezone, &[command])
+                    .await
+                {
+                    Ok(patches) => patches,
+                    Err(err) => {
+                        warn!(stage = "storage", broadcaster = %broadcaster_id, entry_id = %entry_id, error = %err, "failed to expire stale queue entry");
+                        continue;
+                    }
+                };
+                for patch in &patches {
+                    if let Err(err) = expiry
+                        .sse
+                        .broadcast_patch(&broadcaster_id, patch, now)
+                        .await
+                    {
+                        warn!(stage = "sse", broadcaster = %broadcaster_id, error = %err, "failed to broadcast queue expiry patch");
+                    }
+                }
+                drop(guard);
+                expired += 1;
+            }
+            if expired == 0 {
+                continue;
+            }
+
+            counter!("queue_expired_total").increment(expired);
+            info!(
+                stage = "storage",
+                broadcaster = %broadcaster_id,
+                expired,
+                threshold = %threshold.to_rfc3339(),
+                "stale queue entries expired"
+            );
+            self.publish_storage_event(
+                "queue.expired",
+                json!({
+                    "broadcaster_id": broadcaster_id,
+                    "expired": expired,
+                    "threshold": threshold.to_rfc3339(),
+                }),
+            );
+        }
+
+        Ok(())
+    }
+
     async fn delete_expired_rows<Fut>(
         &self,
         table: &'static str,
@@ -160,6 +298,24 @@ impl MaintenanceWorker {
     }
 
     async fn run_checkpoint(&self) -> Result<(), MaintenanceError> {
+        if let Some(threshold) = self.checkpoint_threshold_pages {
+            let wal_pages = self
+                .database
+                .wal_page_count()
+                .await
+                .map_err(|source| MaintenanceError::Checkpoint { source })?;
+            if !matches!(wal_pages, Some(pages) if pages > threshold) {
+                counter!("db_checkpoint_skipped_total").increment(1);
+                debug!(
+                    stage = "storage",
+                    wal_pages = ?wal_pages,
+                    threshold_pages = threshold,
+                    "WAL below checkpoint threshold; skipping"
+                );
+                return Ok(());
+            }
+        }
+
         let start = std::time::Instant::now();
         let checkpoint_result = self.database.wal_checkpoint_truncate().await;
 
@@ -252,6 +408,11 @@ pub enum MaintenanceError {
         #[source]
         source: SqlxError,
     },
+    #[error("failed to load settings for queue expiry")]
+    QueueExpiry {
+        #[source]
+        source: SettingsError,
+    },
     #[error("failed to run WAL checkpoint")]
     Checkpoint {
         #[source]
@@ -272,7 +433,11 @@ mod tests {
     use std::borrow::Cow;
 
     use crate::telemetry;
+    use chrono::TimeZone;
     use tokio::time::timeout;
+    use twi_overlay_core::types::{EnqueueCommand, NormalizedReward, NormalizedUser};
+    use twi_overlay_twitch::HelixClient;
+    use url::Url;
 
     async fn setup_db() -> Database {
         let db = Database::connect("sqlite::memory:?cache=shared")
@@ -343,6 +508,7 @@ mod tests {
                     twi_overlay_storage::NewCommandLog {
                         broadcaster_id: "b-1",
                         op_id: Some(&format!("op-{idx}")),
+                        source: twi_overlay_core::types::CommandSource::Policy,
                         command_type: "queue.enqueue",
                         payload_json: "{}",
                         created_at,
@@ -392,4 +558,245 @@ mod tests {
 
         // Metrics exporter is initialised; individual counters are validated via integration tests.
     }
+
+    async fn run_queue_expiry(decrement: bool) -> (Database, String, i64) {
+        let db = setup_db().await;
+        let settings = json!({
+            "queue_entry_ttl_secs": 600,
+            "expire_decrement_counts": decrement,
+        });
+        sqlx::query("UPDATE broadcasters SET settings_json = ? WHERE id = 'b-1'")
+            .bind(settings.to_string())
+            .execute(db.pool())
+            .await
+            .expect("update settings");
+
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let clock: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync> = Arc::new(move || now);
+        let tap = TapHub::new();
+        let helix = HelixClient::new(
+            "client",
+            Url::parse("https://api.twitch.tv/helix/").expect("url"),
+            reqwest::Client::new(),
+        );
+        let executor = CommandExecutor::new(db.clone(), tap.clone(), clock.clone(), helix);
+        let sse = SseHub::new(db.clone(), 64, Duration::from_secs(60));
+
+        let enqueue = |user: &str, issued_at: DateTime<Utc>| {
+            Command::Enqueue(EnqueueCommand {
+                broadcaster_id: "b-1".to_string(),
+                issued_at,
+                source: CommandSource::Policy,
+                user: NormalizedUser {
+                    id: user.to_string(),
+                    login: None,
+                    display_name: None,
+                },
+                reward: NormalizedReward {
+                    id: "reward-1".to_string(),
+                    title: None,
+                    cost: None,
+                },
+                redemption_id: format!("red-{user}"),
+                managed: None,
+            })
+        };
+        executor
+            .execute(
+                "b-1",
+                "UTC",
+                &[
+                    enqueue("stale", now - ChronoDuration::minutes(20)),
+                    enqueue("fresh", now - ChronoDuration::minutes(1)),
+                ],
+            )
+            .await
+            .expect("enqueue");
+
+        let worker = MaintenanceWorker::new(db.clone(), tap)
+            .with_clock(clock)
+            .with_queue_expiry(executor, sse);
+        worker.run_once().await.expect("run_once");
+
+        let (stale_status, stale_reason): (String, Option<String>) = sqlx::query_as(
+            "SELECT status, status_reason FROM queue_entries WHERE user_id = 'stale'",
+        )
+        .fetch_one(db.pool())
+        .await
+        .expect("stale entry");
+        assert_eq!(stale_status, "REMOVED");
+        assert_eq!(stale_reason.as_deref(), Some("EXPIRED"));
+
+        let (stale_count,): (i64,) = sqlx::query_as(
+            "SELECT count FROM daily_counters WHERE user_id = 'stale' AND day = '2024-01-01'",
+        )
+        .fetch_one(db.pool())
+        .await
+        .expect("stale counter");
+
+        let (fresh_status,): (String,) =
+            sqlx::query_as("SELECT status FROM queue_entries WHERE user_id = 'fresh'")
+                .fetch_one(db.pool())
+                .await
+                .expect("fresh entry");
+        (db, fresh_status, stale_count)
+    }
+
+    #[tokio::test]
+    async fn run_once_expires_stale_queue_entries() {
+        telemetry::init_metrics().expect("metrics");
+
+        let (_db, fresh_status, stale_count) = run_queue_expiry(false).await;
+        assert_eq!(fresh_status, "QUEUED");
+        assert_eq!(stale_count, 1);
+
+        let (_db, fresh_status, stale_count) = run_queue_expiry(true).await;
+        assert_eq!(fresh_status, "QUEUED");
+        assert_eq!(stale_count, 0);
+    }
+
+    #[tokio::test]
+    async fn queue_expiry_skips_failing_entry_and_expires_the_rest() {
+        telemetry::init_metrics().expect("metrics");
+        let db = setup_db().await;
+        sqlx::query("UPDATE broadcasters SET settings_json = ? WHERE id = 'b-1'")
+            .bind(json!({ "queue_entry_ttl_secs": 600 }).to_string())
+            .execute(db.pool())
+            .await
+            .expect("update settings");
+
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let clock: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync> = Arc::new(move || now);
+        let tap = TapHub::new();
+        let helix = HelixClient::new(
+            "client",
+            Url::parse("https://api.twitch.tv/helix/").expect("url"),
+            reqwest::Client::new(),
+        );
+        let executor = CommandExecutor::new(db.clone(), tap.clone(), clock.clone(), helix);
+        let sse = SseHub::new(db.clone(), 64, Duration::from_secs(60));
+
+        let commands: Vec<Command> = [("blocked", 30), ("stale", 20)]
+            .into_iter()
+            .map(|(user, minutes)| {
+                Command::Enqueue(EnqueueCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: now - ChronoDuration::minutes(minutes),
+                    source: CommandSource::Policy,
+                    user: NormalizedUser {
+                        id: user.to_string(),
+                        login: None,
+                        display_name: None,
+                    },
+                    reward: NormalizedReward {
+                        id: "reward-1".to_string(),
+                        title: None,
+                        cost: None,
+                    },
+                    redemption_id: format!("red-{user}"),
+                    managed: None,
+                })
+            })
+            .collect();
+        executor
+            .execute("b-1", "UTC", &commands)
+            .await
+            .expect("enqueue");
+
+        // An unrelated command already owns the op id of the oldest entry's expiry.
+        let (blocked_id,): (String,) =
+            sqlx::query_as("SELECT id FROM queue_entries WHERE user_id = 'blocked'")
+                .fetch_one(db.pool())
+                .await
+                .expect("blocked entry");
+        sqlx::query(
+            "INSERT INTO command_log (broadcaster_id, version, op_id, source, type, payload_json, created_at) \
+             VALUES ('b-1', 999, ?, 'policy', 'queue.complete', '{}', '2024-01-01T00:00:00Z')",
+        )
+        .bind(format!("expire:{blocked_id}"))
+        .execute(db.pool())
+        .await
+        .expect("insert conflicting op id");
+
+        let worker = MaintenanceWorker::new(db.clone(), tap)
+            .with_clock(clock)
+            .with_queue_expiry(executor, sse);
+        worker.run_once().await.expect("run_once");
+
+        let statuses: Vec<(String, String)> =
+            sqlx::query_as("SELECT user_id, status FROM queue_entries ORDER BY user_id")
+                .fetch_all(db.pool())
+                .await
+                .expect("statuses");
+        assert_eq!(
+            statuses,
+            vec![
+                ("blocked".to_string(), "QUEUED".to_string()),
+                ("stale".to_string(), "REMOVED".to_string()),
+            ]
+        );
+    }
+
+    fn drain_messages(rx: &mut tokio::sync::broadcast::Receiver<StageEvent>) -> Vec<String> {
+        let mut messages = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            messages.extend(event.meta.message);
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn checkpoint_runs_only_after_wal_exceeds_threshold() {
+        telemetry::init_metrics().expect("metrics");
+        let dir = tempfile::tempdir().expect("tempdir");
+        let url = format!(
+            "sqlite://{}?mode=rwc",
+            dir.path().join("maintenance.db").display()
+        );
+        let db = Database::connect(&url).await.expect("connect");
+        db.run_migrations().await.expect("migrations");
+        db.wal_checkpoint_truncate()
+            .await
+            .expect("initial checkpoint");
+
+        let tap = TapHub::new();
+        let mut tap_rx = tap.subscribe();
+        let worker = MaintenanceWorker::new(db.clone(), tap).with_checkpoint_threshold(Some(20));
+
+        worker.run_once().await.expect("idle run");
+        let messages = drain_messages(&mut tap_rx);
+        assert!(!messages.iter().any(|message| message == "wal.checkpoint"));
+
+        sqlx::query(
+            "INSERT INTO broadcasters (id, twitch_broadcaster_id, display_name, timezone, settings_json, created_at, updated_at) \
+             VALUES ('b-1', 'twitch-1', 'Example', 'UTC', '{}', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        )
+        .execute(db.pool())
+        .await
+        .expect("insert broadcaster");
+        let payload = "x".repeat(2048);
+        let now = Utc::now();
+        for idx in 0..100 {
+            db.event_raw()
+                .insert(twi_overlay_storage::NewEventRaw {
+                    id: Cow::Owned(format!("evt-{idx}")),
+                    broadcaster_id: Cow::Borrowed("b-1"),
+                    msg_id: Cow::Owned(format!("msg-{idx}")),
+                    event_type: Cow::Borrowed("test.event"),
+                    payload_json: Cow::Borrowed(&payload),
+                    event_at: now,
+                    received_at: now,
+                    source: "webhook",
+                })
+                .await
+                .expect("insert event");
+        }
+        let grown = db.wal_page_count().await.expect("wal pages").unwrap();
+        assert!(grown > 20, "WAL should exceed the threshold, got {grown}");
+
+        worker.run_once().await.expect("busy run");
+        let messages = drain_messages(&mut tap_rx);
+        assert!(messages.iter().any(|message| message == "wal.checkpoint"));
+        assert_eq!(db.wal_page_count().await.expect("wal pages"), Some(0));
+    }
 }
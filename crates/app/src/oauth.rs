@@ -1,6 +1,6 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Redirect, Response},
     Json,
 };
@@ -12,7 +12,14 @@ use rand::{rngs::OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sha2::{Digest, Sha256};
-use tracing::{error, warn};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tracing::{error, info, warn};
 use twi_overlay_storage::{
     NewOauthLink, NewOauthLoginState, OauthFailure, OauthLink, OauthLoginState, OauthTokenUpdate,
     OauthValidationResult, StateIndexError,
@@ -28,7 +35,8 @@ use std::time::Duration as StdDuration;
 use twi_overlay_twitch::HelixClient;
 
 use crate::problem::ProblemResponse;
-use crate::router::AppState;
+use crate::router::{extract_bearer_token, problem_for_token_error, AppState};
+use crate::sse::Audience;
 use crate::tap::{StageEvent, StageKind, StageMetadata, StagePayload};
 
 const OAUTH_SCOPES: &[&str] = &["channel:read:redemptions", "channel:manage:redemptions"];
@@ -39,6 +47,55 @@ const ERROR_REDIRECT_PATH: &str = "/admin/oauth/error";
 const REFRESH_LEEWAY_SECS: i64 = 300;
 const CODE_VERIFIER_LEN: usize = 64;
 
+/// Minimum spacing between OAuth login initiations for a single broadcaster.
+///
+/// A zero window disables the throttle.
+#[derive(Clone, Default)]
+pub struct LoginCooldown {
+    window_ms: Arc<AtomicI64>,
+    last_started: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+}
+
+impl LoginCooldown {
+    pub fn new(window: std::time::Duration) -> Self {
+        let cooldown = Self::default();
+        cooldown.set_window(window);
+        cooldown
+    }
+
+    /// Replaces the window for subsequent logins; shared by every clone of this cooldown.
+    pub fn set_window(&self, window: std::time::Duration) {
+        let window_ms = i64::try_from(window.as_millis()).unwrap_or(i64::MAX);
+        self.window_ms.store(window_ms, Ordering::Relaxed);
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::milliseconds(self.window_ms.load(Ordering::Relaxed))
+    }
+
+    /// Returns the remaining cooldown when a login was started too recently.
+    fn remaining(&self, broadcaster_id: &str, now: DateTime<Utc>) -> Option<Duration> {
+        let window = self.window();
+        if window <= Duration::zero() {
+            return None;
+        }
+        let last_started = self.last_started.lock().expect("login cooldown poisoned");
+        let started_at = last_started.get(broadcaster_id)?;
+        let remaining = *started_at + window - now;
+        (remaining > Duration::zero()).then_some(remaining)
+    }
+
+    fn record(&self, broadcaster_id: &str, now: DateTime<Utc>) {
+        let window = self.window();
+        if window <= Duration::zero() {
+            return;
+        }
+        let mut last_started = self.last_started.lock().expect("login cooldown poisoned");
+        last_started.retain(|_, started_at| *started_at + window > now);
+        last_started.insert(broadcaster_id.to_string(), now);
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LoginQuery {
     pub broadcaster: String,
@@ -77,6 +134,66 @@ pub struct ValidateResponse {
     next_check_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ValidateAllQuery {
+    pub broadcaster: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum ValidateSummaryStatus {
+    Ok,
+    Refresh,
+    Reauth,
+    Failed,
+}
+
+impl From<ValidateStatus> for ValidateSummaryStatus {
+    fn from(status: ValidateStatus) -> Self {
+        match status {
+            ValidateStatus::Ok => Self::Ok,
+            ValidateStatus::Refresh => Self::Refresh,
+            ValidateStatus::Reauth => Self::Reauth,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct ValidateSummary {
+    broadcaster: String,
+    status: ValidateSummaryStatus,
+    next_check_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ValidateSummary {
+    fn from_outcome(
+        broadcaster: String,
+        outcome: Result<Json<ValidateResponse>, ProblemResponse>,
+    ) -> Self {
+        match outcome {
+            Ok(Json(response)) => Self {
+                broadcaster,
+                status: response.status.into(),
+                next_check_at: response.next_check_at,
+                error: None,
+            },
+            Err(problem) => Self {
+                broadcaster,
+                status: ValidateSummaryStatus::Failed,
+                next_check_at: None,
+                error: Some(problem.problem_type().to_string()),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidateAllResponse {
+    results: Vec<ValidateSummary>,
+}
+
 pub async fn login(
     State(state): State<AppState>,
     Query(params): Query<LoginQuery>,
@@ -94,18 +211,34 @@ pub async fn login(
     }
 
     let now = state.now();
+    if let Some(remaining) = state
+        .oauth_login_cooldown()
+        .remaining(&params.broadcaster, now)
+    {
+        return Err(ProblemResponse::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "oauth_login_throttled",
+            format!(
+                "OAuth login was started recently; retry in {}s",
+                remaining.num_seconds().max(1)
+            ),
+        ));
+    }
+
     let login_repo = state.storage().oauth_login_states();
     let has_active = login_repo
         .has_active(&params.broadcaster, now)
         .await
         .map_err(|err| {
+            let incident = ProblemResponse::new_incident();
             error!(
+                incident = %incident,
                 stage = "oauth",
                 broadcaster = %params.broadcaster,
                 error = %err,
                 "failed to query active oauth login state"
             );
-            internal_error("failed to evaluate existing OAuth state")
+            internal_error("failed to evaluate existing OAuth state").with_incident(incident)
         })?;
 
     if has_active {
@@ -131,14 +264,21 @@ pub async fn login(
             login_hint: login_hint.as_deref(),
         })
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to build authorize url");
-            internal_error("failed to build Twitch authorize URL")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to build authorize url"
+            );
+            internal_error("failed to build Twitch authorize URL").with_incident(incident)
         })?;
 
     let expires_at = now
         + Duration::from_std(state.oauth_state_ttl()).map_err(|err| {
-            error!(stage = "oauth", error = %err, "invalid oauth state ttl");
-            internal_error("invalid OAuth state TTL")
+            let incident = ProblemResponse::new_incident();
+            error!(incident = %incident, stage = "oauth", error = %err, "invalid oauth state ttl");
+            internal_error("invalid OAuth state TTL").with_incident(incident)
         })?;
 
     login_repo
@@ -152,9 +292,18 @@ pub async fn login(
         })
         .await
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to persist oauth login state");
-            internal_error("failed to persist OAuth login state")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to persist oauth login state"
+            );
+            internal_error("failed to persist OAuth login state").with_incident(incident)
         })?;
+    state
+        .oauth_login_cooldown()
+        .record(&params.broadcaster, now);
 
     publish_oauth_event(
         &state,
@@ -281,7 +430,8 @@ pub async fn callback(
         }
     };
 
-    if let Some(missing) = missing_required_scope(&validation.scopes) {
+    let scopes = granted_scopes(&token_response, &validation);
+    if let Some(missing) = missing_required_scope(&scopes) {
         warn!(
             stage = "oauth",
             broadcaster = %login_state.broadcaster_id,
@@ -316,7 +466,8 @@ pub async fn callback(
         &login_state,
         &token_response,
         &refresh_token,
-        &validation,
+        &validation.user_id,
+        &scopes,
         now,
     )
     .await?;
@@ -328,7 +479,7 @@ pub async fn callback(
         "oauth.callback.success",
         json!({
             "twitch_user_id": validation.user_id,
-            "scopes": validation.scopes,
+            "scopes": scopes,
         }),
     );
 
@@ -350,8 +501,14 @@ pub async fn validate(
         .fetch_by_broadcaster(&body.broadcaster)
         .await
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to fetch oauth link");
-            internal_error("failed to load OAuth link")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to fetch oauth link"
+            );
+            internal_error("failed to load OAuth link").with_incident(incident)
         })?
     else {
         return Err(ProblemResponse::new(
@@ -361,11 +518,76 @@ pub async fn validate(
         ));
     };
 
+    validate_link(&state, &body.broadcaster, link, body.force).await
+}
+
+/// Validates the caller's active OAuth links, refreshing tokens that are close to expiry.
+///
+/// Admin tokens are scoped to one broadcaster, so the sweep never reaches another tenant's link.
+pub async fn validate_all(
+    State(state): State<AppState>,
+    Query(query): Query<ValidateAllQuery>,
+    headers: HeaderMap,
+) -> Result<Json<ValidateAllResponse>, ProblemResponse> {
+    let token = extract_bearer_token(&headers).ok_or_else(|| {
+        counter!("api_oauth_validate_all_requests_total", "result" => "unauthorized").increment(1);
+        ProblemResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "validate-all endpoint requires a bearer token",
+        )
+    })?;
+
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(token, Audience::Admin, &query.broadcaster, now)
+    {
+        counter!("api_oauth_validate_all_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
+
+    let link = state
+        .storage()
+        .oauth_links()
+        .fetch_by_broadcaster(&query.broadcaster)
+        .await
+        .map_err(|err| {
+            counter!("api_oauth_validate_all_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to load oauth link"
+            );
+            internal_error("failed to load OAuth links").with_incident(incident)
+        })?;
+
+    let mut results = Vec::new();
+    // Same selection as `list_active`: expired or reauth-flagged links are left alone.
+    if let Some(link) = link.filter(|link| link.expires_at > now && !link.requires_reauth) {
+        let broadcaster = link.broadcaster_id.clone();
+        let outcome = validate_link(&state, &broadcaster, link, false).await;
+        results.push(ValidateSummary::from_outcome(broadcaster, outcome));
+    }
+
+    counter!("api_oauth_validate_all_requests_total", "result" => "ok").increment(1);
+    Ok(Json(ValidateAllResponse { results }))
+}
+
+async fn validate_link(
+    state: &AppState,
+    broadcaster: &str,
+    link: OauthLink,
+    force: bool,
+) -> Result<Json<ValidateResponse>, ProblemResponse> {
     if link.requires_reauth {
         publish_oauth_event(
-            &state,
+            state,
             state.now(),
-            &body.broadcaster,
+            broadcaster,
             "oauth.validate.reauth_required",
             json!({ "reason": "flagged" }),
         );
@@ -377,12 +599,12 @@ pub async fn validate(
     }
 
     let refresh_deadline = state.now() + Duration::seconds(REFRESH_LEEWAY_SECS);
-    let should_refresh = body.force || link.expires_at <= refresh_deadline;
+    let should_refresh = force || link.expires_at <= refresh_deadline;
 
     if should_refresh {
-        handle_refresh(&state, &body.broadcaster, link).await
+        handle_refresh(state, broadcaster, link).await
     } else {
-        handle_validation(&state, &body.broadcaster, link).await
+        handle_validation(state, broadcaster, link).await
     }
 }
 
@@ -469,7 +691,8 @@ async fn handle_refresh(
         }
     };
 
-    if let Some(missing) = missing_required_scope(&validation.scopes) {
+    let scopes = granted_scopes(&token_response, &validation);
+    if let Some(missing) = missing_required_scope(&scopes) {
         if record_failure(state, broadcaster, &link, missing, true)
             .await
             .is_err()
@@ -502,8 +725,9 @@ async fn handle_refresh(
     let storage = state.storage().clone();
     let command_repo = storage.command_log();
     let mut tx = command_repo.begin().await.map_err(|err| {
-        error!(stage = "oauth", error = %err, "failed to begin transaction");
-        internal_error("failed to begin transaction")
+        let incident = ProblemResponse::new_incident();
+        error!(incident = %incident, stage = "oauth", error = %err, "failed to begin transaction");
+        internal_error("failed to begin transaction").with_incident(incident)
     })?;
 
     let updated = storage
@@ -516,8 +740,8 @@ async fn handle_refresh(
                 access_token: token_response.access_token.clone(),
                 refresh_token,
                 expires_at,
-                scopes: validation.scopes.clone(),
-                managed_scopes: managed_scopes(&validation.scopes),
+                scopes: scopes.clone(),
+                managed_scopes: managed_scopes(&scopes),
                 refreshed_at,
                 validated_at: refreshed_at,
                 updated_at: refreshed_at,
@@ -525,15 +749,31 @@ async fn handle_refresh(
         )
         .await
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to update oauth tokens");
-            internal_error("failed to persist refreshed tokens")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to update oauth tokens"
+            );
+            internal_error("failed to persist refreshed tokens").with_incident(incident)
         })?;
 
     tx.commit().await.map_err(|err| {
-        error!(stage = "oauth", error = %err, "failed to commit token update");
-        internal_error("failed to commit refreshed tokens")
+        let incident = ProblemResponse::new_incident();
+        error!(
+            incident = %incident,
+            stage = "oauth",
+            error = %err,
+            "failed to commit token update"
+        );
+        internal_error("failed to commit refreshed tokens").with_incident(incident)
     })?;
 
+    if let Some(drift) = scope_drift(&link.scopes, &scopes) {
+        report_scope_drift(state, broadcaster, &scopes, drift, refreshed_at);
+    }
+
     counter!("oauth_refresh_total", "result" => "success").increment(1);
     publish_oauth_event(
         state,
@@ -595,6 +835,10 @@ async fn handle_validation(
         }
     };
 
+    if let Some(drift) = scope_drift(&link.scopes, &validation.scopes) {
+        record_scope_drift(state, broadcaster, &link, &validation.scopes, drift).await?;
+    }
+
     if let Some(missing) = missing_required_scope(&validation.scopes) {
         if record_failure(state, broadcaster, &link, missing, true)
             .await
@@ -621,8 +865,14 @@ async fn handle_validation(
     let storage = state.storage().clone();
     let command_repo = storage.command_log();
     let mut tx = command_repo.begin().await.map_err(|err| {
-        error!(stage = "oauth", error = %err, "failed to begin validation transaction");
-        internal_error("failed to begin validation transaction")
+        let incident = ProblemResponse::new_incident();
+        error!(
+            incident = %incident,
+            stage = "oauth",
+            error = %err,
+            "failed to begin validation transaction"
+        );
+        internal_error("failed to begin validation transaction").with_incident(incident)
     })?;
 
     storage
@@ -639,13 +889,25 @@ async fn handle_validation(
         )
         .await
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to mark validation result");
-            internal_error("failed to record validation result")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to mark validation result"
+            );
+            internal_error("failed to record validation result").with_incident(incident)
         })?;
 
     tx.commit().await.map_err(|err| {
-        error!(stage = "oauth", error = %err, "failed to commit validation result");
-        internal_error("failed to commit validation result")
+        let incident = ProblemResponse::new_incident();
+        error!(
+            incident = %incident,
+            stage = "oauth",
+            error = %err,
+            "failed to commit validation result"
+        );
+        internal_error("failed to commit validation result").with_incident(incident)
     })?;
 
     counter!("oauth_refresh_total", "result" => "skipped").increment(1);
@@ -694,8 +956,14 @@ pub(crate) async fn ensure_broadcaster(
             "broadcaster is not provisioned",
         )),
         Err(err) => {
-            error!(stage = "oauth", error = %err, "failed to query state index");
-            Err(internal_error("failed to query broadcaster state"))
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to query state index"
+            );
+            Err(internal_error("failed to query broadcaster state").with_incident(incident))
         }
     }
 }
@@ -710,8 +978,14 @@ async fn existing_login_hint(
         .fetch_by_broadcaster(broadcaster)
         .await
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to fetch existing oauth link");
-            internal_error("failed to load OAuth link")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to fetch existing oauth link"
+            );
+            internal_error("failed to load OAuth link").with_incident(incident)
         })?;
 
     Ok(link.map(|link| link.twitch_user_id))
@@ -727,8 +1001,14 @@ async fn consume_login_state(
         .consume(state_value)
         .await
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to consume oauth state");
-            internal_error("failed to load OAuth state")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to consume oauth state"
+            );
+            internal_error("failed to load OAuth state").with_incident(incident)
         })
 }
 
@@ -737,14 +1017,16 @@ async fn persist_link(
     login_state: &OauthLoginState,
     token_response: &TokenResponse,
     refresh_token: &str,
-    validation: &ValidateTokenResponse,
+    twitch_user_id: &str,
+    scopes: &[String],
     now: DateTime<Utc>,
 ) -> Result<(), ProblemResponse> {
     let storage = state.storage().clone();
     let command_repo = storage.command_log();
     let mut tx = command_repo.begin().await.map_err(|err| {
-        error!(stage = "oauth", error = %err, "failed to begin transaction");
-        internal_error("failed to begin transaction")
+        let incident = ProblemResponse::new_incident();
+        error!(incident = %incident, stage = "oauth", error = %err, "failed to begin transaction");
+        internal_error("failed to begin transaction").with_incident(incident)
     })?;
 
     let expires_at = token_response.expires_at(now);
@@ -755,9 +1037,9 @@ async fn persist_link(
             &NewOauthLink {
                 id: Uuid::new_v4().to_string(),
                 broadcaster_id: &login_state.broadcaster_id,
-                twitch_user_id: validation.user_id.clone(),
-                scopes: validation.scopes.clone(),
-                managed_scopes: managed_scopes(&validation.scopes),
+                twitch_user_id: twitch_user_id.to_string(),
+                scopes: scopes.to_vec(),
+                managed_scopes: managed_scopes(scopes),
                 access_token: token_response.access_token.clone(),
                 refresh_token: refresh_token.to_string(),
                 expires_at,
@@ -767,13 +1049,20 @@ async fn persist_link(
         )
         .await
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to upsert oauth link");
-            internal_error("failed to persist OAuth link")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to upsert oauth link"
+            );
+            internal_error("failed to persist OAuth link").with_incident(incident)
         })?;
 
     tx.commit().await.map_err(|err| {
-        error!(stage = "oauth", error = %err, "failed to commit oauth link");
-        internal_error("failed to persist OAuth link")
+        let incident = ProblemResponse::new_incident();
+        error!(incident = %incident, stage = "oauth", error = %err, "failed to commit oauth link");
+        internal_error("failed to persist OAuth link").with_incident(incident)
     })
 }
 
@@ -787,8 +1076,14 @@ async fn record_failure(
     let storage = state.storage().clone();
     let command_repo = storage.command_log();
     let mut tx = command_repo.begin().await.map_err(|err| {
-        error!(stage = "oauth", error = %err, "failed to begin failure transaction");
-        internal_error("failed to begin transaction")
+        let incident = ProblemResponse::new_incident();
+        error!(
+            incident = %incident,
+            stage = "oauth",
+            error = %err,
+            "failed to begin failure transaction"
+        );
+        internal_error("failed to begin transaction").with_incident(incident)
     })?;
 
     let occurred_at = state.now();
@@ -806,14 +1101,45 @@ async fn record_failure(
         )
         .await
         .map_err(|err| {
-            error!(stage = "oauth", error = %err, "failed to mark oauth failure");
-            internal_error("failed to record OAuth failure")
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to mark oauth failure"
+            );
+            internal_error("failed to record OAuth failure").with_incident(incident)
         })?;
 
     tx.commit().await.map_err(|err| {
-        error!(stage = "oauth", error = %err, "failed to commit oauth failure");
-        internal_error("failed to record OAuth failure")
-    })
+        let incident = ProblemResponse::new_incident();
+        error!(
+            incident = %incident,
+            stage = "oauth",
+            error = %err,
+            "failed to commit oauth failure"
+        );
+        internal_error("failed to record OAuth failure").with_incident(incident)
+    })?;
+
+    // A forced re-auth invalidates any login that was already in flight for this broadcaster.
+    if requires_reauth {
+        match storage
+            .oauth_login_states()
+            .purge_for_broadcaster(broadcaster)
+            .await
+        {
+            Ok(purged) if purged > 0 => {
+                info!(stage = "oauth", broadcaster = %broadcaster, purged, "purged pending login states");
+            }
+            Ok(_) => {}
+            Err(err) => {
+                warn!(stage = "oauth", broadcaster = %broadcaster, error = %err, "failed to purge pending login states");
+            }
+        }
+    }
+
+    Ok(())
 }
 
 fn redirect_found(location: &str) -> Response {
@@ -866,6 +1192,130 @@ fn missing_required_scope(scopes: &[String]) -> Option<&'static str> {
         .copied()
 }
 
+struct ScopeDrift {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+fn scope_drift(stored: &[String], validated: &[String]) -> Option<ScopeDrift> {
+    let mut added: Vec<String> = validated
+        .iter()
+        .filter(|scope| !stored.contains(scope))
+        .cloned()
+        .collect();
+    let mut removed: Vec<String> = stored
+        .iter()
+        .filter(|scope| !validated.contains(scope))
+        .cloned()
+        .collect();
+    if added.is_empty() && removed.is_empty() {
+        return None;
+    }
+    added.sort();
+    added.dedup();
+    removed.sort();
+    removed.dedup();
+    Some(ScopeDrift { added, removed })
+}
+
+async fn record_scope_drift(
+    state: &AppState,
+    broadcaster: &str,
+    link: &OauthLink,
+    validated: &[String],
+    drift: ScopeDrift,
+) -> Result<(), ProblemResponse> {
+    let now = state.now();
+    let storage = state.storage().clone();
+    let command_repo = storage.command_log();
+    let mut tx = command_repo.begin().await.map_err(|err| {
+        let incident = ProblemResponse::new_incident();
+        error!(
+            incident = %incident,
+            stage = "oauth",
+            error = %err,
+            "failed to begin scope drift transaction"
+        );
+        internal_error("failed to begin scope drift transaction").with_incident(incident)
+    })?;
+
+    storage
+        .oauth_links()
+        .update_scopes(
+            &mut tx,
+            broadcaster,
+            &link.twitch_user_id,
+            validated,
+            &managed_scopes(validated),
+            now,
+        )
+        .await
+        .map_err(|err| {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "oauth",
+                error = %err,
+                "failed to update drifted scopes"
+            );
+            internal_error("failed to update drifted scopes").with_incident(incident)
+        })?;
+
+    tx.commit().await.map_err(|err| {
+        let incident = ProblemResponse::new_incident();
+        error!(
+            incident = %incident,
+            stage = "oauth",
+            error = %err,
+            "failed to commit drifted scopes"
+        );
+        internal_error("failed to commit drifted scopes").with_incident(incident)
+    })?;
+
+    report_scope_drift(state, broadcaster, validated, drift, now);
+    Ok(())
+}
+
+fn report_scope_drift(
+    state: &AppState,
+    broadcaster: &str,
+    granted: &[String],
+    drift: ScopeDrift,
+    now: DateTime<Utc>,
+) {
+    let requires_reauth = missing_required_scope(granted).is_some();
+    counter!("oauth_scope_drift_total").increment(1);
+    warn!(
+        stage = "oauth",
+        broadcaster,
+        added = ?drift.added,
+        removed = ?drift.removed,
+        requires_reauth,
+        "stored OAuth scopes drifted from granted token"
+    );
+    publish_oauth_event(
+        state,
+        now,
+        broadcaster,
+        "oauth.scope_drift",
+        json!({
+            "added": drift.added,
+            "removed": drift.removed,
+            "requires_reauth": requires_reauth,
+        }),
+    );
+}
+
+/// Scopes granted to a freshly issued token. Twitch echoes them in the token response;
+/// `/validate` is the fallback when the response omits them.
+fn granted_scopes(token: &TokenResponse, validation: &ValidateTokenResponse) -> Vec<String> {
+    if token.scope.is_empty() {
+        validation.scopes.clone()
+    } else {
+        token.scope.clone()
+    }
+}
+
 fn managed_scopes(scopes: &[String]) -> Vec<String> {
     scopes
         .iter()
@@ -931,15 +1381,18 @@ mod tests {
     };
     use chrono::{DateTime, Duration, Utc};
     use http_body_util::BodyExt;
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
     use reqwest::Client;
     use serde_json::json;
     use sqlx::query;
+    use std::sync::atomic::{AtomicI64, Ordering};
     use std::sync::Arc;
     use tower::ServiceExt;
     use twi_overlay_storage::Database;
     use twi_overlay_twitch::TwitchOAuthClient;
     use url::Url;
 
+    use crate::sse::TokenClaims;
     use crate::tap::TapHub;
     use crate::telemetry;
 
@@ -1012,6 +1465,77 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[tokio::test]
+    async fn login_is_throttled_within_cooldown() {
+        let context = TestContext::new().await;
+        let offset_secs = Arc::new(AtomicI64::new(0));
+        let clock_offset = offset_secs.clone();
+        let base = context.now;
+        let state = context
+            .state
+            .clone()
+            .with_clock(Arc::new(move || {
+                base + Duration::seconds(clock_offset.load(Ordering::SeqCst))
+            }))
+            .with_oauth_login_cooldown(StdDuration::from_secs(60));
+        let app = Router::new()
+            .route("/oauth/login", get(super::login))
+            .with_state(state);
+        let login = || {
+            Request::builder()
+                .method("GET")
+                .uri("/oauth/login?broadcaster=b-1")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let state_param = |response: &Response| {
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .expect("location header")
+                .to_str()
+                .unwrap();
+            url::form_urlencoded::parse(location.split('?').nth(1).unwrap().as_bytes())
+                .find(|(k, _)| k == "state")
+                .map(|(_, v)| v.into_owned())
+                .expect("state param")
+        };
+
+        let first = app.clone().oneshot(login()).await.unwrap();
+        assert_eq!(first.status(), StatusCode::FOUND);
+        let first_state = state_param(&first);
+
+        offset_secs.store(10, Ordering::SeqCst);
+        let throttled = app.clone().oneshot(login()).await.unwrap();
+        assert_eq!(throttled.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body = throttled.into_body().collect().await.unwrap().to_bytes();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["type"], "oauth_login_throttled");
+        assert_eq!(
+            problem["detail"],
+            "OAuth login was started recently; retry in 50s"
+        );
+
+        // Past both the cooldown and the first state's TTL.
+        offset_secs.store(601, Ordering::SeqCst);
+        let retried = app.oneshot(login()).await.unwrap();
+        assert_eq!(retried.status(), StatusCode::FOUND);
+        let retried_state = state_param(&retried);
+        assert_ne!(retried_state, first_state);
+
+        let stored = context
+            .database
+            .oauth_login_states()
+            .consume(&retried_state)
+            .await
+            .unwrap()
+            .expect("retried state present");
+        assert_eq!(stored.broadcaster_id, BROADCASTER_ID);
+        assert_eq!(stored.created_at, base + Duration::seconds(601));
+        assert_eq!(stored.expires_at, base + Duration::seconds(601 + 600));
+    }
+
     #[tokio::test]
     async fn callback_persists_tokens() {
         let context = TestContext::with_mock().await;
@@ -1083,6 +1607,357 @@ mod tests {
         assert!(link.last_refreshed_at.is_some());
     }
 
+    #[tokio::test]
+    async fn validate_refresh_persists_changed_scopes() {
+        let context = TestContext::with_mock().await;
+        context.insert_oauth_link(Duration::seconds(30)).await;
+        // The refresh grants a scope set that differs from both the stored link and `/validate`,
+        // so the persisted scopes can only come from the `/token` response.
+        let granted = [
+            "channel:read:redemptions",
+            "channel:manage:redemptions",
+            "moderator:read:chatters",
+        ];
+        let validated = ["channel:read:redemptions", "bits:read"];
+        let server = context.mock_server.as_ref().expect("mock server");
+        server.mock(|when, then| {
+            when.method("POST").path("/token");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "access_token": "refresh-access",
+                        "refresh_token": "refresh-refresh",
+                        "expires_in": 3600,
+                        "scope": granted,
+                        "token_type": "bearer"
+                    })
+                    .to_string(),
+                );
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/validate");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "client_id": "client",
+                        "login": "broadcaster",
+                        "scopes": validated,
+                        "user_id": "user-123",
+                        "expires_in": 3600
+                    })
+                    .to_string(),
+                );
+        });
+
+        let response = context
+            .router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/oauth2/validate")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from("{\"broadcaster\":\"b-1\"}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let payload: ValidateResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(payload.status, ValidateStatus::Refresh);
+
+        let link = context
+            .database
+            .oauth_links()
+            .fetch_by_broadcaster(BROADCASTER_ID)
+            .await
+            .unwrap()
+            .expect("link present");
+        assert_eq!(link.scopes, granted.map(String::from).to_vec());
+        assert_eq!(
+            link.managed_scopes,
+            vec![
+                "channel:read:redemptions".to_string(),
+                "channel:manage:redemptions".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn granted_scopes_prefers_token_response() {
+        let validation = ValidateTokenResponse {
+            client_id: "client".into(),
+            login: "broadcaster".into(),
+            scopes: vec!["a".into()],
+            user_id: "user-1".into(),
+            expires_in: 3600,
+        };
+        let mut token = TokenResponse {
+            access_token: "access".into(),
+            refresh_token: None,
+            expires_in: 3600,
+            scope: vec!["a".into(), "b".into()],
+            token_type: "bearer".into(),
+        };
+        assert_eq!(
+            granted_scopes(&token, &validation),
+            vec!["a".to_string(), "b".to_string()]
+        );
+
+        token.scope.clear();
+        assert_eq!(granted_scopes(&token, &validation), vec!["a".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn validate_reauth_purges_pending_login_states() {
+        let context = TestContext::with_mock().await;
+        context.insert_oauth_link(Duration::hours(2)).await;
+        context.insert_login_state(None).await;
+        context
+            .mock_server
+            .as_ref()
+            .expect("mock server")
+            .mock(|when, then| {
+                when.method("GET").path("/validate");
+                then.status(401).body("invalid access token");
+            });
+
+        let response = context
+            .router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/oauth2/validate")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from("{\"broadcaster\":\"b-1\"}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let payload: ValidateResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(payload.status, ValidateStatus::Reauth);
+
+        let pending = context
+            .database
+            .oauth_login_states()
+            .consume("state-1")
+            .await
+            .expect("consume");
+        assert!(pending.is_none());
+    }
+
+    #[tokio::test]
+    async fn validate_detects_scope_drift_and_requires_reauth() {
+        let context = TestContext::with_mock().await;
+        context.insert_oauth_link(Duration::hours(2)).await;
+        context
+            .mock_server
+            .as_ref()
+            .expect("mock server")
+            .mock(|when, then| {
+                when.method("GET").path("/validate");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(
+                        json!({
+                            "client_id": "client",
+                            "login": "broadcaster",
+                            "scopes": ["channel:read:redemptions"],
+                            "user_id": "user-1",
+                            "expires_in": 3600
+                        })
+                        .to_string(),
+                    );
+            });
+
+        let response = context
+            .router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/oauth2/validate")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from("{\"broadcaster\":\"b-1\"}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let payload: ValidateResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(payload.status, ValidateStatus::Reauth);
+
+        let link = context
+            .database
+            .oauth_links()
+            .fetch_by_broadcaster(BROADCASTER_ID)
+            .await
+            .unwrap()
+            .expect("link present");
+        assert_eq!(link.scopes, vec!["channel:read:redemptions".to_string()]);
+        assert_eq!(
+            link.managed_scopes,
+            vec!["channel:read:redemptions".to_string()]
+        );
+        assert!(link.requires_reauth);
+    }
+
+    #[tokio::test]
+    async fn validate_all_only_touches_the_callers_link() {
+        let context = TestContext::with_mock().await;
+        context.insert_oauth_link(Duration::hours(2)).await;
+
+        query(
+            "INSERT INTO broadcasters (id, twitch_broadcaster_id, display_name, timezone, settings_json, created_at, updated_at) \
+             VALUES ('b-2', 'twitch-b-2', 'Second', 'UTC', '{}', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        )
+        .execute(context.database.pool())
+        .await
+        .expect("insert broadcaster");
+        let command_repo = context.database.command_log();
+        let mut tx = command_repo.begin().await.expect("begin tx");
+        context
+            .database
+            .oauth_links()
+            .upsert_link(
+                &mut tx,
+                &NewOauthLink {
+                    id: "link-2".into(),
+                    broadcaster_id: "b-2",
+                    twitch_user_id: "user-2".into(),
+                    scopes: OAUTH_SCOPES.iter().map(|scope| scope.to_string()).collect(),
+                    managed_scopes: OAUTH_SCOPES.iter().map(|scope| scope.to_string()).collect(),
+                    access_token: "revoked".into(),
+                    refresh_token: "refresh-2".into(),
+                    expires_at: context.now + Duration::hours(2),
+                    created_at: context.now,
+                    updated_at: context.now,
+                },
+            )
+            .await
+            .expect("insert link");
+        tx.commit().await.expect("commit");
+
+        let server = context.mock_server.as_ref().expect("mock server");
+        server.mock(|when, then| {
+            when.method("GET")
+                .path("/validate")
+                .header("authorization", "OAuth access");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "client_id": "client",
+                        "login": "broadcaster",
+                        "scopes": OAUTH_SCOPES,
+                        "user_id": "user-1",
+                        "expires_in": 3600
+                    })
+                    .to_string(),
+                );
+        });
+        let other_tenant = server.mock(|when, then| {
+            when.method("GET")
+                .path("/validate")
+                .header("authorization", "OAuth revoked");
+            then.status(401)
+                .header("content-type", "application/json")
+                .body(json!({"status": 401, "message": "invalid access token"}).to_string());
+        });
+
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &TokenClaims {
+                sub: BROADCASTER_ID.to_string(),
+                aud: Audience::Admin.as_str().to_string(),
+                exp: (context.now + Duration::minutes(10)).timestamp() as usize,
+                nbf: None,
+                jti: None,
+            },
+            &EncodingKey::from_secret(b"token-secret"),
+        )
+        .expect("token encode");
+
+        let unauthorized = context
+            .router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/oauth/validate-all?broadcaster=b-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+        let cross_tenant = context
+            .router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/oauth/validate-all?broadcaster=b-2")
+                    .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(cross_tenant.status(), StatusCode::FORBIDDEN);
+
+        let response = context
+            .router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/oauth/validate-all?broadcaster=b-1")
+                    .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let payload: ValidateAllResponse = serde_json::from_slice(&body_bytes).unwrap();
+        let statuses: Vec<_> = payload
+            .results
+            .iter()
+            .map(|summary| (summary.broadcaster.as_str(), &summary.status))
+            .collect();
+        assert_eq!(statuses, vec![("b-1", &ValidateSummaryStatus::Ok)]);
+
+        other_tenant.assert_hits(0);
+        let untouched = context
+            .database
+            .oauth_links()
+            .fetch_by_broadcaster("b-2")
+            .await
+            .unwrap()
+            .expect("link present");
+        assert!(!untouched.requires_reauth);
+        assert!(untouched.last_validated_at.is_none());
+    }
+
+    #[test]
+    fn scope_drift_ignores_order() {
+        let stored = vec!["a".to_string(), "b".to_string()];
+        let validated = vec!["b".to_string(), "a".to_string()];
+        assert!(scope_drift(&stored, &validated).is_none());
+
+        let drift = scope_drift(&stored, &["b".to_string(), "c".to_string()]).expect("drift");
+        assert_eq!(drift.added, vec!["c".to_string()]);
+        assert_eq!(drift.removed, vec!["a".to_string()]);
+    }
+
     struct TestContext {
         database: Database,
         state: AppState,
@@ -1183,6 +2058,7 @@ mod tests {
                 .route("/oauth/login", get(super::login))
                 .route("/oauth/callback", get(super::callback))
                 .route("/oauth2/validate", post(super::validate))
+                .route("/api/oauth/validate-all", post(super::validate_all))
                 .with_state(self.state.clone())
         }
 
@@ -0,0 +1,74 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use twi_overlay_core::{policy::PolicyOutcome, types::NormalizedEvent};
+
+/// One recorded policy evaluation, with both sides redacted as in the tap.
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyTraceEntry {
+    pub at: DateTime<Utc>,
+    pub event: serde_json::Value,
+    pub outcome: serde_json::Value,
+}
+
+/// Per-broadcaster ring of the most recent policy evaluations.
+///
+/// A capacity of zero disables recording.
+#[derive(Clone)]
+pub struct PolicyTrace {
+    capacity: Arc<AtomicUsize>,
+    entries: Arc<Mutex<HashMap<String, VecDeque<PolicyTraceEntry>>>>,
+}
+
+impl PolicyTrace {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: Arc::new(AtomicUsize::new(capacity)),
+            entries: Arc::default(),
+        }
+    }
+
+    /// Changes the ring size; existing rings are trimmed on their next insert.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+    }
+
+    pub fn record(
+        &self,
+        broadcaster_id: &str,
+        at: DateTime<Utc>,
+        event: &NormalizedEvent,
+        outcome: &PolicyOutcome,
+    ) {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        if capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().expect("policy trace poisoned");
+        let ring = entries.entry(broadcaster_id.to_string()).or_default();
+        ring.push_back(PolicyTraceEntry {
+            at,
+            event: event.redacted(),
+            outcome: outcome.redacted(),
+        });
+        while ring.len() > capacity {
+            ring.pop_front();
+        }
+    }
+
+    /// Returns the recorded evaluations for a broadcaster, newest first.
+    pub fn recent(&self, broadcaster_id: &str) -> Vec<PolicyTraceEntry> {
+        let entries = self.entries.lock().expect("policy trace poisoned");
+        entries
+            .get(broadcaster_id)
+            .map(|ring| ring.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+}
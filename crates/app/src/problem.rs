@@ -4,6 +4,8 @@ use axum::{
     Json,
 };
 use serde::Serialize;
+use tracing::debug;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize)]
 struct ProblemDetails {
@@ -11,6 +13,9 @@ struct ProblemDetails {
     problem_type: &'static str,
     title: &'static str,
     detail: String,
+    /// Incident id (`urn:uuid:…`) attached to 5xx responses, matching the handler's error log.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
 }
 
 pub struct ProblemResponse {
@@ -26,13 +31,44 @@ impl ProblemResponse {
                 problem_type,
                 title: status.canonical_reason().unwrap_or("error"),
                 detail: detail.into(),
+                instance: None,
             },
         }
     }
+
+    /// Generates an incident id for a handler to log with the real cause of a 5xx response.
+    pub fn new_incident() -> String {
+        format!("urn:uuid:{}", Uuid::new_v4())
+    }
+
+    /// Attaches the incident id the handler logged, so clients can report it back.
+    pub fn with_incident(mut self, incident: String) -> Self {
+        self.body.instance = Some(incident);
+        self
+    }
+
+    pub fn problem_type(&self) -> &'static str {
+        self.body.problem_type
+    }
 }
 
 impl IntoResponse for ProblemResponse {
-    fn into_response(self) -> Response {
+    fn into_response(mut self) -> Response {
+        if self.status.is_server_error() && self.body.instance.is_none() {
+            // The handler did not log an incident; keep the id traceable without a second
+            // error line for the same failure.
+            let instance = Self::new_incident();
+            debug!(
+                stage = "app",
+                incident = %instance,
+                status = self.status.as_u16(),
+                problem_type = self.body.problem_type,
+                detail = %self.body.detail,
+                "request failed with server error"
+            );
+            self.body.instance = Some(instance);
+        }
+
         let mut response = Json(self.body).into_response();
         *response.status_mut() = self.status;
         response.headers_mut().insert(
@@ -42,3 +78,104 @@ impl IntoResponse for ProblemResponse {
         response
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use http_body_util::BodyExt;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn capture<T>(level: tracing::Level, f: impl FnOnce() -> T) -> (T, String) {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_max_level(level)
+            .with_ansi(false)
+            .finish();
+        let value = tracing::subscriber::with_default(subscriber, f);
+        let logged = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        (value, logged)
+    }
+
+    #[tokio::test]
+    async fn server_errors_keep_handler_incident_without_logging_again() {
+        let incident = ProblemResponse::new_incident();
+        let (response, logged) = capture(tracing::Level::INFO, || {
+            ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "failed to load state",
+            )
+            .with_incident(incident.clone())
+            .into_response()
+        });
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = body_json(response).await;
+        assert_eq!(body["instance"], incident.as_str());
+        assert!(logged.is_empty(), "unexpected log output: {logged}");
+    }
+
+    #[tokio::test]
+    async fn server_errors_without_handler_incident_log_generated_id_at_debug() {
+        let (response, logged) = capture(tracing::Level::DEBUG, || {
+            ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "failed to load state",
+            )
+            .into_response()
+        });
+
+        let body = body_json(response).await;
+        let instance = body["instance"].as_str().expect("instance");
+        assert!(instance.starts_with("urn:uuid:"));
+        assert!(
+            logged.contains("DEBUG") && logged.contains(instance),
+            "log should mention {instance} at debug: {logged}"
+        );
+        assert!(!logged.contains("ERROR"));
+    }
+
+    #[tokio::test]
+    async fn client_errors_omit_incident_id() {
+        let response =
+            ProblemResponse::new(StatusCode::BAD_REQUEST, "invalid_argument", "bad input")
+                .into_response();
+
+        let body = body_json(response).await;
+        assert!(body.get("instance").is_none());
+    }
+}
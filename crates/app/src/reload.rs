@@ -0,0 +1,57 @@
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+use twi_overlay_util::{read_env_file, AppConfig};
+
+use crate::{router::AppState, telemetry};
+
+/// Re-reads the reloadable configuration subset whenever the process receives SIGHUP.
+pub fn spawn(state: AppState) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(err) => {
+                warn!(stage = "app", error = %err, "failed to install SIGHUP handler");
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            reload_config(&state);
+        }
+    })
+}
+
+fn reload_config(state: &AppState) {
+    let overrides = read_env_file();
+    let config = match AppConfig::from_env_with_overrides(&overrides) {
+        Ok(config) => config,
+        Err(err) => {
+            warn!(stage = "app", error = %err, "config reload rejected; keeping current settings");
+            return;
+        }
+    };
+
+    // Rejecting the whole reload keeps a typo in RUST_LOG from silently resetting the filter.
+    let log_filter = match telemetry::reload_log_filter(
+        overrides.get("RUST_LOG").map(String::as_str),
+    ) {
+        Ok(filter) => filter,
+        Err(err) => {
+            warn!(stage = "app", error = %err, "config reload rejected; keeping current settings");
+            return;
+        }
+    };
+    state.apply_reloadable_config(&config);
+
+    info!(
+        stage = "app",
+        log_filter = log_filter.as_deref().unwrap_or("unchanged"),
+        patch_coalesce_threshold = config.patch_coalesce_threshold,
+        policy_trace_capacity = config.policy_trace_capacity,
+        sse_max_lag = config.sse_max_lag,
+        oauth_login_cooldown_secs = config.oauth_login_cooldown_secs,
+        "configuration reloaded",
+    );
+}
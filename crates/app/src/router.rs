@@ -1,10 +1,13 @@
-use std::{collections::HashSet, sync::Arc, time::Duration};
+use std::{collections::HashSet, str::FromStr, sync::Arc, time::Duration};
 
 use axum::{
     body::Body,
     extract::{Query, State},
-    http::{header, HeaderMap, StatusCode},
-    response::{sse::Sse, IntoResponse, Response},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
     Json, Router,
 };
@@ -13,28 +16,46 @@ use metrics::counter;
 use metrics_exporter_prometheus::PrometheusHandle;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use twi_overlay_core::policy::PolicyEngine;
 use twi_overlay_core::types::{
-    Command, CommandSource, Patch, QueueCompleteCommand, QueueRemovalReason, QueueRemoveCommand,
-    SettingsUpdateCommand,
+    Command, CommandSource, CounterCorrectCommand, NoopCommand, NormalizedUser, Patch, PatchKind,
+    QueueAddCommand, QueueCompleteCommand, QueueEntryStatus, QueueRemovalReason,
+    QueueRemoveCommand, SettingsUpdateCommand, SnapshotHealth, TimezoneUpdateCommand,
+};
+use twi_overlay_storage::{
+    Database, NewSseTokenUse, QueueError, SettingsError, StateIndexError, TimezoneUpdateError,
 };
-use twi_overlay_storage::{Database, QueueError, SettingsError};
 use twi_overlay_twitch::{HelixClient, TwitchOAuthClient};
+#[cfg(any(unix, test))]
+use twi_overlay_util::AppConfig;
+use twi_overlay_util::{EndpointGroup, Environment, EventSubTransport};
 use uuid::Uuid;
 
 use crate::backfill;
-use crate::command::{CommandApplyResult, CommandExecutor, CommandExecutorError};
+use crate::command::{
+    compute_local_day, local_day_bounds, CommandApplyResult, CommandExecutor, CommandExecutorError,
+};
+use crate::policy_trace::PolicyTrace;
 use crate::problem::ProblemResponse;
 use crate::sse::{Audience, SseHub, SseStream, SseTokenValidator, TokenError};
-use crate::state::{build_state_snapshot, StateScope};
+use crate::state::{
+    apply_queue_limit, build_queue_page, build_state_snapshot, build_touched_entries_diff,
+    decode_queue_cursor, load_recent_completed, load_snapshot_health, QueuePage, StateDiff,
+    StateError, StateScope,
+};
 use crate::tap::{
     parse_stage_list, tap_keep_alive, tap_stream, StageEvent, StageKind, StageMetadata,
     StagePayload, TapFilter, TapHub,
 };
 use crate::webhook::emit_sse_stage;
 use crate::{oauth, telemetry, webhook};
-use twi_overlay_core::types::StateSnapshot;
+
+const DEFAULT_SNAPSHOT_QUEUE_LIMIT: usize = 500;
+const DEFAULT_OAUTH_LOGIN_COOLDOWN: Duration = Duration::from_secs(30);
+const DEFAULT_POLICY_TRACE_CAPACITY: usize = 50;
+const DEFAULT_SSE_MAX_FILTER_TYPES: usize = 64;
+const DEFAULT_SSE_HEARTBEAT_TEXT: &str = "heartbeat";
 
 #[derive(Clone)]
 pub struct AppState {
@@ -48,11 +69,21 @@ pub struct AppState {
     sse: SseHub,
     token_validator: SseTokenValidator,
     sse_heartbeat_secs: u64,
+    sse_heartbeat_text: Arc<str>,
+    sse_heartbeat_event: Option<Arc<str>>,
+    snapshot_queue_limit: usize,
+    sse_max_filter_types: usize,
+    environment: Environment,
     #[cfg(test)]
     helix_client: HelixClient,
     oauth_client: TwitchOAuthClient,
     oauth_redirect_uri: String,
     oauth_state_ttl: Duration,
+    oauth_login_cooldown: oauth::LoginCooldown,
+    eventsub_transport: EventSubTransport,
+    eventsub_persist_types: Arc<[String]>,
+    enabled_endpoints: Arc<[EndpointGroup]>,
+    policy_trace: PolicyTrace,
     backfill: backfill::BackfillService,
     #[cfg(test)]
     helix_backfill_interval: Duration,
@@ -98,6 +129,8 @@ impl AppState {
             helix_backfill_interval,
             helix_backfill_page_size,
         );
+        let policy_trace = PolicyTrace::new(DEFAULT_POLICY_TRACE_CAPACITY);
+        let backfill_worker = backfill_worker.with_policy_trace(policy_trace.clone());
         let token_validator = SseTokenValidator::new(sse_token_secret);
         let state = Self {
             metrics,
@@ -110,11 +143,21 @@ impl AppState {
             sse,
             token_validator,
             sse_heartbeat_secs,
+            sse_heartbeat_text: Arc::from(DEFAULT_SSE_HEARTBEAT_TEXT),
+            sse_heartbeat_event: None,
+            snapshot_queue_limit: DEFAULT_SNAPSHOT_QUEUE_LIMIT,
+            sse_max_filter_types: DEFAULT_SSE_MAX_FILTER_TYPES,
+            environment: Environment::Development,
             #[cfg(test)]
             helix_client,
             oauth_client,
             oauth_redirect_uri,
             oauth_state_ttl,
+            oauth_login_cooldown: oauth::LoginCooldown::new(DEFAULT_OAUTH_LOGIN_COOLDOWN),
+            eventsub_transport: EventSubTransport::default(),
+            eventsub_persist_types: Arc::from([]),
+            enabled_endpoints: Arc::from(EndpointGroup::ALL),
+            policy_trace,
             backfill: backfill_service,
             #[cfg(test)]
             helix_backfill_interval,
@@ -124,6 +167,102 @@ impl AppState {
         (state, backfill_worker)
     }
 
+    /// Overrides the maximum number of queue entries returned by `/api/state`.
+    pub fn with_snapshot_queue_limit(mut self, limit: usize) -> Self {
+        self.snapshot_queue_limit = limit;
+        self
+    }
+
+    /// Sets the SSE keep-alive payload: a `: <text>` comment, or an `event: <name>` frame with
+    /// `text` as data when an event name is given.
+    pub fn with_sse_heartbeat(mut self, text: &str, event: Option<&str>) -> Self {
+        self.sse_heartbeat_text = Arc::from(text);
+        self.sse_heartbeat_event = event.map(Arc::from);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_sse_heartbeat_secs(mut self, secs: u64) -> Self {
+        self.sse_heartbeat_secs = secs;
+        self
+    }
+
+    /// Caps how many entries an SSE `types` filter may list (0 disables the cap).
+    pub fn with_sse_max_filter_types(mut self, max: usize) -> Self {
+        self.sse_max_filter_types = max;
+        self
+    }
+
+    /// Disconnects SSE subscribers that fall more than `max_lag` messages behind (0 disables).
+    pub fn with_sse_max_lag(self, max_lag: usize) -> Self {
+        self.sse.set_max_lag(max_lag);
+        self
+    }
+
+    /// Restricts the route groups mounted by [`app_router`]; routes outside them return 404.
+    pub fn with_enabled_endpoints(mut self, groups: &[EndpointGroup]) -> Self {
+        self.enabled_endpoints = Arc::from(groups);
+        self
+    }
+
+    /// Sets the runtime environment; development-only diagnostics are hidden in production.
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Sets the minimum interval between OAuth login initiations per broadcaster.
+    pub fn with_oauth_login_cooldown(mut self, cooldown: Duration) -> Self {
+        self.oauth_login_cooldown = oauth::LoginCooldown::new(cooldown);
+        self
+    }
+
+    /// Coalesces command batches producing more than `threshold` patches into one `patch.batch`.
+    pub fn with_patch_coalesce_threshold(self, threshold: usize) -> Self {
+        self.command_executor.set_coalesce_threshold(threshold);
+        self
+    }
+
+    /// Declares the EventSub transport the webhook endpoint is subscribed with.
+    pub fn with_eventsub_transport(mut self, transport: EventSubTransport) -> Self {
+        self.eventsub_transport = transport;
+        self
+    }
+
+    /// Limits `event_raw` persistence to the listed EventSub types (empty persists every type).
+    pub fn with_eventsub_persist_types(mut self, types: &[String]) -> Self {
+        self.eventsub_persist_types = Arc::from(types);
+        self
+    }
+
+    /// Sets how many recent policy evaluations are kept per broadcaster (0 disables).
+    pub fn with_policy_trace_capacity(self, capacity: usize) -> Self {
+        self.policy_trace.set_capacity(capacity);
+        self
+    }
+
+    /// Applies the SIGHUP-reloadable subset of configuration to the running state.
+    ///
+    /// Bind address, database URL, secrets and everything else still require a restart.
+    #[cfg(any(unix, test))]
+    pub fn apply_reloadable_config(&self, config: &AppConfig) {
+        self.command_executor
+            .set_coalesce_threshold(config.patch_coalesce_threshold);
+        self.policy_trace.set_capacity(config.policy_trace_capacity);
+        self.sse.set_max_lag(config.sse_max_lag);
+        self.oauth_login_cooldown
+            .set_window(Duration::from_secs(config.oauth_login_cooldown_secs));
+    }
+
+    /// Requires single-use SSE tokens for the provided audiences.
+    pub fn with_single_use_sse_audiences(
+        mut self,
+        audiences: impl IntoIterator<Item = Audience>,
+    ) -> Self {
+        self.token_validator = self.token_validator.with_single_use(audiences);
+        self
+    }
+
     #[cfg(test)]
     pub fn with_clock(mut self, clock: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>) -> Self {
         self.clock = clock.clone();
@@ -148,6 +287,26 @@ impl AppState {
         self
     }
 
+    pub fn policy_trace(&self) -> &PolicyTrace {
+        &self.policy_trace
+    }
+
+    pub fn eventsub_transport(&self) -> EventSubTransport {
+        self.eventsub_transport
+    }
+
+    pub fn persists_event_type(&self, event_type: &str) -> bool {
+        self.eventsub_persist_types.is_empty()
+            || self
+                .eventsub_persist_types
+                .iter()
+                .any(|value| value == event_type)
+    }
+
+    pub fn endpoint_enabled(&self, group: EndpointGroup) -> bool {
+        self.enabled_endpoints.contains(&group)
+    }
+
     pub fn metrics(&self) -> &PrometheusHandle {
         &self.metrics
     }
@@ -188,6 +347,26 @@ impl AppState {
         self.sse_heartbeat_secs
     }
 
+    pub fn snapshot_queue_limit(&self) -> usize {
+        self.snapshot_queue_limit
+    }
+
+    pub fn sse_max_filter_types(&self) -> usize {
+        self.sse_max_filter_types
+    }
+
+    pub fn environment(&self) -> Environment {
+        self.environment
+    }
+
+    /// Returns the broadcaster's current version, served from a short-lived cache.
+    pub async fn current_version(&self, broadcaster_id: &str) -> Result<u64, StateIndexError> {
+        self.command_executor
+            .versions()
+            .current_version(&self.storage, broadcaster_id)
+            .await
+    }
+
     pub fn oauth_client(&self) -> &TwitchOAuthClient {
         &self.oauth_client
     }
@@ -200,33 +379,88 @@ impl AppState {
         self.oauth_state_ttl
     }
 
+    pub fn oauth_login_cooldown(&self) -> &oauth::LoginCooldown {
+        &self.oauth_login_cooldown
+    }
+
     pub fn backfill(&self) -> &backfill::BackfillService {
         &self.backfill
     }
 }
 
 pub fn app_router(state: AppState) -> Router {
-    Router::new()
+    let mut router = Router::new()
         .route("/healthz", get(healthz))
-        .route("/metrics", get(metrics))
-        .route("/_debug/tap", get(debug_tap))
-        .route("/_debug/helix", get(backfill::debug_helix))
-        .route("/overlay/sse", get(overlay_sse))
-        .route("/admin/sse", get(admin_sse))
-        .route("/api/state", get(state_snapshot))
-        .route("/api/queue/dequeue", post(queue_dequeue))
-        .route("/api/settings/update", post(settings_update))
-        .route("/eventsub/webhook", post(webhook::handle))
-        .route("/oauth/login", get(oauth::login))
-        .route("/oauth/callback", get(oauth::callback))
-        .route("/oauth2/validate", post(oauth::validate))
+        .route("/metrics", get(metrics));
+    for group in EndpointGroup::ALL {
+        if state.endpoint_enabled(group) {
+            router = router.merge(endpoint_routes(group));
+        }
+    }
+    router
+        .fallback(not_found)
+        .method_not_allowed_fallback(method_not_allowed)
         .with_state(state)
 }
 
+fn endpoint_routes(group: EndpointGroup) -> Router<AppState> {
+    match group {
+        EndpointGroup::Debug => Router::new()
+            .route("/_debug/tap", get(debug_tap))
+            .route("/_debug/helix", get(backfill::debug_helix))
+            .route("/_debug/commands", get(debug_commands))
+            .route("/_debug/policy/trace", get(debug_policy_trace))
+            .route("/_debug/db", get(debug_db))
+            .route("/_debug/db/checkpoint", post(debug_db_checkpoint)),
+        EndpointGroup::Sse => Router::new()
+            .route("/overlay/sse", get(overlay_sse))
+            .route("/admin/sse", get(admin_sse)),
+        EndpointGroup::State => Router::new()
+            .route("/api/state", get(state_snapshot))
+            .route("/api/state/diff", get(state_diff)),
+        EndpointGroup::Queue => Router::new()
+            .route("/api/queue", get(queue_page))
+            .route("/api/queue/served-today", get(queue_served_today))
+            .route("/api/queue/export.csv", get(queue_export_csv)),
+        EndpointGroup::QueueMutations => Router::new()
+            .route("/api/queue/add", post(queue_add))
+            .route("/api/queue/dequeue", post(queue_dequeue)),
+        EndpointGroup::Settings => Router::new()
+            .route("/api/settings/update", post(settings_update))
+            .route("/api/settings/timezone", post(settings_timezone)),
+        EndpointGroup::Counters => {
+            Router::new().route("/api/counters/correct", post(counter_correct))
+        }
+        EndpointGroup::Commands => Router::new().route("/api/commands/noop", post(command_noop)),
+        EndpointGroup::Eventsub => Router::new().route("/eventsub/webhook", post(webhook::handle)),
+        EndpointGroup::Oauth => Router::new()
+            .route("/oauth/login", get(oauth::login))
+            .route("/oauth/callback", get(oauth::callback))
+            .route("/oauth2/validate", post(oauth::validate))
+            .route("/api/oauth/validate-all", post(oauth::validate_all)),
+    }
+}
+
 async fn healthz() -> StatusCode {
     StatusCode::OK
 }
 
+async fn not_found(uri: Uri) -> ProblemResponse {
+    ProblemResponse::new(
+        StatusCode::NOT_FOUND,
+        "not_found",
+        format!("no route for {}", uri.path()),
+    )
+}
+
+async fn method_not_allowed(method: Method, uri: Uri) -> ProblemResponse {
+    ProblemResponse::new(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "method_not_allowed",
+        format!("{method} is not allowed on {}", uri.path()),
+    )
+}
+
 async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
     let body = telemetry::render_metrics(state.metrics());
     Response::builder()
@@ -242,6 +476,32 @@ struct TapQuery {
     s: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct DebugCommandsQuery {
+    broadcaster: String,
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DebugPolicyTraceQuery {
+    broadcaster: String,
+}
+
+#[derive(Debug, Serialize)]
+struct DebugCommandsResponse {
+    broadcaster: String,
+    commands: Vec<DebugCommandEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct DebugCommandEntry {
+    version: u64,
+    source: Option<String>,
+    #[serde(rename = "type")]
+    command_type: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct SseQuery {
     broadcaster: String,
@@ -260,10 +520,81 @@ struct StateQuery {
     scope: Option<String>,
     #[serde(default)]
     since: Option<String>,
+    /// Number of entries completed today to attach as `recent_completed` (capped).
+    #[serde(default)]
+    recent_completed: Option<usize>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+const MAX_RECENT_COMPLETED: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct QueuePageQuery {
+    broadcaster: String,
+    #[serde(default)]
+    cursor: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServedTodayQuery {
+    broadcaster: String,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateDiffQuery {
+    broadcaster: String,
+    from: u64,
+    to: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ServedTodayResponse {
+    day: String,
+    completed: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueueExportQuery {
+    broadcaster: String,
+    #[serde(default)]
+    status: Option<String>,
     #[serde(default)]
     token: Option<String>,
 }
 
+const QUEUE_EXPORT_HEADER: &str =
+    "user_id,user_login,user_display_name,reward_id,status,enqueued_at,today_count\n";
+
+#[derive(Debug, Deserialize)]
+struct QueueAddRequest {
+    broadcaster: String,
+    user_id: String,
+    #[serde(default)]
+    user_login: Option<String>,
+    #[serde(default)]
+    user_display_name: Option<String>,
+    op_id: String,
+}
+
+#[derive(Debug, Serialize)]
+struct QueueAddResultBody {
+    entry_id: String,
+    user_today_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct QueueAddResponse {
+    version: u64,
+    result: QueueAddResultBody,
+}
+
 #[derive(Debug, Deserialize)]
 struct QueueDequeueRequest {
     broadcaster: String,
@@ -319,6 +650,52 @@ struct SettingsUpdateResponse {
     result: SettingsUpdateResultBody,
 }
 
+#[derive(Debug, Deserialize)]
+struct TimezoneUpdateRequest {
+    broadcaster: String,
+    timezone: String,
+    op_id: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TimezoneUpdateResponse {
+    version: u64,
+    timezone: String,
+    day: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CounterCorrectRequest {
+    broadcaster: String,
+    user_id: String,
+    count: u32,
+    op_id: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CounterCorrectResultBody {
+    user_id: String,
+    count: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct CounterCorrectResponse {
+    version: u64,
+    result: CounterCorrectResultBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct NoopRequest {
+    broadcaster: String,
+    op_id: String,
+}
+
+#[derive(Debug, Serialize)]
+struct NoopResponse {
+    version: u64,
+    duplicate: bool,
+}
+
 async fn debug_tap(
     State(state): State<AppState>,
     Query(query): Query<TapQuery>,
@@ -333,6 +710,142 @@ async fn debug_tap(
     Ok(Sse::new(stream).keep_alive(tap_keep_alive()))
 }
 
+async fn debug_db(State(state): State<AppState>) -> Result<Json<Value>, ProblemResponse> {
+    ensure_development(&state)?;
+
+    let snapshot = state.storage().pragma_snapshot().await.map_err(|err| {
+        let incident = ProblemResponse::new_incident();
+        error!(
+            incident = %incident,
+            stage = "storage",
+            error = %err,
+            "failed to read sqlite pragmas"
+        );
+        ProblemResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "storage_error",
+            "failed to read sqlite pragmas",
+        )
+        .with_incident(incident)
+    })?;
+
+    Ok(Json(json!({
+        "journal_mode": snapshot.journal_mode,
+        "page_size": snapshot.page_size,
+        "page_count": snapshot.page_count,
+        "freelist_count": snapshot.freelist_count,
+        "wal_autocheckpoint": snapshot.wal_autocheckpoint,
+        "wal_size_bytes": snapshot.wal_size_bytes,
+    })))
+}
+
+async fn debug_db_checkpoint(
+    State(state): State<AppState>,
+) -> Result<Json<Value>, ProblemResponse> {
+    ensure_development(&state)?;
+
+    let stats = state
+        .storage()
+        .wal_checkpoint_truncate()
+        .await
+        .map_err(|err| {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "storage",
+                error = %err,
+                "manual WAL checkpoint failed"
+            );
+            ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "checkpoint_error",
+                "failed to run WAL checkpoint",
+            )
+            .with_incident(incident)
+        })?;
+
+    info!(
+        stage = "storage",
+        busy_frames = stats.busy_frames,
+        log_frames = stats.log_frames,
+        checkpointed_frames = stats.checkpointed_frames,
+        "manual WAL checkpoint completed"
+    );
+
+    Ok(Json(json!({
+        "busy_frames": stats.busy_frames,
+        "log_frames": stats.log_frames,
+        "checkpointed_frames": stats.checkpointed_frames,
+    })))
+}
+
+fn ensure_development(state: &AppState) -> Result<(), ProblemResponse> {
+    if state.environment().is_development() || state.environment().is_test() {
+        return Ok(());
+    }
+    Err(ProblemResponse::new(
+        StatusCode::NOT_FOUND,
+        "not_found",
+        "diagnostics are only available in development",
+    ))
+}
+
+async fn debug_commands(
+    State(state): State<AppState>,
+    Query(query): Query<DebugCommandsQuery>,
+) -> Result<Json<DebugCommandsResponse>, ProblemResponse> {
+    ensure_development(&state)?;
+    oauth::ensure_broadcaster(&state, &query.broadcaster).await?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let commands = state
+        .storage()
+        .command_log()
+        .list_recent(&query.broadcaster, limit)
+        .await
+        .map_err(|err| {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "command",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to load command log for debug"
+            );
+            ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "debug_command_log_error",
+                "failed to load command log",
+            )
+            .with_incident(incident)
+        })?;
+
+    Ok(Json(DebugCommandsResponse {
+        broadcaster: query.broadcaster,
+        commands: commands
+            .into_iter()
+            .map(|entry| DebugCommandEntry {
+                version: entry.version,
+                source: entry.source,
+                command_type: entry.command_type,
+            })
+            .collect(),
+    }))
+}
+
+async fn debug_policy_trace(
+    State(state): State<AppState>,
+    Query(query): Query<DebugPolicyTraceQuery>,
+) -> Result<Json<Value>, ProblemResponse> {
+    ensure_development(&state)?;
+    oauth::ensure_broadcaster(&state, &query.broadcaster).await?;
+
+    Ok(Json(json!({
+        "broadcaster": query.broadcaster,
+        "entries": state.policy_trace().recent(&query.broadcaster),
+    })))
+}
+
 async fn overlay_sse(
     State(state): State<AppState>,
     Query(query): Query<SseQuery>,
@@ -353,7 +866,7 @@ async fn state_snapshot(
     State(state): State<AppState>,
     Query(query): Query<StateQuery>,
     headers: HeaderMap,
-) -> Result<Json<StateSnapshot>, ProblemResponse> {
+) -> Result<Response, ProblemResponse> {
     let token = extract_bearer_token(&headers)
         .map(|value| value.to_string())
         .or_else(|| query.token.clone())
@@ -405,7 +918,9 @@ async fn state_snapshot(
         }
         Err(err) => {
             counter!("api_state_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
+                incident = %incident,
                 stage = "state",
                 broadcaster = %query.broadcaster,
                 error = %err,
@@ -415,59 +930,163 @@ async fn state_snapshot(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "settings_error",
                 "failed to load broadcaster settings",
-            ));
+            )
+            .with_incident(incident));
         }
     };
 
-    let snapshot =
-        match build_state_snapshot(state.storage(), &query.broadcaster, &profile, now, scope).await
-        {
-            Ok(snapshot) => snapshot,
+    // Health is not versioned, so admin ETags also fingerprint it.
+    let health = if audience == Audience::Admin {
+        match load_snapshot_health(state.storage(), &query.broadcaster).await {
+            Ok(health) => Some(health),
             Err(err) => {
                 counter!("api_state_requests_total", "result" => "error").increment(1);
+                let incident = ProblemResponse::new_incident();
                 error!(
+                    incident = %incident,
                     stage = "state",
                     broadcaster = %query.broadcaster,
                     error = %err,
-                    "failed to build state snapshot"
+                    "failed to load snapshot health"
                 );
                 return Err(ProblemResponse::new(
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "state_error",
-                    "failed to build state snapshot",
-                ));
+                    "failed to load integration health",
+                )
+                .with_incident(incident));
             }
-        };
-
-    counter!("api_state_requests_total", "result" => "ok").increment(1);
+        }
+    } else {
+        None
+    };
 
-    let scope_label = match scope {
-        StateScope::Session => "session",
-        StateScope::Since(_) => "since",
+    // Session snapshots only change when the version moves or the local day rolls over.
+    let etag = match scope {
+        StateScope::Session => {
+            match compute_local_day(now, &profile.timezone, profile.settings.day_rollover_hour) {
+                Ok(day) => match state.current_version(&query.broadcaster).await {
+                    Ok(version) => Some(snapshot_etag(version, &day, health.as_ref())),
+                    Err(err) => {
+                        warn!(
+                            stage = "state",
+                            broadcaster = %query.broadcaster,
+                            error = %err,
+                            "failed to resolve cached version"
+                        );
+                        None
+                    }
+                },
+                Err(_) => None,
+            }
+        }
+        StateScope::Since(_) => None,
     };
 
-    info!(
-        stage = "state",
-        broadcaster = %query.broadcaster,
-        scope = scope_label,
-        version = snapshot.version,
-        audience = audience.as_str(),
-        queue_len = snapshot.queue.len(),
-        counter_len = snapshot.counters_today.len(),
-        "state snapshot served"
-    );
+    if let Some(etag) = etag.as_deref() {
+        if if_none_match(&headers, etag) {
+            counter!("api_state_requests_total", "result" => "not_modified").increment(1);
+            return Ok(
+                (StatusCode::NOT_MODIFIED, [(header::ETAG, etag.to_string())]).into_response(),
+            );
+        }
+    }
 
-    let event = StageEvent {
-        ts: state.now(),
-        stage: StageKind::State,
-        trace_id: None,
-        op_id: None,
-        version: Some(snapshot.version),
-        broadcaster_id: Some(query.broadcaster.clone()),
-        meta: StageMetadata {
-            message: Some(scope_label.to_string()),
-            ..StageMetadata::default()
-        },
+    let mut snapshot = match build_state_snapshot(
+        state.storage(),
+        state.sse().active_queue_cache(),
+        &query.broadcaster,
+        &profile,
+        now,
+        scope,
+    )
+    .await
+    {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            counter!("api_state_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to build state snapshot"
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "state_error",
+                "failed to build state snapshot",
+            )
+            .with_incident(incident));
+        }
+    };
+
+    // Cursors index into the full session ordering, so only session snapshots are capped.
+    if matches!(scope, StateScope::Session) {
+        apply_queue_limit(&mut snapshot, state.snapshot_queue_limit());
+    }
+
+    snapshot.health = health.clone();
+
+    if let Some(limit) = query
+        .recent_completed
+        .map(|limit| limit.min(MAX_RECENT_COMPLETED))
+        .filter(|limit| *limit > 0)
+    {
+        match load_recent_completed(state.storage(), &query.broadcaster, &profile, now, limit).await
+        {
+            Ok(entries) => snapshot.recent_completed = Some(entries),
+            Err(err) => {
+                counter!("api_state_requests_total", "result" => "error").increment(1);
+                let incident = ProblemResponse::new_incident();
+                error!(
+                    incident = %incident,
+                    stage = "state",
+                    broadcaster = %query.broadcaster,
+                    error = %err,
+                    "failed to load recently completed entries"
+                );
+                return Err(ProblemResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "state_error",
+                    "failed to load recently completed entries",
+                )
+                .with_incident(incident));
+            }
+        }
+    }
+
+    counter!("api_state_requests_total", "result" => "ok").increment(1);
+
+    let scope_label = match scope {
+        StateScope::Session => "session",
+        StateScope::Since(_) => "since",
+    };
+
+    info!(
+        stage = "state",
+        broadcaster = %query.broadcaster,
+        scope = scope_label,
+        version = snapshot.version,
+        audience = audience.as_str(),
+        queue_len = snapshot.queue.len(),
+        counter_len = snapshot.counters_today.len(),
+        truncated = snapshot.truncated,
+        "state snapshot served"
+    );
+
+    let event = StageEvent {
+        ts: state.now(),
+        stage: StageKind::State,
+        trace_id: None,
+        op_id: None,
+        version: Some(snapshot.version),
+        broadcaster_id: Some(query.broadcaster.clone()),
+        meta: StageMetadata {
+            message: Some(scope_label.to_string()),
+            ..StageMetadata::default()
+        },
         r#in: StagePayload {
             redacted: true,
             payload: json!({
@@ -488,51 +1107,100 @@ async fn state_snapshot(
     };
     state.tap().publish(event);
 
-    Ok(Json(snapshot))
+    let etag = match scope {
+        StateScope::Session => {
+            compute_local_day(now, &profile.timezone, profile.settings.day_rollover_hour)
+                .ok()
+                .map(|day| snapshot_etag(snapshot.version, &day, health.as_ref()))
+        }
+        StateScope::Since(_) => None,
+    };
+    let mut response = Json(snapshot).into_response();
+    if let Some(value) = etag.and_then(|etag| header::HeaderValue::from_str(&etag).ok()) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    Ok(response)
+}
+
+fn snapshot_etag(version: u64, day: &str, health: Option<&SnapshotHealth>) -> String {
+    match health {
+        Some(health) => format!(
+            "W/\"{version}-{day}-{}-{}-{}\"",
+            u8::from(health.requires_reauth),
+            health
+                .token_expires_at
+                .map(|at| at.timestamp())
+                .unwrap_or_default(),
+            health.backfill_status.as_deref().unwrap_or("none"),
+        ),
+        None => format!("W/\"{version}-{day}\""),
+    }
 }
 
-async fn queue_dequeue(
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate == etag)
+}
+
+async fn queue_page(
     State(state): State<AppState>,
+    Query(query): Query<QueuePageQuery>,
     headers: HeaderMap,
-    Json(payload): Json<QueueDequeueRequest>,
-) -> Result<Json<QueueDequeueResponse>, ProblemResponse> {
-    let token = extract_bearer_token(&headers).ok_or_else(|| {
-        counter!("api_queue_dequeue_requests_total", "result" => "unauthorized").increment(1);
-        ProblemResponse::new(
-            StatusCode::UNAUTHORIZED,
-            "missing_token",
-            "queue dequeue endpoint requires a bearer token",
-        )
-    })?;
-
-    if Uuid::parse_str(&payload.op_id).is_err() {
-        counter!("api_queue_dequeue_requests_total", "result" => "error").increment(1);
-        return Err(ProblemResponse::new(
-            StatusCode::BAD_REQUEST,
-            "invalid_op_id",
-            "op_id must be a valid UUID",
-        ));
-    }
+) -> Result<Json<QueuePage>, ProblemResponse> {
+    let token = extract_bearer_token(&headers)
+        .map(|value| value.to_string())
+        .or_else(|| query.token.clone())
+        .ok_or_else(|| {
+            counter!("api_queue_page_requests_total", "result" => "unauthorized").increment(1);
+            ProblemResponse::new(
+                StatusCode::UNAUTHORIZED,
+                "missing_token",
+                "queue endpoint requires a bearer token",
+            )
+        })?;
 
     let now = state.now();
-    if let Err(err) =
-        state
-            .token_validator()
-            .validate(token, Audience::Admin, &payload.broadcaster, now)
-    {
-        counter!("api_queue_dequeue_requests_total", "result" => "unauthorized").increment(1);
+    if let Err(err) = state.token_validator().validate_any(
+        &token,
+        &[Audience::Overlay, Audience::Admin],
+        &query.broadcaster,
+        now,
+    ) {
+        counter!("api_queue_page_requests_total", "result" => "unauthorized").increment(1);
         return Err(problem_for_token_error(err));
     }
 
+    let cursor = match query.cursor.as_deref() {
+        None => None,
+        Some(raw) => Some(decode_queue_cursor(raw).ok_or_else(|| {
+            counter!("api_queue_page_requests_total", "result" => "error").increment(1);
+            ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_cursor",
+                "cursor is not a valid queue cursor",
+            )
+        })?),
+    };
+    let max_limit = match state.snapshot_queue_limit() {
+        0 => DEFAULT_SNAPSHOT_QUEUE_LIMIT,
+        limit => limit,
+    };
+    let limit = query.limit.unwrap_or(max_limit).clamp(1, max_limit);
+
     let profile = match state
         .storage()
         .broadcasters()
-        .fetch_settings(&payload.broadcaster)
+        .fetch_settings(&query.broadcaster)
         .await
     {
         Ok(profile) => profile,
         Err(SettingsError::NotFound) => {
-            counter!("api_queue_dequeue_requests_total", "result" => "error").increment(1);
+            counter!("api_queue_page_requests_total", "result" => "error").increment(1);
             return Err(ProblemResponse::new(
                 StatusCode::NOT_FOUND,
                 "broadcaster_not_found",
@@ -540,144 +1208,104 @@ async fn queue_dequeue(
             ));
         }
         Err(err) => {
-            counter!("api_queue_dequeue_requests_total", "result" => "error").increment(1);
+            counter!("api_queue_page_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
-                stage = "mutation",
-                broadcaster = %payload.broadcaster,
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
                 error = %err,
-                "failed to load broadcaster settings",
+                "failed to load broadcaster settings"
             );
             return Err(ProblemResponse::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "settings_error",
                 "failed to load broadcaster settings",
-            ));
+            )
+            .with_incident(incident));
         }
     };
 
-    let command = match payload.mode {
-        QueueDequeueMode::Complete => Command::QueueComplete(QueueCompleteCommand {
-            broadcaster_id: payload.broadcaster.clone(),
-            issued_at: now,
-            source: CommandSource::Admin,
-            entry_id: payload.entry_id.clone(),
-            op_id: payload.op_id.clone(),
-        }),
-        QueueDequeueMode::Undo => Command::QueueRemove(QueueRemoveCommand {
-            broadcaster_id: payload.broadcaster.clone(),
-            issued_at: now,
-            source: CommandSource::Admin,
-            entry_id: payload.entry_id.clone(),
-            reason: QueueRemovalReason::Undo,
-            op_id: payload.op_id.clone(),
-        }),
-    };
-
-    let application = match state
-        .command_executor()
-        .execute_admin_command(&payload.broadcaster, &profile.timezone, command)
-        .await
+    let page = match build_queue_page(
+        state.storage(),
+        &query.broadcaster,
+        &profile,
+        now,
+        cursor,
+        limit,
+    )
+    .await
     {
-        Ok(application) => application,
-        Err(err) => {
-            let (problem, label) = queue_error_response(&payload, err);
-            counter!("api_queue_dequeue_requests_total", "result" => label).increment(1);
-            return Err(problem);
+        Ok(page) => page,
+        Err(err @ StateError::StaleCursor { .. }) => {
+            counter!("api_queue_page_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::CONFLICT,
+                "cursor_stale",
+                err.to_string(),
+            ));
         }
-    };
-
-    broadcast_patches(&state, &payload.broadcaster, &application.patches).await;
-
-    let (entry_id, mode, user_today_count) = match application.result {
-        CommandApplyResult::QueueMutation {
-            entry_id,
-            mode,
-            user_today_count,
-        } => (entry_id, mode, user_today_count),
-        other => {
-            counter!("api_queue_dequeue_requests_total", "result" => "error").increment(1);
+        Err(err) => {
+            counter!("api_queue_page_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
-                stage = "mutation",
-                broadcaster = %payload.broadcaster,
-                mode = payload.mode.as_str(),
-                op_id = %payload.op_id,
-                result = ?other,
-                "unexpected command result for queue dequeue",
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to build queue page"
             );
             return Err(ProblemResponse::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "unexpected_result",
-                "executor returned unexpected result",
-            ));
+                "state_error",
+                "failed to build queue page",
+            )
+            .with_incident(incident));
         }
     };
 
-    counter!("api_queue_dequeue_requests_total", "result" => "ok").increment(1);
-    info!(
-        stage = "mutation",
-        kind = "queue.dequeue",
-        broadcaster = %payload.broadcaster,
-        entry_id = %entry_id,
-        mode = mode.as_str(),
-        op_id = %payload.op_id,
-        duplicate = application.duplicate,
-        version = application.version,
-        user_today_count,
-        "queue entry updated via admin mutation",
-    );
-
-    Ok(Json(QueueDequeueResponse {
-        version: application.version,
-        result: QueueDequeueResultBody {
-            entry_id,
-            mode: mode.as_str().to_string(),
-            user_today_count,
-        },
-    }))
+    counter!("api_queue_page_requests_total", "result" => "ok").increment(1);
+    Ok(Json(page))
 }
 
-async fn settings_update(
+async fn queue_served_today(
     State(state): State<AppState>,
+    Query(query): Query<ServedTodayQuery>,
     headers: HeaderMap,
-    Json(payload): Json<SettingsUpdateRequest>,
-) -> Result<Json<SettingsUpdateResponse>, ProblemResponse> {
-    let token = extract_bearer_token(&headers).ok_or_else(|| {
-        counter!("api_settings_update_requests_total", "result" => "unauthorized").increment(1);
-        ProblemResponse::new(
-            StatusCode::UNAUTHORIZED,
-            "missing_token",
-            "settings update endpoint requires a bearer token",
-        )
-    })?;
-
-    if Uuid::parse_str(&payload.op_id).is_err() {
-        counter!("api_settings_update_requests_total", "result" => "error").increment(1);
-        return Err(ProblemResponse::new(
-            StatusCode::BAD_REQUEST,
-            "invalid_op_id",
-            "op_id must be a valid UUID",
-        ));
-    }
+) -> Result<Json<ServedTodayResponse>, ProblemResponse> {
+    let token = extract_bearer_token(&headers)
+        .map(|value| value.to_string())
+        .or_else(|| query.token.clone())
+        .ok_or_else(|| {
+            counter!("api_queue_served_today_requests_total", "result" => "unauthorized")
+                .increment(1);
+            ProblemResponse::new(
+                StatusCode::UNAUTHORIZED,
+                "missing_token",
+                "served-today endpoint requires a bearer token",
+            )
+        })?;
 
     let now = state.now();
-    if let Err(err) =
-        state
-            .token_validator()
-            .validate(token, Audience::Admin, &payload.broadcaster, now)
-    {
-        counter!("api_settings_update_requests_total", "result" => "unauthorized").increment(1);
+    if let Err(err) = state.token_validator().validate_any(
+        &token,
+        &[Audience::Overlay, Audience::Admin],
+        &query.broadcaster,
+        now,
+    ) {
+        counter!("api_queue_served_today_requests_total", "result" => "unauthorized").increment(1);
         return Err(problem_for_token_error(err));
     }
 
     let profile = match state
         .storage()
         .broadcasters()
-        .fetch_settings(&payload.broadcaster)
+        .fetch_settings(&query.broadcaster)
         .await
     {
         Ok(profile) => profile,
         Err(SettingsError::NotFound) => {
-            counter!("api_settings_update_requests_total", "result" => "error").increment(1);
+            counter!("api_queue_served_today_requests_total", "result" => "error").increment(1);
             return Err(ProblemResponse::new(
                 StatusCode::NOT_FOUND,
                 "broadcaster_not_found",
@@ -685,987 +1313,4288 @@ async fn settings_update(
             ));
         }
         Err(err) => {
-            counter!("api_settings_update_requests_total", "result" => "error").increment(1);
+            counter!("api_queue_served_today_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
-                stage = "mutation",
-                broadcaster = %payload.broadcaster,
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
                 error = %err,
-                "failed to load broadcaster settings",
+                "failed to load broadcaster settings"
             );
             return Err(ProblemResponse::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "settings_error",
                 "failed to load broadcaster settings",
-            ));
+            )
+            .with_incident(incident));
         }
     };
 
-    let command = Command::SettingsUpdate(SettingsUpdateCommand {
-        broadcaster_id: payload.broadcaster.clone(),
-        issued_at: now,
-        source: CommandSource::Admin,
-        patch: payload.patch.clone(),
-        op_id: payload.op_id.clone(),
-    });
+    let (day, (day_start, day_end)) =
+        match compute_local_day(now, &profile.timezone, profile.settings.day_rollover_hour)
+            .and_then(|day| {
+                local_day_bounds(now, &profile.timezone, profile.settings.day_rollover_hour)
+                    .map(|bounds| (day, bounds))
+            }) {
+            Ok(result) => result,
+            Err(err) => {
+                counter!("api_queue_served_today_requests_total", "result" => "error").increment(1);
+                return Err(ProblemResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "invalid_timezone",
+                    err.to_string(),
+                ));
+            }
+        };
 
-    let application = match state
-        .command_executor()
-        .execute_admin_command(&payload.broadcaster, &profile.timezone, command)
+    let completed = match state
+        .storage()
+        .queue()
+        .count_terminal_today(
+            &query.broadcaster,
+            day_start,
+            day_end,
+            QueueEntryStatus::Completed,
+        )
         .await
     {
-        Ok(application) => application,
+        Ok(count) => count,
         Err(err) => {
-            let (problem, label) = settings_error_response(&payload, err);
-            counter!("api_settings_update_requests_total", "result" => label).increment(1);
-            return Err(problem);
+            counter!("api_queue_served_today_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to count completed entries"
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "state_error",
+                "failed to count completed entries",
+            )
+            .with_incident(incident));
         }
     };
 
-    broadcast_patches(&state, &payload.broadcaster, &application.patches).await;
+    counter!("api_queue_served_today_requests_total", "result" => "ok").increment(1);
+    Ok(Json(ServedTodayResponse { day, completed }))
+}
 
-    let applied = match application.result {
-        CommandApplyResult::SettingsUpdated { applied } => applied,
-        other => {
-            counter!("api_settings_update_requests_total", "result" => "error").increment(1);
+/// Returns the entries and counters touched between two versions, replayed from the command
+/// log; entries are shown as their current rows (admin only).
+async fn state_diff(
+    State(state): State<AppState>,
+    Query(query): Query<StateDiffQuery>,
+    headers: HeaderMap,
+) -> Result<Json<StateDiff>, ProblemResponse> {
+    let token = extract_bearer_token(&headers).ok_or_else(|| {
+        counter!("api_state_diff_requests_total", "result" => "unauthorized").increment(1);
+        ProblemResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "state diff endpoint requires a bearer token",
+        )
+    })?;
+
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(token, Audience::Admin, &query.broadcaster, now)
+    {
+        counter!("api_state_diff_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
+
+    let profile = match state
+        .storage()
+        .broadcasters()
+        .fetch_settings(&query.broadcaster)
+        .await
+    {
+        Ok(profile) => profile,
+        Err(SettingsError::NotFound) => {
+            counter!("api_state_diff_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ));
+        }
+        Err(err) => {
+            counter!("api_state_diff_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
-                stage = "mutation",
-                broadcaster = %payload.broadcaster,
-                op_id = %payload.op_id,
-                result = ?other,
-                "unexpected command result for settings update",
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to load broadcaster settings"
             );
             return Err(ProblemResponse::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "unexpected_result",
-                "executor returned unexpected result",
-            ));
+                "settings_error",
+                "failed to load broadcaster settings",
+            )
+            .with_incident(incident));
         }
     };
 
-    counter!("api_settings_update_requests_total", "result" => "ok").increment(1);
-    info!(
-        stage = "mutation",
-        kind = "settings.update",
-        broadcaster = %payload.broadcaster,
-        op_id = %payload.op_id,
-        duplicate = application.duplicate,
-        version = application.version,
-        "settings updated via admin mutation",
-    );
+    let current = match state
+        .command_executor()
+        .versions()
+        .current_version(state.storage(), &query.broadcaster)
+        .await
+    {
+        Ok(version) => version,
+        Err(err) => {
+            counter!("api_state_diff_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to load current version"
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "state_error",
+                "failed to load current version",
+            )
+            .with_incident(incident));
+        }
+    };
+    if query.from >= query.to || query.to > current {
+        counter!("api_state_diff_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_range",
+            format!("from must be lower than to, and to at most {current}"),
+        ));
+    }
 
-    Ok(Json(SettingsUpdateResponse {
-        version: application.version,
-        result: SettingsUpdateResultBody { applied },
-    }))
+    match build_touched_entries_diff(
+        state.storage(),
+        &query.broadcaster,
+        &profile,
+        query.from,
+        query.to,
+    )
+    .await
+    {
+        Ok(diff) => {
+            counter!("api_state_diff_requests_total", "result" => "ok").increment(1);
+            Ok(Json(diff))
+        }
+        Err(err @ StateError::HistoryUnavailable { .. }) => {
+            counter!("api_state_diff_requests_total", "result" => "error").increment(1);
+            Err(ProblemResponse::new(
+                StatusCode::GONE,
+                "history_unavailable",
+                err.to_string(),
+            ))
+        }
+        Err(err) => {
+            counter!("api_state_diff_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to build state diff"
+            );
+            Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "state_error",
+                "failed to build state diff",
+            )
+            .with_incident(incident))
+        }
+    }
 }
 
-async fn sse_handler(
-    state: AppState,
-    query: SseQuery,
+/// Exports the active queue, or today's entries in a terminal `status`, as CSV.
+async fn queue_export_csv(
+    State(state): State<AppState>,
+    Query(query): Query<QueueExportQuery>,
     headers: HeaderMap,
-    audience: Audience,
-) -> Result<Sse<SseStream>, (StatusCode, String)> {
-    let token = query
-        .token
-        .as_deref()
-        .ok_or((StatusCode::UNAUTHORIZED, "missing_token".to_string()))?;
-
-    let since_version = headers
-        .get("Last-Event-ID")
-        .and_then(|value| value.to_str().ok())
-        .and_then(|raw| raw.parse::<u64>().ok())
-        .or(query.since_version);
+) -> Result<Response, ProblemResponse> {
+    let token = extract_bearer_token(&headers)
+        .map(|value| value.to_string())
+        .or_else(|| query.token.clone())
+        .ok_or_else(|| {
+            counter!("api_queue_export_requests_total", "result" => "unauthorized").increment(1);
+            ProblemResponse::new(
+                StatusCode::UNAUTHORIZED,
+                "missing_token",
+                "queue export endpoint requires a bearer token",
+            )
+        })?;
 
-    let filter_types = parse_types(query.types.clone())?;
+    let status = match query.status.as_deref().map(str::to_ascii_uppercase) {
+        None => QueueEntryStatus::Queued,
+        Some(value) if value == "QUEUED" => QueueEntryStatus::Queued,
+        Some(value) if value == "COMPLETED" => QueueEntryStatus::Completed,
+        Some(value) if value == "REMOVED" => QueueEntryStatus::Removed,
+        Some(_) => {
+            counter!("api_queue_export_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_status",
+                "status must be one of QUEUED, COMPLETED, REMOVED",
+            ));
+        }
+    };
 
-    state
-        .token_validator()
-        .validate(token, audience, &query.broadcaster, state.now())
-        .map_err(|_| (StatusCode::FORBIDDEN, "invalid_token".to_string()))?;
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(&token, Audience::Admin, &query.broadcaster, now)
+    {
+        counter!("api_queue_export_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
 
-    let profile = state
+    let profile = match state
         .storage()
         .broadcasters()
         .fetch_settings(&query.broadcaster)
         .await
-        .map_err(|err| match err {
-            SettingsError::NotFound => (StatusCode::NOT_FOUND, "broadcaster_not_found".to_string()),
-            _ => (
+    {
+        Ok(profile) => profile,
+        Err(SettingsError::NotFound) => {
+            counter!("api_queue_export_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ));
+        }
+        Err(err) => {
+            counter!("api_queue_export_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to load broadcaster settings"
+            );
+            return Err(ProblemResponse::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "failed_to_load_settings".to_string(),
-            ),
-        })?;
-
-    let subscription = state
-        .sse()
-        .subscribe(&query.broadcaster, audience, since_version, filter_types)
-        .await;
+                "settings_error",
+                "failed to load broadcaster settings",
+            )
+            .with_incident(incident));
+        }
+    };
 
-    let stream = if subscription.ring_miss() {
-        let patch = state
-            .sse()
-            .build_state_replace(&query.broadcaster, &profile, state.now())
-            .await
-            .map_err(|_| {
-                (
+    let (day, (day_start, day_end)) =
+        match compute_local_day(now, &profile.timezone, profile.settings.day_rollover_hour)
+            .and_then(|day| {
+                local_day_bounds(now, &profile.timezone, profile.settings.day_rollover_hour)
+                    .map(|bounds| (day, bounds))
+            }) {
+            Ok(result) => result,
+            Err(err) => {
+                counter!("api_queue_export_requests_total", "result" => "error").increment(1);
+                return Err(ProblemResponse::new(
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed_to_build_state".to_string(),
-                )
-            })?;
-        let message = state.sse().event_from_patch(&patch).map_err(|_| {
-            (
+                    "invalid_timezone",
+                    err.to_string(),
+                ));
+            }
+        };
+
+    let queue_repo = state.storage().queue();
+    let rows = match status {
+        QueueEntryStatus::Queued => {
+            queue_repo
+                .list_active_with_counts(&query.broadcaster, &day)
+                .await
+        }
+        terminal => {
+            queue_repo
+                .list_terminal_with_counts(&query.broadcaster, &day, day_start, day_end, terminal)
+                .await
+        }
+    };
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(err) => {
+            counter!("api_queue_export_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "state",
+                broadcaster = %query.broadcaster,
+                error = %err,
+                "failed to list queue entries for export"
+            );
+            return Err(ProblemResponse::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "failed_to_serialize_state".to_string(),
+                "state_error",
+                "failed to list queue entries",
             )
-        })?;
-        subscription.into_stream_with_initial(vec![message])
-    } else {
-        subscription.into_stream()
+            .with_incident(incident));
+        }
     };
 
-    let keep_alive = axum::response::sse::KeepAlive::new()
-        .interval(Duration::from_secs(state.sse_heartbeat()))
-        .text("heartbeat");
+    let lines =
+        std::iter::once(QUEUE_EXPORT_HEADER.to_string()).chain(rows.into_iter().map(|row| {
+            let (entry, today_count) = row.into_domain();
+            format!(
+                "{},{},{},{},{},{},{}\n",
+                csv_field(&entry.user_id),
+                csv_field(&entry.user_login),
+                csv_field(&entry.user_display_name),
+                csv_field(&entry.reward_id),
+                entry.status.as_str(),
+                entry.enqueued_at.to_rfc3339(),
+                today_count
+            )
+        }));
+    let body = Body::from_stream(tokio_stream::iter(
+        lines.map(Ok::<_, std::convert::Infallible>),
+    ));
 
-    Ok(Sse::new(stream).keep_alive(keep_alive))
+    counter!("api_queue_export_requests_total", "result" => "ok").increment(1);
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/csv; charset=utf-8")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"queue-{day}.csv\""),
+        )
+        .body(body)
+        .unwrap())
 }
 
-fn parse_types(raw: Option<String>) -> Result<Option<HashSet<String>>, (StatusCode, String)> {
-    let Some(value) = raw else {
-        return Ok(None);
+/// Quotes a CSV field when it contains a delimiter, quote, or line break.
+///
+/// Viewer-controlled text starting with a formula trigger is prefixed with `'` so spreadsheets
+/// show it as text instead of evaluating it.
+fn csv_field(value: &str) -> std::borrow::Cow<'_, str> {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        std::borrow::Cow::Owned(format!("'{value}"))
+    } else {
+        std::borrow::Cow::Borrowed(value)
     };
-    let mut set = HashSet::new();
-    for item in value.split(',') {
-        let trimmed = item.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        set.insert(trimmed.to_string());
+    if value.contains([',', '"', '\n', '\r']) {
+        std::borrow::Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        value
     }
-    Ok(Some(set))
 }
 
-async fn broadcast_patches(state: &AppState, broadcaster_id: &str, patches: &[Patch]) {
-    for patch in patches {
-        if let Err(err) = state
-            .sse()
-            .broadcast_patch(broadcaster_id, patch, state.now())
-            .await
-        {
-            error!(
-                stage = "sse",
-                broadcaster = %broadcaster_id,
-                error = %err,
-                "failed to broadcast patch",
-            );
-            continue;
-        }
-        emit_sse_stage(state, broadcaster_id, patch);
+async fn queue_add(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<QueueAddRequest>,
+) -> Result<Json<QueueAddResponse>, ProblemResponse> {
+    let token = extract_bearer_token(&headers).ok_or_else(|| {
+        counter!("api_queue_add_requests_total", "result" => "unauthorized").increment(1);
+        ProblemResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "queue add endpoint requires a bearer token",
+        )
+    })?;
+
+    if Uuid::parse_str(&payload.op_id).is_err() {
+        counter!("api_queue_add_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_op_id",
+            "op_id must be a valid UUID",
+        ));
     }
-}
 
-fn queue_error_response(
-    request: &QueueDequeueRequest,
-    err: CommandExecutorError,
-) -> (ProblemResponse, &'static str) {
-    match err {
-        CommandExecutorError::Queue(QueueError::NotFound) => {
-            error!(
-                stage = "mutation",
-                broadcaster = %request.broadcaster,
-                entry_id = %request.entry_id,
-                mode = request.mode.as_str(),
-                "queue entry not found",
-            );
-            (
-                ProblemResponse::new(
-                    StatusCode::NOT_FOUND,
-                    "queue_entry_not_found",
-                    "queue entry not found",
-                ),
-                "not_found",
-            )
+    if payload.user_id.trim().is_empty() {
+        counter!("api_queue_add_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_user",
+            "user_id must not be empty",
+        ));
+    }
+
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(token, Audience::Admin, &payload.broadcaster, now)
+    {
+        counter!("api_queue_add_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
+
+    let profile = match state
+        .storage()
+        .broadcasters()
+        .fetch_settings(&payload.broadcaster)
+        .await
+    {
+        Ok(profile) => profile,
+        Err(SettingsError::NotFound) => {
+            counter!("api_queue_add_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ));
         }
-        CommandExecutorError::Queue(QueueError::InvalidTransition(status)) => {
+        Err(err) => {
+            counter!("api_queue_add_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
+                incident = %incident,
                 stage = "mutation",
-                broadcaster = %request.broadcaster,
-                entry_id = %request.entry_id,
-                mode = request.mode.as_str(),
-                status = ?status,
-                "queue entry is not queued",
+                broadcaster = %payload.broadcaster,
+                error = %err,
+                "failed to load broadcaster settings",
             );
-            (
-                ProblemResponse::new(
-                    StatusCode::CONFLICT,
-                    "invalid_transition",
-                    format!("queue entry is not queued (current={status:?})"),
-                ),
-                "conflict",
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "settings_error",
+                "failed to load broadcaster settings",
             )
+            .with_incident(incident));
         }
-        CommandExecutorError::OpConflict { op_id } => {
-            error!(
-                stage = "mutation",
-                broadcaster = %request.broadcaster,
-                entry_id = %request.entry_id,
-                op_id = %op_id,
-                "op_id conflict for queue dequeue",
-            );
-            (
-                ProblemResponse::new(
-                    StatusCode::PRECONDITION_FAILED,
-                    "op_conflict",
-                    "op_id already used with different payload",
-                ),
-                "conflict",
-            )
+    };
+
+    if !profile.settings.policy.allow_manual_add {
+        counter!("api_queue_add_requests_total", "result" => "forbidden").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::FORBIDDEN,
+            "manual_add_disabled",
+            "manual queue additions are disabled for this broadcaster",
+        ));
+    }
+
+    let command = Command::QueueAdd(QueueAddCommand {
+        broadcaster_id: payload.broadcaster.clone(),
+        issued_at: now,
+        source: CommandSource::Admin,
+        user: NormalizedUser {
+            id: payload.user_id.clone(),
+            login: payload.user_login.clone(),
+            display_name: payload.user_display_name.clone(),
+        },
+        op_id: payload.op_id.clone(),
+    });
+
+    let guard = state.command_executor().lock(&payload.broadcaster).await;
+    let application = match state
+        .command_executor()
+        .execute_admin_command_locked(&guard, &profile.timezone, Audience::Admin, command)
+        .await
+    {
+        Ok(application) => application,
+        Err(err) => {
+            let (problem, label) = queue_add_error_response(&payload, err);
+            counter!("api_queue_add_requests_total", "result" => label).increment(1);
+            return Err(problem);
         }
-        CommandExecutorError::InvalidTimezone(detail) => {
+    };
+
+    broadcast_patches(&state, &payload.broadcaster, &application.patches).await;
+    drop(guard);
+
+    let (entry_id, user_today_count) = match application.result {
+        CommandApplyResult::QueueAdded {
+            entry_id,
+            user_today_count,
+        } => (entry_id, user_today_count),
+        other => {
+            counter!("api_queue_add_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
+                incident = %incident,
                 stage = "mutation",
-                broadcaster = %request.broadcaster,
-                entry_id = %request.entry_id,
-                detail = %detail,
-                "invalid timezone while processing queue dequeue",
+                broadcaster = %payload.broadcaster,
+                op_id = %payload.op_id,
+                result = ?other,
+                "unexpected command result for queue add",
             );
-            (
-                ProblemResponse::new(
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "invalid_timezone",
-                    detail,
-                ),
-                "error",
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "unexpected_result",
+                "executor returned unexpected result",
             )
+            .with_incident(incident));
         }
-        CommandExecutorError::Settings(SettingsError::NotFound) => {
+    };
+
+    counter!("api_queue_add_requests_total", "result" => "ok").increment(1);
+    info!(
+        stage = "mutation",
+        kind = "queue.add",
+        broadcaster = %payload.broadcaster,
+        entry_id = %entry_id,
+        op_id = %payload.op_id,
+        duplicate = application.duplicate,
+        version = application.version,
+        user_today_count,
+        "queue entry added via admin mutation",
+    );
+
+    Ok(Json(QueueAddResponse {
+        version: application.version,
+        result: QueueAddResultBody {
+            entry_id,
+            user_today_count,
+        },
+    }))
+}
+
+async fn queue_dequeue(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<QueueDequeueRequest>,
+) -> Result<Json<QueueDequeueResponse>, ProblemResponse> {
+    let token = extract_bearer_token(&headers).ok_or_else(|| {
+        counter!("api_queue_dequeue_requests_total", "result" => "unauthorized").increment(1);
+        ProblemResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "queue dequeue endpoint requires a bearer token",
+        )
+    })?;
+
+    if Uuid::parse_str(&payload.op_id).is_err() {
+        counter!("api_queue_dequeue_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_op_id",
+            "op_id must be a valid UUID",
+        ));
+    }
+
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(token, Audience::Admin, &payload.broadcaster, now)
+    {
+        counter!("api_queue_dequeue_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
+
+    let profile = match state
+        .storage()
+        .broadcasters()
+        .fetch_settings(&payload.broadcaster)
+        .await
+    {
+        Ok(profile) => profile,
+        Err(SettingsError::NotFound) => {
+            counter!("api_queue_dequeue_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ));
+        }
+        Err(err) => {
+            counter!("api_queue_dequeue_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
+                incident = %incident,
                 stage = "mutation",
-                broadcaster = %request.broadcaster,
-                entry_id = %request.entry_id,
-                "broadcaster missing during queue dequeue",
+                broadcaster = %payload.broadcaster,
+                error = %err,
+                "failed to load broadcaster settings",
             );
-            (
-                ProblemResponse::new(
-                    StatusCode::NOT_FOUND,
-                    "broadcaster_not_found",
-                    "broadcaster is not provisioned",
-                ),
-                "error",
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "settings_error",
+                "failed to load broadcaster settings",
             )
+            .with_incident(incident));
         }
-        other => {
-            error!(
-                stage = "mutation",
-                broadcaster = %request.broadcaster,
-                entry_id = %request.entry_id,
-                mode = request.mode.as_str(),
-                error = %other,
-                "failed to execute queue dequeue",
-            );
-            (
-                ProblemResponse::new(
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "command_error",
-                    "failed to execute queue dequeue",
-                ),
-                "error",
-            )
+    };
+
+    let command = match payload.mode {
+        QueueDequeueMode::Complete => Command::QueueComplete(QueueCompleteCommand {
+            broadcaster_id: payload.broadcaster.clone(),
+            issued_at: now,
+            source: CommandSource::Admin,
+            entry_id: payload.entry_id.clone(),
+            op_id: payload.op_id.clone(),
+        }),
+        QueueDequeueMode::Undo => Command::QueueRemove(QueueRemoveCommand {
+            broadcaster_id: payload.broadcaster.clone(),
+            issued_at: now,
+            source: CommandSource::Admin,
+            entry_id: payload.entry_id.clone(),
+            reason: QueueRemovalReason::Undo,
+            op_id: payload.op_id.clone(),
+        }),
+    };
+
+    let guard = state.command_executor().lock(&payload.broadcaster).await;
+    let application = match state
+        .command_executor()
+        .execute_admin_command_locked(&guard, &profile.timezone, Audience::Admin, command)
+        .await
+    {
+        Ok(application) => application,
+        Err(err) => {
+            let (problem, label) = queue_error_response(&payload, err);
+            counter!("api_queue_dequeue_requests_total", "result" => label).increment(1);
+            return Err(problem);
         }
-    }
-}
+    };
 
-fn settings_error_response(
-    request: &SettingsUpdateRequest,
-    err: CommandExecutorError,
-) -> (ProblemResponse, &'static str) {
-    match err {
-        CommandExecutorError::OpConflict { op_id } => {
+    broadcast_patches(&state, &payload.broadcaster, &application.patches).await;
+    drop(guard);
+
+    let (entry_id, mode, user_today_count) = match application.result {
+        CommandApplyResult::QueueMutation {
+            entry_id,
+            mode,
+            user_today_count,
+        } => (entry_id, mode, user_today_count),
+        other => {
+            counter!("api_queue_dequeue_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
+                incident = %incident,
                 stage = "mutation",
-                broadcaster = %request.broadcaster,
-                op_id = %op_id,
-                "op_id conflict for settings update",
+                broadcaster = %payload.broadcaster,
+                mode = payload.mode.as_str(),
+                op_id = %payload.op_id,
+                result = ?other,
+                "unexpected command result for queue dequeue",
             );
-            (
-                ProblemResponse::new(
-                    StatusCode::PRECONDITION_FAILED,
-                    "op_conflict",
-                    "op_id already used with different payload",
-                ),
-                "conflict",
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "unexpected_result",
+                "executor returned unexpected result",
             )
+            .with_incident(incident));
         }
-        CommandExecutorError::InvalidSettingsPatch(detail) => {
-            error!(
-                stage = "mutation",
-                broadcaster = %request.broadcaster,
-                detail = %detail,
-                "invalid settings patch",
-            );
-            (
-                ProblemResponse::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_patch", detail),
-                "error",
-            )
+    };
+
+    counter!("api_queue_dequeue_requests_total", "result" => "ok").increment(1);
+    info!(
+        stage = "mutation",
+        kind = "queue.dequeue",
+        broadcaster = %payload.broadcaster,
+        entry_id = %entry_id,
+        mode = mode.as_str(),
+        op_id = %payload.op_id,
+        duplicate = application.duplicate,
+        version = application.version,
+        user_today_count,
+        "queue entry updated via admin mutation",
+    );
+
+    Ok(Json(QueueDequeueResponse {
+        version: application.version,
+        result: QueueDequeueResultBody {
+            entry_id,
+            mode: mode.as_str().to_string(),
+            user_today_count,
+        },
+    }))
+}
+
+async fn settings_update(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<SettingsUpdateRequest>,
+) -> Result<Json<SettingsUpdateResponse>, ProblemResponse> {
+    let token = extract_bearer_token(&headers).ok_or_else(|| {
+        counter!("api_settings_update_requests_total", "result" => "unauthorized").increment(1);
+        ProblemResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "settings update endpoint requires a bearer token",
+        )
+    })?;
+
+    if Uuid::parse_str(&payload.op_id).is_err() {
+        counter!("api_settings_update_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_op_id",
+            "op_id must be a valid UUID",
+        ));
+    }
+
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(token, Audience::Admin, &payload.broadcaster, now)
+    {
+        counter!("api_settings_update_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
+
+    let profile = match state
+        .storage()
+        .broadcasters()
+        .fetch_settings(&payload.broadcaster)
+        .await
+    {
+        Ok(profile) => profile,
+        Err(SettingsError::NotFound) => {
+            counter!("api_settings_update_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ));
         }
-        CommandExecutorError::Settings(SettingsError::NotFound) => {
+        Err(err) => {
+            counter!("api_settings_update_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
+                incident = %incident,
                 stage = "mutation",
-                broadcaster = %request.broadcaster,
-                "broadcaster missing during settings update",
+                broadcaster = %payload.broadcaster,
+                error = %err,
+                "failed to load broadcaster settings",
             );
-            (
-                ProblemResponse::new(
-                    StatusCode::NOT_FOUND,
-                    "broadcaster_not_found",
-                    "broadcaster is not provisioned",
-                ),
-                "error",
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "settings_error",
+                "failed to load broadcaster settings",
             )
+            .with_incident(incident));
+        }
+    };
+
+    let command = Command::SettingsUpdate(SettingsUpdateCommand {
+        broadcaster_id: payload.broadcaster.clone(),
+        issued_at: now,
+        source: CommandSource::Admin,
+        patch: payload.patch.clone(),
+        op_id: payload.op_id.clone(),
+    });
+
+    let guard = state.command_executor().lock(&payload.broadcaster).await;
+    let application = match state
+        .command_executor()
+        .execute_admin_command_locked(&guard, &profile.timezone, Audience::Admin, command)
+        .await
+    {
+        Ok(application) => application,
+        Err(err) => {
+            let (problem, label) = settings_error_response(&payload, err);
+            counter!("api_settings_update_requests_total", "result" => label).increment(1);
+            return Err(problem);
         }
+    };
+
+    broadcast_patches(&state, &payload.broadcaster, &application.patches).await;
+    drop(guard);
+
+    let applied = match application.result {
+        CommandApplyResult::SettingsUpdated { applied } => applied,
         other => {
+            counter!("api_settings_update_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
             error!(
+                incident = %incident,
                 stage = "mutation",
-                broadcaster = %request.broadcaster,
-                error = %other,
-                "failed to execute settings update",
+                broadcaster = %payload.broadcaster,
+                op_id = %payload.op_id,
+                result = ?other,
+                "unexpected command result for settings update",
             );
-            (
-                ProblemResponse::new(
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "command_error",
-                    "failed to execute settings update",
-                ),
-                "error",
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "unexpected_result",
+                "executor returned unexpected result",
             )
+            .with_incident(incident));
         }
-    }
-}
+    };
 
-fn problem_for_token_error(err: TokenError) -> ProblemResponse {
-    ProblemResponse::new(StatusCode::FORBIDDEN, "invalid_token", err.to_string())
+    counter!("api_settings_update_requests_total", "result" => "ok").increment(1);
+    info!(
+        stage = "mutation",
+        kind = "settings.update",
+        broadcaster = %payload.broadcaster,
+        op_id = %payload.op_id,
+        duplicate = application.duplicate,
+        version = application.version,
+        "settings updated via admin mutation",
+    );
+
+    Ok(Json(SettingsUpdateResponse {
+        version: application.version,
+        result: SettingsUpdateResultBody { applied },
+    }))
 }
 
-fn parse_state_scope(
-    scope: Option<&str>,
-    since: Option<&str>,
-) -> Result<StateScope, ProblemResponse> {
-    match scope {
-        None | Some("session") => Ok(StateScope::Session),
-        Some("since") => {
-            let since_raw = since.ok_or_else(|| {
-                ProblemResponse::new(
-                    StatusCode::BAD_REQUEST,
-                    "missing_since",
-                    "scope=since requires the since parameter",
-                )
-            })?;
-            let parsed = DateTime::parse_from_rfc3339(since_raw).map_err(|err| {
-                ProblemResponse::new(
-                    StatusCode::BAD_REQUEST,
-                    "invalid_since",
-                    format!("invalid since timestamp: {err}"),
-                )
-            })?;
-            Ok(StateScope::Since(parsed.with_timezone(&Utc)))
-        }
-        Some(other) => Err(ProblemResponse::new(
+async fn settings_timezone(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<TimezoneUpdateRequest>,
+) -> Result<Json<TimezoneUpdateResponse>, ProblemResponse> {
+    let token = extract_bearer_token(&headers).ok_or_else(|| {
+        counter!("api_settings_timezone_requests_total", "result" => "unauthorized").increment(1);
+        ProblemResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "timezone update endpoint requires a bearer token",
+        )
+    })?;
+
+    if Uuid::parse_str(&payload.op_id).is_err() {
+        counter!("api_settings_timezone_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
             StatusCode::BAD_REQUEST,
-            "invalid_scope",
-            format!("unsupported scope: {other}"),
-        )),
+            "invalid_op_id",
+            "op_id must be a valid UUID",
+        ));
     }
-}
 
-fn extract_bearer_token(headers: &HeaderMap) -> Option<&str> {
-    headers
-        .get(header::AUTHORIZATION)
-        .and_then(|value| value.to_str().ok())
-        .and_then(|value| {
-            value
-                .strip_prefix("Bearer ")
-                .or_else(|| value.strip_prefix("bearer "))
-        })
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-}
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(token, Audience::Admin, &payload.broadcaster, now)
+    {
+        counter!("api_settings_timezone_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum::{
-        body::Body,
-        http::{HeaderValue, Method, Request, StatusCode},
+    let profile = match state
+        .storage()
+        .broadcasters()
+        .fetch_settings(&payload.broadcaster)
+        .await
+    {
+        Ok(profile) => profile,
+        Err(SettingsError::NotFound) => {
+            counter!("api_settings_timezone_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ));
+        }
+        Err(err) => {
+            counter!("api_settings_timezone_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %payload.broadcaster,
+                error = %err,
+                "failed to load broadcaster settings",
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "settings_error",
+                "failed to load broadcaster settings",
+            )
+            .with_incident(incident));
+        }
     };
-    use chrono::{Duration as ChronoDuration, SecondsFormat, TimeZone};
-    use http_body_util::BodyExt;
-    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
-    use serde_json::Value;
-    use serde_urlencoded::to_string;
-    use sqlx::query;
-    use std::{sync::Arc, time::Duration as StdDuration};
-    use tokio::time::{self, Duration};
-    use tower::ServiceExt;
-
-    use crate::sse::TokenClaims;
-    use crate::tap::StageEvent;
-    use reqwest::Client;
-    use twi_overlay_core::types::{QueueEntryStatus, Settings};
-    use twi_overlay_twitch::{HelixClient, TwitchOAuthClient};
-    use url::Url;
 
-    async fn setup_state() -> AppState {
-        let metrics = telemetry::init_metrics().expect("metrics init");
-        let tap = TapHub::new();
+    let timezone = payload.timezone.trim().to_string();
+    let command = Command::TimezoneUpdate(TimezoneUpdateCommand {
+        broadcaster_id: payload.broadcaster.clone(),
+        issued_at: now,
+        source: CommandSource::Admin,
+        timezone: timezone.clone(),
+        op_id: payload.op_id.clone(),
+    });
 
-        let database = Database::connect("sqlite::memory:?cache=shared")
-            .await
-            .expect("connect");
-        database.run_migrations().await.expect("migrations");
+    let guard = state.command_executor().lock(&payload.broadcaster).await;
+    let application = match state
+        .command_executor()
+        .execute_admin_command_locked(&guard, &profile.timezone, Audience::Admin, command)
+        .await
+    {
+        Ok(application) => application,
+        Err(err) => {
+            let (problem, label) = timezone_error_response(&payload, err);
+            counter!("api_settings_timezone_requests_total", "result" => label).increment(1);
+            return Err(problem);
+        }
+    };
 
-        let secret: Arc<[u8]> = Arc::from(b"test-secret".to_vec().into_boxed_slice());
-        let http = Client::builder().build().expect("client");
-        let oauth_client = TwitchOAuthClient::new(
-            "client",
-            "secret",
-            Url::parse("https://id.twitch.tv/oauth2/").expect("url"),
-            http.clone(),
-        );
-        let helix_client = HelixClient::new(
-            "client",
-            Url::parse("https://api.twitch.tv/helix/").expect("url"),
-            http,
-        );
-        let (state, _worker) = AppState::new(
-            metrics,
-            tap,
-            database,
-            secret,
-            b"token-secret".to_vec(),
-            64,
-            StdDuration::from_secs(60),
-            25,
-            helix_client,
-            oauth_client,
-            "http://localhost/oauth/callback".to_string(),
-            StdDuration::from_secs(600),
-            StdDuration::from_secs(300),
-            50,
-        );
-        state
+    broadcast_patches(&state, &payload.broadcaster, &application.patches).await;
+    if !application.duplicate {
+        // Queue and counters are keyed by the local day, so connected clients get a snapshot
+        // rebuilt for the new timezone instead of keeping the old day until they reconnect.
+        broadcast_state_replace(&state, &payload.broadcaster).await;
     }
+    drop(guard);
 
-    fn issue_token(
-        secret: &[u8],
-        broadcaster: &str,
-        audience: &str,
-        exp: chrono::DateTime<Utc>,
-    ) -> String {
-        let claims = TokenClaims {
-            sub: broadcaster.to_string(),
-            aud: audience.to_string(),
-            exp: exp.timestamp() as usize,
-            nbf: None,
-        };
-        let header = Header::new(Algorithm::HS256);
-        encode(&header, &claims, &EncodingKey::from_secret(secret)).expect("token encode")
-    }
+    let day =
+        compute_local_day(now, &timezone, profile.settings.day_rollover_hour).map_err(|_| {
+            counter!("api_settings_timezone_requests_total", "result" => "error").increment(1);
+            ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "invalid_timezone",
+                "failed to compute local day",
+            )
+        })?;
 
-    fn bearer(value: &str) -> HeaderValue {
-        let header_value = format!("Bearer {value}");
-        HeaderValue::from_str(&header_value).expect("header value")
-    }
+    counter!("api_settings_timezone_requests_total", "result" => "ok").increment(1);
+    info!(
+        stage = "mutation",
+        kind = "settings.timezone",
+        broadcaster = %payload.broadcaster,
+        op_id = %payload.op_id,
+        duplicate = application.duplicate,
+        version = application.version,
+        timezone = %timezone,
+        %day,
+        "broadcaster timezone updated",
+    );
 
-    fn fmt_time(value: chrono::DateTime<Utc>) -> String {
-        value.to_rfc3339_opts(SecondsFormat::Millis, true)
-    }
+    Ok(Json(TimezoneUpdateResponse {
+        version: application.version,
+        timezone,
+        day,
+    }))
+}
 
-    async fn provision_broadcaster(state: &AppState, version: i64) {
-        let pool = state.storage().pool();
-        let created_at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
-        query(
-            "INSERT INTO broadcasters (id, twitch_broadcaster_id, display_name, timezone, settings_json, created_at, updated_at) VALUES ('b-1','twitch-1','Example','UTC',?, ?, ?)",
+async fn counter_correct(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CounterCorrectRequest>,
+) -> Result<Json<CounterCorrectResponse>, ProblemResponse> {
+    let token = extract_bearer_token(&headers).ok_or_else(|| {
+        counter!("api_counter_correct_requests_total", "result" => "unauthorized").increment(1);
+        ProblemResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "counter correction endpoint requires a bearer token",
         )
-        .bind("{}")
-        .bind(fmt_time(created_at))
-        .bind(fmt_time(created_at))
-        .execute(pool)
-        .await
-        .expect("insert broadcaster");
+    })?;
 
-        query(
-            "INSERT INTO state_index (broadcaster_id, current_version, updated_at) VALUES ('b-1', ?, ?)",
-        )
-        .bind(version)
-        .bind(fmt_time(created_at))
-        .execute(pool)
-        .await
-        .expect("insert state index");
+    if Uuid::parse_str(&payload.op_id).is_err() {
+        counter!("api_counter_correct_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_op_id",
+            "op_id must be a valid UUID",
+        ));
     }
 
-    async fn insert_queue_entry(
-        state: &AppState,
-        id: &str,
-        user_id: &str,
-        enqueued_at: chrono::DateTime<Utc>,
-        last_updated_at: chrono::DateTime<Utc>,
-    ) {
-        let pool = state.storage().pool();
-        query(
-            r#"INSERT INTO queue_entries (
-                id, broadcaster_id, user_id, user_login, user_display_name, user_avatar,
-                reward_id, redemption_id, enqueued_at, status, status_reason, managed, last_updated_at
-            ) VALUES (?, 'b-1', ?, ?, ?, ?, 'reward-1', ?, ?, 'QUEUED', NULL, 0, ?)"#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .bind(format!("{user_id}-login"))
-        .bind(format!("User {user_id}"))
-        .bind(Option::<String>::None)
-        .bind(Option::<String>::Some(format!("red-{id}")))
-        .bind(fmt_time(enqueued_at))
-        .bind(fmt_time(last_updated_at))
-        .execute(pool)
-        .await
-        .expect("insert queue entry");
+    if payload.user_id.trim().is_empty() {
+        counter!("api_counter_correct_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_user",
+            "user_id must not be empty",
+        ));
     }
 
-    async fn insert_counter(
-        state: &AppState,
-        user_id: &str,
-        count: i64,
-        updated_at: chrono::DateTime<Utc>,
-    ) {
-        let pool = state.storage().pool();
-        let day = updated_at.format("%Y-%m-%d").to_string();
-        query(
-            "INSERT INTO daily_counters(day, broadcaster_id, user_id, count, updated_at) VALUES (?, 'b-1', ?, ?, ?)",
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(token, Audience::Admin, &payload.broadcaster, now)
+    {
+        counter!("api_counter_correct_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
+
+    let profile = match state
+        .storage()
+        .broadcasters()
+        .fetch_settings(&payload.broadcaster)
+        .await
+    {
+        Ok(profile) => profile,
+        Err(SettingsError::NotFound) => {
+            counter!("api_counter_correct_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ));
+        }
+        Err(err) => {
+            counter!("api_counter_correct_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %payload.broadcaster,
+                error = %err,
+                "failed to load broadcaster settings",
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "settings_error",
+                "failed to load broadcaster settings",
+            )
+            .with_incident(incident));
+        }
+    };
+
+    let command = Command::CounterCorrect(CounterCorrectCommand {
+        broadcaster_id: payload.broadcaster.clone(),
+        issued_at: now,
+        source: CommandSource::Admin,
+        user_id: payload.user_id.clone(),
+        count: payload.count,
+        op_id: payload.op_id.clone(),
+    });
+
+    let guard = state.command_executor().lock(&payload.broadcaster).await;
+    let application = match state
+        .command_executor()
+        .execute_admin_command_locked(&guard, &profile.timezone, Audience::Admin, command)
+        .await
+    {
+        Ok(application) => application,
+        Err(CommandExecutorError::OpConflict { op_id }) => {
+            counter!("api_counter_correct_requests_total", "result" => "conflict").increment(1);
+            error!(
+                stage = "mutation",
+                broadcaster = %payload.broadcaster,
+                op_id = %op_id,
+                "op_id conflict for counter correction",
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::PRECONDITION_FAILED,
+                "op_conflict",
+                "op_id already used with different payload",
+            ));
+        }
+        Err(err) => {
+            counter!("api_counter_correct_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %payload.broadcaster,
+                op_id = %payload.op_id,
+                error = %err,
+                "failed to execute counter correction",
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "command_error",
+                "failed to execute counter correction",
+            )
+            .with_incident(incident));
+        }
+    };
+
+    broadcast_patches(&state, &payload.broadcaster, &application.patches).await;
+    drop(guard);
+
+    let (user_id, count) = match application.result {
+        CommandApplyResult::CounterCorrected { user_id, count } => (user_id, count),
+        other => {
+            counter!("api_counter_correct_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %payload.broadcaster,
+                op_id = %payload.op_id,
+                result = ?other,
+                "unexpected command result for counter correction",
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "unexpected_result",
+                "executor returned unexpected result",
+            )
+            .with_incident(incident));
+        }
+    };
+
+    counter!("api_counter_correct_requests_total", "result" => "ok").increment(1);
+    info!(
+        stage = "mutation",
+        kind = "counter.correct",
+        broadcaster = %payload.broadcaster,
+        op_id = %payload.op_id,
+        duplicate = application.duplicate,
+        version = application.version,
+        count,
+        "counter corrected via admin mutation",
+    );
+
+    Ok(Json(CounterCorrectResponse {
+        version: application.version,
+        result: CounterCorrectResultBody { user_id, count },
+    }))
+}
+
+async fn command_noop(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<NoopRequest>,
+) -> Result<Json<NoopResponse>, ProblemResponse> {
+    let token = extract_bearer_token(&headers).ok_or_else(|| {
+        counter!("api_command_noop_requests_total", "result" => "unauthorized").increment(1);
+        ProblemResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "noop command endpoint requires a bearer token",
         )
-        .bind(day)
-        .bind(user_id)
-        .bind(count)
-        .bind(fmt_time(updated_at))
-        .execute(pool)
+    })?;
+
+    if Uuid::parse_str(&payload.op_id).is_err() {
+        counter!("api_command_noop_requests_total", "result" => "error").increment(1);
+        return Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_op_id",
+            "op_id must be a valid UUID",
+        ));
+    }
+
+    let now = state.now();
+    if let Err(err) =
+        state
+            .token_validator()
+            .validate(token, Audience::Admin, &payload.broadcaster, now)
+    {
+        counter!("api_command_noop_requests_total", "result" => "unauthorized").increment(1);
+        return Err(problem_for_token_error(err));
+    }
+
+    let profile = match state
+        .storage()
+        .broadcasters()
+        .fetch_settings(&payload.broadcaster)
         .await
-        .expect("insert counter");
+    {
+        Ok(profile) => profile,
+        Err(SettingsError::NotFound) => {
+            counter!("api_command_noop_requests_total", "result" => "error").increment(1);
+            return Err(ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ));
+        }
+        Err(err) => {
+            counter!("api_command_noop_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %payload.broadcaster,
+                error = %err,
+                "failed to load broadcaster settings",
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "settings_error",
+                "failed to load broadcaster settings",
+            )
+            .with_incident(incident));
+        }
+    };
+
+    let command = Command::Noop(NoopCommand {
+        broadcaster_id: payload.broadcaster.clone(),
+        issued_at: now,
+        source: CommandSource::Admin,
+        op_id: payload.op_id.clone(),
+    });
+
+    let guard = state.command_executor().lock(&payload.broadcaster).await;
+    let application = match state
+        .command_executor()
+        .execute_admin_command_locked(&guard, &profile.timezone, Audience::Admin, command)
+        .await
+    {
+        Ok(application) => application,
+        Err(CommandExecutorError::OpConflict { op_id }) => {
+            counter!("api_command_noop_requests_total", "result" => "conflict").increment(1);
+            error!(
+                stage = "mutation",
+                broadcaster = %payload.broadcaster,
+                op_id = %op_id,
+                "op_id conflict for noop command",
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::PRECONDITION_FAILED,
+                "op_conflict",
+                "op_id already used by a different command",
+            ));
+        }
+        Err(err) => {
+            counter!("api_command_noop_requests_total", "result" => "error").increment(1);
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %payload.broadcaster,
+                op_id = %payload.op_id,
+                error = %err,
+                "failed to execute noop command",
+            );
+            return Err(ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "command_error",
+                "failed to execute noop command",
+            )
+            .with_incident(incident));
+        }
+    };
+    drop(guard);
+
+    counter!("api_command_noop_requests_total", "result" => "ok").increment(1);
+    info!(
+        stage = "mutation",
+        kind = "command.noop",
+        broadcaster = %payload.broadcaster,
+        op_id = %payload.op_id,
+        duplicate = application.duplicate,
+        version = application.version,
+        "op_id reserved via noop command",
+    );
+
+    Ok(Json(NoopResponse {
+        version: application.version,
+        duplicate: application.duplicate,
+    }))
+}
+
+async fn sse_handler(
+    state: AppState,
+    query: SseQuery,
+    headers: HeaderMap,
+    audience: Audience,
+) -> Result<Sse<SseStream>, (StatusCode, String)> {
+    let token = query
+        .token
+        .as_deref()
+        .ok_or((StatusCode::UNAUTHORIZED, "missing_token".to_string()))?;
+
+    let since_version = headers
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|raw| raw.parse::<u64>().ok())
+        .or(query.since_version);
+
+    let filter_types = parse_types(query.types.clone(), state.sse_max_filter_types())?;
+
+    let now = state.now();
+    let claims = state
+        .token_validator()
+        .validate(token, audience, &query.broadcaster, now)
+        .map_err(|_| (StatusCode::FORBIDDEN, "invalid_token".to_string()))?;
+
+    if state.token_validator().requires_single_use(audience) {
+        let jti = claims.jti.as_deref().ok_or((
+            StatusCode::FORBIDDEN,
+            "single_use_token_required".to_string(),
+        ))?;
+        let expires_at = DateTime::<Utc>::from_timestamp(claims.exp as i64, 0).unwrap_or(now);
+        let first_use = state
+            .storage()
+            .sse_tokens()
+            .mark_used(&NewSseTokenUse {
+                jti,
+                broadcaster_id: &query.broadcaster,
+                audience: audience.as_str(),
+                used_at: now,
+                expires_at,
+            })
+            .await
+            .map_err(|err| {
+                error!(
+                    stage = "sse",
+                    broadcaster = %query.broadcaster,
+                    error = %err,
+                    "failed to record single-use token"
+                );
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed_to_record_token".to_string(),
+                )
+            })?;
+        if !first_use {
+            return Err((StatusCode::FORBIDDEN, "token_reused".to_string()));
+        }
+    }
+
+    let profile = state
+        .storage()
+        .broadcasters()
+        .fetch_settings(&query.broadcaster)
+        .await
+        .map_err(|err| match err {
+            SettingsError::NotFound => (StatusCode::NOT_FOUND, "broadcaster_not_found".to_string()),
+            _ => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed_to_load_settings".to_string(),
+            ),
+        })?;
+
+    let subscription = state
+        .sse()
+        .subscribe(&query.broadcaster, audience, since_version, filter_types)
+        .await;
+
+    let stream = if subscription.ring_miss() {
+        let patch = state
+            .sse()
+            .build_state_replace(&query.broadcaster, &profile, state.now())
+            .await
+            .map_err(|_| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed_to_build_state".to_string(),
+                )
+            })?;
+        let message = state.sse().event_from_patch(&patch).map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed_to_serialize_state".to_string(),
+            )
+        })?;
+        subscription.into_stream_with_initial(vec![message])
+    } else {
+        subscription.into_stream()
+    };
+
+    Ok(Sse::new(stream).keep_alive(sse_keep_alive(&state)))
+}
+
+fn sse_keep_alive(state: &AppState) -> KeepAlive {
+    let keep_alive = KeepAlive::new().interval(Duration::from_secs(state.sse_heartbeat()));
+    match &state.sse_heartbeat_event {
+        Some(name) => keep_alive.event(
+            Event::default()
+                .event(name.as_ref())
+                .data(state.sse_heartbeat_text.as_ref()),
+        ),
+        None => keep_alive.text(state.sse_heartbeat_text.as_ref()),
+    }
+}
+
+/// Parses the `types` filter, rejecting names that are not patch kinds so that typos do not
+/// silently yield an empty stream, and lists longer than `max_types` (0 disables the cap).
+fn parse_types(
+    raw: Option<String>,
+    max_types: usize,
+) -> Result<Option<HashSet<String>>, (StatusCode, String)> {
+    let Some(value) = raw else {
+        return Ok(None);
+    };
+    let items: Vec<&str> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect();
+    if max_types > 0 && items.len() > max_types {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("too_many_types: {} > {max_types}", items.len()),
+        ));
+    }
+    let mut set = HashSet::new();
+    let mut unknown = Vec::new();
+    for trimmed in items {
+        if PatchKind::from_str(trimmed).is_err() {
+            unknown.push(trimmed);
+            continue;
+        }
+        set.insert(trimmed.to_string());
+    }
+    if !unknown.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("unknown_types: {}", unknown.join(",")),
+        ));
+    }
+    Ok(Some(set))
+}
+
+/// Callers keep the broadcaster's [`crate::command::BroadcasterGuard`] until this returns.
+async fn broadcast_patches(state: &AppState, broadcaster_id: &str, patches: &[Patch]) {
+    for patch in patches {
+        if let Err(err) = state
+            .sse()
+            .broadcast_patch(broadcaster_id, patch, state.now())
+            .await
+        {
+            error!(
+                stage = "sse",
+                broadcaster = %broadcaster_id,
+                error = %err,
+                "failed to broadcast patch",
+            );
+            continue;
+        }
+        emit_sse_stage(state, broadcaster_id, patch);
+    }
+}
+
+/// Broadcasts a freshly built `state.replace` for the broadcaster's current settings.
+///
+/// Failures are logged only: the mutation is already committed and clients resync on reconnect.
+async fn broadcast_state_replace(state: &AppState, broadcaster_id: &str) {
+    let profile = match state
+        .storage()
+        .broadcasters()
+        .fetch_settings(broadcaster_id)
+        .await
+    {
+        Ok(profile) => profile,
+        Err(err) => {
+            error!(
+                stage = "sse",
+                broadcaster = %broadcaster_id,
+                error = %err,
+                "failed to load settings for state replace",
+            );
+            return;
+        }
+    };
+    match state
+        .sse()
+        .build_state_replace(broadcaster_id, &profile, state.now())
+        .await
+    {
+        Ok(patch) => broadcast_patches(state, broadcaster_id, &[patch]).await,
+        Err(err) => error!(
+            stage = "sse",
+            broadcaster = %broadcaster_id,
+            error = %err,
+            "failed to build state replace",
+        ),
+    }
+}
+
+fn queue_error_response(
+    request: &QueueDequeueRequest,
+    err: CommandExecutorError,
+) -> (ProblemResponse, &'static str) {
+    match err {
+        CommandExecutorError::Queue(QueueError::NotFound) => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                entry_id = %request.entry_id,
+                mode = request.mode.as_str(),
+                "queue entry not found",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::NOT_FOUND,
+                    "queue_entry_not_found",
+                    "queue entry not found",
+                ),
+                "not_found",
+            )
+        }
+        CommandExecutorError::Queue(QueueError::InvalidTransition(status)) => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                entry_id = %request.entry_id,
+                mode = request.mode.as_str(),
+                status = ?status,
+                "queue entry is not queued",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::CONFLICT,
+                    "invalid_transition",
+                    format!("queue entry is not queued (current={status:?})"),
+                ),
+                "conflict",
+            )
+        }
+        CommandExecutorError::OpConflict { op_id } => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                entry_id = %request.entry_id,
+                op_id = %op_id,
+                "op_id conflict for queue dequeue",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::PRECONDITION_FAILED,
+                    "op_conflict",
+                    "op_id already used with different payload",
+                ),
+                "conflict",
+            )
+        }
+        CommandExecutorError::InvalidTimezone(detail) => {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                entry_id = %request.entry_id,
+                detail = %detail,
+                "invalid timezone while processing queue dequeue",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "invalid_timezone",
+                    detail,
+                )
+                .with_incident(incident),
+                "error",
+            )
+        }
+        CommandExecutorError::Settings(SettingsError::NotFound) => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                entry_id = %request.entry_id,
+                "broadcaster missing during queue dequeue",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::NOT_FOUND,
+                    "broadcaster_not_found",
+                    "broadcaster is not provisioned",
+                ),
+                "error",
+            )
+        }
+        other => {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                entry_id = %request.entry_id,
+                mode = request.mode.as_str(),
+                error = %other,
+                "failed to execute queue dequeue",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "command_error",
+                    "failed to execute queue dequeue",
+                )
+                .with_incident(incident),
+                "error",
+            )
+        }
+    }
+}
+
+fn queue_add_error_response(
+    request: &QueueAddRequest,
+    err: CommandExecutorError,
+) -> (ProblemResponse, &'static str) {
+    match err {
+        CommandExecutorError::OpConflict { op_id } => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                op_id = %op_id,
+                "op_id conflict for queue add",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::PRECONDITION_FAILED,
+                    "op_conflict",
+                    "op_id already used with different payload",
+                ),
+                "conflict",
+            )
+        }
+        CommandExecutorError::InvalidTimezone(detail) => {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                detail = %detail,
+                "invalid timezone while processing queue add",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "invalid_timezone",
+                    detail,
+                )
+                .with_incident(incident),
+                "error",
+            )
+        }
+        other => {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                op_id = %request.op_id,
+                error = %other,
+                "failed to execute queue add",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "command_error",
+                    "failed to execute queue add",
+                )
+                .with_incident(incident),
+                "error",
+            )
+        }
+    }
+}
+
+fn timezone_error_response(
+    request: &TimezoneUpdateRequest,
+    err: CommandExecutorError,
+) -> (ProblemResponse, &'static str) {
+    match err {
+        CommandExecutorError::OpConflict { op_id } => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                op_id = %op_id,
+                "op_id conflict for timezone update",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::PRECONDITION_FAILED,
+                    "op_conflict",
+                    "op_id already used with different payload",
+                ),
+                "conflict",
+            )
+        }
+        CommandExecutorError::TimezoneUpdate(TimezoneUpdateError::InvalidTimezone(zone)) => (
+            ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_timezone",
+                format!("unknown IANA timezone: {zone}"),
+            ),
+            "invalid",
+        ),
+        CommandExecutorError::TimezoneUpdate(TimezoneUpdateError::NotFound) => (
+            ProblemResponse::new(
+                StatusCode::NOT_FOUND,
+                "broadcaster_not_found",
+                "broadcaster is not provisioned",
+            ),
+            "error",
+        ),
+        other => {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                op_id = %request.op_id,
+                error = %other,
+                "failed to update broadcaster timezone",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "settings_error",
+                    "failed to update timezone",
+                )
+                .with_incident(incident),
+                "error",
+            )
+        }
+    }
+}
+
+fn settings_error_response(
+    request: &SettingsUpdateRequest,
+    err: CommandExecutorError,
+) -> (ProblemResponse, &'static str) {
+    match err {
+        CommandExecutorError::OpConflict { op_id } => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                op_id = %op_id,
+                "op_id conflict for settings update",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::PRECONDITION_FAILED,
+                    "op_conflict",
+                    "op_id already used with different payload",
+                ),
+                "conflict",
+            )
+        }
+        CommandExecutorError::InvalidSettingsPatch(detail) => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                detail = %detail,
+                "invalid settings patch",
+            );
+            (
+                ProblemResponse::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_patch", detail),
+                "error",
+            )
+        }
+        CommandExecutorError::Settings(SettingsError::NotFound) => {
+            error!(
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                "broadcaster missing during settings update",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::NOT_FOUND,
+                    "broadcaster_not_found",
+                    "broadcaster is not provisioned",
+                ),
+                "error",
+            )
+        }
+        other => {
+            let incident = ProblemResponse::new_incident();
+            error!(
+                incident = %incident,
+                stage = "mutation",
+                broadcaster = %request.broadcaster,
+                error = %other,
+                "failed to execute settings update",
+            );
+            (
+                ProblemResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "command_error",
+                    "failed to execute settings update",
+                )
+                .with_incident(incident),
+                "error",
+            )
+        }
+    }
+}
+
+pub(crate) fn problem_for_token_error(err: TokenError) -> ProblemResponse {
+    ProblemResponse::new(StatusCode::FORBIDDEN, "invalid_token", err.to_string())
+}
+
+fn parse_state_scope(
+    scope: Option<&str>,
+    since: Option<&str>,
+) -> Result<StateScope, ProblemResponse> {
+    match scope {
+        None | Some("session") => Ok(StateScope::Session),
+        Some("since") => {
+            let since_raw = since.ok_or_else(|| {
+                ProblemResponse::new(
+                    StatusCode::BAD_REQUEST,
+                    "missing_since",
+                    "scope=since requires the since parameter",
+                )
+            })?;
+            let parsed = DateTime::parse_from_rfc3339(since_raw).map_err(|err| {
+                ProblemResponse::new(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_since",
+                    format!("invalid since timestamp: {err}"),
+                )
+            })?;
+            Ok(StateScope::Since(parsed.with_timezone(&Utc)))
+        }
+        Some(other) => Err(ProblemResponse::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_scope",
+            format!("unsupported scope: {other}"),
+        )),
+    }
+}
+
+pub(crate) fn extract_bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .strip_prefix("Bearer ")
+                .or_else(|| value.strip_prefix("bearer "))
+        })
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{HeaderValue, Method, Request, StatusCode},
+    };
+    use chrono::{Duration as ChronoDuration, SecondsFormat, TimeZone};
+    use http_body_util::BodyExt;
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use serde_json::Value;
+    use serde_urlencoded::to_string;
+    use sqlx::query;
+    use std::{sync::Arc, time::Duration as StdDuration};
+    use tokio::time::{self, Duration};
+    use tower::ServiceExt;
+
+    use crate::sse::TokenClaims;
+    use crate::tap::StageEvent;
+    use reqwest::Client;
+    use twi_overlay_core::types::{QueueEntryStatus, Settings};
+    use twi_overlay_storage::{HelixBackfillCheckpoint, HelixBackfillStatus, NewOauthLink};
+    use twi_overlay_twitch::{HelixClient, TwitchOAuthClient};
+    use url::Url;
+
+    async fn setup_state() -> AppState {
+        let metrics = telemetry::init_metrics().expect("metrics init");
+        let tap = TapHub::new();
+
+        let database = Database::connect("sqlite::memory:?cache=shared")
+            .await
+            .expect("connect");
+        database.run_migrations().await.expect("migrations");
+
+        let secret: Arc<[u8]> = Arc::from(b"test-secret".to_vec().into_boxed_slice());
+        let http = Client::builder().build().expect("client");
+        let oauth_client = TwitchOAuthClient::new(
+            "client",
+            "secret",
+            Url::parse("https://id.twitch.tv/oauth2/").expect("url"),
+            http.clone(),
+        );
+        let helix_client = HelixClient::new(
+            "client",
+            Url::parse("https://api.twitch.tv/helix/").expect("url"),
+            http,
+        );
+        let (state, _worker) = AppState::new(
+            metrics,
+            tap,
+            database,
+            secret,
+            b"token-secret".to_vec(),
+            64,
+            StdDuration::from_secs(60),
+            25,
+            helix_client,
+            oauth_client,
+            "http://localhost/oauth/callback".to_string(),
+            StdDuration::from_secs(600),
+            StdDuration::from_secs(300),
+            50,
+        );
+        state
+    }
+
+    fn issue_token(
+        secret: &[u8],
+        broadcaster: &str,
+        audience: &str,
+        exp: chrono::DateTime<Utc>,
+    ) -> String {
+        issue_token_with_jti(secret, broadcaster, audience, exp, None)
+    }
+
+    fn issue_token_with_jti(
+        secret: &[u8],
+        broadcaster: &str,
+        audience: &str,
+        exp: chrono::DateTime<Utc>,
+        jti: Option<&str>,
+    ) -> String {
+        let claims = TokenClaims {
+            sub: broadcaster.to_string(),
+            aud: audience.to_string(),
+            exp: exp.timestamp() as usize,
+            nbf: None,
+            jti: jti.map(str::to_string),
+        };
+        let header = Header::new(Algorithm::HS256);
+        encode(&header, &claims, &EncodingKey::from_secret(secret)).expect("token encode")
+    }
+
+    fn bearer(value: &str) -> HeaderValue {
+        let header_value = format!("Bearer {value}");
+        HeaderValue::from_str(&header_value).expect("header value")
+    }
+
+    fn fmt_time(value: chrono::DateTime<Utc>) -> String {
+        value.to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    async fn provision_broadcaster(state: &AppState, version: i64) {
+        let pool = state.storage().pool();
+        let created_at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        query(
+            "INSERT INTO broadcasters (id, twitch_broadcaster_id, display_name, timezone, settings_json, created_at, updated_at) VALUES ('b-1','twitch-1','Example','UTC',?, ?, ?)",
+        )
+        .bind("{}")
+        .bind(fmt_time(created_at))
+        .bind(fmt_time(created_at))
+        .execute(pool)
+        .await
+        .expect("insert broadcaster");
+
+        query(
+            "INSERT INTO state_index (broadcaster_id, current_version, updated_at) VALUES ('b-1', ?, ?)",
+        )
+        .bind(version)
+        .bind(fmt_time(created_at))
+        .execute(pool)
+        .await
+        .expect("insert state index");
+    }
+
+    async fn insert_queue_entry(
+        state: &AppState,
+        id: &str,
+        user_id: &str,
+        enqueued_at: chrono::DateTime<Utc>,
+        last_updated_at: chrono::DateTime<Utc>,
+    ) {
+        let pool = state.storage().pool();
+        query(
+            r#"INSERT INTO queue_entries (
+                id, broadcaster_id, user_id, user_login, user_display_name, user_avatar,
+                reward_id, redemption_id, enqueued_at, status, status_reason, managed, last_updated_at
+            ) VALUES (?, 'b-1', ?, ?, ?, ?, 'reward-1', ?, ?, 'QUEUED', NULL, 0, ?)"#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(format!("{user_id}-login"))
+        .bind(format!("User {user_id}"))
+        .bind(Option::<String>::None)
+        .bind(Option::<String>::Some(format!("red-{id}")))
+        .bind(fmt_time(enqueued_at))
+        .bind(fmt_time(last_updated_at))
+        .execute(pool)
+        .await
+        .expect("insert queue entry");
+    }
+
+    async fn insert_counter(
+        state: &AppState,
+        user_id: &str,
+        count: i64,
+        updated_at: chrono::DateTime<Utc>,
+    ) {
+        let pool = state.storage().pool();
+        let day = updated_at.format("%Y-%m-%d").to_string();
+        query(
+            "INSERT INTO daily_counters(day, broadcaster_id, user_id, count, updated_at) VALUES (?, 'b-1', ?, ?, ?)",
+        )
+        .bind(day)
+        .bind(user_id)
+        .bind(count)
+        .bind(fmt_time(updated_at))
+        .execute(pool)
+        .await
+        .expect("insert counter");
+    }
+
+    #[tokio::test]
+    async fn healthz_returns_ok() {
+        let app = app_router(setup_state().await);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/healthz")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("handler should respond");
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn metrics_exports_build_info() {
+        let app = app_router(setup_state().await);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("handler should respond");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let collected = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body should read");
+        let body = String::from_utf8(collected.to_bytes().to_vec()).expect("utf-8");
+        assert!(body.contains("app_build_info"));
+        assert!(body.contains("app_uptime_seconds"));
+    }
+
+    #[tokio::test]
+    async fn tap_stream_emits_events() {
+        let state = setup_state().await;
+        let tap = state.tap().clone();
+        let app = app_router(state);
+
+        let request = Request::builder()
+            .uri("/_debug/tap")
+            .body(Body::empty())
+            .unwrap();
+
+        let publish = tokio::spawn(async move {
+            time::sleep(Duration::from_millis(25)).await;
+            tap.publish(StageEvent::mock("test.event"));
+        });
+
+        let mut response = app.oneshot(request).await.expect("handler should respond");
+
+        let frame = time::timeout(Duration::from_secs(1), response.body_mut().frame())
+            .await
+            .expect("stream produced chunk")
+            .expect("chunk ok")
+            .expect("chunk available");
+
+        let data = match frame.into_data() {
+            Ok(data) => data,
+            Err(_) => panic!("expected data frame"),
+        };
+        let text = String::from_utf8(data.to_vec()).expect("utf-8");
+        assert!(text.contains("data:"));
+        assert!(text.contains("\"stage\":\"sse\""));
+
+        publish.await.expect("publish task");
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_requires_token() {
+        let app = app_router(setup_state().await);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/state?broadcaster=b-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("handler should respond");
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_returns_session_scope() {
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let clock_now = fixed_now;
+        let state = setup_state().await.with_clock(Arc::new(move || clock_now));
+        provision_broadcaster(&state, 42).await;
+        insert_queue_entry(
+            &state,
+            "entry-1",
+            "user-1",
+            fixed_now - ChronoDuration::minutes(5),
+            fixed_now - ChronoDuration::minutes(5),
+        )
+        .await;
+        insert_counter(&state, "user-1", 3, fixed_now - ChronoDuration::minutes(4)).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let app = app_router(state.clone());
+
+        state
+            .token_validator()
+            .validate_any(
+                &token,
+                &[Audience::Overlay, Audience::Admin],
+                "b-1",
+                fixed_now,
+            )
+            .expect("token should be accepted");
+
+        let profile = state
+            .storage()
+            .broadcasters()
+            .fetch_settings("b-1")
+            .await
+            .expect("profile should load");
+        build_state_snapshot(
+            state.storage(),
+            state.sse().active_queue_cache(),
+            "b-1",
+            &profile,
+            fixed_now,
+            StateScope::Session,
+        )
+        .await
+        .expect("snapshot should build");
+
+        let mut response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/state?broadcaster=b-1")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("handler should respond");
+
+        if response.status() != StatusCode::OK {
+            let body = response.body_mut().collect().await.unwrap().to_bytes();
+            panic!(
+                "unexpected status {} body {}",
+                response.status(),
+                String::from_utf8_lossy(&body)
+            );
+        }
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).expect("json");
+        assert_eq!(json.get("version").and_then(Value::as_u64), Some(42));
+        assert_eq!(
+            json.get("queue")
+                .and_then(Value::as_array)
+                .map(|arr| arr.len()),
+            Some(1)
+        );
+        assert_eq!(
+            json.get("counters_today")
+                .and_then(Value::as_array)
+                .map(|arr| arr.len()),
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_reuses_active_queue_until_version_changes() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        insert_queue_entry(&state, "entry-1", "user-1", fixed_now, fixed_now).await;
+
+        let overlay = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let admin = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let fetch_state = || async {
+            let response = app_router(state.clone())
+                .oneshot(
+                    Request::builder()
+                        .uri("/api/state?broadcaster=b-1")
+                        .header(axum::http::header::AUTHORIZATION, bearer(&overlay))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .expect("response");
+            assert_eq!(response.status(), StatusCode::OK);
+        };
+        let cache = state.sse().active_queue_cache().clone();
+
+        fetch_state().await;
+        fetch_state().await;
+        assert_eq!(cache.loads(), 1);
+
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "user_id": "user-9",
+            "op_id": Uuid::new_v4(),
+        }))
+        .expect("serialize body");
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/queue/add")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&admin))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        fetch_state().await;
+        assert_eq!(cache.loads(), 2);
+    }
+
+    #[tokio::test]
+    async fn state_diff_reports_completion_as_updated_entry() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+
+        let entry_id = Uuid::new_v4().to_string();
+        let executor = state.command_executor();
+        executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::QueueAdd(QueueAddCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: fixed_now,
+                    source: CommandSource::Admin,
+                    user: NormalizedUser {
+                        id: "user-9".to_string(),
+                        login: None,
+                        display_name: None,
+                    },
+                    op_id: entry_id.clone(),
+                }),
+            )
+            .await
+            .expect("add");
+        executor
+            .execute_admin_command(
+                "b-1",
+                "UTC",
+                Audience::Admin,
+                Command::QueueComplete(QueueCompleteCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: fixed_now,
+                    source: CommandSource::Admin,
+                    entry_id: entry_id.clone(),
+                    op_id: Uuid::new_v4().to_string(),
+                }),
+            )
+            .await
+            .expect("complete");
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let fetch_diff = |from: u64, to: u64| {
+            let state = state.clone();
+            let token = token.clone();
+            async move {
+                let response = app_router(state)
+                    .oneshot(
+                        Request::builder()
+                            .uri(format!(
+                                "/api/state/diff?broadcaster=b-1&from={from}&to={to}"
+                            ))
+                            .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .expect("response");
+                let status = response.status();
+                let bytes = response.into_body().collect().await.unwrap().to_bytes();
+                (
+                    status,
+                    serde_json::from_slice::<Value>(&bytes).expect("json"),
+                )
+            }
+        };
+
+        let (status, diff) = fetch_diff(2, 3).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(diff["queue"]["added"], json!([]));
+        assert_eq!(diff["queue"]["updated"][0]["id"], entry_id.as_str());
+        assert_eq!(diff["queue"]["updated"][0]["status"], "COMPLETED");
+        assert_eq!(diff["counters"], json!([]));
+
+        let (status, diff) = fetch_diff(1, 3).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(diff["queue"]["added"][0]["id"], entry_id.as_str());
+        assert_eq!(diff["queue"]["updated"], json!([]));
+        assert_eq!(diff["counters"][0]["user_id"], "user-9");
+        assert_eq!(diff["counters"][0]["delta"], 1);
+
+        // Entries are reported as their current rows, even when completed after `to`.
+        let (status, diff) = fetch_diff(1, 2).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(diff["queue"]["added"][0]["id"], entry_id.as_str());
+        assert_eq!(diff["queue"]["added"][0]["status"], "COMPLETED");
+
+        let (status, diff) = fetch_diff(3, 4).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(diff["type"], "invalid_range");
+
+        sqlx::query("DELETE FROM command_log WHERE version = 2")
+            .execute(state.storage().pool())
+            .await
+            .expect("trim log");
+        let (status, diff) = fetch_diff(1, 3).await;
+        assert_eq!(status, StatusCode::GONE);
+        assert_eq!(diff["type"], "history_unavailable");
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_lists_recent_completed_newest_first() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+
+        let base = Utc::now()
+            .date_naive()
+            .and_hms_opt(12, 0, 0)
+            .expect("noon")
+            .and_utc();
+        let offset_secs = Arc::new(AtomicI64::new(0));
+        let clock_offset = offset_secs.clone();
+        let state = setup_state().await.with_clock(Arc::new(move || {
+            base + ChronoDuration::seconds(clock_offset.load(Ordering::SeqCst))
+        }));
+        provision_broadcaster(&state, 1).await;
+
+        let executor = state.command_executor();
+        let mut entry_ids = Vec::new();
+        for idx in 0..4 {
+            let entry_id = Uuid::new_v4().to_string();
+            executor
+                .execute_admin_command(
+                    "b-1",
+                    "UTC",
+                    Audience::Admin,
+                    Command::QueueAdd(QueueAddCommand {
+                        broadcaster_id: "b-1".to_string(),
+                        issued_at: base,
+                        source: CommandSource::Admin,
+                        user: NormalizedUser {
+                            id: format!("user-{idx}"),
+                            login: None,
+                            display_name: None,
+                        },
+                        op_id: entry_id.clone(),
+                    }),
+                )
+                .await
+                .expect("add");
+            entry_ids.push(entry_id);
+        }
+        for entry_id in &entry_ids[..3] {
+            offset_secs.fetch_add(60, Ordering::SeqCst);
+            executor
+                .execute_admin_command(
+                    "b-1",
+                    "UTC",
+                    Audience::Admin,
+                    Command::QueueComplete(QueueCompleteCommand {
+                        broadcaster_id: "b-1".to_string(),
+                        issued_at: base,
+                        source: CommandSource::Admin,
+                        entry_id: entry_id.clone(),
+                        op_id: Uuid::new_v4().to_string(),
+                    }),
+                )
+                .await
+                .expect("complete");
+        }
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            base + ChronoDuration::minutes(30),
+        );
+        let fetch_state = |uri: &'static str| {
+            let state = state.clone();
+            let token = token.clone();
+            async move {
+                let response = app_router(state)
+                    .oneshot(
+                        Request::builder()
+                            .uri(uri)
+                            .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .expect("response");
+                assert_eq!(response.status(), StatusCode::OK);
+                let bytes = response.into_body().collect().await.unwrap().to_bytes();
+                serde_json::from_slice::<Value>(&bytes).expect("json")
+            }
+        };
+
+        let snapshot = fetch_state("/api/state?broadcaster=b-1&recent_completed=2").await;
+        let recent: Vec<&str> = snapshot["recent_completed"]
+            .as_array()
+            .expect("recent_completed")
+            .iter()
+            .map(|entry| entry["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(recent, vec![entry_ids[2].as_str(), entry_ids[1].as_str()]);
+        assert_eq!(snapshot["recent_completed"][0]["status"], "COMPLETED");
+        assert_eq!(snapshot["queue"][0]["id"], entry_ids[3].as_str());
+
+        let snapshot = fetch_state("/api/state?broadcaster=b-1").await;
+        assert!(snapshot.get("recent_completed").is_none());
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_since_filters_old_records() {
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let clock_now = fixed_now;
+        let state = setup_state().await.with_clock(Arc::new(move || clock_now));
+        provision_broadcaster(&state, 55).await;
+        let earlier = fixed_now - ChronoDuration::minutes(30);
+        let recent = fixed_now - ChronoDuration::minutes(5);
+        insert_queue_entry(&state, "entry-early", "user-1", earlier, earlier).await;
+        insert_queue_entry(&state, "entry-recent", "user-2", recent, recent).await;
+        insert_counter(&state, "user-1", 2, earlier).await;
+        insert_counter(&state, "user-2", 5, recent).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let since = (fixed_now - ChronoDuration::minutes(10)).to_rfc3339();
+        let query = to_string([
+            ("broadcaster", "b-1"),
+            ("scope", "since"),
+            ("since", since.as_str()),
+        ])
+        .expect("query should serialize");
+        let uri = format!("/api/state?{query}");
+        let app = app_router(state.clone());
+
+        state
+            .token_validator()
+            .validate_any(
+                &token,
+                &[Audience::Overlay, Audience::Admin],
+                "b-1",
+                fixed_now,
+            )
+            .expect("token should be accepted");
+
+        let profile = state
+            .storage()
+            .broadcasters()
+            .fetch_settings("b-1")
+            .await
+            .expect("profile should load");
+        build_state_snapshot(
+            state.storage(),
+            state.sse().active_queue_cache(),
+            "b-1",
+            &profile,
+            fixed_now,
+            StateScope::Since(fixed_now - ChronoDuration::minutes(10)),
+        )
+        .await
+        .expect("snapshot should build");
+
+        let mut response = app
+            .oneshot(
+                Request::builder()
+                    .uri(&uri)
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("handler should respond");
+
+        if response.status() != StatusCode::OK {
+            let body = response.body_mut().collect().await.unwrap().to_bytes();
+            panic!(
+                "unexpected status {} body {}",
+                response.status(),
+                String::from_utf8_lossy(&body)
+            );
+        }
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).expect("json");
+        let queue = json
+            .get("queue")
+            .and_then(Value::as_array)
+            .expect("queue array");
+        assert_eq!(queue.len(), 1);
+        assert_eq!(
+            queue[0].get("id").and_then(Value::as_str),
+            Some("entry-recent")
+        );
+        let counters = json
+            .get("counters_today")
+            .and_then(Value::as_array)
+            .expect("counters array");
+        assert_eq!(counters.len(), 1);
+        assert_eq!(
+            counters[0].get("user_id").and_then(Value::as_str),
+            Some("user-2")
+        );
+    }
+
+    #[tokio::test]
+    async fn queue_dequeue_complete_succeeds() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        insert_queue_entry(&state, "entry-1", "user-1", fixed_now, fixed_now).await;
+        insert_counter(&state, "user-1", 2, fixed_now).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let op_id = Uuid::new_v4();
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "entry_id": "entry-1",
+            "mode": "COMPLETE",
+            "op_id": op_id,
+        }))
+        .expect("serialize body");
+
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/queue/dequeue")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["result"]["mode"].as_str(), Some("COMPLETE"));
+
+        let status: (String,) =
+            sqlx::query_as("SELECT status FROM queue_entries WHERE id = 'entry-1'")
+                .fetch_one(state.storage().pool())
+                .await
+                .expect("entry status");
+        assert_eq!(status.0, QueueEntryStatus::Completed.as_str());
+
+        let count: (i64,) =
+            sqlx::query_as("SELECT count FROM daily_counters WHERE user_id = 'user-1'")
+                .fetch_one(state.storage().pool())
+                .await
+                .expect("counter");
+        assert_eq!(count.0, 2);
+    }
+
+    #[tokio::test]
+    async fn queue_dequeue_undo_decrements_counter() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        insert_queue_entry(&state, "entry-1", "user-1", fixed_now, fixed_now).await;
+        insert_counter(&state, "user-1", 3, fixed_now).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let op_id = Uuid::new_v4();
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "entry_id": "entry-1",
+            "mode": "UNDO",
+            "op_id": op_id,
+        }))
+        .expect("serialize body");
+
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/queue/dequeue")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["result"]["mode"].as_str(), Some("UNDO"));
+        assert_eq!(json["result"]["user_today_count"].as_u64(), Some(2));
+
+        let row: (String, Option<String>) =
+            sqlx::query_as("SELECT status, status_reason FROM queue_entries WHERE id = 'entry-1'")
+                .fetch_one(state.storage().pool())
+                .await
+                .expect("entry status");
+        assert_eq!(row.0, QueueEntryStatus::Removed.as_str());
+        assert_eq!(row.1.as_deref(), Some(QueueRemovalReason::Undo.as_str()));
+
+        let count: (i64,) =
+            sqlx::query_as("SELECT count FROM daily_counters WHERE user_id = 'user-1'")
+                .fetch_one(state.storage().pool())
+                .await
+                .expect("counter");
+        assert_eq!(count.0, 2);
+
+        let conflict_body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "entry_id": "entry-1",
+            "mode": "COMPLETE",
+            "op_id": op_id,
+        }))
+        .expect("serialize body");
+
+        let conflict = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/queue/dequeue")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(conflict_body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(conflict.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn queue_served_today_counts_only_local_day() {
+        // 2024-01-03 01:00 in Asia/Tokyo; the local day starts at 2024-01-02 15:00 UTC.
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        query("UPDATE broadcasters SET timezone = 'Asia/Tokyo' WHERE id = 'b-1'")
+            .execute(state.storage().pool())
+            .await
+            .expect("set timezone");
+
+        let completions = [
+            (
+                "entry-1",
+                Utc.with_ymd_and_hms(2024, 1, 2, 14, 59, 59).unwrap(),
+            ),
+            (
+                "entry-2",
+                Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap(),
+            ),
+            (
+                "entry-3",
+                Utc.with_ymd_and_hms(2024, 1, 2, 15, 30, 0).unwrap(),
+            ),
+        ];
+        for (id, completed_at) in completions {
+            insert_queue_entry(&state, id, "user-1", completed_at, completed_at).await;
+            query(
+                "UPDATE queue_entries SET status = 'COMPLETED', last_updated_at = ? WHERE id = ?",
+            )
+            .bind(fmt_time(completed_at))
+            .bind(id)
+            .execute(state.storage().pool())
+            .await
+            .expect("complete entry");
+        }
+        insert_queue_entry(&state, "entry-4", "user-2", fixed_now, fixed_now).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let response = app_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/api/queue/served-today?broadcaster=b-1")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["day"].as_str(), Some("2024-01-03"));
+        assert_eq!(json["completed"].as_u64(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn queue_export_csv_lists_active_entries() {
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        insert_queue_entry(&state, "entry-1", "user-1", fixed_now, fixed_now).await;
+        insert_counter(&state, "user-1", 2, fixed_now).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri("/api/queue/export.csv?broadcaster=b-1")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(axum::http::header::CONTENT_TYPE),
+            Some(&axum::http::HeaderValue::from_static(
+                "text/csv; charset=utf-8"
+            ))
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let csv = String::from_utf8(bytes.to_vec()).expect("utf8");
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("user_id,user_login,user_display_name,reward_id,status,enqueued_at,today_count")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("user-1,user-1-login,User user-1,reward-1,QUEUED,2024-01-02T12:00:00+00:00,2")
+        );
+        assert_eq!(lines.next(), None);
+
+        let response = app_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/api/queue/export.csv?broadcaster=b-1&status=completed")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            String::from_utf8(bytes.to_vec()).unwrap().lines().count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn queue_export_csv_neutralizes_formula_cells() {
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        insert_queue_entry(&state, "entry-1", "user-1", fixed_now, fixed_now).await;
+        query(
+            "UPDATE queue_entries SET user_login = '+cmd', user_display_name = '=HYPERLINK(\"http://x\",\"a\")' WHERE id = 'entry-1'",
+        )
+        .execute(state.storage().pool())
+        .await
+        .expect("update entry");
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let response = app_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/api/queue/export.csv?broadcaster=b-1")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let csv = String::from_utf8(bytes.to_vec()).expect("utf8");
+        assert_eq!(
+            csv.lines().nth(1),
+            Some(
+                "user-1,'+cmd,\"'=HYPERLINK(\"\"http://x\"\",\"\"a\"\")\",reward-1,QUEUED,2024-01-02T12:00:00+00:00,0"
+            )
+        );
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("plain"), "plain");
+    }
+
+    #[tokio::test]
+    async fn queue_add_inserts_manual_entry_when_enabled() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let op_id = Uuid::new_v4();
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "user_id": "user-9",
+            "user_login": "viewer9",
+            "op_id": op_id,
+        }))
+        .expect("serialize body");
+
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/queue/add")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["version"].as_u64(), Some(2));
+        assert_eq!(
+            json["result"]["entry_id"].as_str(),
+            Some(op_id.to_string().as_str())
+        );
+        assert_eq!(json["result"]["user_today_count"].as_u64(), Some(1));
+
+        let row: (String, String, Option<String>, String) = sqlx::query_as(
+            "SELECT user_login, reward_id, redemption_id, enqueued_source FROM queue_entries WHERE user_id = 'user-9'",
+        )
+        .fetch_one(state.storage().pool())
+        .await
+        .expect("manual entry");
+        assert_eq!(row.0, "viewer9");
+        assert_eq!(row.1, "manual");
+        assert!(row.2.is_none());
+        assert_eq!(row.3, "manual");
+    }
+
+    #[tokio::test]
+    async fn counter_correct_sets_daily_count() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "user_id": "user-9",
+            "count": 3,
+            "op_id": Uuid::new_v4(),
+        }))
+        .expect("serialize body");
+
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/counters/correct")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["version"].as_u64(), Some(2));
+        assert_eq!(json["result"]["user_id"].as_str(), Some("user-9"));
+        assert_eq!(json["result"]["count"].as_u64(), Some(3));
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT count FROM daily_counters WHERE broadcaster_id = 'b-1' AND user_id = 'user-9'",
+        )
+        .fetch_one(state.storage().pool())
+        .await
+        .expect("counter row");
+        assert_eq!(count, 3);
+    }
+
+    #[tokio::test]
+    async fn command_noop_reserves_op_id_idempotently() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let op_id = Uuid::new_v4().to_string();
+        let noop = |op_id: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/api/commands/noop")
+                .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    json!({ "broadcaster": "b-1", "op_id": op_id }).to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app_router(state.clone())
+            .oneshot(noop(&op_id))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["version"].as_u64(), Some(2));
+        assert_eq!(json["duplicate"].as_bool(), Some(false));
+
+        let response = app_router(state.clone())
+            .oneshot(noop(&op_id))
+            .await
+            .expect("replay response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["version"].as_u64(), Some(2));
+        assert_eq!(json["duplicate"].as_bool(), Some(true));
+
+        let (command_type, queue_rows): (String, i64) = sqlx::query_as(
+            "SELECT type, (SELECT COUNT(*) FROM queue_entries) FROM command_log WHERE op_id = ?",
+        )
+        .bind(&op_id)
+        .fetch_one(state.storage().pool())
+        .await
+        .expect("command log row");
+        assert_eq!(command_type, "noop");
+        assert_eq!(queue_rows, 0);
+
+        let response = app_router(state)
+            .oneshot(noop("not-a-uuid"))
+            .await
+            .expect("invalid response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn queue_add_rejected_when_manual_add_disabled() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        query("UPDATE broadcasters SET settings_json = ? WHERE id = 'b-1'")
+            .bind(r#"{"policy":{"allow_manual_add":false}}"#)
+            .execute(state.storage().pool())
+            .await
+            .expect("disable manual add");
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "user_id": "user-9",
+            "op_id": Uuid::new_v4(),
+        }))
+        .expect("serialize body");
+
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/queue/add")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["type"].as_str(), Some("manual_add_disabled"));
+
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM queue_entries")
+            .fetch_one(state.storage().pool())
+            .await
+            .expect("count entries");
+        assert_eq!(count.0, 0);
+    }
+
+    #[tokio::test]
+    async fn settings_timezone_update_moves_day_key() {
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+
+        let served_day = |state: AppState| {
+            let token = token.clone();
+            async move {
+                let response = app_router(state)
+                    .oneshot(
+                        Request::builder()
+                            .uri("/api/queue/served-today?broadcaster=b-1")
+                            .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .expect("response");
+                assert_eq!(response.status(), StatusCode::OK);
+                let bytes = response.into_body().collect().await.unwrap().to_bytes();
+                let json: Value = serde_json::from_slice(&bytes).expect("json");
+                json["day"].as_str().expect("day").to_string()
+            }
+        };
+        let update = |state: AppState, timezone: &'static str, op_id: String| {
+            let token = token.clone();
+            async move {
+                app_router(state)
+                    .oneshot(
+                        Request::builder()
+                            .method(Method::POST)
+                            .uri("/api/settings/timezone")
+                            .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                            .header(axum::http::header::CONTENT_TYPE, "application/json")
+                            .body(Body::from(
+                                json!({"broadcaster": "b-1", "timezone": timezone, "op_id": op_id})
+                                    .to_string(),
+                            ))
+                            .unwrap(),
+                    )
+                    .await
+                    .expect("response")
+            }
+        };
+
+        query(
+            "INSERT INTO daily_counters(day, broadcaster_id, user_id, count, updated_at) VALUES ('2024-01-02', 'b-1', 'user-1', 2, ?)",
+        )
+        .bind(fixed_now.to_rfc3339())
+        .execute(state.storage().pool())
+        .await
+        .expect("insert counter");
+        assert_eq!(served_day(state.clone()).await, "2024-01-02");
+
+        let sse_token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let mut sse = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/overlay/sse?broadcaster=b-1&token={sse_token}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("sse response");
+        assert_eq!(sse.status(), StatusCode::OK);
+
+        let op_id = Uuid::new_v4().to_string();
+        let response = update(state.clone(), "Pacific/Kiritimati", op_id.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["timezone"], "Pacific/Kiritimati");
+        assert_eq!(json["day"], "2024-01-03");
+        let version = json["version"].as_u64().expect("version");
+        assert_eq!(served_day(state.clone()).await, "2024-01-03");
+
+        let mut received = String::new();
+        while !received.contains("\"type\":\"state.replace\"") {
+            let frame = time::timeout(Duration::from_secs(3), sse.body_mut().frame())
+                .await
+                .expect("patches delivered before timeout")
+                .expect("stream open")
+                .expect("frame ok");
+            if let Ok(data) = frame.into_data() {
+                received.push_str(std::str::from_utf8(&data).expect("utf-8"));
+            }
+        }
+        assert!(received.contains("\"type\":\"settings.updated\""));
+        assert!(received.contains("\"timezone\":\"Pacific/Kiritimati\""));
+        assert!(received.contains("\"counters_today\":[]"));
+
+        let response = update(state.clone(), "Pacific/Kiritimati", op_id.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["version"].as_u64(), Some(version));
+        let (logged,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM command_log WHERE type = 'settings.timezone'")
+                .fetch_one(state.storage().pool())
+                .await
+                .expect("command log count");
+        assert_eq!(logged, 1);
+
+        let response = update(state.clone(), "UTC", op_id).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response = update(state.clone(), "Not/AZone", Uuid::new_v4().to_string()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["type"], "invalid_timezone");
+        assert_eq!(served_day(state).await, "2024-01-03");
+    }
+
+    #[tokio::test]
+    async fn settings_update_applies_patch() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "patch": {"group_size": 4},
+            "op_id": Uuid::new_v4(),
+        }))
+        .expect("serialize body");
+
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/settings/update")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&payload).expect("json");
+        assert_eq!(json["result"]["applied"].as_bool(), Some(true));
+
+        let settings_json: (String,) =
+            sqlx::query_as("SELECT settings_json FROM broadcasters WHERE id = 'b-1'")
+                .fetch_one(state.storage().pool())
+                .await
+                .expect("settings json");
+        let settings: Settings = serde_json::from_str(&settings_json.0).expect("decode settings");
+        assert_eq!(settings.group_size, 4);
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_truncates_queue_beyond_limit() {
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let state = setup_state()
+            .await
+            .with_clock(Arc::new(move || fixed_now))
+            .with_snapshot_queue_limit(2);
+        provision_broadcaster(&state, 7).await;
+        for idx in 0..5 {
+            let at = fixed_now - ChronoDuration::minutes(10 - idx);
+            insert_queue_entry(
+                &state,
+                &format!("entry-{idx}"),
+                &format!("user-{idx}"),
+                at,
+                at,
+            )
+            .await;
+        }
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri("/api/state?broadcaster=b-1")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("handler should respond");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).expect("json");
+        assert_eq!(json["truncated"].as_bool(), Some(true));
+        assert_eq!(json["queue"].as_array().map(Vec::len), Some(2));
+        assert_eq!(json["queue"][0]["id"].as_str(), Some("entry-0"));
+        let mut cursor = json["next"].as_str().expect("next cursor").to_string();
+
+        let mut ids = Vec::new();
+        loop {
+            let response = app_router(state.clone())
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/api/queue?broadcaster=b-1&cursor={cursor}"))
+                        .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .expect("handler should respond");
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let page: Value = serde_json::from_slice(&body).expect("json");
+            assert_eq!(page["version"].as_u64(), Some(7));
+            for entry in page["queue"].as_array().expect("queue array") {
+                ids.push(entry["id"].as_str().unwrap().to_string());
+            }
+            match page["next"].as_str() {
+                Some(next) => cursor = next.to_string(),
+                None => break,
+            }
+        }
+        assert_eq!(ids, vec!["entry-2", "entry-3", "entry-4"]);
+
+        // A cursor issued before a mutation no longer points at the same position.
+        let stale_cursor = json["next"].as_str().expect("next cursor");
+        query("UPDATE state_index SET current_version = 8 WHERE broadcaster_id = 'b-1'")
+            .execute(state.storage().pool())
+            .await
+            .expect("bump version");
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/queue?broadcaster=b-1&cursor={stale_cursor}"))
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("handler should respond");
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let problem: Value = serde_json::from_slice(&body).expect("json");
+        assert_eq!(problem["type"], "cursor_stale");
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_within_limit_is_not_truncated() {
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let state = setup_state()
+            .await
+            .with_clock(Arc::new(move || fixed_now))
+            .with_snapshot_queue_limit(2);
+        provision_broadcaster(&state, 1).await;
+        insert_queue_entry(&state, "entry-1", "user-1", fixed_now, fixed_now).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let response = app_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/api/state?broadcaster=b-1")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("handler should respond");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).expect("json");
+        assert!(json.get("truncated").is_none());
+        assert!(json.get("next").is_none());
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_etag_tracks_cached_version() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let fetch_state = |if_none_match: Option<String>| {
+            let mut builder = Request::builder()
+                .uri("/api/state?broadcaster=b-1")
+                .header(axum::http::header::AUTHORIZATION, bearer(&token));
+            if let Some(etag) = if_none_match {
+                builder = builder.header(axum::http::header::IF_NONE_MATCH, etag);
+            }
+            app_router(state.clone()).oneshot(builder.body(Body::empty()).unwrap())
+        };
+
+        let response = fetch_state(None).await.expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[axum::http::header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let response = fetch_state(Some(etag.clone())).await.expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "patch": {"group_size": 3},
+            "op_id": Uuid::new_v4(),
+        }))
+        .expect("serialize body");
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/settings/update")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.current_version("b-1").await.unwrap(), 2);
+
+        let response = fetch_state(Some(etag.clone())).await.expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let new_etag = response.headers()[axum::http::header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_ne!(new_etag, etag);
+
+        let response = fetch_state(Some(new_etag)).await.expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn state_snapshot_includes_health_only_for_admin() {
+        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+
+        let expires_at = fixed_now + ChronoDuration::hours(2);
+        let mut tx = state.storage().pool().begin().await.expect("begin");
+        state
+            .storage()
+            .oauth_links()
+            .upsert_link(
+                &mut tx,
+                &NewOauthLink {
+                    id: "link-1".into(),
+                    broadcaster_id: "b-1",
+                    twitch_user_id: "twitch-1".into(),
+                    scopes: Vec::new(),
+                    managed_scopes: Vec::new(),
+                    access_token: "access".into(),
+                    refresh_token: "refresh".into(),
+                    expires_at,
+                    created_at: fixed_now,
+                    updated_at: fixed_now,
+                },
+            )
+            .await
+            .expect("upsert link");
+        state
+            .storage()
+            .helix_backfill()
+            .upsert(
+                &mut tx,
+                &HelixBackfillCheckpoint {
+                    broadcaster_id: "b-1".into(),
+                    cursor: None,
+                    last_redemption_id: None,
+                    last_seen_at: None,
+                    last_run_at: fixed_now,
+                    status: HelixBackfillStatus::Error,
+                    error_message: Some("twitch:error".into()),
+                    updated_at: fixed_now,
+                },
+            )
+            .await
+            .expect("upsert checkpoint");
+        tx.commit().await.expect("commit");
+
+        let fetch_state = |audience: Audience| {
+            let token = issue_token(
+                b"token-secret",
+                "b-1",
+                audience.as_str(),
+                fixed_now + ChronoDuration::minutes(10),
+            );
+            app_router(state.clone()).oneshot(
+                Request::builder()
+                    .uri("/api/state?broadcaster=b-1")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = fetch_state(Audience::Overlay).await.expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).expect("json");
+        assert!(json.get("health").is_none());
+
+        let response = fetch_state(Audience::Admin).await.expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).expect("json");
+        let health: SnapshotHealth =
+            serde_json::from_value(json["health"].clone()).expect("health block");
+        assert_eq!(
+            health,
+            SnapshotHealth {
+                requires_reauth: false,
+                token_expires_at: Some(expires_at),
+                backfill_status: Some("error".into()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_path_returns_problem_json() {
+        let state = setup_state().await;
+        let response = app_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/api/does-not-exist")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response.headers().get(axum::http::header::CONTENT_TYPE),
+            Some(&HeaderValue::from_static("application/problem+json"))
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["type"], "not_found");
+        assert_eq!(json["detail"], "no route for /api/does-not-exist");
+    }
+
+    #[tokio::test]
+    async fn disabled_endpoint_group_returns_not_found() {
+        let fixed_now = Utc::now();
+        let groups: Vec<EndpointGroup> = EndpointGroup::ALL
+            .into_iter()
+            .filter(|group| *group != EndpointGroup::Settings)
+            .collect();
+        let state = setup_state()
+            .await
+            .with_clock(Arc::new(move || fixed_now))
+            .with_enabled_endpoints(&groups);
+        provision_broadcaster(&state, 1).await;
+
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "patch": {"group_size": 4},
+            "op_id": Uuid::new_v4(),
+        }))
+        .expect("serialize body");
+
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/settings/update")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["type"], "not_found");
+
+        let response = app_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/api/state?broadcaster=b-1")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn wrong_method_returns_problem_json() {
+        let state = setup_state().await;
+        let response = app_router(state)
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/api/queue/add")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            response.headers().get(axum::http::header::CONTENT_TYPE),
+            Some(&HeaderValue::from_static("application/problem+json"))
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(json["type"], "method_not_allowed");
+        assert_eq!(json["detail"], "GET is not allowed on /api/queue/add");
+    }
+
+    #[test]
+    fn parse_types_accepts_known_patch_kinds() {
+        let types = parse_types(Some("queue.enqueued, counter.updated,".to_string()), 2)
+            .expect("valid filter")
+            .expect("filter present");
+        assert_eq!(
+            types,
+            HashSet::from(["queue.enqueued".to_string(), "counter.updated".to_string()])
+        );
+        assert!(parse_types(None, 2).expect("no filter").is_none());
+    }
+
+    #[test]
+    fn parse_types_rejects_more_than_max_entries() {
+        let raw = vec!["queue.enqueued"; 65].join(",");
+        let (status, message) = parse_types(Some(raw.clone()), 64).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(message, "too_many_types: 65 > 64");
+
+        let within = vec!["queue.enqueued"; 64].join(",");
+        assert!(parse_types(Some(within), 64).is_ok());
+        assert!(parse_types(Some(raw), 0).is_ok());
     }
 
     #[tokio::test]
-    async fn healthz_returns_ok() {
-        let app = app_router(setup_state().await);
+    async fn overlay_sse_rejects_unknown_types() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
 
-        let response = app
+        let response = app_router(state.clone())
             .oneshot(
                 Request::builder()
-                    .uri("/healthz")
+                    .uri(format!(
+                        "/overlay/sse?broadcaster=b-1&token={token}&types=queue.enqueued,queue.enqued,counter"
+                    ))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
-            .expect("handler should respond");
-
-        assert_eq!(response.status(), StatusCode::OK);
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&bytes[..], b"unknown_types: queue.enqued,counter");
     }
 
     #[tokio::test]
-    async fn metrics_exports_build_info() {
-        let app = app_router(setup_state().await);
+    async fn overlay_sse_rejects_oversized_types_filter() {
+        let fixed_now = Utc::now();
+        let state = setup_state()
+            .await
+            .with_clock(Arc::new(move || fixed_now))
+            .with_sse_max_filter_types(2);
+        provision_broadcaster(&state, 1).await;
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
 
-        let response = app
+        let response = app_router(state.clone())
             .oneshot(
                 Request::builder()
-                    .uri("/metrics")
+                    .uri(format!(
+                        "/overlay/sse?broadcaster=b-1&token={token}&types=queue.enqueued,queue.removed,counter.updated"
+                    ))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
-            .expect("handler should respond");
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&bytes[..], b"too_many_types: 3 > 2");
 
-        assert_eq!(response.status(), StatusCode::OK);
-        let collected = response
-            .into_body()
-            .collect()
+        let response = app_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/overlay/sse?broadcaster=b-1&token={token}&types=queue.enqueued,counter.updated"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
             .await
-            .expect("body should read");
-        let body = String::from_utf8(collected.to_bytes().to_vec()).expect("utf-8");
-        assert!(body.contains("app_build_info"));
-        assert!(body.contains("app_uptime_seconds"));
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn tap_stream_emits_events() {
-        let state = setup_state().await;
-        let tap = state.tap().clone();
-        let app = app_router(state);
-
-        let request = Request::builder()
-            .uri("/_debug/tap")
-            .body(Body::empty())
-            .unwrap();
-
-        let publish = tokio::spawn(async move {
-            time::sleep(Duration::from_millis(25)).await;
-            tap.publish(StageEvent::mock("test.event"));
-        });
-
-        let mut response = app.oneshot(request).await.expect("handler should respond");
-
-        let frame = time::timeout(Duration::from_secs(1), response.body_mut().frame())
+    async fn overlay_sse_heartbeat_uses_configured_text_and_event() {
+        let fixed_now = Utc::now();
+        let base = setup_state()
             .await
-            .expect("stream produced chunk")
-            .expect("chunk ok")
-            .expect("chunk available");
+            .with_clock(Arc::new(move || fixed_now))
+            .with_sse_heartbeat_secs(1);
+        provision_broadcaster(&base, 1).await;
+        let token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
 
-        let data = match frame.into_data() {
-            Ok(data) => data,
-            Err(_) => panic!("expected data frame"),
+        let read_heartbeat = |state: AppState, expected: &'static str| {
+            let token = token.clone();
+            async move {
+                let mut response = app_router(state)
+                    .oneshot(
+                        Request::builder()
+                            .uri(format!("/overlay/sse?broadcaster=b-1&token={token}"))
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .expect("response");
+                assert_eq!(response.status(), StatusCode::OK);
+
+                let mut received = String::new();
+                while !received.contains(expected) {
+                    let frame = time::timeout(Duration::from_secs(3), response.body_mut().frame())
+                        .await
+                        .expect("heartbeat before timeout")
+                        .expect("stream open")
+                        .expect("frame ok");
+                    if let Ok(data) = frame.into_data() {
+                        received.push_str(std::str::from_utf8(&data).expect("utf-8"));
+                    }
+                }
+                received
+            }
         };
-        let text = String::from_utf8(data.to_vec()).expect("utf-8");
-        assert!(text.contains("data:"));
-        assert!(text.contains("\"stage\":\"sse\""));
 
-        publish.await.expect("publish task");
+        let (comment, event) = tokio::join!(
+            read_heartbeat(base.clone().with_sse_heartbeat("ping", None), ": ping\n"),
+            read_heartbeat(
+                base.clone().with_sse_heartbeat("ping", Some("keepalive")),
+                "event: keepalive\ndata: ping\n",
+            ),
+        );
+        assert!(!comment.contains("heartbeat"));
+        assert!(!event.lines().any(|line| line == ": ping"));
     }
 
     #[tokio::test]
-    async fn state_snapshot_requires_token() {
-        let app = app_router(setup_state().await);
+    async fn overlay_sse_single_use_token_rejected_on_reuse() {
+        let fixed_now = Utc::now();
+        let state = setup_state()
+            .await
+            .with_clock(Arc::new(move || fixed_now))
+            .with_single_use_sse_audiences([Audience::Overlay]);
+        provision_broadcaster(&state, 1).await;
 
-        let response = app
-            .oneshot(
+        let exp = fixed_now + ChronoDuration::minutes(10);
+        let connect = |token: String| {
+            app_router(state.clone()).oneshot(
                 Request::builder()
-                    .uri("/api/state?broadcaster=b-1")
+                    .uri(format!("/overlay/sse?broadcaster=b-1&token={token}"))
                     .body(Body::empty())
                     .unwrap(),
             )
-            .await
-            .expect("handler should respond");
+        };
 
-        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let token = issue_token_with_jti(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            exp,
+            Some("jti-1"),
+        );
+        let response = connect(token.clone()).await.expect("first connect");
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(response);
+
+        let response = connect(token).await.expect("second connect");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&bytes[..], b"token_reused");
+
+        let response = connect(issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            exp,
+        ))
+        .await
+        .expect("connect without jti");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Audiences not configured as single-use keep accepting reusable tokens.
+        let admin_token = issue_token(b"token-secret", "b-1", Audience::Admin.as_str(), exp);
+        for _ in 0..2 {
+            let response = app_router(state.clone())
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/admin/sse?broadcaster=b-1&token={admin_token}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .expect("admin connect");
+            assert_eq!(response.status(), StatusCode::OK);
+        }
     }
 
     #[tokio::test]
-    async fn state_snapshot_returns_session_scope() {
-        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
-        let clock_now = fixed_now;
-        let state = setup_state().await.with_clock(Arc::new(move || clock_now));
-        provision_broadcaster(&state, 42).await;
-        insert_queue_entry(
-            &state,
-            "entry-1",
-            "user-1",
-            fixed_now - ChronoDuration::minutes(5),
-            fixed_now - ChronoDuration::minutes(5),
-        )
-        .await;
-        insert_counter(&state, "user-1", 3, fixed_now - ChronoDuration::minutes(4)).await;
-
+    async fn sse_connection_gauge_tracks_open_streams() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
         let token = issue_token(
             b"token-secret",
             "b-1",
             Audience::Overlay.as_str(),
             fixed_now + ChronoDuration::minutes(10),
         );
-        let app = app_router(state.clone());
+        assert_eq!(state.sse().active_connections(Audience::Overlay), 0);
 
-        state
-            .token_validator()
-            .validate_any(
-                &token,
-                &[Audience::Overlay, Audience::Admin],
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/overlay/sse?broadcaster=b-1&token={token}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.sse().active_connections(Audience::Overlay), 1);
+        assert_eq!(state.sse().active_connections(Audience::Admin), 0);
+        let rendered = state.metrics().render();
+        assert!(rendered.contains("sse_active_connections{aud=\"overlay\"}"));
+        assert!(rendered.contains("sse_clients{aud=\"overlay\"}"));
+        assert!(rendered.contains("sse_connections_total{aud=\"overlay\"}"));
+
+        drop(response);
+        assert_eq!(state.sse().active_connections(Audience::Overlay), 0);
+
+        let patches = state
+            .command_executor()
+            .execute(
                 "b-1",
-                fixed_now,
+                "UTC",
+                &[Command::QueueAdd(QueueAddCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: fixed_now,
+                    source: CommandSource::Admin,
+                    user: NormalizedUser {
+                        id: "user-1".to_string(),
+                        login: None,
+                        display_name: None,
+                    },
+                    op_id: Uuid::new_v4().to_string(),
+                })],
             )
-            .expect("token should be accepted");
-
-        let profile = state
-            .storage()
-            .broadcasters()
-            .fetch_settings("b-1")
             .await
-            .expect("profile should load");
-        build_state_snapshot(
-            state.storage(),
-            "b-1",
-            &profile,
-            fixed_now,
-            StateScope::Session,
-        )
-        .await
-        .expect("snapshot should build");
-
-        let mut response = app
+            .expect("execute");
+        state
+            .sse()
+            .broadcast_patch("b-1", &patches[0], fixed_now)
+            .await
+            .expect("broadcast");
+        let response = app_router(state.clone())
             .oneshot(
                 Request::builder()
-                    .uri("/api/state?broadcaster=b-1")
-                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .uri(format!(
+                        "/overlay/sse?broadcaster=b-1&since_version=0&token={token}"
+                    ))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
-            .expect("handler should respond");
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let rendered = state.metrics().render();
+        assert!(rendered.contains("sse_ring_misses_total{aud=\"overlay\"}"));
+        assert!(rendered.contains("sse_ring_miss_total{aud=\"overlay\"}"));
+    }
 
-        if response.status() != StatusCode::OK {
-            let body = response.body_mut().collect().await.unwrap().to_bytes();
-            panic!(
-                "unexpected status {} body {}",
-                response.status(),
-                String::from_utf8_lossy(&body)
-            );
-        }
+    #[tokio::test]
+    async fn reloaded_env_file_applies_without_touching_process_env() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
 
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let json: Value = serde_json::from_slice(&body).expect("json");
-        assert_eq!(json.get("version").and_then(Value::as_u64), Some(42));
-        assert_eq!(
-            json.get("queue")
-                .and_then(Value::as_array)
-                .map(|arr| arr.len()),
-            Some(1)
-        );
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(".env");
+        std::fs::write(
+            &path,
+            "PATCH_COALESCE_THRESHOLD=1\nSSE_MAX_LAG=7\nOAUTH_LOGIN_COOLDOWN_SECS=90\n",
+        )
+        .expect("write env file");
+        let overrides = twi_overlay_util::read_env_path(&path);
+        let config = AppConfig::from_env_with_overrides(&overrides).expect("config");
+        state.apply_reloadable_config(&config);
+        assert!(std::env::var("PATCH_COALESCE_THRESHOLD").is_err());
+        assert_eq!(state.sse().max_lag(), 7);
         assert_eq!(
-            json.get("counters_today")
-                .and_then(Value::as_array)
-                .map(|arr| arr.len()),
-            Some(1)
+            state.oauth_login_cooldown().window(),
+            ChronoDuration::seconds(90)
         );
+
+        let commands: Vec<Command> = (0..2)
+            .map(|idx| {
+                Command::QueueAdd(QueueAddCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: fixed_now,
+                    source: CommandSource::Admin,
+                    user: NormalizedUser {
+                        id: format!("user-{idx}"),
+                        login: None,
+                        display_name: None,
+                    },
+                    op_id: Uuid::new_v4().to_string(),
+                })
+            })
+            .collect();
+        let patches = state
+            .command_executor()
+            .execute("b-1", "UTC", &commands)
+            .await
+            .expect("execute");
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].kind, PatchKind::Batch);
     }
 
     #[tokio::test]
-    async fn state_snapshot_since_filters_old_records() {
-        let fixed_now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
-        let clock_now = fixed_now;
-        let state = setup_state().await.with_clock(Arc::new(move || clock_now));
-        provision_broadcaster(&state, 55).await;
-        let earlier = fixed_now - ChronoDuration::minutes(30);
-        let recent = fixed_now - ChronoDuration::minutes(5);
-        insert_queue_entry(&state, "entry-early", "user-1", earlier, earlier).await;
-        insert_queue_entry(&state, "entry-recent", "user-2", recent, recent).await;
-        insert_counter(&state, "user-1", 2, earlier).await;
-        insert_counter(&state, "user-2", 5, recent).await;
-
+    async fn overlay_sse_types_filter_delivers_coalesced_enqueues() {
+        let fixed_now = Utc::now();
+        let state = setup_state()
+            .await
+            .with_clock(Arc::new(move || fixed_now))
+            .with_patch_coalesce_threshold(1);
+        provision_broadcaster(&state, 1).await;
         let token = issue_token(
             b"token-secret",
             "b-1",
             Audience::Overlay.as_str(),
             fixed_now + ChronoDuration::minutes(10),
         );
-        let since = (fixed_now - ChronoDuration::minutes(10)).to_rfc3339();
-        let query = to_string([
-            ("broadcaster", "b-1"),
-            ("scope", "since"),
-            ("since", since.as_str()),
-        ])
-        .expect("query should serialize");
-        let uri = format!("/api/state?{query}");
-        let app = app_router(state.clone());
-
-        state
-            .token_validator()
-            .validate_any(
-                &token,
-                &[Audience::Overlay, Audience::Admin],
-                "b-1",
-                fixed_now,
-            )
-            .expect("token should be accepted");
-
-        let profile = state
-            .storage()
-            .broadcasters()
-            .fetch_settings("b-1")
-            .await
-            .expect("profile should load");
-        build_state_snapshot(
-            state.storage(),
+
+        let mut response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/overlay/sse?broadcaster=b-1&token={token}&types=queue.enqueued"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let commands: Vec<Command> = (0..2)
+            .map(|idx| {
+                Command::QueueAdd(QueueAddCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: fixed_now,
+                    source: CommandSource::Admin,
+                    user: NormalizedUser {
+                        id: format!("user-{idx}"),
+                        login: None,
+                        display_name: None,
+                    },
+                    op_id: Uuid::new_v4().to_string(),
+                })
+            })
+            .collect();
+        let patches = state
+            .command_executor()
+            .execute("b-1", "UTC", &commands)
+            .await
+            .expect("execute");
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].kind, PatchKind::Batch);
+        state
+            .sse()
+            .broadcast_patch("b-1", &patches[0], fixed_now)
+            .await
+            .expect("broadcast");
+
+        let mut received = String::new();
+        while !received.contains("\n\n") || !received.contains("patch.batch") {
+            let frame = time::timeout(Duration::from_secs(3), response.body_mut().frame())
+                .await
+                .expect("batch delivered before timeout")
+                .expect("stream open")
+                .expect("frame ok");
+            if let Ok(data) = frame.into_data() {
+                received.push_str(std::str::from_utf8(&data).expect("utf-8"));
+            }
+        }
+        assert!(received.contains("\"type\":\"patch.batch\""));
+        assert_eq!(received.matches("\"type\":\"queue.enqueued\"").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn overlay_sse_delivers_concurrent_mutations_in_version_order() {
+        let fixed_now = Utc::now();
+        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        provision_broadcaster(&state, 1).await;
+        let overlay_token = issue_token(
+            b"token-secret",
+            "b-1",
+            Audience::Overlay.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
+        let admin_token = issue_token(
+            b"token-secret",
             "b-1",
-            &profile,
-            fixed_now,
-            StateScope::Since(fixed_now - ChronoDuration::minutes(10)),
-        )
-        .await
-        .expect("snapshot should build");
+            Audience::Admin.as_str(),
+            fixed_now + ChronoDuration::minutes(10),
+        );
 
-        let mut response = app
+        let mut response = app_router(state.clone())
             .oneshot(
                 Request::builder()
-                    .uri(&uri)
-                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
+                    .uri(format!(
+                        "/overlay/sse?broadcaster=b-1&token={overlay_token}"
+                    ))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
-            .expect("handler should respond");
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
 
-        if response.status() != StatusCode::OK {
-            let body = response.body_mut().collect().await.unwrap().to_bytes();
-            panic!(
-                "unexpected status {} body {}",
-                response.status(),
-                String::from_utf8_lossy(&body)
-            );
-        }
+        // Apply one mutation and keep the lock while it has not been broadcast yet.
+        let executor = state.command_executor().clone();
+        let guard = executor.lock("b-1").await;
+        let first = executor
+            .execute_locked(
+                &guard,
+                "UTC",
+                &[Command::QueueAdd(QueueAddCommand {
+                    broadcaster_id: "b-1".to_string(),
+                    issued_at: fixed_now,
+                    source: CommandSource::Admin,
+                    user: NormalizedUser {
+                        id: "user-first".to_string(),
+                        login: None,
+                        display_name: None,
+                    },
+                    op_id: Uuid::new_v4().to_string(),
+                })],
+            )
+            .await
+            .expect("execute");
 
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let json: Value = serde_json::from_slice(&body).expect("json");
-        let queue = json
-            .get("queue")
-            .and_then(Value::as_array)
-            .expect("queue array");
-        assert_eq!(queue.len(), 1);
-        assert_eq!(
-            queue[0].get("id").and_then(Value::as_str),
-            Some("entry-recent")
+        let body = serde_json::to_string(&json!({
+            "broadcaster": "b-1",
+            "user_id": "user-second",
+            "op_id": Uuid::new_v4(),
+        }))
+        .expect("serialize body");
+        let mut second = tokio::spawn(
+            app_router(state.clone()).oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/queue/add")
+                    .header(axum::http::header::AUTHORIZATION, bearer(&admin_token))
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            ),
         );
-        let counters = json
-            .get("counters_today")
-            .and_then(Value::as_array)
-            .expect("counters array");
-        assert_eq!(counters.len(), 1);
-        assert_eq!(
-            counters[0].get("user_id").and_then(Value::as_str),
-            Some("user-2")
+        assert!(
+            time::timeout(Duration::from_millis(100), &mut second)
+                .await
+                .is_err(),
+            "second mutation must wait for the lock"
         );
+
+        for patch in &first {
+            state
+                .sse()
+                .broadcast_patch("b-1", patch, fixed_now)
+                .await
+                .expect("broadcast");
+        }
+        drop(guard);
+        let response_second = second.await.expect("join").expect("response");
+        assert_eq!(response_second.status(), StatusCode::OK);
+
+        let mut received = String::new();
+        let mut versions = Vec::new();
+        while versions.len() < 2 {
+            let frame = time::timeout(Duration::from_secs(3), response.body_mut().frame())
+                .await
+                .expect("patch delivered before timeout")
+                .expect("stream open")
+                .expect("frame ok");
+            if let Ok(data) = frame.into_data() {
+                received.push_str(std::str::from_utf8(&data).expect("utf-8"));
+            }
+            versions = received
+                .lines()
+                .filter_map(|line| line.strip_prefix("id:"))
+                .map(|id| id.trim().parse::<u64>().expect("numeric id"))
+                .collect::<Vec<u64>>();
+        }
+        assert_eq!(versions, vec![first[0].version, first[0].version + 1]);
     }
 
     #[tokio::test]
-    async fn queue_dequeue_complete_succeeds() {
+    async fn overlay_sse_disconnects_subscriber_exceeding_max_lag() {
         let fixed_now = Utc::now();
-        let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
+        let state = setup_state()
+            .await
+            .with_clock(Arc::new(move || fixed_now))
+            .with_sse_max_lag(3);
         provision_broadcaster(&state, 1).await;
-        insert_queue_entry(&state, "entry-1", "user-1", fixed_now, fixed_now).await;
-        insert_counter(&state, "user-1", 2, fixed_now).await;
-
         let token = issue_token(
             b"token-secret",
             "b-1",
-            Audience::Admin.as_str(),
+            Audience::Overlay.as_str(),
             fixed_now + ChronoDuration::minutes(10),
         );
-        let op_id = Uuid::new_v4();
-        let body = serde_json::to_string(&json!({
-            "broadcaster": "b-1",
-            "entry_id": "entry-1",
-            "mode": "COMPLETE",
-            "op_id": op_id,
-        }))
-        .expect("serialize body");
 
-        let response = app_router(state.clone())
+        let mut response = app_router(state.clone())
             .oneshot(
                 Request::builder()
-                    .method(Method::POST)
-                    .uri("/api/queue/dequeue")
-                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
-                    .header(axum::http::header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(body))
+                    .uri(format!("/overlay/sse?broadcaster=b-1&token={token}"))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .expect("response");
-
         assert_eq!(response.status(), StatusCode::OK);
-        let bytes = response.into_body().collect().await.unwrap().to_bytes();
-        let json: Value = serde_json::from_slice(&bytes).expect("json");
-        assert_eq!(json["result"]["mode"].as_str(), Some("COMPLETE"));
 
-        let status: (String,) =
-            sqlx::query_as("SELECT status FROM queue_entries WHERE id = 'entry-1'")
-                .fetch_one(state.storage().pool())
+        // The subscriber reads nothing while more than `max_lag` patches are published.
+        for version in 1..=4 {
+            let patch = Patch {
+                version,
+                kind: PatchKind::SettingsUpdated,
+                at: fixed_now,
+                data: json!({"group_size": version}),
+            };
+            state
+                .sse()
+                .broadcast_patch("b-1", &patch, fixed_now)
                 .await
-                .expect("entry status");
-        assert_eq!(status.0, QueueEntryStatus::Completed.as_str());
+                .expect("broadcast");
+        }
 
-        let count: (i64,) =
-            sqlx::query_as("SELECT count FROM daily_counters WHERE user_id = 'user-1'")
-                .fetch_one(state.storage().pool())
-                .await
-                .expect("counter");
-        assert_eq!(count.0, 2);
+        let mut received = String::new();
+        while let Some(frame) = time::timeout(Duration::from_secs(3), response.body_mut().frame())
+            .await
+            .expect("stream closes before timeout")
+        {
+            if let Ok(data) = frame.expect("frame ok").into_data() {
+                received.push_str(std::str::from_utf8(&data).expect("utf-8"));
+            }
+        }
+        assert!(received.contains("event: overflow\n"));
+        assert!(!received.contains("event: patch"));
     }
 
     #[tokio::test]
-    async fn queue_dequeue_undo_decrements_counter() {
+    async fn overlay_sse_stream_ends_on_shutdown() {
         let fixed_now = Utc::now();
         let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
         provision_broadcaster(&state, 1).await;
-        insert_queue_entry(&state, "entry-1", "user-1", fixed_now, fixed_now).await;
-        insert_counter(&state, "user-1", 3, fixed_now).await;
-
         let token = issue_token(
             b"token-secret",
             "b-1",
-            Audience::Admin.as_str(),
+            Audience::Overlay.as_str(),
             fixed_now + ChronoDuration::minutes(10),
         );
-        let op_id = Uuid::new_v4();
-        let body = serde_json::to_string(&json!({
-            "broadcaster": "b-1",
-            "entry_id": "entry-1",
-            "mode": "UNDO",
-            "op_id": op_id,
-        }))
-        .expect("serialize body");
 
-        let response = app_router(state.clone())
+        let mut response = app_router(state.clone())
             .oneshot(
                 Request::builder()
-                    .method(Method::POST)
-                    .uri("/api/queue/dequeue")
-                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
-                    .header(axum::http::header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(body))
+                    .uri(format!("/overlay/sse?broadcaster=b-1&token={token}"))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        state.sse().shutdown();
+        let frame = time::timeout(Duration::from_secs(1), response.body_mut().frame())
+            .await
+            .expect("stream ends before timeout");
+        assert!(frame.is_none());
+    }
+
+    #[tokio::test]
+    async fn debug_db_reports_pragmas_only_in_development() {
+        let state = setup_state().await;
 
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri("/_debug/db")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
         assert_eq!(response.status(), StatusCode::OK);
         let bytes = response.into_body().collect().await.unwrap().to_bytes();
         let json: Value = serde_json::from_slice(&bytes).expect("json");
-        assert_eq!(json["result"]["mode"].as_str(), Some("UNDO"));
-        assert_eq!(json["result"]["user_today_count"].as_u64(), Some(2));
+        assert!(json["journal_mode"].is_string());
+        assert!(json["page_count"].as_i64().unwrap() > 0);
+        assert!(json["freelist_count"].as_i64().is_some());
+        assert!(json["wal_autocheckpoint"].as_i64().is_some());
 
-        let row: (String, Option<String>) =
-            sqlx::query_as("SELECT status, status_reason FROM queue_entries WHERE id = 'entry-1'")
-                .fetch_one(state.storage().pool())
-                .await
-                .expect("entry status");
-        assert_eq!(row.0, QueueEntryStatus::Removed.as_str());
-        assert_eq!(row.1.as_deref(), Some(QueueRemovalReason::Undo.as_str()));
+        let response = app_router(state.with_environment(Environment::Production))
+            .oneshot(
+                Request::builder()
+                    .uri("/_debug/db")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 
-        let count: (i64,) =
-            sqlx::query_as("SELECT count FROM daily_counters WHERE user_id = 'user-1'")
-                .fetch_one(state.storage().pool())
-                .await
-                .expect("counter");
-        assert_eq!(count.0, 2);
+    #[tokio::test]
+    async fn debug_db_checkpoint_returns_stats() {
+        let state = setup_state().await;
 
-        let conflict_body = serde_json::to_string(&json!({
-            "broadcaster": "b-1",
-            "entry_id": "entry-1",
-            "mode": "COMPLETE",
-            "op_id": op_id,
-        }))
-        .expect("serialize body");
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/_debug/db/checkpoint")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert!(json["busy_frames"].as_i64().is_some());
+        assert!(json["log_frames"].as_i64().is_some());
+        assert!(json["checkpointed_frames"].as_i64().is_some());
 
-        let conflict = app_router(state.clone())
+        let response = app_router(state.with_environment(Environment::Production))
             .oneshot(
                 Request::builder()
                     .method(Method::POST)
-                    .uri("/api/queue/dequeue")
-                    .header(axum::http::header::AUTHORIZATION, bearer(&token))
-                    .header(axum::http::header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(conflict_body))
+                    .uri("/_debug/db/checkpoint")
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .expect("response");
-        assert_eq!(conflict.status(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
     #[tokio::test]
-    async fn settings_update_applies_patch() {
+    async fn debug_commands_lists_source() {
         let fixed_now = Utc::now();
         let state = setup_state().await.with_clock(Arc::new(move || fixed_now));
         provision_broadcaster(&state, 1).await;
@@ -1678,11 +5607,10 @@ mod tests {
         );
         let body = serde_json::to_string(&json!({
             "broadcaster": "b-1",
-            "patch": {"group_size": 4},
+            "patch": {"group_size": 2},
             "op_id": Uuid::new_v4(),
         }))
         .expect("serialize body");
-
         let response = app_router(state.clone())
             .oneshot(
                 Request::builder()
@@ -1695,18 +5623,36 @@ mod tests {
             )
             .await
             .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
 
+        let response = app_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri("/_debug/commands?broadcaster=b-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
         assert_eq!(response.status(), StatusCode::OK);
-        let payload = response.into_body().collect().await.unwrap().to_bytes();
-        let json: Value = serde_json::from_slice(&payload).expect("json");
-        assert_eq!(json["result"]["applied"].as_bool(), Some(true));
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(
+            json["commands"][0]["type"].as_str(),
+            Some("settings.update")
+        );
+        assert_eq!(json["commands"][0]["source"].as_str(), Some("admin"));
+        assert_eq!(json["commands"][0]["version"].as_u64(), Some(2));
 
-        let settings_json: (String,) =
-            sqlx::query_as("SELECT settings_json FROM broadcasters WHERE id = 'b-1'")
-                .fetch_one(state.storage().pool())
-                .await
-                .expect("settings json");
-        let settings: Settings = serde_json::from_str(&settings_json.0).expect("decode settings");
-        assert_eq!(settings.group_size, 4);
+        let response = app_router(state.with_environment(Environment::Production))
+            .oneshot(
+                Request::builder()
+                    .uri("/_debug/commands?broadcaster=b-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 }
@@ -1,10 +1,11 @@
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     convert::Infallible,
+    future::Future,
     pin::Pin,
     sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex as StdMutex, Weak,
     },
     task::{Context, Poll},
     time::{Duration, Instant},
@@ -17,18 +18,25 @@ use metrics::{counter, gauge, histogram};
 use serde::{Deserialize, Serialize};
 use serde_json::to_string;
 use thiserror::Error;
-use tokio::sync::{broadcast, Mutex, RwLock};
-use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tokio::sync::{broadcast, watch, Mutex, RwLock};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream, StreamExt,
+};
+use tracing::warn;
 
 use twi_overlay_core::projector::Projector;
-use twi_overlay_core::types::Patch;
+use twi_overlay_core::types::{Patch, PatchKind};
 use twi_overlay_storage::{BroadcasterSettings, Database, StateIndexError};
 
 use crate::command::CommandExecutorError;
-use crate::state::{build_state_snapshot, StateError, StateScope};
+use crate::state::{build_state_snapshot, ActiveQueueCache, StateError, StateScope};
 
 const EVENT_NAME: &str = "patch";
+const OVERFLOW_EVENT_NAME: &str = "overflow";
 const BROADCAST_BUFFER: usize = 256;
+/// Default number of undelivered messages after which a subscriber is disconnected.
+const DEFAULT_MAX_LAG: usize = 128;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Audience {
@@ -43,12 +51,21 @@ impl Audience {
             Audience::Admin => "admin",
         }
     }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "overlay" => Some(Audience::Overlay),
+            "admin" => Some(Audience::Admin),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct SseTokenValidator {
     decoding_key: DecodingKey,
     validation: Validation,
+    single_use: HashSet<Audience>,
 }
 
 impl SseTokenValidator {
@@ -60,22 +77,33 @@ impl SseTokenValidator {
         Self {
             decoding_key: DecodingKey::from_secret(&secret),
             validation,
+            single_use: HashSet::new(),
         }
     }
 
+    /// Marks the audiences whose SSE tokens may only be used for a single connection.
+    pub fn with_single_use(mut self, audiences: impl IntoIterator<Item = Audience>) -> Self {
+        self.single_use = audiences.into_iter().collect();
+        self
+    }
+
+    pub fn requires_single_use(&self, audience: Audience) -> bool {
+        self.single_use.contains(&audience)
+    }
+
     pub fn validate(
         &self,
         token: &str,
         expected_audience: Audience,
         broadcaster_id: &str,
         now: DateTime<Utc>,
-    ) -> Result<(), TokenError> {
+    ) -> Result<TokenClaims, TokenError> {
         let claims = self.decode_claims(token)?;
         self.validate_claims(&claims, broadcaster_id, now)?;
         if claims.aud != expected_audience.as_str() {
             return Err(TokenError::Invalid("audience_mismatch".to_string()));
         }
-        Ok(())
+        Ok(claims)
     }
 
     pub fn validate_any(
@@ -130,6 +158,9 @@ pub struct TokenClaims {
     pub exp: usize,
     #[serde(default)]
     pub nbf: Option<usize>,
+    /// Token identifier; required for audiences configured as single-use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
 }
 
 #[derive(Debug, Error)]
@@ -145,6 +176,9 @@ pub struct SseHub {
     ring_max: usize,
     ring_ttl: Duration,
     counters: Arc<ClientCounters>,
+    active_queue: ActiveQueueCache,
+    max_lag: Arc<AtomicUsize>,
+    shutdown: Arc<watch::Sender<bool>>,
 }
 
 impl SseHub {
@@ -155,9 +189,40 @@ impl SseHub {
             ring_max,
             ring_ttl,
             counters: Arc::new(ClientCounters::new()),
+            active_queue: ActiveQueueCache::default(),
+            max_lag: Arc::new(AtomicUsize::new(DEFAULT_MAX_LAG)),
+            shutdown: Arc::new(watch::Sender::new(false)),
         }
     }
 
+    /// Sets how many messages a subscriber may fall behind before it is sent `event: overflow`
+    /// and disconnected (0 disables the check; the broadcast buffer still bounds memory).
+    pub fn set_max_lag(&self, max_lag: usize) {
+        self.max_lag.store(max_lag, Ordering::Relaxed);
+    }
+
+    /// Ends every open and future stream so graceful shutdown does not wait on SSE clients,
+    /// which otherwise never finish their responses.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    #[cfg(test)]
+    pub fn max_lag(&self) -> usize {
+        self.max_lag.load(Ordering::Relaxed)
+    }
+
+    /// Number of open SSE streams for the audience, as reported by `sse_active_connections`.
+    #[cfg(test)]
+    pub fn active_connections(&self, audience: Audience) -> usize {
+        self.counters.get(audience)
+    }
+
+    /// Active-queue cache shared by SSE ring-miss replays and `/api/state`.
+    pub fn active_queue_cache(&self) -> &ActiveQueueCache {
+        &self.active_queue
+    }
+
     async fn ensure_channel(&self, broadcaster_id: &str, audience: Audience) -> Arc<Channel> {
         let key = ChannelKey::new(broadcaster_id, audience);
         let mut guard = self.channels.write().await;
@@ -194,6 +259,8 @@ impl SseHub {
                 }
             }
             let _ = channel.sender.send(message.clone());
+            let sent = channel.sent.fetch_add(1, Ordering::SeqCst) + 1;
+            channel.flag_lagging(broadcaster_id, sent, self.max_lag.load(Ordering::Relaxed));
         }
 
         Ok(())
@@ -218,6 +285,8 @@ impl SseHub {
             .map(|first| since_version.map(|v| v < first.version).unwrap_or(false))
             .unwrap_or(false);
         if ring_miss {
+            counter!("sse_ring_misses_total", "aud" => audience.as_str()).increment(1);
+            // Deprecated alias kept for existing dashboards.
             counter!("sse_ring_miss_total", "aud" => audience.as_str()).increment(1);
         }
 
@@ -229,12 +298,15 @@ impl SseHub {
 
         let guard = ClientGuard::new(self.counters.clone(), audience);
         let receiver = BroadcastStream::new(channel.sender.subscribe());
+        let lag = channel.track(audience);
         Subscription {
             backlog,
             receiver,
             filter,
             guard,
+            lag,
             ring_miss,
+            shutdown: self.shutdown.subscribe(),
         }
     }
 
@@ -246,6 +318,7 @@ impl SseHub {
     ) -> Result<Patch, SseError> {
         let snapshot = build_state_snapshot(
             &self.database,
+            &self.active_queue,
             broadcaster_id,
             profile,
             now,
@@ -269,12 +342,7 @@ fn retain_backlog(
     messages
         .into_iter()
         .filter(move |msg| since.map(|version| msg.version > version).unwrap_or(true))
-        .filter(move |msg| {
-            filter
-                .as_ref()
-                .map(|set| set.contains(msg.kind.as_str()))
-                .unwrap_or(true)
-        })
+        .filter(move |msg| filter.as_ref().map(|set| msg.matches(set)).unwrap_or(true))
         .collect()
 }
 
@@ -296,6 +364,9 @@ impl ChannelKey {
 struct Channel {
     sender: broadcast::Sender<Arc<SseMessage>>,
     ring: Mutex<VecDeque<Arc<SseMessage>>>,
+    /// Number of messages sent on this channel so far.
+    sent: AtomicU64,
+    subscribers: StdMutex<Vec<Weak<SubscriberLag>>>,
 }
 
 impl Channel {
@@ -304,8 +375,79 @@ impl Channel {
         Self {
             sender,
             ring: Mutex::new(VecDeque::new()),
+            sent: AtomicU64::new(0),
+            subscribers: StdMutex::new(Vec::new()),
         }
     }
+
+    fn track(&self, audience: Audience) -> Arc<SubscriberLag> {
+        let lag = Arc::new(SubscriberLag {
+            audience,
+            start: self.sent.load(Ordering::SeqCst),
+            received: AtomicU64::new(0),
+            overflowed: AtomicBool::new(false),
+        });
+        self.subscribers
+            .lock()
+            .expect("sse subscribers poisoned")
+            .push(Arc::downgrade(&lag));
+        lag
+    }
+
+    /// Flags subscribers that fell more than `max_lag` messages behind; their streams end with
+    /// `event: overflow` on the next poll.
+    fn flag_lagging(&self, broadcaster_id: &str, sent: u64, max_lag: usize) {
+        let mut subscribers = self.subscribers.lock().expect("sse subscribers poisoned");
+        subscribers.retain(|weak| {
+            let Some(lag) = weak.upgrade() else {
+                return false;
+            };
+            if max_lag > 0 && lag.pending(sent) > max_lag as u64 && lag.mark_overflowed() {
+                warn!(
+                    stage = "sse",
+                    broadcaster_id,
+                    aud = lag.audience.as_str(),
+                    max_lag,
+                    "disconnecting slow sse subscriber"
+                );
+            }
+            true
+        });
+    }
+}
+
+/// Delivery progress of a single subscriber, used to detect clients that stopped reading.
+struct SubscriberLag {
+    audience: Audience,
+    start: u64,
+    received: AtomicU64,
+    overflowed: AtomicBool,
+}
+
+impl SubscriberLag {
+    fn pending(&self, sent: u64) -> u64 {
+        sent.saturating_sub(self.start)
+            .saturating_sub(self.received.load(Ordering::SeqCst))
+    }
+
+    /// Returns `true` only for the call that flagged the subscriber.
+    fn mark_overflowed(&self) -> bool {
+        let first = !self.overflowed.swap(true, Ordering::SeqCst);
+        if first {
+            counter!("sse_overflow_disconnects_total", "aud" => self.audience.as_str())
+                .increment(1);
+        }
+        first
+    }
+
+    fn is_overflowed(&self) -> bool {
+        self.overflowed.load(Ordering::SeqCst)
+    }
+}
+
+enum Delivery {
+    Event(Event),
+    Overflow,
 }
 
 pub struct Subscription {
@@ -313,7 +455,9 @@ pub struct Subscription {
     receiver: BroadcastStream<Arc<SseMessage>>,
     filter: Option<Arc<HashSet<String>>>,
     guard: ClientGuard,
+    lag: Arc<SubscriberLag>,
     ring_miss: bool,
+    shutdown: watch::Receiver<bool>,
 }
 
 impl Subscription {
@@ -331,39 +475,54 @@ impl Subscription {
         }
 
         if let Some(filter) = &self.filter {
-            self.backlog
-                .retain(|msg| filter.contains(msg.kind.as_str()));
+            self.backlog.retain(|msg| msg.matches(filter));
         }
 
         let backlog_stream =
-            tokio_stream::iter(self.backlog).map(|msg| Ok::<_, Infallible>(msg.to_event()));
+            tokio_stream::iter(self.backlog).map(|msg| Delivery::Event(msg.to_event()));
 
         let filter_live = self.filter.clone();
+        let lag = self.lag;
         let live_stream = self.receiver.filter_map(move |result| match result {
             Ok(msg) => {
+                lag.received.fetch_add(1, Ordering::SeqCst);
+                if lag.is_overflowed() {
+                    return Some(Delivery::Overflow);
+                }
                 let allow = filter_live
                     .as_ref()
-                    .map(|set| set.contains(msg.kind.as_str()))
+                    .map(|set| msg.matches(set))
                     .unwrap_or(true);
                 if allow {
-                    Some(Ok(msg.to_event()))
+                    Some(Delivery::Event(msg.to_event()))
                 } else {
                     None
                 }
             }
-            Err(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(_)) => {
+                lag.mark_overflowed();
+                Some(Delivery::Overflow)
+            }
         });
 
         let stream = backlog_stream.chain(live_stream);
+        let mut shutdown = self.shutdown;
         SseStream {
             inner: Box::pin(stream),
+            shutdown: Box::pin(async move {
+                // A dropped hub counts as shutdown as well.
+                let _ = shutdown.wait_for(|closed| *closed).await;
+            }),
+            closed: false,
             _guard: self.guard,
         }
     }
 }
 
 pub struct SseStream {
-    inner: Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>,
+    inner: Pin<Box<dyn Stream<Item = Delivery> + Send>>,
+    shutdown: Pin<Box<dyn Future<Output = ()> + Send>>,
+    closed: bool,
     _guard: ClientGuard,
 }
 
@@ -372,13 +531,29 @@ impl Stream for SseStream {
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
-        this.inner.as_mut().poll_next(cx)
+        if this.closed || this.shutdown.as_mut().poll(cx).is_ready() {
+            this.closed = true;
+            return Poll::Ready(None);
+        }
+        match this.inner.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Delivery::Event(event))) => Poll::Ready(Some(Ok(event))),
+            Poll::Ready(Some(Delivery::Overflow)) => {
+                // Send one final frame so the client reconnects and resyncs via `since_version`.
+                this.closed = true;
+                Poll::Ready(Some(Ok(Event::default()
+                    .event(OVERFLOW_EVENT_NAME)
+                    .data("subscriber fell too far behind"))))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
     }
 }
 
 pub(crate) struct SseMessage {
     version: u64,
-    kind: String,
+    /// Patch kind followed by the kinds carried inside a `patch.batch`, used by `types` filters.
+    kinds: Vec<String>,
     data: String,
     created_at: Instant,
 }
@@ -386,14 +561,28 @@ pub(crate) struct SseMessage {
 impl SseMessage {
     fn from_patch(patch: &Patch) -> Result<Self, serde_json::Error> {
         let data = to_string(patch)?;
+        let mut kinds = vec![patch.kind_str().to_string()];
+        if patch.kind == PatchKind::Batch {
+            let inner = patch.data["patches"].as_array().into_iter().flatten();
+            kinds.extend(
+                inner
+                    .filter_map(|inner| inner["type"].as_str())
+                    .map(str::to_string),
+            );
+        }
         Ok(Self {
             version: patch.version,
-            kind: patch.kind_str().to_string(),
+            kinds,
             data,
             created_at: Instant::now(),
         })
     }
 
+    /// A batch passes the filter when any patch it carries does, so it is delivered whole.
+    fn matches(&self, filter: &HashSet<String>) -> bool {
+        self.kinds.iter().any(|kind| filter.contains(kind))
+    }
+
     fn to_event(&self) -> Event {
         Event::default()
             .id(self.version.to_string())
@@ -415,11 +604,22 @@ impl ClientCounters {
         }
     }
 
+    #[cfg(test)]
+    fn get(&self, audience: Audience) -> usize {
+        match audience {
+            Audience::Overlay => self.overlay.load(Ordering::SeqCst),
+            Audience::Admin => self.admin.load(Ordering::SeqCst),
+        }
+    }
+
     fn increment(&self, audience: Audience) {
         let value = match audience {
             Audience::Overlay => self.overlay.fetch_add(1, Ordering::SeqCst) + 1,
             Audience::Admin => self.admin.fetch_add(1, Ordering::SeqCst) + 1,
         };
+        counter!("sse_connections_total", "aud" => audience.as_str()).increment(1);
+        gauge!("sse_active_connections", "aud" => audience.as_str()).set(value as f64);
+        // Deprecated alias kept for existing dashboards.
         gauge!("sse_clients", "aud" => audience.as_str()).set(value as f64);
     }
 
@@ -431,6 +631,8 @@ impl ClientCounters {
                 .saturating_sub(1),
             Audience::Admin => self.admin.fetch_sub(1, Ordering::SeqCst).saturating_sub(1),
         };
+        gauge!("sse_active_connections", "aud" => audience.as_str()).set(value as f64);
+        // Deprecated alias kept for existing dashboards.
         gauge!("sse_clients", "aud" => audience.as_str()).set(value as f64);
     }
 }
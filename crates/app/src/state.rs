@@ -1,12 +1,28 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
 use chrono::{DateTime, Utc};
 use thiserror::Error;
+use tokio::sync::RwLock;
 
-use twi_overlay_core::types::{StateSnapshot, UserCounter};
+use serde::Serialize;
+use serde_json::Value;
+
+use twi_overlay_core::types::{
+    QueueEntry, QueueEntryStatus, SnapshotHealth, StateSnapshot, UserCounter,
+};
 use twi_overlay_storage::{
-    BroadcasterSettings, DailyCounterError, Database, QueueError, StateIndexError,
+    BroadcasterSettings, CommandLogError, DailyCounterError, Database, HelixBackfillError,
+    OauthLinkError, QueueError, StateIndexError,
 };
 
-use crate::command::{compute_local_day, CommandExecutorError};
+use crate::command::{compute_local_day, local_day_bounds, CommandExecutorError};
 
 #[derive(Debug, Clone, Copy)]
 pub enum StateScope {
@@ -16,6 +32,7 @@ pub enum StateScope {
 
 pub async fn build_state_snapshot(
     database: &Database,
+    active_queue: &ActiveQueueCache,
     broadcaster_id: &str,
     profile: &BroadcasterSettings,
     now: DateTime<Utc>,
@@ -30,26 +47,27 @@ pub async fn build_state_snapshot(
     let counter_repo = database.daily_counters();
 
     let snapshot_day = match scope {
-        StateScope::Session => compute_local_day(now, &profile.timezone)?,
-        StateScope::Since(since) => compute_local_day(since, &profile.timezone)?,
-    };
-
-    let queue_rows = match scope {
         StateScope::Session => {
-            queue_repo
-                .list_active_with_counts(broadcaster_id, &snapshot_day)
-                .await?
+            compute_local_day(now, &profile.timezone, profile.settings.day_rollover_hour)?
         }
         StateScope::Since(since) => {
-            queue_repo
-                .list_active_with_counts_since(broadcaster_id, &snapshot_day, since)
-                .await?
+            compute_local_day(since, &profile.timezone, profile.settings.day_rollover_hour)?
         }
     };
-    let queue = queue_rows
-        .into_iter()
-        .map(|row| row.into_domain().0)
-        .collect();
+
+    let queue = match scope {
+        StateScope::Session => active_queue
+            .load(database, broadcaster_id, &snapshot_day, version)
+            .await?
+            .as_ref()
+            .clone(),
+        StateScope::Since(since) => queue_repo
+            .list_active_with_counts_since(broadcaster_id, &snapshot_day, since)
+            .await?
+            .into_iter()
+            .map(|row| row.into_domain().0)
+            .collect(),
+    };
 
     let counters_rows = match scope {
         StateScope::Session => {
@@ -76,9 +94,450 @@ pub async fn build_state_snapshot(
         queue,
         counters_today: counters,
         settings: profile.settings.clone(),
+        truncated: false,
+        next: None,
+        health: None,
+        recent_completed: None,
+    })
+}
+
+/// Loads the OAuth link and backfill checkpoint status attached to admin snapshots.
+pub async fn load_snapshot_health(
+    database: &Database,
+    broadcaster_id: &str,
+) -> Result<SnapshotHealth, StateError> {
+    let link = database
+        .oauth_links()
+        .fetch_by_broadcaster(broadcaster_id)
+        .await?;
+    let checkpoint = database.helix_backfill().fetch(broadcaster_id).await?;
+
+    Ok(SnapshotHealth {
+        requires_reauth: link.as_ref().is_none_or(|link| link.requires_reauth),
+        token_expires_at: link.map(|link| link.expires_at),
+        backfill_status: checkpoint.map(|checkpoint| checkpoint.status.as_str().to_string()),
+    })
+}
+
+/// Loads up to `limit` entries completed since the start of the broadcaster's local day, newest first.
+pub async fn load_recent_completed(
+    database: &Database,
+    broadcaster_id: &str,
+    profile: &BroadcasterSettings,
+    now: DateTime<Utc>,
+    limit: usize,
+) -> Result<Vec<QueueEntry>, StateError> {
+    let rollover_hour = profile.settings.day_rollover_hour;
+    let day = compute_local_day(now, &profile.timezone, rollover_hour)?;
+    let (day_start, _) = local_day_bounds(now, &profile.timezone, rollover_hour)?;
+    let rows = database
+        .queue()
+        .list_recent_terminal_with_counts(
+            broadcaster_id,
+            &day,
+            day_start,
+            QueueEntryStatus::Completed,
+            limit,
+        )
+        .await?;
+
+    Ok(rows.into_iter().map(|row| row.into_domain().0).collect())
+}
+
+/// Caps the snapshot queue at `limit` entries and records a cursor for the remainder.
+///
+/// A `limit` of zero disables the guard.
+pub fn apply_queue_limit(snapshot: &mut StateSnapshot, limit: usize) {
+    if limit == 0 || snapshot.queue.len() <= limit {
+        return;
+    }
+    snapshot.queue.truncate(limit);
+    snapshot.truncated = true;
+    snapshot.next = Some(encode_queue_cursor(QueueCursor {
+        version: snapshot.version,
+        offset: limit,
+    }));
+}
+
+/// Page of active queue entries served by `GET /api/queue`.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuePage {
+    pub version: u64,
+    pub queue: Vec<QueueEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+/// Builds one page of the active queue; a `cursor` issued at another version is rejected
+/// because the offset would skip or repeat entries once the queue changed.
+pub async fn build_queue_page(
+    database: &Database,
+    broadcaster_id: &str,
+    profile: &BroadcasterSettings,
+    now: DateTime<Utc>,
+    cursor: Option<QueueCursor>,
+    limit: usize,
+) -> Result<QueuePage, StateError> {
+    let version = database
+        .state_index()
+        .fetch_current_version(broadcaster_id)
+        .await?;
+    let offset = match cursor {
+        Some(cursor) if cursor.version != version => {
+            return Err(StateError::StaleCursor {
+                cursor: cursor.version,
+                current: version,
+            });
+        }
+        Some(cursor) => cursor.offset,
+        None => 0,
+    };
+    let day = compute_local_day(now, &profile.timezone, profile.settings.day_rollover_hour)?;
+
+    // Fetch one extra row to learn whether another page follows.
+    let mut rows = database
+        .queue()
+        .list_active_with_counts_page(broadcaster_id, &day, limit as i64 + 1, offset as i64)
+        .await?;
+    let next = if rows.len() > limit {
+        rows.truncate(limit);
+        Some(encode_queue_cursor(QueueCursor {
+            version,
+            offset: offset + limit,
+        }))
+    } else {
+        None
+    };
+
+    Ok(QueuePage {
+        version,
+        queue: rows.into_iter().map(|row| row.into_domain().0).collect(),
+        next,
+    })
+}
+
+/// Entries and counters touched between two state versions, found via the command log.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateDiff {
+    pub from: u64,
+    pub to: u64,
+    pub queue: QueueDiff,
+    pub counters: Vec<CounterDelta>,
+}
+
+/// Queue entries touched between two versions, shown as they are stored now rather than as of `to`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueueDiff {
+    pub added: Vec<QueueEntry>,
+    pub updated: Vec<QueueEntry>,
+    pub removed: Vec<RemovedQueueEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedQueueEntry {
+    pub entry_id: String,
+    pub reason: String,
+}
+
+/// Net counter change for a viewer; `corrected_to` is set when an admin correction overwrote
+/// the count, in which case `delta` only covers changes after the last correction.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CounterDelta {
+    pub user_id: String,
+    pub delta: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corrected_to: Option<u32>,
+}
+
+enum EntryChange {
+    Added,
+    Updated,
+    Removed(String),
+}
+
+/// Replays the command log in `(from, to]` to find the entries and viewers it touched.
+///
+/// Queue entries are reported as their current rows, not as they were at `to`: an entry
+/// completed after `to` already shows as completed. Counter deltas only use the log and are
+/// exact for the range. Every command bumps the version by one, so a range with fewer log rows
+/// than versions means the TTL sweep already deleted part of it and the diff cannot be built.
+pub async fn build_touched_entries_diff(
+    database: &Database,
+    broadcaster_id: &str,
+    profile: &BroadcasterSettings,
+    from: u64,
+    to: u64,
+) -> Result<StateDiff, StateError> {
+    let commands = database
+        .command_log()
+        .list_range(broadcaster_id, from, to)
+        .await?;
+    if commands.len() as u64 != to - from {
+        return Err(StateError::HistoryUnavailable { from, to });
+    }
+
+    let queue_repo = database.queue();
+    let mut entries: BTreeMap<String, EntryChange> = BTreeMap::new();
+    let mut counters: BTreeMap<String, CounterDelta> = BTreeMap::new();
+
+    for command in commands {
+        let payload: Value = serde_json::from_str(&command.payload_json)
+            .map_err(|err| StateError::Unexpected(err.to_string()))?;
+        let text = |key: &str| payload.get(key).and_then(Value::as_str);
+        match command.command_type.as_str() {
+            "enqueue" => {
+                let Some(redemption_id) = text("redemption_id") else {
+                    continue;
+                };
+                if let Some(entry) = queue_repo
+                    .find_entry_by_redemption(broadcaster_id, redemption_id)
+                    .await?
+                {
+                    entries.insert(entry.id, EntryChange::Added);
+                }
+                if let Some(user_id) = payload["user"]["id"].as_str() {
+                    counter_delta(&mut counters, user_id).delta += 1;
+                }
+            }
+            "queue.add" => {
+                if let Some(entry_id) = text("op_id") {
+                    entries.insert(entry_id.to_string(), EntryChange::Added);
+                }
+                if let Some(user_id) = payload["user"]["id"].as_str() {
+                    counter_delta(&mut counters, user_id).delta += 1;
+                }
+            }
+            "queue.complete" => {
+                let Some(entry_id) = text("entry_id") else {
+                    continue;
+                };
+                entries
+                    .entry(entry_id.to_string())
+                    .or_insert(EntryChange::Updated);
+            }
+            "queue.remove" => {
+                let (Some(entry_id), Some(reason)) = (text("entry_id"), text("reason")) else {
+                    continue;
+                };
+                if matches!(entries.get(entry_id), Some(EntryChange::Added)) {
+                    entries.remove(entry_id);
+                } else {
+                    entries.insert(
+                        entry_id.to_string(),
+                        EntryChange::Removed(reason.to_string()),
+                    );
+                }
+                let decrements = match reason {
+                    "UNDO" => true,
+                    "EXPIRED" => profile.settings.expire_decrement_counts,
+                    _ => false,
+                };
+                if decrements {
+                    if let Some(entry) = queue_repo.find_entry(broadcaster_id, entry_id).await? {
+                        counter_delta(&mut counters, &entry.user_id).delta -= 1;
+                    }
+                }
+            }
+            "counter.correct" => {
+                let (Some(user_id), Some(count)) = (
+                    text("user_id"),
+                    payload.get("count").and_then(Value::as_u64),
+                ) else {
+                    continue;
+                };
+                let counter = counter_delta(&mut counters, user_id);
+                counter.delta = 0;
+                counter.corrected_to = Some(count as u32);
+            }
+            _ => {}
+        }
+    }
+
+    let mut queue = QueueDiff::default();
+    for (entry_id, change) in entries {
+        match change {
+            EntryChange::Removed(reason) => {
+                queue.removed.push(RemovedQueueEntry { entry_id, reason })
+            }
+            EntryChange::Added | EntryChange::Updated => {
+                let Some(entry) = queue_repo.find_entry(broadcaster_id, &entry_id).await? else {
+                    continue;
+                };
+                if matches!(change, EntryChange::Added) {
+                    queue.added.push(entry);
+                } else {
+                    queue.updated.push(entry);
+                }
+            }
+        }
+    }
+    Ok(StateDiff {
+        from,
+        to,
+        queue,
+        counters: counters
+            .into_values()
+            .filter(|counter| counter.delta != 0 || counter.corrected_to.is_some())
+            .collect(),
     })
 }
 
+fn counter_delta<'a>(
+    counters: &'a mut BTreeMap<String, CounterDelta>,
+    user_id: &str,
+) -> &'a mut CounterDelta {
+    counters
+        .entry(user_id.to_string())
+        .or_insert_with(|| CounterDelta {
+            user_id: user_id.to_string(),
+            ..CounterDelta::default()
+        })
+}
+
+/// Position in the active queue, only valid while the state stays at `version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueCursor {
+    pub version: u64,
+    pub offset: usize,
+}
+
+pub fn encode_queue_cursor(cursor: QueueCursor) -> String {
+    format!("{}-{}", cursor.version, cursor.offset)
+}
+
+pub fn decode_queue_cursor(cursor: &str) -> Option<QueueCursor> {
+    let (version, offset) = cursor.split_once('-')?;
+    Some(QueueCursor {
+        version: version.parse().ok()?,
+        offset: offset.parse().ok()?,
+    })
+}
+
+const VERSION_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Short-lived per-broadcaster cache of the latest committed `state_index` version.
+///
+/// The command executor records the new version after each commit, so cached values never lag
+/// behind a mutation that has already been acknowledged.
+#[derive(Clone)]
+pub struct VersionCache {
+    entries: Arc<RwLock<HashMap<String, (u64, Instant)>>>,
+    ttl: Duration,
+}
+
+impl Default for VersionCache {
+    fn default() -> Self {
+        Self::new(VERSION_CACHE_TTL)
+    }
+}
+
+impl VersionCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// Returns the current version, consulting `state_index` only when the cache is stale.
+    pub async fn current_version(
+        &self,
+        database: &Database,
+        broadcaster_id: &str,
+    ) -> Result<u64, StateIndexError> {
+        if let Some((version, cached_at)) = self.entries.read().await.get(broadcaster_id) {
+            if cached_at.elapsed() < self.ttl {
+                return Ok(*version);
+            }
+        }
+
+        let version = database
+            .state_index()
+            .fetch_current_version(broadcaster_id)
+            .await?;
+        Ok(self.record(broadcaster_id, version).await)
+    }
+
+    /// Records a committed version. Versions only move forward, so older values are ignored.
+    pub async fn record(&self, broadcaster_id: &str, version: u64) -> u64 {
+        let mut entries = self.entries.write().await;
+        let now = Instant::now();
+        let entry = entries
+            .entry(broadcaster_id.to_string())
+            .or_insert((version, now));
+        if version >= entry.0 {
+            *entry = (version, now);
+        }
+        entry.0
+    }
+}
+
+/// Per-broadcaster cache of the active queue, keyed by `(day, version)`.
+///
+/// Only the latest key is kept per broadcaster: any committed command bumps the version, so the
+/// next snapshot misses and replaces the entry.
+#[derive(Clone, Default)]
+pub struct ActiveQueueCache {
+    entries: Arc<RwLock<HashMap<String, ActiveQueueEntry>>>,
+    loads: Arc<AtomicU64>,
+}
+
+struct ActiveQueueEntry {
+    day: String,
+    version: u64,
+    queue: Arc<Vec<QueueEntry>>,
+}
+
+impl ActiveQueueCache {
+    /// Returns the active queue for `day` at `version`, querying storage only on a miss.
+    pub async fn load(
+        &self,
+        database: &Database,
+        broadcaster_id: &str,
+        day: &str,
+        version: u64,
+    ) -> Result<Arc<Vec<QueueEntry>>, StateError> {
+        if let Some(entry) = self.entries.read().await.get(broadcaster_id) {
+            if entry.version == version && entry.day == day {
+                return Ok(entry.queue.clone());
+            }
+        }
+
+        self.loads.fetch_add(1, Ordering::Relaxed);
+        let queue: Arc<Vec<QueueEntry>> = Arc::new(
+            database
+                .queue()
+                .list_active_with_counts(broadcaster_id, day)
+                .await?
+                .into_iter()
+                .map(|row| row.into_domain().0)
+                .collect(),
+        );
+
+        let mut entries = self.entries.write().await;
+        let replace = entries
+            .get(broadcaster_id)
+            .is_none_or(|entry| version >= entry.version);
+        if replace {
+            entries.insert(
+                broadcaster_id.to_string(),
+                ActiveQueueEntry {
+                    day: day.to_string(),
+                    version,
+                    queue: queue.clone(),
+                },
+            );
+        }
+        Ok(queue)
+    }
+
+    /// Number of storage queries issued so far.
+    #[cfg(test)]
+    pub fn loads(&self) -> u64 {
+        self.loads.load(Ordering::Relaxed)
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum StateError {
     #[error("failed to load state index: {0}")]
@@ -87,6 +546,16 @@ pub enum StateError {
     Queue(#[from] QueueError),
     #[error("failed to load counters: {0}")]
     Counter(#[from] DailyCounterError),
+    #[error("failed to load oauth link: {0}")]
+    OauthLink(#[from] OauthLinkError),
+    #[error("failed to load backfill checkpoint: {0}")]
+    Backfill(#[from] HelixBackfillError),
+    #[error("failed to read command log: {0}")]
+    CommandLog(#[from] CommandLogError),
+    #[error("command log between versions {from} and {to} is no longer retained")]
+    HistoryUnavailable { from: u64, to: u64 },
+    #[error("queue cursor was issued at version {cursor} but the state is at {current}")]
+    StaleCursor { cursor: u64, current: u64 },
     #[error("invalid timezone: {0}")]
     InvalidTimezone(String),
     #[error("unexpected error: {0}")]
@@ -7,9 +7,12 @@ use std::{
     sync::{Mutex, OnceLock},
     time::Instant,
 };
+#[cfg(any(unix, test))]
+use tracing_subscriber::Registry;
 use tracing_subscriber::{
     fmt::{self as tracing_fmt, time::UtcTime},
     layer::SubscriberExt,
+    reload,
     util::SubscriberInitExt,
     EnvFilter,
 };
@@ -20,6 +23,10 @@ use twi_overlay_util::{AppConfig, Environment};
 pub enum TelemetryError {
     Tracing(tracing_subscriber::util::TryInitError),
     Metrics(PrometheusBuildError),
+    #[cfg(any(unix, test))]
+    Reload(reload::Error),
+    #[cfg(any(unix, test))]
+    LogFilter(tracing_subscriber::filter::ParseError),
 }
 
 impl stdfmt::Display for TelemetryError {
@@ -27,6 +34,10 @@ impl stdfmt::Display for TelemetryError {
         match self {
             Self::Tracing(err) => write!(f, "failed to initialize tracing: {err}"),
             Self::Metrics(err) => write!(f, "failed to initialize prometheus recorder: {err}"),
+            #[cfg(any(unix, test))]
+            Self::Reload(err) => write!(f, "failed to reload log filter: {err}"),
+            #[cfg(any(unix, test))]
+            Self::LogFilter(err) => write!(f, "invalid RUST_LOG directives: {err}"),
         }
     }
 }
@@ -46,6 +57,8 @@ impl From<PrometheusBuildError> for TelemetryError {
 }
 
 static TRACING_INIT: OnceLock<()> = OnceLock::new();
+#[cfg(unix)]
+static LOG_FILTER: OnceLock<LogFilterHandle> = OnceLock::new();
 static METRICS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
 static METRICS_INSTALL_GUARD: OnceLock<Mutex<()>> = OnceLock::new();
 static START_TIME: OnceLock<Instant> = OnceLock::new();
@@ -56,12 +69,56 @@ fn build_git_sha() -> &'static str {
     option_env!("GIT_SHA").unwrap_or("unknown")
 }
 
+/// Swaps the active `EnvFilter` of the global subscriber at runtime.
+#[cfg(any(unix, test))]
+#[derive(Clone)]
+pub struct LogFilterHandle(reload::Handle<EnvFilter, Registry>);
+
+#[cfg(any(unix, test))]
+impl LogFilterHandle {
+    pub fn reload(&self, filter: EnvFilter) -> Result<(), TelemetryError> {
+        self.0.reload(filter).map_err(TelemetryError::Reload)
+    }
+
+    /// Returns the directives of the filter currently in effect.
+    pub fn current(&self) -> Option<String> {
+        self.0.with_current(|filter| filter.to_string()).ok()
+    }
+}
+
+fn env_filter_from_env() -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))
+}
+
+/// Re-applies `RUST_LOG` to the running subscriber.
+///
+/// `directives` replaces the process `RUST_LOG` when given (e.g. read from a reloaded `.env`).
+/// Invalid directives are rejected and leave the current filter in place. Returns the new
+/// directives, or `None` when tracing was never initialised.
+#[cfg(unix)]
+pub fn reload_log_filter(directives: Option<&str>) -> Result<Option<String>, TelemetryError> {
+    let filter = parse_log_filter(directives)?;
+    let Some(handle) = LOG_FILTER.get() else {
+        return Ok(None);
+    };
+    handle.reload(filter)?;
+    Ok(handle.current())
+}
+
+#[cfg(any(unix, test))]
+fn parse_log_filter(directives: Option<&str>) -> Result<EnvFilter, TelemetryError> {
+    match directives {
+        Some(directives) => EnvFilter::try_new(directives).map_err(TelemetryError::LogFilter),
+        None => Ok(env_filter_from_env()),
+    }
+}
+
 pub fn init_tracing(config: &AppConfig) -> Result<(), TelemetryError> {
     if TRACING_INIT.get().is_some() {
         return Ok(());
     }
 
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter_from_env());
 
     match config.environment {
         Environment::Development | Environment::Test => {
@@ -74,7 +131,7 @@ pub fn init_tracing(config: &AppConfig) -> Result<(), TelemetryError> {
                 .event_format(tracing_fmt::format().pretty());
 
             tracing_subscriber::registry()
-                .with(env_filter.clone())
+                .with(env_filter)
                 .with(fmt_layer)
                 .try_init()
                 .map_err(TelemetryError::Tracing)?;
@@ -96,6 +153,11 @@ pub fn init_tracing(config: &AppConfig) -> Result<(), TelemetryError> {
         }
     }
 
+    // Only the SIGHUP reload (unix) swaps the filter after startup.
+    #[cfg(unix)]
+    LOG_FILTER.set(LogFilterHandle(filter_handle)).ok();
+    #[cfg(not(unix))]
+    drop(filter_handle);
     TRACING_INIT.set(()).ok();
     tracing::info!(stage = "telemetry", env = %config.environment.as_str(), version = BUILD_VERSION, git_sha = build_git_sha(), "tracing initialized");
     Ok(())
@@ -116,12 +178,29 @@ pub fn init_metrics() -> Result<PrometheusHandle, TelemetryError> {
         return Ok(handle.clone());
     }
 
+    // The recorder is process-global: install it once and hand every later caller (tests
+    // call this from each setup) the same handle.
     let handle = PrometheusBuilder::new().install_recorder()?;
-    METRICS_HANDLE.set(handle.clone()).ok();
-    drop(guard);
 
     describe_gauge!("app_build_info", "Build metadata for the running binary");
     describe_gauge!("app_uptime_seconds", "Seconds since the process started");
+    describe_gauge!(
+        "sse_active_connections",
+        "Number of open SSE streams, labelled by audience"
+    );
+    describe_gauge!("sse_clients", "Deprecated alias of sse_active_connections");
+    describe_counter!(
+        "sse_connections_total",
+        "Count of SSE streams opened, labelled by audience"
+    );
+    describe_counter!(
+        "sse_ring_misses_total",
+        "Count of SSE subscriptions whose since_version fell outside the replay ring"
+    );
+    describe_counter!(
+        "sse_ring_miss_total",
+        "Deprecated alias of sse_ring_misses_total"
+    );
     describe_counter!(
         "eventsub_ingress_total",
         "Count of EventSub webhook requests processed, labelled by message type"
@@ -130,6 +209,14 @@ pub fn init_metrics() -> Result<PrometheusHandle, TelemetryError> {
         "eventsub_invalid_signature_total",
         "Count of EventSub webhook requests rejected due to invalid signatures"
     );
+    describe_counter!(
+        "eventsub_ignored_total",
+        "Count of EventSub messages acknowledged without persisting, labelled by subscription type"
+    );
+    describe_counter!(
+        "webhook_requests_total",
+        "Count of EventSub webhook requests, labelled by processing result"
+    );
     describe_histogram!(
         "webhook_ack_latency_seconds",
         "Latency in seconds to acknowledge EventSub webhook requests"
@@ -142,14 +229,54 @@ pub fn init_metrics() -> Result<PrometheusHandle, TelemetryError> {
         "api_state_requests_total",
         "Count of state API requests, labelled by result"
     );
+    describe_counter!(
+        "api_state_diff_requests_total",
+        "Count of state diff API requests, labelled by result"
+    );
+    describe_counter!(
+        "api_queue_page_requests_total",
+        "Count of paginated queue API requests, labelled by result"
+    );
+    describe_counter!(
+        "api_queue_add_requests_total",
+        "Count of manual queue add API requests, labelled by result"
+    );
+    describe_counter!(
+        "api_counter_correct_requests_total",
+        "Count of admin counter correction API requests, labelled by result"
+    );
+    describe_counter!(
+        "api_queue_served_today_requests_total",
+        "Count of served-today summary API requests, labelled by result"
+    );
+    describe_counter!(
+        "api_queue_export_requests_total",
+        "Count of queue CSV export API requests, labelled by result"
+    );
+    describe_counter!(
+        "api_settings_timezone_requests_total",
+        "Count of broadcaster timezone update API requests, labelled by result"
+    );
+    describe_counter!(
+        "api_oauth_validate_all_requests_total",
+        "Count of bulk OAuth validation API requests, labelled by result"
+    );
     describe_counter!(
         "db_ttl_deleted_total",
         "Count of rows deleted by TTL sweeps, labelled by table"
     );
+    describe_counter!(
+        "queue_expired_total",
+        "Count of QUEUED entries removed as EXPIRED by the maintenance worker"
+    );
     describe_histogram!(
         "db_checkpoint_seconds",
         "Duration of WAL checkpoint operations in seconds"
     );
+    describe_counter!(
+        "db_checkpoint_skipped_total",
+        "Maintenance cycles that skipped the WAL checkpoint because the WAL was below the threshold"
+    );
     describe_counter!(
         "db_busy_total",
         "Number of SQLite busy conditions encountered by maintenance tasks, labelled by operation"
@@ -158,6 +285,10 @@ pub fn init_metrics() -> Result<PrometheusHandle, TelemetryError> {
         "oauth_validate_failures_total",
         "Count of OAuth token validation attempts that resulted in failure"
     );
+    describe_counter!(
+        "oauth_scope_drift_total",
+        "Count of OAuth validations where Twitch reported scopes differing from the stored set"
+    );
     describe_counter!(
         "oauth_refresh_total",
         "Count of OAuth refresh attempts, labelled by result"
@@ -184,6 +315,11 @@ pub fn init_metrics() -> Result<PrometheusHandle, TelemetryError> {
     );
     START_TIME.get_or_init(Instant::now);
 
+    // Published only once descriptions are registered, so concurrent callers never observe a
+    // half-initialised recorder.
+    METRICS_HANDLE.set(handle.clone()).ok();
+    drop(guard);
+
     Ok(handle)
 }
 pub fn render_metrics(handle: &PrometheusHandle) -> String {
@@ -208,3 +344,53 @@ pub fn render_metrics(handle: &PrometheusHandle) -> String {
 
     body
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_filter_handle_reloads_directives() {
+        let (layer, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let handle = LogFilterHandle(handle);
+        let subscriber = Registry::default().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(!tracing::enabled!(tracing::Level::DEBUG));
+
+            handle.reload(EnvFilter::new("debug")).expect("reload");
+            tracing::callsite::rebuild_interest_cache();
+
+            assert_eq!(handle.current().as_deref(), Some("debug"));
+            assert!(tracing::enabled!(tracing::Level::DEBUG));
+        });
+    }
+
+    #[test]
+    fn invalid_log_filter_directives_are_rejected() {
+        assert!(matches!(
+            parse_log_filter(Some("twi_overlay=verbose")),
+            Err(TelemetryError::LogFilter(_))
+        ));
+        let filter = parse_log_filter(Some("twi_overlay=debug")).expect("valid directives");
+        assert_eq!(filter.to_string(), "twi_overlay=debug");
+    }
+
+    #[test]
+    fn init_metrics_is_idempotent_across_threads() {
+        let handles: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(init_metrics))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|thread| thread.join().expect("thread").expect("metrics"))
+            .collect();
+        let again = init_metrics().expect("second init");
+
+        metrics::counter!("api_state_requests_total", "result" => "ok").increment(1);
+        for handle in handles.iter().chain([&again]) {
+            let body = render_metrics(handle);
+            assert!(body.contains("api_state_requests_total"));
+            assert!(body.contains("app_build_info"));
+        }
+    }
+}
@@ -12,12 +12,12 @@ use metrics::{counter, histogram};
 use serde_json::{json, Value};
 use sha2::Sha256;
 use subtle::ConstantTimeEq;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use twi_overlay_core::normalizer::{Normalizer, NormalizerError};
-use twi_overlay_core::types::{Command, NormalizedEvent, Patch, Settings};
-use twi_overlay_storage::{
-    BroadcasterSettings, EventRawError, EventRawInsertOutcome, NewEventRaw, SettingsError,
-};
+use twi_overlay_core::policy::PolicyError;
+use twi_overlay_core::types::{Command, NormalizedEvent, Patch};
+use twi_overlay_storage::{BroadcasterSettings, EventRawError, EventRawInsertOutcome, NewEventRaw};
+use twi_overlay_util::EventSubTransport;
 use uuid::Uuid;
 
 use crate::problem::ProblemResponse;
@@ -29,37 +29,206 @@ const HEADER_TIMESTAMP: &str = "Twitch-Eventsub-Message-Timestamp";
 const HEADER_SIGNATURE: &str = "Twitch-Eventsub-Message-Signature";
 const HEADER_MESSAGE_TYPE: &str = "Twitch-Eventsub-Message-Type";
 
+/// Result of processing a single EventSub webhook request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookOutcome {
+    /// Verification handshake; the challenge is echoed back verbatim.
+    Challenge(String),
+    /// Notification accepted; `commands` counts the policy commands dispatched.
+    Notification {
+        persisted: bool,
+        duplicate: bool,
+        commands: usize,
+    },
+    /// Subscription revocation recorded.
+    Revocation,
+    Rejected(RejectReason),
+}
+
+impl WebhookOutcome {
+    /// Label recorded on `webhook_requests_total`.
+    pub fn metric_result(&self) -> &'static str {
+        match self {
+            Self::Challenge(_) => "challenge",
+            Self::Notification {
+                duplicate: true, ..
+            } => "duplicate",
+            Self::Notification { .. } => "notification",
+            Self::Revocation => "revocation",
+            Self::Rejected(RejectReason::BadSignature(_)) => "bad_signature",
+            Self::Rejected(RejectReason::StaleTimestamp) => "stale",
+            Self::Rejected(_) => "error",
+        }
+    }
+}
+
+/// Why a webhook request was not accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectReason {
+    MissingHeader(&'static str),
+    InvalidMessageType(String),
+    InvalidTimestamp(String),
+    StaleTimestamp,
+    BadSignature(String),
+    InvalidPayload,
+    InvalidJson(String),
+    MissingChallenge,
+    MissingSubscription,
+    MissingEventType,
+    MissingBroadcaster,
+    UnknownBroadcaster,
+    /// Batched body received while configured for the webhook transport.
+    UnexpectedBatch,
+    /// Conduit batch whose `events` field is not an array.
+    InvalidBatch,
+    Storage,
+}
+
+impl RejectReason {
+    fn into_problem(self) -> ProblemResponse {
+        match self {
+            Self::MissingHeader(name) => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "missing_header",
+                format!("missing header {name}"),
+            ),
+            Self::InvalidMessageType(detail) => {
+                ProblemResponse::new(StatusCode::BAD_REQUEST, "invalid_message_type", detail)
+            }
+            Self::InvalidTimestamp(detail) => {
+                ProblemResponse::new(StatusCode::BAD_REQUEST, "invalid_timestamp", detail)
+            }
+            Self::StaleTimestamp => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "timestamp_out_of_range",
+                "timestamp outside the allowed ±10 minute window",
+            ),
+            Self::BadSignature(detail) => {
+                ProblemResponse::new(StatusCode::FORBIDDEN, "invalid_signature", detail)
+            }
+            Self::InvalidPayload => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_payload",
+                "request body must be valid UTF-8",
+            ),
+            Self::InvalidJson(detail) => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_json",
+                format!("failed to parse payload: {detail}"),
+            ),
+            Self::MissingChallenge => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "missing_challenge",
+                "verification payload must include challenge",
+            ),
+            Self::MissingSubscription => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "missing_subscription",
+                "payload missing subscription block",
+            ),
+            Self::MissingEventType => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "missing_event_type",
+                "subscription.type is required",
+            ),
+            Self::MissingBroadcaster => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "missing_broadcaster",
+                "unable to resolve broadcaster id from payload",
+            ),
+            Self::UnknownBroadcaster => ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "missing_broadcaster",
+                "broadcaster is not provisioned for webhook ingress",
+            ),
+            Self::UnexpectedBatch => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "unexpected_batch",
+                "batched notifications require EVENTSUB_TRANSPORT=conduit",
+            ),
+            Self::InvalidBatch => ProblemResponse::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_batch",
+                "conduit batch events must be an array",
+            ),
+            Self::Storage => ProblemResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "storage_error",
+                "failed to persist webhook payload",
+            ),
+        }
+    }
+}
+
 pub async fn handle(
     State(state): State<AppState>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<Response, ProblemResponse> {
     let start = Instant::now();
-    let message_type_value = get_required_header(&headers, HEADER_MESSAGE_TYPE)?;
-    let message_type_header = message_type_value.to_string();
-    let message_type = match MessageType::try_from(message_type_header.as_str()) {
-        Ok(mt) => mt,
-        Err(detail) => {
-            histogram!("webhook_ack_latency_seconds", "type" => "unknown")
-                .record(start.elapsed().as_secs_f64());
-            return Err(ProblemResponse::new(
-                StatusCode::BAD_REQUEST,
-                "invalid_message_type",
-                detail,
-            ));
+    let message_label = headers
+        .get(HEADER_MESSAGE_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| MessageType::try_from(value).ok())
+        .map(MessageType::metric_label)
+        .unwrap_or("unknown");
+
+    let outcome = process(&state, &headers, &body, start).await;
+    counter!("webhook_requests_total", "result" => outcome.metric_result()).increment(1);
+    histogram!("webhook_ack_latency_seconds", "type" => message_label)
+        .record(start.elapsed().as_secs_f64());
+
+    match outcome {
+        WebhookOutcome::Challenge(challenge) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(axum::http::header::CONTENT_TYPE, "text/plain")
+            .body(challenge.into())
+            .unwrap()),
+        WebhookOutcome::Notification {
+            persisted,
+            duplicate,
+            commands,
+        } => {
+            debug!(
+                stage = "ingress",
+                persisted, duplicate, commands, "webhook notification processed"
+            );
+            Ok(no_content())
         }
-    };
+        WebhookOutcome::Revocation => Ok(no_content()),
+        WebhookOutcome::Rejected(reason) => Err(reason.into_problem()),
+    }
+}
+
+/// Verifies, persists, and dispatches a webhook request, reporting what happened.
+pub async fn process(
+    state: &AppState,
+    headers: &HeaderMap,
+    body: &[u8],
+    start: Instant,
+) -> WebhookOutcome {
+    match process_inner(state, headers, body, start).await {
+        Ok(outcome) => outcome,
+        Err(reason) => WebhookOutcome::Rejected(reason),
+    }
+}
+
+async fn process_inner(
+    state: &AppState,
+    headers: &HeaderMap,
+    body: &[u8],
+    start: Instant,
+) -> Result<WebhookOutcome, RejectReason> {
+    let message_type_header = get_required_header(headers, HEADER_MESSAGE_TYPE)?;
+    let message_type =
+        MessageType::try_from(message_type_header).map_err(RejectReason::InvalidMessageType)?;
     let message_label = message_type.metric_label();
 
-    let message_id = get_required_header(&headers, HEADER_MESSAGE_ID)?;
-    let timestamp_raw = get_required_header(&headers, HEADER_TIMESTAMP)?;
-    let signature = get_required_header(&headers, HEADER_SIGNATURE)?;
+    let message_id = get_required_header(headers, HEADER_MESSAGE_ID)?;
+    let timestamp_raw = get_required_header(headers, HEADER_TIMESTAMP)?;
+    let signature = get_required_header(headers, HEADER_SIGNATURE)?;
 
-    let timestamp = parse_timestamp(timestamp_raw).map_err(|err| {
-        histogram!("webhook_ack_latency_seconds", "type" => message_label)
-            .record(start.elapsed().as_secs_f64());
-        ProblemResponse::new(StatusCode::BAD_REQUEST, "invalid_timestamp", err)
-    })?;
+    let timestamp = parse_timestamp(timestamp_raw).map_err(RejectReason::InvalidTimestamp)?;
 
     let now = state.now();
     let skew = now.signed_duration_since(timestamp).num_seconds().abs();
@@ -72,46 +241,23 @@ pub async fn handle(
             skew_seconds = skew,
             "timestamp outside ±10 minute window"
         );
-        histogram!("webhook_ack_latency_seconds", "type" => message_label)
-            .record(start.elapsed().as_secs_f64());
-        return Err(ProblemResponse::new(
-            StatusCode::BAD_REQUEST,
-            "timestamp_out_of_range",
-            "timestamp outside the allowed ±10 minute window",
-        ));
+        return Err(RejectReason::StaleTimestamp);
     }
 
     let secret = state.webhook_secret();
-    verify_signature(&secret, message_id, timestamp_raw, &body, signature).map_err(|err| {
+    verify_signature(&secret, message_id, timestamp_raw, body, signature).map_err(|err| {
         counter!("eventsub_invalid_signature_total", "type" => message_label).increment(1);
-        histogram!("webhook_ack_latency_seconds", "type" => message_label)
-            .record(start.elapsed().as_secs_f64());
-        ProblemResponse::new(StatusCode::FORBIDDEN, "invalid_signature", err)
+        RejectReason::BadSignature(err)
     })?;
 
     counter!("eventsub_ingress_total", "type" => message_label).increment(1);
 
     let body_len = body.len() as u64;
-    let body_string = String::from_utf8(body.to_vec()).map_err(|_| {
-        histogram!("webhook_ack_latency_seconds", "type" => message_label)
-            .record(start.elapsed().as_secs_f64());
-        ProblemResponse::new(
-            StatusCode::BAD_REQUEST,
-            "invalid_payload",
-            "request body must be valid UTF-8",
-        )
-    })?;
-    let json_value: Value = serde_json::from_str(&body_string).map_err(|err| {
-        histogram!("webhook_ack_latency_seconds", "type" => message_label)
-            .record(start.elapsed().as_secs_f64());
-        ProblemResponse::new(
-            StatusCode::BAD_REQUEST,
-            "invalid_json",
-            format!("failed to parse payload: {err}"),
-        )
-    })?;
+    let body_string = String::from_utf8(body.to_vec()).map_err(|_| RejectReason::InvalidPayload)?;
+    let json_value: Value = serde_json::from_str(&body_string)
+        .map_err(|err| RejectReason::InvalidJson(err.to_string()))?;
 
-    let response = match message_type {
+    match message_type {
         MessageType::Verification => {
             let subscription = json_value.get("subscription");
             let event_type = subscription
@@ -126,18 +272,10 @@ pub async fn handle(
             let challenge = json_value
                 .get("challenge")
                 .and_then(Value::as_str)
-                .ok_or_else(|| {
-                    histogram!("webhook_ack_latency_seconds", "type" => message_label)
-                        .record(start.elapsed().as_secs_f64());
-                    ProblemResponse::new(
-                        StatusCode::BAD_REQUEST,
-                        "missing_challenge",
-                        "verification payload must include challenge",
-                    )
-                })?;
+                .ok_or(RejectReason::MissingChallenge)?;
 
             emit_tap(TapPublish {
-                state: &state,
+                state,
                 message_id,
                 broadcaster_id: broadcaster,
                 event_type,
@@ -149,42 +287,87 @@ pub async fn handle(
                 status: StatusCode::OK,
             });
 
-            let response = Response::builder()
-                .status(StatusCode::OK)
-                .header(axum::http::header::CONTENT_TYPE, "text/plain")
-                .body(challenge.to_string().into())
-                .unwrap();
-            histogram!("webhook_ack_latency_seconds", "type" => message_label)
-                .record(start.elapsed().as_secs_f64());
-            return Ok(response);
+            Ok(WebhookOutcome::Challenge(challenge.to_string()))
         }
         MessageType::Notification | MessageType::Revocation => {
-            match handle_persisted_message(
-                &state,
-                &json_value,
-                &body_string,
-                message_id,
-                timestamp,
-                message_label,
-                start,
-            )
-            .await
-            {
-                Ok(response) => {
-                    histogram!("webhook_ack_latency_seconds", "type" => message_label)
-                        .record(start.elapsed().as_secs_f64());
-                    response
+            let persisted = match (state.eventsub_transport(), json_value.get("events")) {
+                (EventSubTransport::Conduit, Some(events)) => {
+                    handle_conduit_batch(state, events, message_id, timestamp, message_label, start)
+                        .await?
                 }
-                Err(err) => {
-                    histogram!("webhook_ack_latency_seconds", "type" => message_label)
-                        .record(start.elapsed().as_secs_f64());
-                    return Err(err);
+                (EventSubTransport::Webhook, Some(_)) => {
+                    return Err(RejectReason::UnexpectedBatch);
+                }
+                (_, None) => {
+                    handle_persisted_message(
+                        state,
+                        &json_value,
+                        &body_string,
+                        message_id,
+                        timestamp,
+                        message_label,
+                        start,
+                    )
+                    .await?
                 }
+            };
+            if matches!(message_type, MessageType::Revocation) {
+                return Ok(WebhookOutcome::Revocation);
             }
+            Ok(WebhookOutcome::Notification {
+                persisted: persisted.persisted,
+                duplicate: persisted.duplicate,
+                commands: persisted.commands,
+            })
         }
+    }
+}
+
+/// Persists each message of a conduit batch, deduplicating by the per-message id.
+///
+/// Items without their own `message_id` fall back to `{header id}:{index}`.
+async fn handle_conduit_batch(
+    state: &AppState,
+    events: &Value,
+    message_id: &str,
+    timestamp: DateTime<Utc>,
+    message_label: &'static str,
+    start: Instant,
+) -> Result<PersistedMessage, RejectReason> {
+    let items = events.as_array().ok_or(RejectReason::InvalidBatch)?;
+    let mut aggregate = PersistedMessage {
+        persisted: false,
+        duplicate: true,
+        commands: 0,
     };
+    for (index, item) in items.iter().enumerate() {
+        let item_id = item
+            .get("message_id")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{message_id}:{index}"));
+        let persisted = handle_persisted_message(
+            state,
+            item,
+            &item.to_string(),
+            &item_id,
+            timestamp,
+            message_label,
+            start,
+        )
+        .await?;
+        aggregate.persisted |= persisted.persisted;
+        aggregate.duplicate &= persisted.duplicate;
+        aggregate.commands += persisted.commands;
+    }
+    Ok(aggregate)
+}
 
-    Ok(response)
+struct PersistedMessage {
+    /// `false` for duplicates and for types outside `EVENTSUB_PERSIST_TYPES`.
+    persisted: bool,
+    duplicate: bool,
+    commands: usize,
 }
 
 async fn handle_persisted_message(
@@ -195,24 +378,24 @@ async fn handle_persisted_message(
     timestamp: DateTime<Utc>,
     message_label: &'static str,
     start: Instant,
-) -> Result<Response, ProblemResponse> {
-    let subscription = json_value.get("subscription").ok_or_else(|| {
-        ProblemResponse::new(
-            StatusCode::BAD_REQUEST,
-            "missing_subscription",
-            "payload missing subscription block",
-        )
-    })?;
+) -> Result<PersistedMessage, RejectReason> {
+    let subscription = json_value
+        .get("subscription")
+        .ok_or(RejectReason::MissingSubscription)?;
     let event_type = subscription
         .get("type")
         .and_then(Value::as_str)
-        .ok_or_else(|| {
-            ProblemResponse::new(
-                StatusCode::BAD_REQUEST,
-                "missing_event_type",
-                "subscription.type is required",
-            )
-        })?;
+        .ok_or(RejectReason::MissingEventType)?;
+
+    if !state.persists_event_type(event_type) {
+        counter!("eventsub_ignored_total", "type" => event_type.to_string()).increment(1);
+        debug!(stage = "ingress", %message_id, event_type, "eventsub type not persisted");
+        return Ok(PersistedMessage {
+            persisted: false,
+            duplicate: false,
+            commands: 0,
+        });
+    }
 
     let broadcaster_id = subscription
         .get("condition")
@@ -225,13 +408,7 @@ async fn handle_persisted_message(
                 .and_then(|event| event.get("broadcaster_user_id"))
                 .and_then(Value::as_str)
         })
-        .ok_or_else(|| {
-            ProblemResponse::new(
-                StatusCode::BAD_REQUEST,
-                "missing_broadcaster",
-                "unable to resolve broadcaster id from payload",
-            )
-        })?;
+        .ok_or(RejectReason::MissingBroadcaster)?;
 
     let repo = state.storage().event_raw();
     let received_at = state.now();
@@ -249,19 +426,11 @@ async fn handle_persisted_message(
     let insert_outcome = repo.insert(record).await.map_err(|err| match err {
         EventRawError::MissingBroadcaster => {
             error!(stage = "ingress", %message_id, broadcaster_id, "broadcaster missing in database");
-            ProblemResponse::new(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "missing_broadcaster",
-                "broadcaster is not provisioned for webhook ingress",
-            )
+            RejectReason::UnknownBroadcaster
         }
         EventRawError::Database(db_err) => {
             error!(stage = "ingress", %message_id, error = %db_err, "failed to persist event raw");
-            ProblemResponse::new(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "storage_error",
-                "failed to persist webhook payload",
-            )
+            RejectReason::Storage
         }
     })?;
 
@@ -283,7 +452,9 @@ async fn handle_persisted_message(
         status: StatusCode::NO_CONTENT,
     });
 
-    if !duplicate {
+    let commands = if duplicate {
+        0
+    } else {
         process_pipeline(
             state,
             json_value,
@@ -292,15 +463,24 @@ async fn handle_persisted_message(
             broadcaster_id,
             message_id,
         )
-        .await;
-    }
+        .await
+    };
 
-    Ok(Response::builder()
+    Ok(PersistedMessage {
+        persisted: !duplicate,
+        duplicate,
+        commands,
+    })
+}
+
+fn no_content() -> Response {
+    Response::builder()
         .status(StatusCode::NO_CONTENT)
         .body(axum::body::Body::empty())
-        .unwrap())
+        .unwrap()
 }
 
+/// Runs normalizer, policy, and command dispatch; returns the number of commands emitted.
 async fn process_pipeline(
     state: &AppState,
     json_value: &Value,
@@ -308,7 +488,7 @@ async fn process_pipeline(
     event_type: &str,
     broadcaster_id: &str,
     message_id: &str,
-) {
+) -> usize {
     let normalized = match normalize_payload(
         state,
         json_value,
@@ -318,7 +498,7 @@ async fn process_pipeline(
         message_id,
     ) {
         Ok(event) => event,
-        Err(_) => return,
+        Err(_) => return 0,
     };
 
     let profile = match state
@@ -329,12 +509,18 @@ async fn process_pipeline(
     {
         Ok(settings) => settings,
         Err(err) => {
-            emit_policy_error(state, broadcaster_id, &normalized, err);
-            return;
+            emit_policy_error(state, broadcaster_id, &normalized, "settings_error", &err);
+            return 0;
         }
     };
 
-    let outcome = evaluate_policy(state, broadcaster_id, &normalized, &profile.settings);
+    let outcome = match evaluate_policy(state, broadcaster_id, &normalized, &profile) {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            emit_policy_error(state, broadcaster_id, &normalized, "policy_error", &err);
+            return 0;
+        }
+    };
 
     if !outcome.commands.is_empty() {
         dispatch_commands(
@@ -346,6 +532,7 @@ async fn process_pipeline(
         )
         .await;
     }
+    outcome.commands.len()
 }
 
 fn normalize_payload(
@@ -486,11 +673,14 @@ fn evaluate_policy(
     state: &AppState,
     broadcaster_id: &str,
     normalized: &NormalizedEvent,
-    settings: &Settings,
-) -> twi_overlay_core::policy::PolicyOutcome {
+    profile: &BroadcasterSettings,
+) -> Result<twi_overlay_core::policy::PolicyOutcome, PolicyError> {
     let issued_at = state.now();
     let start = Instant::now();
-    let outcome = state.policy().evaluate(settings, normalized, issued_at);
+    let outcome =
+        state
+            .policy()
+            .evaluate(&profile.settings, &profile.timezone, normalized, issued_at)?;
     let mut meta = StageMetadata {
         event_type: Some(normalized.event_type().to_string()),
         latency_ms: Some(start.elapsed().as_secs_f64() * 1000.0),
@@ -521,24 +711,29 @@ fn evaluate_policy(
         },
     };
     state.tap().publish(event);
+    state
+        .policy_trace()
+        .record(broadcaster_id, issued_at, normalized, &outcome);
 
     for command in &outcome.commands {
         counter!("policy_commands_total", "kind" => command.metric_kind()).increment(1);
     }
-    outcome
+    Ok(outcome)
 }
 
 fn emit_policy_error(
     state: &AppState,
     broadcaster_id: &str,
     normalized: &NormalizedEvent,
-    err: SettingsError,
+    message: &str,
+    err: &dyn std::fmt::Display,
 ) {
     error!(
         stage = "policy",
         broadcaster_id,
         error = %err,
-        "failed to load broadcaster settings"
+        message,
+        "failed to evaluate policy"
     );
 
     let event = StageEvent {
@@ -550,7 +745,7 @@ fn emit_policy_error(
         broadcaster_id: Some(broadcaster_id.to_string()),
         meta: StageMetadata {
             event_type: Some(normalized.event_type().to_string()),
-            message: Some("settings_error".to_string()),
+            message: Some(message.to_string()),
             ..StageMetadata::default()
         },
         r#in: StagePayload {
@@ -574,9 +769,11 @@ async fn dispatch_commands(
     commands: &[Command],
     normalized: &NormalizedEvent,
 ) {
-    match state
-        .command_executor()
-        .execute(broadcaster_id, &profile.timezone, commands)
+    let executor = state.command_executor();
+    // Held until the patches are broadcast so subscribers see them in version order.
+    let guard = executor.lock(broadcaster_id).await;
+    match executor
+        .execute_locked(&guard, &profile.timezone, commands)
         .await
     {
         Ok(patches) => {
@@ -720,17 +917,14 @@ fn emit_tap(ctx: TapPublish<'_>) {
 
     ctx.state.tap().publish(event);
 }
-fn get_required_header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, ProblemResponse> {
+fn get_required_header<'a>(
+    headers: &'a HeaderMap,
+    name: &'static str,
+) -> Result<&'a str, RejectReason> {
     headers
         .get(name)
         .and_then(|value| value.to_str().ok())
-        .ok_or_else(|| {
-            ProblemResponse::new(
-                StatusCode::BAD_REQUEST,
-                "missing_header",
-                format!("missing header {name}"),
-            )
-        })
+        .ok_or(RejectReason::MissingHeader(name))
 }
 
 fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
@@ -1097,6 +1291,61 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[tokio::test]
+    async fn unlisted_event_types_are_acknowledged_without_persisting() {
+        let ctx = setup_context().await;
+        let state = ctx.state.clone().with_eventsub_persist_types(&[
+            "channel.channel_points_custom_reward_redemption.add".to_string(),
+        ]);
+        let timestamp = ctx.now.to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let ignored = json!({
+            "subscription": {
+                "type": "channel.follow",
+                "version": "2",
+                "condition": {"broadcaster_user_id": BROADCASTER_ID}
+            },
+            "event": {"broadcaster_user_id": BROADCASTER_ID, "user_id": "user-9"}
+        })
+        .to_string();
+        let signature = sign(&ctx.secret, "msg-ignored", &timestamp, &ignored);
+        let headers_ignored = headers("notification", "msg-ignored", &timestamp, &signature);
+        assert_eq!(
+            process(&state, &headers_ignored, ignored.as_bytes(), Instant::now()).await,
+            WebhookOutcome::Notification {
+                persisted: false,
+                duplicate: false,
+                commands: 0,
+            }
+        );
+        let response = call_webhook(state.clone(), headers_ignored, ignored).await;
+        assert!(response.status().is_success());
+
+        let ignored_rows: i64 =
+            query_scalar("SELECT COUNT(*) FROM event_raw WHERE msg_id = 'msg-ignored'")
+                .fetch_one(ctx.database.pool())
+                .await
+                .expect("count");
+        assert_eq!(ignored_rows, 0);
+
+        let body = notification_body();
+        let signature = sign(&ctx.secret, "msg-kept", &timestamp, &body);
+        let response = call_webhook(
+            state,
+            headers("notification", "msg-kept", &timestamp, &signature),
+            body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let kept_rows: i64 =
+            query_scalar("SELECT COUNT(*) FROM event_raw WHERE msg_id = 'msg-kept'")
+                .fetch_one(ctx.database.pool())
+                .await
+                .expect("count");
+        assert_eq!(kept_rows, 1);
+    }
+
     #[tokio::test]
     async fn rejects_invalid_signature() {
         let ctx = setup_context().await;
@@ -1120,4 +1369,258 @@ mod tests {
         let response = call_webhook(ctx.state.clone(), headers, body).await;
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
+
+    fn signed_headers(
+        ctx: &TestContext,
+        message_type: &str,
+        message_id: &str,
+        body: &str,
+    ) -> HeaderMap {
+        let timestamp = ctx.now.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let signature = sign(&ctx.secret, message_id, &timestamp, body);
+        headers(message_type, message_id, &timestamp, &signature)
+    }
+
+    #[tokio::test]
+    async fn process_reports_challenge() {
+        let ctx = setup_context().await;
+        let body = json!({
+            "challenge": "abc",
+            "subscription": {
+                "type": "channel.channel_points_custom_reward_redemption.add",
+                "condition": {"broadcaster_user_id": BROADCASTER_ID}
+            }
+        })
+        .to_string();
+        let headers = signed_headers(&ctx, "webhook_callback_verification", "msg-c", &body);
+
+        let outcome = process(&ctx.state, &headers, body.as_bytes(), Instant::now()).await;
+        assert_eq!(outcome, WebhookOutcome::Challenge("abc".to_string()));
+    }
+
+    #[tokio::test]
+    async fn process_reports_notification_and_duplicate() {
+        let ctx = setup_context().await;
+        let body = notification_body();
+        let headers = signed_headers(&ctx, "notification", "msg-n", &body);
+
+        let outcome = process(&ctx.state, &headers, body.as_bytes(), Instant::now()).await;
+        assert_eq!(
+            outcome,
+            WebhookOutcome::Notification {
+                persisted: true,
+                duplicate: false,
+                commands: 2,
+            }
+        );
+
+        let outcome = process(&ctx.state, &headers, body.as_bytes(), Instant::now()).await;
+        assert_eq!(
+            outcome,
+            WebhookOutcome::Notification {
+                persisted: false,
+                duplicate: true,
+                commands: 0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn process_reports_revocation() {
+        let ctx = setup_context().await;
+        let body = json!({
+            "subscription": {
+                "type": "channel.channel_points_custom_reward_redemption.add",
+                "status": "authorization_revoked",
+                "condition": {"broadcaster_user_id": BROADCASTER_ID}
+            }
+        })
+        .to_string();
+        let headers = signed_headers(&ctx, "revocation", "msg-r", &body);
+
+        let outcome = process(&ctx.state, &headers, body.as_bytes(), Instant::now()).await;
+        assert_eq!(outcome, WebhookOutcome::Revocation);
+    }
+
+    #[tokio::test]
+    async fn process_rejects_bad_signature_and_stale_timestamp() {
+        let ctx = setup_context().await;
+        let body = notification_body();
+        let timestamp = ctx.now.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let bad = headers("notification", "msg-b", &timestamp, "sha256=deadbeef");
+
+        let outcome = process(&ctx.state, &bad, body.as_bytes(), Instant::now()).await;
+        assert!(matches!(
+            outcome,
+            WebhookOutcome::Rejected(RejectReason::BadSignature(_))
+        ));
+
+        let stale_at =
+            (ctx.now - Duration::minutes(11)).to_rfc3339_opts(SecondsFormat::Millis, true);
+        let signature = sign(&ctx.secret, "msg-s", &stale_at, &body);
+        let stale = headers("notification", "msg-s", &stale_at, &signature);
+
+        let outcome = process(&ctx.state, &stale, body.as_bytes(), Instant::now()).await;
+        assert_eq!(
+            outcome,
+            WebhookOutcome::Rejected(RejectReason::StaleTimestamp)
+        );
+    }
+
+    fn conduit_item(message_id: &str, redemption_id: &str, user_id: &str) -> Value {
+        let mut item: Value = serde_json::from_str(&notification_body()).expect("body");
+        item["message_id"] = json!(message_id);
+        item["event"]["id"] = json!(redemption_id);
+        item["event"]["user_id"] = json!(user_id);
+        item
+    }
+
+    #[tokio::test]
+    async fn webhook_transport_rejects_batched_body() {
+        let ctx = setup_context().await;
+        let body = json!({ "events": [conduit_item("m-w1", "red-w1", "user-w1")] }).to_string();
+        let headers = signed_headers(&ctx, "notification", "msg-batch-w", &body);
+
+        let outcome = process(&ctx.state, &headers, body.as_bytes(), Instant::now()).await;
+        assert_eq!(
+            outcome,
+            WebhookOutcome::Rejected(RejectReason::UnexpectedBatch)
+        );
+    }
+
+    #[tokio::test]
+    async fn conduit_transport_processes_batched_and_single_bodies() {
+        let ctx = setup_context().await;
+        let state = ctx
+            .state
+            .clone()
+            .with_eventsub_transport(EventSubTransport::Conduit);
+
+        let body = json!({
+            "events": [
+                conduit_item("m-c1", "red-c1", "user-c1"),
+                conduit_item("m-c2", "red-c2", "user-c2"),
+            ]
+        })
+        .to_string();
+        let headers = signed_headers(&ctx, "notification", "msg-batch-c", &body);
+        let outcome = process(&state, &headers, body.as_bytes(), Instant::now()).await;
+        assert_eq!(
+            outcome,
+            WebhookOutcome::Notification {
+                persisted: true,
+                duplicate: false,
+                commands: 4,
+            }
+        );
+
+        let stored: i64 =
+            query_scalar("SELECT COUNT(*) FROM event_raw WHERE msg_id IN ('m-c1', 'm-c2')")
+                .fetch_one(ctx.database.pool())
+                .await
+                .expect("count");
+        assert_eq!(stored, 2);
+
+        let replay = process(&state, &headers, body.as_bytes(), Instant::now()).await;
+        assert_eq!(
+            replay,
+            WebhookOutcome::Notification {
+                persisted: false,
+                duplicate: true,
+                commands: 0,
+            }
+        );
+
+        let single = notification_body();
+        let headers = signed_headers(&ctx, "notification", "msg-single-c", &single);
+        let outcome = process(&state, &headers, single.as_bytes(), Instant::now()).await;
+        assert!(matches!(
+            outcome,
+            WebhookOutcome::Notification {
+                persisted: true,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn policy_trace_lists_recent_evaluations_newest_first() {
+        let ctx = setup_context().await;
+        query(
+            "INSERT OR IGNORE INTO state_index (broadcaster_id, current_version, updated_at) VALUES (?, 0, ?)",
+        )
+        .bind(BROADCASTER_ID)
+        .bind(FIXED_NOW)
+        .execute(ctx.database.pool())
+        .await
+        .expect("insert state index");
+        for index in 1..=3 {
+            let mut body: Value = serde_json::from_str(&notification_body()).expect("body");
+            body["event"]["id"] = json!(format!("red-trace-{index}"));
+            body["event"]["user_id"] = json!(format!("user-trace-{index}"));
+            let body = body.to_string();
+            let message_id = format!("msg-trace-{index}");
+            let headers = signed_headers(&ctx, "notification", &message_id, &body);
+            process(&ctx.state, &headers, body.as_bytes(), Instant::now()).await;
+        }
+
+        let response = app_router(ctx.state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/_debug/policy/trace?broadcaster={BROADCASTER_ID}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).expect("json");
+        let redemptions: Vec<_> = json["entries"]
+            .as_array()
+            .expect("entries")
+            .iter()
+            .map(|entry| entry["event"]["redemption_id"].as_str().unwrap_or_default())
+            .collect();
+        assert_eq!(
+            redemptions,
+            vec!["red-trace-3", "red-trace-2", "red-trace-1"]
+        );
+        assert_eq!(json["entries"][0]["outcome"]["action"], json!("applied"));
+    }
+
+    fn webhook_requests(state: &AppState, result: &str) -> u64 {
+        let rendered = telemetry::render_metrics(state.metrics());
+        let prefix = format!("webhook_requests_total{{result=\"{result}\"}} ");
+        rendered
+            .lines()
+            .find_map(|line| line.strip_prefix(prefix.as_str()))
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0)
+    }
+
+    #[tokio::test]
+    async fn webhook_requests_counter_tracks_results() {
+        let ctx = setup_context().await;
+        let bad_before = webhook_requests(&ctx.state, "bad_signature");
+        let ok_before = webhook_requests(&ctx.state, "notification");
+
+        let body = notification_body();
+        let timestamp = ctx.now.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let bad = headers(
+            "notification",
+            "msg-metric-bad",
+            &timestamp,
+            "sha256=deadbeef",
+        );
+        let response = call_webhook(ctx.state.clone(), bad, body.clone()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let good = signed_headers(&ctx, "notification", "msg-metric-ok", &body);
+        let response = call_webhook(ctx.state.clone(), good, body).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        assert!(webhook_requests(&ctx.state, "bad_signature") > bad_before);
+        assert!(webhook_requests(&ctx.state, "notification") > ok_before);
+    }
 }
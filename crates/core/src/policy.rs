@@ -1,13 +1,22 @@
 use std::{collections::HashMap, sync::Mutex};
 
 use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
 use serde_json::{json, Value};
+use thiserror::Error;
 
 use crate::types::{
-    Command, CommandResult, CommandSource, EnqueueCommand, NormalizedEvent, NormalizedReward,
-    NormalizedUser, RedemptionUpdateCommand, RedemptionUpdateMode, Settings,
+    Command, CommandResult, CommandSource, EnqueueCommand, FulfillOn, NormalizedEvent,
+    NormalizedReward, NormalizedUser, RedemptionUpdateCommand, RedemptionUpdateMode, Settings,
 };
 
+/// Errors raised while evaluating a policy.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PolicyError {
+    #[error("invalid timezone: {0}")]
+    InvalidTimezone(String),
+}
+
 /// Policy engine that evaluates normalized events and produces commands.
 #[derive(Debug, Default)]
 pub struct PolicyEngine {
@@ -21,12 +30,16 @@ impl PolicyEngine {
     }
 
     /// Evaluates a normalized event with the provided settings and returns the resulting commands.
+    ///
+    /// `timezone` is the broadcaster's IANA timezone, used for local-time rules such as quiet
+    /// hours; an unknown name is reported as [`PolicyError::InvalidTimezone`] when a rule needs it.
     pub fn evaluate(
         &self,
         settings: &Settings,
+        timezone: &str,
         event: &NormalizedEvent,
         issued_at: DateTime<Utc>,
-    ) -> PolicyOutcome {
+    ) -> Result<PolicyOutcome, PolicyError> {
         match event {
             NormalizedEvent::RedemptionAdd {
                 broadcaster_id,
@@ -42,18 +55,19 @@ impl PolicyEngine {
                     reward,
                     occurred_at: *occurred_at,
                 };
-                self.evaluate_redemption_add(settings, context, issued_at)
+                self.evaluate_redemption_add(settings, timezone, context, issued_at)
             }
-            _ => PolicyOutcome::ignored("event_not_supported"),
+            _ => Ok(PolicyOutcome::ignored("event_not_supported")),
         }
     }
 
     fn evaluate_redemption_add(
         &self,
         settings: &Settings,
+        timezone: &str,
         context: RedemptionAddContext<'_>,
         issued_at: DateTime<Utc>,
-    ) -> PolicyOutcome {
+    ) -> Result<PolicyOutcome, PolicyError> {
         let RedemptionAddContext {
             broadcaster_id,
             redemption_id,
@@ -62,12 +76,21 @@ impl PolicyEngine {
             occurred_at,
         } = context;
         let policy = settings.policy();
-        if policy.target_rewards.is_empty() {
-            return PolicyOutcome::ignored("policy_disabled");
+        if !policy.has_reward_targets() {
+            return Ok(PolicyOutcome::ignored("policy_disabled"));
         }
 
-        if !policy.is_reward_enabled(&reward.id) {
-            return PolicyOutcome::ignored("reward_not_targeted");
+        if !policy.is_reward_enabled(&reward.id, reward.title.as_deref()) {
+            return Ok(PolicyOutcome::ignored("reward_not_targeted"));
+        }
+
+        if let Some(quiet_hours) = policy.quiet_hours {
+            let tz: Tz = timezone
+                .parse()
+                .map_err(|_| PolicyError::InvalidTimezone(timezone.to_string()))?;
+            if quiet_hours.contains(occurred_at.with_timezone(&tz).time()) {
+                return Ok(PolicyOutcome::ignored("policy:quiet_hours"));
+            }
         }
 
         let key = DuplicateKey {
@@ -76,12 +99,23 @@ impl PolicyEngine {
             reward_id: reward.id.clone(),
         };
 
+        // The window is measured between redemption occurrence times (`redeemed_at`), never
+        // ingestion time, so webhook and backfill deliveries of the same stream compare alike.
+        // Backfill may deliver older redemptions after newer ones, hence the absolute distance
+        // and keeping the latest occurrence as the reference.
+        let window = Duration::seconds(policy.anti_spam_window_sec as i64);
         let mut duplicates = self.duplicate_window.lock().expect("duplicate guard");
-        let duplicate = duplicates
-            .get(&key)
-            .map(|last| occurred_at - *last < Duration::seconds(policy.anti_spam_window_sec as i64))
-            .unwrap_or(false);
-        duplicates.insert(key, occurred_at);
+        let duplicate = match duplicates.get_mut(&key) {
+            Some(last) => {
+                let within = (occurred_at - *last).abs() < window;
+                *last = (*last).max(occurred_at);
+                within
+            }
+            None => {
+                duplicates.insert(key, occurred_at);
+                false
+            }
+        };
         drop(duplicates);
 
         if duplicate {
@@ -99,7 +133,7 @@ impl PolicyEngine {
                 managed: None,
                 error: None,
             });
-            PolicyOutcome::duplicate(vec![update])
+            Ok(PolicyOutcome::duplicate(vec![update]))
         } else {
             let enqueue = Command::Enqueue(EnqueueCommand {
                 broadcaster_id: broadcaster_id.to_string(),
@@ -110,6 +144,10 @@ impl PolicyEngine {
                 redemption_id: redemption_id.to_string(),
                 managed: None,
             });
+            if policy.fulfill_on == FulfillOn::Complete {
+                // The executor fulfills the redemption when the entry is completed.
+                return Ok(PolicyOutcome::applied(vec![enqueue]));
+            }
             let update = Command::RedemptionUpdate(RedemptionUpdateCommand {
                 broadcaster_id: broadcaster_id.to_string(),
                 issued_at,
@@ -121,7 +159,7 @@ impl PolicyEngine {
                 managed: None,
                 error: None,
             });
-            PolicyOutcome::applied(vec![enqueue, update])
+            Ok(PolicyOutcome::applied(vec![enqueue, update]))
         }
     }
 }
@@ -210,7 +248,7 @@ impl PolicyAction {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{DuplicatePolicy, PolicySettings};
+    use crate::types::{DuplicatePolicy, PolicySettings, QuietHours};
 
     fn settings(target_reward: &str, duplicate_policy: DuplicatePolicy) -> Settings {
         Settings {
@@ -218,10 +256,18 @@ mod tests {
             group_size: 1,
             clear_on_stream_start: false,
             clear_decrement_counts: false,
+            queue_entry_ttl_secs: None,
+            expire_decrement_counts: false,
+            day_rollover_hour: 0,
             policy: PolicySettings {
                 anti_spam_window_sec: 60,
                 duplicate_policy,
                 target_rewards: vec![target_reward.to_string()],
+                target_reward_titles: Vec::new(),
+                allow_manual_add: true,
+                fulfill_on: FulfillOn::Enqueue,
+                quiet_hours: None,
+                verify_reward_ownership: false,
             },
         }
     }
@@ -254,11 +300,14 @@ mod tests {
             reward.id = "other".to_string();
         }
 
-        let outcome = engine.evaluate(
-            &settings("reward-1", DuplicatePolicy::Consume),
-            &event,
-            Utc::now(),
-        );
+        let outcome = engine
+            .evaluate(
+                &settings("reward-1", DuplicatePolicy::Consume),
+                "UTC",
+                &event,
+                Utc::now(),
+            )
+            .expect("evaluate");
 
         assert!(outcome.commands.is_empty());
         assert_eq!(outcome.action, PolicyAction::Ignored);
@@ -269,16 +318,65 @@ mod tests {
         let engine = PolicyEngine::new();
         let event = redemption_event();
         let issued_at = event.occurred_at();
-        let outcome = engine.evaluate(
-            &settings("reward-1", DuplicatePolicy::Consume),
-            &event,
-            issued_at,
-        );
+        let outcome = engine
+            .evaluate(
+                &settings("reward-1", DuplicatePolicy::Consume),
+                "UTC",
+                &event,
+                issued_at,
+            )
+            .expect("evaluate");
 
         assert_eq!(outcome.commands.len(), 2);
         assert_eq!(outcome.action, PolicyAction::Applied);
     }
 
+    #[test]
+    fn backfilled_old_redemption_is_not_deduped_against_recent_event() {
+        let engine = PolicyEngine::new();
+        let settings = settings("reward-1", DuplicatePolicy::Consume);
+        let recent = redemption_event();
+        let now = recent.occurred_at();
+
+        let outcome = engine
+            .evaluate(&settings, "UTC", &recent, now)
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Applied);
+
+        // Backfill delivers an older redemption after the webhook one; it is evaluated
+        // against its own `redeemed_at`, ten minutes earlier, not the ingestion time.
+        let mut backfilled = redemption_event();
+        if let NormalizedEvent::RedemptionAdd {
+            occurred_at,
+            redemption_id,
+            ..
+        } = &mut backfilled
+        {
+            *occurred_at -= Duration::minutes(10);
+            *redemption_id = "r-old".to_string();
+        }
+        let outcome = engine
+            .evaluate(&settings, "UTC", &backfilled, now)
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Applied);
+
+        // The recent occurrence stays the reference for later events.
+        let mut follow_up = redemption_event();
+        if let NormalizedEvent::RedemptionAdd {
+            occurred_at,
+            redemption_id,
+            ..
+        } = &mut follow_up
+        {
+            *occurred_at += Duration::seconds(30);
+            *redemption_id = "r-2".to_string();
+        }
+        let outcome = engine
+            .evaluate(&settings, "UTC", &follow_up, now)
+            .expect("evaluate");
+        assert!(outcome.is_duplicate());
+    }
+
     #[test]
     fn duplicate_within_window_uses_policy_mode() {
         let engine = PolicyEngine::new();
@@ -286,21 +384,27 @@ mod tests {
         let issued_at = event.occurred_at();
 
         // First event primes the cache
-        let _ = engine.evaluate(
-            &settings("reward-1", DuplicatePolicy::Refund),
-            &event,
-            issued_at,
-        );
+        let _ = engine
+            .evaluate(
+                &settings("reward-1", DuplicatePolicy::Refund),
+                "UTC",
+                &event,
+                issued_at,
+            )
+            .expect("evaluate");
 
         if let NormalizedEvent::RedemptionAdd { occurred_at, .. } = &mut event {
             *occurred_at += Duration::seconds(30);
         }
 
-        let outcome = engine.evaluate(
-            &settings("reward-1", DuplicatePolicy::Refund),
-            &event,
-            issued_at,
-        );
+        let outcome = engine
+            .evaluate(
+                &settings("reward-1", DuplicatePolicy::Refund),
+                "UTC",
+                &event,
+                issued_at,
+            )
+            .expect("evaluate");
 
         assert_eq!(outcome.commands.len(), 1);
         assert!(outcome.is_duplicate());
@@ -314,14 +418,251 @@ mod tests {
         let issued_at = event.occurred_at();
 
         let settings = settings("reward-1", DuplicatePolicy::Consume);
-        let _ = engine.evaluate(&settings, &event, issued_at);
+        let _ = engine
+            .evaluate(&settings, "UTC", &event, issued_at)
+            .expect("evaluate");
 
         if let NormalizedEvent::RedemptionAdd { occurred_at, .. } = &mut event {
             *occurred_at += Duration::seconds(61);
         }
 
-        let outcome = engine.evaluate(&settings, &event, issued_at);
+        let outcome = engine
+            .evaluate(&settings, "UTC", &event, issued_at)
+            .expect("evaluate");
         assert_eq!(outcome.action, PolicyAction::Applied);
         assert_eq!(outcome.commands.len(), 2);
     }
+
+    fn event_with_reward(id: &str, title: &str) -> NormalizedEvent {
+        let mut event = redemption_event();
+        if let NormalizedEvent::RedemptionAdd { reward, .. } = &mut event {
+            reward.id = id.to_string();
+            reward.title = Some(title.to_string());
+        }
+        event
+    }
+
+    #[test]
+    fn matches_reward_by_id_only() {
+        let settings = settings("reward-1", DuplicatePolicy::Consume);
+
+        let outcome = PolicyEngine::new()
+            .evaluate(
+                &settings,
+                "UTC",
+                &event_with_reward("reward-1", "Anything"),
+                Utc::now(),
+            )
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Applied);
+
+        let outcome = PolicyEngine::new()
+            .evaluate(
+                &settings,
+                "UTC",
+                &event_with_reward("reward-2", "Join"),
+                Utc::now(),
+            )
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Ignored);
+    }
+
+    #[test]
+    fn matches_reward_by_title_case_insensitively() {
+        let mut settings = settings("reward-1", DuplicatePolicy::Consume);
+        settings.policy.target_rewards.clear();
+        settings.policy.target_reward_titles = vec!["Join Queue".to_string()];
+
+        let outcome = PolicyEngine::new()
+            .evaluate(
+                &settings,
+                "UTC",
+                &event_with_reward("recreated-id", "join queue"),
+                Utc::now(),
+            )
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Applied);
+
+        let outcome = PolicyEngine::new()
+            .evaluate(
+                &settings,
+                "UTC",
+                &event_with_reward("recreated-id", "Other Reward"),
+                Utc::now(),
+            )
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Ignored);
+    }
+
+    #[test]
+    fn matches_non_ascii_reward_title_case_insensitively() {
+        let mut settings = settings("reward-1", DuplicatePolicy::Consume);
+        settings.policy.target_rewards.clear();
+        settings.policy.target_reward_titles = vec![
+            "ＶＩＰ参加".to_string(),
+            "Café".to_string(),
+            "Straße".to_string(),
+        ];
+
+        for title in [
+            "ｖｉｐ参加",
+            "ＶＩＰ参加",
+            "CAFÉ",
+            "  café\u{3000}",
+            "STRAßE",
+        ] {
+            let outcome = PolicyEngine::new()
+                .evaluate(
+                    &settings,
+                    "UTC",
+                    &event_with_reward("recreated-id", title),
+                    Utc::now(),
+                )
+                .expect("evaluate");
+            assert_eq!(outcome.action, PolicyAction::Applied, "{title}");
+        }
+
+        let outcome = PolicyEngine::new()
+            .evaluate(
+                &settings,
+                "UTC",
+                &event_with_reward("recreated-id", "一般参加"),
+                Utc::now(),
+            )
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Ignored);
+
+        // Lowercasing does not fold `SS` into `ß`.
+        let outcome = PolicyEngine::new()
+            .evaluate(
+                &settings,
+                "UTC",
+                &event_with_reward("recreated-id", "STRASSE"),
+                Utc::now(),
+            )
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Ignored);
+    }
+
+    #[test]
+    fn matches_reward_by_id_or_title_when_combined() {
+        let mut settings = settings("reward-1", DuplicatePolicy::Consume);
+        settings.policy.target_reward_titles = vec!["Join Queue".to_string()];
+
+        for (id, title) in [("reward-1", "Renamed"), ("reward-9", "JOIN QUEUE")] {
+            let outcome = PolicyEngine::new()
+                .evaluate(&settings, "UTC", &event_with_reward(id, title), Utc::now())
+                .expect("evaluate");
+            assert_eq!(outcome.action, PolicyAction::Applied, "{id} / {title}");
+        }
+
+        let outcome = PolicyEngine::new()
+            .evaluate(
+                &settings,
+                "UTC",
+                &event_with_reward("reward-9", "Other"),
+                Utc::now(),
+            )
+            .expect("evaluate");
+        assert_eq!(outcome.action, PolicyAction::Ignored);
+    }
+
+    fn quiet_settings(start: &str, end: &str) -> Settings {
+        let mut settings = settings("reward-1", DuplicatePolicy::Consume);
+        settings.policy.quiet_hours = Some(
+            serde_json::from_value(json!({ "start": start, "end": end })).expect("quiet hours"),
+        );
+        settings
+    }
+
+    fn event_at(timestamp: &str) -> NormalizedEvent {
+        let mut event = redemption_event();
+        if let NormalizedEvent::RedemptionAdd { occurred_at, .. } = &mut event {
+            *occurred_at = DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .with_timezone(&Utc);
+        }
+        event
+    }
+
+    #[test]
+    fn quiet_hours_skip_enqueue_inside_window() {
+        // 09:30 in Asia/Tokyo.
+        let event = event_at("2024-01-01T00:30:00Z");
+        let outcome = PolicyEngine::new()
+            .evaluate(
+                &quiet_settings("09:00", "10:00"),
+                "Asia/Tokyo",
+                &event,
+                Utc::now(),
+            )
+            .expect("evaluate");
+
+        assert_eq!(outcome.action, PolicyAction::Ignored);
+        assert_eq!(outcome.reason.as_deref(), Some("policy:quiet_hours"));
+        assert!(outcome.commands.is_empty());
+    }
+
+    #[test]
+    fn quiet_hours_allow_enqueue_outside_window() {
+        let event = event_at("2024-01-01T10:00:00Z");
+        let outcome = PolicyEngine::new()
+            .evaluate(&quiet_settings("09:00", "10:00"), "UTC", &event, Utc::now())
+            .expect("evaluate");
+
+        assert_eq!(outcome.action, PolicyAction::Applied);
+        assert_eq!(outcome.commands.len(), 2);
+    }
+
+    #[test]
+    fn quiet_hours_window_spans_midnight() {
+        let settings = quiet_settings("22:00", "06:00");
+        for (timestamp, expected) in [
+            ("2024-01-01T23:30:00Z", PolicyAction::Ignored),
+            ("2024-01-02T05:59:59Z", PolicyAction::Ignored),
+            ("2024-01-02T06:00:00Z", PolicyAction::Applied),
+            ("2024-01-02T21:59:59Z", PolicyAction::Applied),
+        ] {
+            let outcome = PolicyEngine::new()
+                .evaluate(&settings, "UTC", &event_at(timestamp), Utc::now())
+                .expect("evaluate");
+            assert_eq!(outcome.action, expected, "{timestamp}");
+        }
+
+        let empty = QuietHours {
+            start: chrono::NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            end: chrono::NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+        };
+        assert!(!empty.contains(chrono::NaiveTime::from_hms_opt(6, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn quiet_hours_reject_unknown_timezone() {
+        let err = PolicyEngine::new()
+            .evaluate(
+                &quiet_settings("09:00", "10:00"),
+                "Mars/Olympus_Mons",
+                &event_at("2024-01-01T09:30:00Z"),
+                Utc::now(),
+            )
+            .expect_err("unknown timezone");
+        assert_eq!(
+            err,
+            PolicyError::InvalidTimezone("Mars/Olympus_Mons".to_string())
+        );
+    }
+
+    #[test]
+    fn fulfill_on_complete_defers_redemption_update() {
+        let mut settings = settings("reward-1", DuplicatePolicy::Consume);
+        settings.policy.fulfill_on = FulfillOn::Complete;
+
+        let outcome = PolicyEngine::new()
+            .evaluate(&settings, "UTC", &redemption_event(), Utc::now())
+            .expect("evaluate");
+
+        assert_eq!(outcome.action, PolicyAction::Applied);
+        assert_eq!(outcome.commands.len(), 1);
+        assert!(matches!(outcome.commands[0], Command::Enqueue(_)));
+    }
 }
@@ -66,6 +66,29 @@ impl Projector {
         }
     }
 
+    /// Coalesces `patches` into a single `patch.batch` carrying them in order.
+    ///
+    /// The batch takes the last patch's version, so clients advance to the batch end at once.
+    pub fn batch(at: DateTime<Utc>, patches: Vec<Patch>) -> Patch {
+        let version = patches
+            .last()
+            .map(|patch| patch.version)
+            .unwrap_or_default();
+        let from_version = patches
+            .first()
+            .map(|patch| patch.version)
+            .unwrap_or(version);
+        Patch {
+            version,
+            kind: PatchKind::Batch,
+            at,
+            data: json!({
+                "from_version": from_version,
+                "patches": patches,
+            }),
+        }
+    }
+
     /// Builds a `queue.completed` patch for the provided entry identifier.
     pub fn queue_completed(version: u64, at: DateTime<Utc>, entry_id: &str) -> Patch {
         Patch {
@@ -97,7 +120,15 @@ impl Projector {
     }
 
     /// Builds a `counter.updated` patch for the provided user.
-    pub fn counter_updated(version: u64, at: DateTime<Utc>, user_id: &str, count: u32) -> Patch {
+    ///
+    /// `updated_at` is the counter row's own timestamp, which may differ from the command time.
+    pub fn counter_updated(
+        version: u64,
+        at: DateTime<Utc>,
+        user_id: &str,
+        count: u32,
+        updated_at: DateTime<Utc>,
+    ) -> Patch {
         Patch {
             version,
             kind: PatchKind::CounterUpdated,
@@ -105,6 +136,7 @@ impl Projector {
             data: json!({
                 "user_id": user_id,
                 "count": count,
+                "updated_at": updated_at,
             }),
         }
     }
@@ -124,7 +156,7 @@ impl Projector {
 mod tests {
     use super::*;
     use crate::types::{
-        CommandResult, QueueEntryStatus, QueueRemovalReason, Settings, UserCounter,
+        CommandResult, EnqueuedSource, QueueEntryStatus, QueueRemovalReason, Settings, UserCounter,
     };
 
     fn sample_entry() -> QueueEntry {
@@ -136,12 +168,14 @@ mod tests {
             user_display_name: "Alice".to_string(),
             user_avatar: None,
             reward_id: "r-join".to_string(),
+            reward_title: None,
             redemption_id: Some("red-1".to_string()),
             enqueued_at: Utc::now(),
             status: QueueEntryStatus::Queued,
             status_reason: None,
             managed: true,
             last_updated_at: Utc::now(),
+            enqueued_source: EnqueuedSource::Policy,
         }
     }
 
@@ -189,6 +223,10 @@ mod tests {
                 count: 2,
             }],
             settings,
+            truncated: false,
+            next: None,
+            health: None,
+            recent_completed: None,
         };
         let patch = Projector::state_replace(12, at, snapshot.clone());
         assert_eq!(patch.kind_str(), "state.replace");
@@ -224,10 +262,12 @@ mod tests {
     #[test]
     fn counter_updated_embeds_user_and_count() {
         let at = Utc::now();
-        let patch = Projector::counter_updated(9, at, "user-1", 10);
+        let updated_at = at + chrono::Duration::seconds(1);
+        let patch = Projector::counter_updated(9, at, "user-1", 10, updated_at);
         assert_eq!(patch.kind_str(), "counter.updated");
         assert_eq!(patch.data["user_id"].as_str(), Some("user-1"));
         assert_eq!(patch.data["count"].as_u64(), Some(10));
+        assert_eq!(patch.data["updated_at"], json!(updated_at));
     }
 
     #[test]
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, Utc};
 use serde::de::Error as _;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, Value};
@@ -17,6 +17,15 @@ pub struct Settings {
     pub clear_on_stream_start: bool,
     #[serde(default)]
     pub clear_decrement_counts: bool,
+    /// Seconds a `QUEUED` entry may wait before maintenance removes it as `EXPIRED`.
+    #[serde(default)]
+    pub queue_entry_ttl_secs: Option<u64>,
+    /// Whether expiring an entry also decrements the viewer's daily counter.
+    #[serde(default)]
+    pub expire_decrement_counts: bool,
+    /// Local hour (0-23) at which the counter day rolls over; 0 means midnight.
+    #[serde(default)]
+    pub day_rollover_hour: u8,
     #[serde(default)]
     pub policy: PolicySettings,
 }
@@ -32,6 +41,9 @@ pub struct QueueEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_avatar: Option<String>,
     pub reward_id: String,
+    /// Reward title captured at enqueue time; absent for manual entries and older rows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward_title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub redemption_id: Option<String>,
     pub enqueued_at: DateTime<Utc>,
@@ -40,6 +52,53 @@ pub struct QueueEntry {
     pub status_reason: Option<String>,
     pub managed: bool,
     pub last_updated_at: DateTime<Utc>,
+    /// How the entry entered the queue.
+    #[serde(default)]
+    pub enqueued_source: EnqueuedSource,
+}
+
+/// Origin of a queue entry, derived from the command that created it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnqueuedSource {
+    /// Enqueued by the policy stage from a live EventSub notification.
+    #[default]
+    Policy,
+    /// Enqueued by the policy stage while replaying Helix backfill results.
+    Backfill,
+    /// Added from the admin interface.
+    Manual,
+}
+
+impl EnqueuedSource {
+    /// Returns the canonical database representation for the source.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Policy => "policy",
+            Self::Backfill => "backfill",
+            Self::Manual => "manual",
+        }
+    }
+
+    /// Parses the database representation, returning `None` for unknown values.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "policy" => Some(Self::Policy),
+            "backfill" => Some(Self::Backfill),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+}
+
+impl From<CommandSource> for EnqueuedSource {
+    fn from(source: CommandSource) -> Self {
+        match source {
+            CommandSource::Policy => Self::Policy,
+            CommandSource::Backfill => Self::Backfill,
+            CommandSource::Admin => Self::Manual,
+        }
+    }
 }
 
 /// Queue entry status persisted in the database.
@@ -79,6 +138,38 @@ pub struct PolicySettings {
     pub duplicate_policy: DuplicatePolicy,
     #[serde(default)]
     pub target_rewards: Vec<String>,
+    /// Reward titles matched case-insensitively, for rewards that get recreated with new ids.
+    #[serde(default)]
+    pub target_reward_titles: Vec<String>,
+    #[serde(default = "PolicySettings::default_allow_manual_add")]
+    pub allow_manual_add: bool,
+    /// When the Twitch redemption is marked fulfilled.
+    #[serde(default)]
+    pub fulfill_on: FulfillOn,
+    /// Local-time window (broadcaster timezone) during which new redemptions are not enqueued.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+    /// Refuses Helix fulfillment for queue entries whose reward is not targeted by this policy.
+    #[serde(default)]
+    pub verify_reward_ownership: bool,
+}
+
+/// Daily local-time window; `start > end` spans midnight and `start == end` is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Returns `true` when `time` falls inside the `[start, end)` window.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
 }
 
 impl PolicySettings {
@@ -86,9 +177,26 @@ impl PolicySettings {
         60
     }
 
-    /// Returns `true` when the provided reward identifier is enabled for policy evaluation.
-    pub fn is_reward_enabled(&self, reward_id: &str) -> bool {
+    fn default_allow_manual_add() -> bool {
+        true
+    }
+
+    /// Returns `true` when any reward id or title is targeted.
+    pub fn has_reward_targets(&self) -> bool {
+        !self.target_rewards.is_empty() || !self.target_reward_titles.is_empty()
+    }
+
+    /// Returns `true` when the reward is enabled for policy evaluation by id or by title.
+    pub fn is_reward_enabled(&self, reward_id: &str, reward_title: Option<&str>) -> bool {
         self.target_rewards.iter().any(|value| value == reward_id)
+            || reward_title.is_some_and(|title| {
+                // Unicode lowercasing, not full case folding: full-width and accented letters
+                // match regardless of case, multi-character folds such as `ß`/`SS` do not.
+                let title = title.trim().to_lowercase();
+                self.target_reward_titles
+                    .iter()
+                    .any(|value| value.trim().to_lowercase() == title)
+            })
     }
 }
 
@@ -98,10 +206,24 @@ impl Default for PolicySettings {
             anti_spam_window_sec: Self::default_window_sec(),
             duplicate_policy: DuplicatePolicy::default(),
             target_rewards: Vec::new(),
+            target_reward_titles: Vec::new(),
+            allow_manual_add: Self::default_allow_manual_add(),
+            fulfill_on: FulfillOn::default(),
+            quiet_hours: None,
+            verify_reward_ownership: false,
         }
     }
 }
 
+/// Stage at which a queued redemption is fulfilled on Twitch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FulfillOn {
+    #[default]
+    Enqueue,
+    Complete,
+}
+
 /// Behaviour when a duplicate redemption is detected inside the spam window.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -110,6 +232,7 @@ pub enum DuplicatePolicy {
     Refund,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for DuplicatePolicy {
     fn default() -> Self {
         Self::Consume
@@ -168,6 +291,8 @@ impl NormalizedEvent {
     }
 
     /// Returns the canonical event type string used across telemetry.
+    ///
+    /// Keep this match free of catch-all arms so every new variant has to pick a label.
     pub fn event_type(&self) -> &'static str {
         match self {
             Self::RedemptionAdd { .. } => "redemption.add",
@@ -293,6 +418,10 @@ pub enum Command {
     QueueComplete(QueueCompleteCommand),
     QueueRemove(QueueRemoveCommand),
     SettingsUpdate(SettingsUpdateCommand),
+    TimezoneUpdate(TimezoneUpdateCommand),
+    QueueAdd(QueueAddCommand),
+    CounterCorrect(CounterCorrectCommand),
+    Noop(NoopCommand),
 }
 
 impl Command {
@@ -307,6 +436,40 @@ impl Command {
             Self::QueueComplete(_) => "complete",
             Self::QueueRemove(_) => "undo",
             Self::SettingsUpdate(_) => "settings",
+            Self::TimezoneUpdate(_) => "timezone",
+            Self::QueueAdd(_) => "add",
+            Self::CounterCorrect(_) => "counter",
+            Self::Noop(_) => "noop",
+        }
+    }
+
+    /// Returns the origin recorded on the command.
+    pub fn source(&self) -> CommandSource {
+        match self {
+            Self::Enqueue(command) => command.source,
+            Self::RedemptionUpdate(command) => command.source,
+            Self::QueueComplete(command) => command.source,
+            Self::QueueRemove(command) => command.source,
+            Self::SettingsUpdate(command) => command.source,
+            Self::TimezoneUpdate(command) => command.source,
+            Self::QueueAdd(command) => command.source,
+            Self::CounterCorrect(command) => command.source,
+            Self::Noop(command) => command.source,
+        }
+    }
+
+    /// Overrides the origin recorded on the command.
+    pub fn set_source(&mut self, source: CommandSource) {
+        match self {
+            Self::Enqueue(command) => command.source = source,
+            Self::RedemptionUpdate(command) => command.source = source,
+            Self::QueueComplete(command) => command.source = source,
+            Self::QueueRemove(command) => command.source = source,
+            Self::SettingsUpdate(command) => command.source = source,
+            Self::TimezoneUpdate(command) => command.source = source,
+            Self::QueueAdd(command) => command.source = source,
+            Self::CounterCorrect(command) => command.source = source,
+            Self::Noop(command) => command.source = source,
         }
     }
 
@@ -318,6 +481,10 @@ impl Command {
             Self::QueueComplete(command) => command.redacted(),
             Self::QueueRemove(command) => command.redacted(),
             Self::SettingsUpdate(command) => command.redacted(),
+            Self::TimezoneUpdate(command) => command.redacted(),
+            Self::QueueAdd(command) => command.redacted(),
+            Self::CounterCorrect(command) => command.redacted(),
+            Self::Noop(command) => command.redacted(),
         }
     }
 }
@@ -328,6 +495,18 @@ impl Command {
 pub enum CommandSource {
     Policy,
     Admin,
+    /// Policy output replayed from Helix backfill rather than a live notification.
+    Backfill,
+}
+
+impl CommandSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Policy => "policy",
+            Self::Admin => "admin",
+            Self::Backfill => "backfill",
+        }
+    }
 }
 
 /// Queue enqueue command emitted by the policy stage.
@@ -451,6 +630,7 @@ pub enum QueueRemovalReason {
     Undo,
     ExplicitRemove,
     StreamStartClear,
+    Expired,
 }
 
 impl QueueRemovalReason {
@@ -460,6 +640,7 @@ impl QueueRemovalReason {
             Self::Undo => "UNDO",
             Self::ExplicitRemove => "EXPLICIT_REMOVE",
             Self::StreamStartClear => "STREAM_START_CLEAR",
+            Self::Expired => "EXPIRED",
         }
     }
 }
@@ -485,6 +666,93 @@ impl SettingsUpdateCommand {
     }
 }
 
+/// Admin change of the IANA timezone used to derive the broadcaster's `day` key.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimezoneUpdateCommand {
+    pub broadcaster_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub source: CommandSource,
+    pub timezone: String,
+    pub op_id: String,
+}
+
+impl TimezoneUpdateCommand {
+    fn redacted(&self) -> Value {
+        json!({
+            "type": "settings.timezone",
+            "broadcaster_id": self.broadcaster_id,
+            "issued_at": self.issued_at,
+            "source": self.source,
+            "timezone": self.timezone,
+        })
+    }
+}
+
+/// Admin correction overwriting a viewer's counter for the current day.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CounterCorrectCommand {
+    pub broadcaster_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub source: CommandSource,
+    pub user_id: String,
+    pub count: u32,
+    pub op_id: String,
+}
+
+impl CounterCorrectCommand {
+    fn redacted(&self) -> Value {
+        json!({
+            "type": "counter.correct",
+            "broadcaster_id": self.broadcaster_id,
+            "issued_at": self.issued_at,
+            "source": self.source,
+            "count": self.count,
+        })
+    }
+}
+
+/// Marker command that only reserves an op_id in the command log; it changes no state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NoopCommand {
+    pub broadcaster_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub source: CommandSource,
+    pub op_id: String,
+}
+
+impl NoopCommand {
+    fn redacted(&self) -> Value {
+        json!({
+            "type": "noop",
+            "broadcaster_id": self.broadcaster_id,
+            "issued_at": self.issued_at,
+            "source": self.source,
+        })
+    }
+}
+
+/// Manual queue addition command emitted by the admin interface.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueAddCommand {
+    pub broadcaster_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub source: CommandSource,
+    pub user: NormalizedUser,
+    pub op_id: String,
+}
+
+impl QueueAddCommand {
+    fn redacted(&self) -> Value {
+        json!({
+            "type": "queue.add",
+            "broadcaster_id": self.broadcaster_id,
+            "issued_at": self.issued_at,
+            "source": self.source,
+            "user": self.user.redacted(),
+        })
+    }
+}
+
 /// Result of attempting to execute a command. Currently a placeholder until Helix integration lands.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -501,6 +769,28 @@ pub struct StateSnapshot {
     pub queue: Vec<QueueEntry>,
     pub counters_today: Vec<UserCounter>,
     pub settings: Settings,
+    /// Set when `queue` only holds the first page of the active queue.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Cursor for fetching the remaining entries via the paginated queue endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+    /// OAuth/backfill status; only populated for admin-audience requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<SnapshotHealth>,
+    /// Entries completed during the current local day, newest first; only populated on request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recent_completed: Option<Vec<QueueEntry>>,
+}
+
+/// Integration status surfaced to admin overlays alongside the snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotHealth {
+    /// `true` when no OAuth link exists or Twitch rejected the stored token.
+    pub requires_reauth: bool,
+    pub token_expires_at: Option<DateTime<Utc>>,
+    /// Helix backfill checkpoint status (`idle` / `running` / `error`), if any.
+    pub backfill_status: Option<String>,
 }
 
 /// Daily counter value for a user.
@@ -537,6 +827,7 @@ pub enum PatchKind {
     SettingsUpdated,
     RedemptionUpdated,
     StateReplace,
+    Batch,
 }
 
 impl PatchKind {
@@ -549,6 +840,7 @@ impl PatchKind {
             Self::SettingsUpdated => "settings.updated",
             Self::RedemptionUpdated => "redemption.updated",
             Self::StateReplace => "state.replace",
+            Self::Batch => "patch.batch",
         }
     }
 }
@@ -584,7 +876,85 @@ impl FromStr for PatchKind {
             "settings.updated" => Ok(Self::SettingsUpdated),
             "redemption.updated" => Ok(Self::RedemptionUpdated),
             "state.replace" => Ok(Self::StateReplace),
+            "patch.batch" => Ok(Self::Batch),
             _ => Err(()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_type_covers_every_variant() {
+        let at = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let user = NormalizedUser {
+            id: "u-1".to_string(),
+            login: None,
+            display_name: None,
+        };
+        let reward = NormalizedReward {
+            id: "r-1".to_string(),
+            title: None,
+            cost: None,
+        };
+        // Exhaustive on purpose: a new variant must be listed here with its expected label.
+        let cases = [
+            (
+                NormalizedEvent::RedemptionAdd {
+                    broadcaster_id: "b-1".to_string(),
+                    occurred_at: at,
+                    redemption_id: "red-1".to_string(),
+                    user: user.clone(),
+                    reward: reward.clone(),
+                },
+                "redemption.add",
+            ),
+            (
+                NormalizedEvent::RedemptionUpdate {
+                    broadcaster_id: "b-1".to_string(),
+                    occurred_at: at,
+                    redemption_id: "red-1".to_string(),
+                    status: NormalizedRedemptionStatus::Fulfilled,
+                    user,
+                    reward,
+                },
+                "redemption.update",
+            ),
+            (
+                NormalizedEvent::StreamOnline {
+                    broadcaster_id: "b-1".to_string(),
+                    occurred_at: at,
+                },
+                "stream.online",
+            ),
+            (
+                NormalizedEvent::StreamOffline {
+                    broadcaster_id: "b-1".to_string(),
+                    occurred_at: at,
+                },
+                "stream.offline",
+            ),
+        ];
+
+        for (event, expected) in cases {
+            match &event {
+                NormalizedEvent::RedemptionAdd { .. }
+                | NormalizedEvent::RedemptionUpdate { .. }
+                | NormalizedEvent::StreamOnline { .. }
+                | NormalizedEvent::StreamOffline { .. } => {}
+            }
+            assert_eq!(event.event_type(), expected);
+            assert_ne!(event.event_type(), "unknown");
+
+            let tag = serde_json::to_value(&event).unwrap()["type"]
+                .as_str()
+                .unwrap()
+                .replace('_', ".");
+            assert_eq!(tag, expected, "serde tag and event_type diverged");
+        }
+    }
+}
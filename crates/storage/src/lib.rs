@@ -7,7 +7,10 @@ use sqlx::{
 use thiserror::Error;
 use uuid::Uuid;
 
-use twi_overlay_core::types::{QueueEntry, QueueEntryStatus, QueueRemovalReason, Settings};
+use twi_overlay_core::types::{
+    CommandSource, EnqueueCommand, EnqueuedSource, QueueEntry, QueueEntryStatus,
+    QueueRemovalReason, Settings,
+};
 
 use serde_json::{self, to_string};
 
@@ -17,14 +20,63 @@ pub struct Database {
     pool: SqlitePool,
 }
 
+/// Connection tuning applied when the pool is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatabaseOptions {
+    pub max_connections: u32,
+    /// `PRAGMA wal_autocheckpoint` in pages, applied to every pooled connection.
+    /// `None` keeps SQLite's default.
+    pub wal_autocheckpoint: Option<u32>,
+}
+
+impl Default for DatabaseOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: 5,
+            wal_autocheckpoint: None,
+        }
+    }
+}
+
 impl Database {
     /// Establishes a new SQLite connection pool for the provided connection string.
     pub async fn connect(database_url: &str) -> Result<Self, StorageError> {
+        Self::connect_with(database_url, DatabaseOptions::default()).await
+    }
+
+    /// Establishes a new SQLite connection pool using the provided options.
+    pub async fn connect_with(
+        database_url: &str,
+        options: DatabaseOptions,
+    ) -> Result<Self, StorageError> {
+        let wal_autocheckpoint = options.wal_autocheckpoint;
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
+            .max_connections(options.max_connections)
+            .after_connect(move |conn, _meta| {
+                Box::pin(async move {
+                    if let Some(pages) = wal_autocheckpoint {
+                        sqlx::query(&format!("PRAGMA wal_autocheckpoint = {pages};"))
+                            .execute(&mut *conn)
+                            .await?;
+                    }
+                    Ok(())
+                })
+            })
             .connect(database_url)
             .await
-            .map_err(StorageError::Connect)?;
+            .map_err(|source| StorageError::Connect {
+                url: redact_database_url(database_url),
+                source,
+            })?;
+
+        // Surface unreadable/unwritable paths here rather than on the first real query.
+        sqlx::query("SELECT 1")
+            .execute(&pool)
+            .await
+            .map_err(|source| StorageError::Connect {
+                url: redact_database_url(database_url),
+                source,
+            })?;
 
         apply_pragmas(&pool).await?;
 
@@ -103,11 +155,92 @@ impl Database {
         }
     }
 
+    /// Returns a handle for single-use SSE token markers.
+    pub fn sse_tokens(&self) -> SseTokenRepository {
+        SseTokenRepository {
+            pool: self.pool.clone(),
+        }
+    }
+
     /// Exposes the inner pool when lower level access is required.
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
 
+    /// Reads a single-valued PRAGMA such as `page_count`.
+    pub async fn execute_pragma<T>(&self, pragma: &'static str) -> Result<T, sqlx::Error>
+    where
+        T: for<'r> sqlx::Decode<'r, Sqlite> + sqlx::Type<Sqlite> + Send + Unpin,
+    {
+        sqlx::query_scalar(&format!("PRAGMA {pragma};"))
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    pub async fn journal_mode(&self) -> Result<String, sqlx::Error> {
+        self.execute_pragma("journal_mode").await
+    }
+
+    pub async fn page_size(&self) -> Result<i64, sqlx::Error> {
+        self.execute_pragma("page_size").await
+    }
+
+    pub async fn page_count(&self) -> Result<i64, sqlx::Error> {
+        self.execute_pragma("page_count").await
+    }
+
+    pub async fn freelist_count(&self) -> Result<i64, sqlx::Error> {
+        self.execute_pragma("freelist_count").await
+    }
+
+    pub async fn wal_autocheckpoint(&self) -> Result<i64, sqlx::Error> {
+        self.execute_pragma("wal_autocheckpoint").await
+    }
+
+    /// Returns the size of the main database's `-wal` file, if it exists on disk.
+    pub async fn wal_size_bytes(&self) -> Result<Option<u64>, sqlx::Error> {
+        let rows = sqlx::query("PRAGMA database_list;")
+            .fetch_all(&self.pool)
+            .await?;
+        let file = rows
+            .iter()
+            .find(|row| row.get::<String, _>("name") == "main")
+            .map(|row| row.get::<String, _>("file"))
+            .filter(|file| !file.is_empty());
+
+        Ok(file.and_then(|file| {
+            std::fs::metadata(format!("{file}-wal"))
+                .ok()
+                .map(|meta| meta.len())
+        }))
+    }
+
+    /// Returns the number of page frames held in the `-wal` file, if it exists on disk.
+    pub async fn wal_page_count(&self) -> Result<Option<u64>, sqlx::Error> {
+        // WAL layout: a 32-byte file header followed by frames of a 24-byte header plus one page.
+        const WAL_HEADER_BYTES: u64 = 32;
+        const FRAME_HEADER_BYTES: u64 = 24;
+
+        let Some(size) = self.wal_size_bytes().await? else {
+            return Ok(None);
+        };
+        let page_size = u64::try_from(self.page_size().await?).unwrap_or(0);
+        let frame_bytes = page_size + FRAME_HEADER_BYTES;
+        Ok(Some(size.saturating_sub(WAL_HEADER_BYTES) / frame_bytes))
+    }
+
+    /// Collects the PRAGMA values exposed for diagnostics.
+    pub async fn pragma_snapshot(&self) -> Result<PragmaSnapshot, sqlx::Error> {
+        Ok(PragmaSnapshot {
+            journal_mode: self.journal_mode().await?,
+            page_size: self.page_size().await?,
+            page_count: self.page_count().await?,
+            freelist_count: self.freelist_count().await?,
+            wal_autocheckpoint: self.wal_autocheckpoint().await?,
+            wal_size_bytes: self.wal_size_bytes().await?,
+        })
+    }
+
     /// Executes `PRAGMA wal_checkpoint(TRUNCATE)` and returns the reported counters.
     pub async fn wal_checkpoint_truncate(&self) -> Result<WalCheckpointStats, sqlx::Error> {
         let row = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
@@ -122,6 +255,31 @@ impl Database {
     }
 }
 
+/// PRAGMA values reported by the diagnostics endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PragmaSnapshot {
+    pub journal_mode: String,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    pub wal_autocheckpoint: i64,
+    /// Size of the `-wal` file on disk; `None` for in-memory databases.
+    pub wal_size_bytes: Option<u64>,
+}
+
+/// Dashboard aggregates for one broadcaster's queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Entries still `QUEUED`.
+    pub active: u64,
+    /// Active entries inside the head group currently being served.
+    pub serving: u64,
+    pub completed_today: u64,
+    pub removed_today: u64,
+    /// Distinct viewers with an entry enqueued today.
+    pub distinct_users_today: u64,
+}
+
 /// Counters returned by `PRAGMA wal_checkpoint`.
 #[derive(Debug, Clone, Copy)]
 pub struct WalCheckpointStats {
@@ -154,11 +312,19 @@ async fn apply_pragmas(pool: &SqlitePool) -> Result<(), StorageError> {
     Ok(())
 }
 
+/// Drops query parameters (which may carry keys or other options) from a database URL.
+fn redact_database_url(database_url: &str) -> String {
+    match database_url.split_once('?') {
+        Some((base, _)) => format!("{base}?<redacted>"),
+        None => database_url.to_string(),
+    }
+}
+
 /// General storage level errors.
 #[derive(Debug, Error)]
 pub enum StorageError {
-    #[error("failed to connect to sqlite: {0}")]
-    Connect(sqlx::Error),
+    #[error("failed to connect to sqlite at {url}: {source}")]
+    Connect { url: String, source: sqlx::Error },
     #[error("failed to apply pragma: {0}")]
     Pragma(sqlx::Error),
     #[error("failed to run database migrations: {0}")]
@@ -191,6 +357,45 @@ impl BroadcasterRepository {
         Ok(BroadcasterSettings { settings, timezone })
     }
 
+    /// Loads the settings within an ongoing transaction, so they match the rows it mutates.
+    pub async fn fetch_settings_for_update(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+    ) -> Result<BroadcasterSettings, SettingsError> {
+        let row = sqlx::query("SELECT settings_json, timezone FROM broadcasters WHERE id = ?")
+            .bind(broadcaster_id)
+            .fetch_optional(&mut **tx)
+            .await?
+            .ok_or(SettingsError::NotFound)?;
+
+        let json_value: String = row.get("settings_json");
+        let settings: Settings = serde_json::from_str(&json_value)?;
+        let timezone: String = row.get("timezone");
+        Ok(BroadcasterSettings { settings, timezone })
+    }
+
+    /// Loads the settings of every provisioned broadcaster, keyed by broadcaster id.
+    pub async fn list_settings(&self) -> Result<Vec<(String, BroadcasterSettings)>, SettingsError> {
+        let rows = sqlx::query("SELECT id, settings_json, timezone FROM broadcasters ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let json_value: String = row.get("settings_json");
+                let settings: Settings = serde_json::from_str(&json_value)?;
+                Ok((
+                    row.get("id"),
+                    BroadcasterSettings {
+                        settings,
+                        timezone: row.get("timezone"),
+                    },
+                ))
+            })
+            .collect()
+    }
+
     /// Updates the persisted settings payload for a broadcaster.
     pub async fn update_settings(
         &self,
@@ -214,6 +419,33 @@ impl BroadcasterRepository {
 
         Ok(())
     }
+
+    /// Changes the IANA timezone used to derive a broadcaster's local `day` key.
+    pub async fn update_timezone(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+        timezone: &str,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), TimezoneUpdateError> {
+        if timezone.parse::<chrono_tz::Tz>().is_err() {
+            return Err(TimezoneUpdateError::InvalidTimezone(timezone.to_string()));
+        }
+
+        let updated_rows =
+            sqlx::query("UPDATE broadcasters SET timezone = ?, updated_at = ? WHERE id = ?")
+                .bind(timezone)
+                .bind(to_rfc3339(updated_at))
+                .bind(broadcaster_id)
+                .execute(&mut **tx)
+                .await?;
+
+        if updated_rows.rows_affected() == 0 {
+            return Err(TimezoneUpdateError::NotFound);
+        }
+
+        Ok(())
+    }
 }
 
 /// Errors that can occur while reading settings.
@@ -245,6 +477,17 @@ pub enum SettingsUpdateError {
     Database(#[from] sqlx::Error),
 }
 
+/// Errors that can occur while changing a broadcaster's timezone.
+#[derive(Debug, Error)]
+pub enum TimezoneUpdateError {
+    #[error("invalid timezone: {0}")]
+    InvalidTimezone(String),
+    #[error("broadcaster not found")]
+    NotFound,
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
 /// Repository responsible for interacting with the `event_raw` table.
 #[derive(Clone)]
 pub struct EventRawRepository {
@@ -371,6 +614,20 @@ impl CommandLogRepository {
         self.pool.begin().await
     }
 
+    /// Begins a transaction that holds the database write lock before returning.
+    ///
+    /// SQLite has no `SELECT ... FOR UPDATE`; taking the lock upfront (the `BEGIN IMMEDIATE`
+    /// equivalent) serialises read-modify-write sequences such as `mark_completed`, so a
+    /// concurrent writer waits for `busy_timeout` and then reads the committed row.
+    pub async fn begin_immediate(&self) -> Result<Transaction<'_, Sqlite>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        // sqlx 0.7 always issues a deferred BEGIN; a no-op write upgrades it to RESERVED.
+        sqlx::query("UPDATE state_index SET current_version = current_version WHERE 0")
+            .execute(&mut *tx)
+            .await?;
+        Ok(tx)
+    }
+
     /// Appends a new record to the command log while incrementing the state version.
     pub async fn append(
         &self,
@@ -399,12 +656,13 @@ impl CommandLogRepository {
         let payload_json = record.payload_json;
         sqlx::query(
             "INSERT INTO command_log \
-             (broadcaster_id, version, op_id, type, payload_json, created_at) \
-             VALUES (?, ?, ?, ?, ?, ?)",
+             (broadcaster_id, version, op_id, source, type, payload_json, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(record.broadcaster_id)
         .bind(version)
         .bind(record.op_id)
+        .bind(record.source.as_str())
         .bind(record.command_type)
         .bind(payload_json)
         .bind(&updated_at)
@@ -446,7 +704,7 @@ impl CommandLogRepository {
         op_id: &str,
     ) -> Result<Option<LoggedCommand>, CommandLogError> {
         let row = sqlx::query(
-            "SELECT version, type, payload_json FROM command_log WHERE broadcaster_id = ? AND op_id = ?",
+            "SELECT version, source, type, payload_json FROM command_log WHERE broadcaster_id = ? AND op_id = ?",
         )
         .bind(broadcaster_id)
         .bind(op_id)
@@ -458,17 +716,84 @@ impl CommandLogRepository {
             let version: i64 = row.get("version");
             LoggedCommand {
                 version: version as u64,
+                source: row.get("source"),
                 command_type: row.get("type"),
                 payload_json: row.get("payload_json"),
             }
         }))
     }
+
+    /// Lists command log entries with `after < version <= up_to`, oldest first.
+    pub async fn list_range(
+        &self,
+        broadcaster_id: &str,
+        after: u64,
+        up_to: u64,
+    ) -> Result<Vec<LoggedCommand>, CommandLogError> {
+        let rows = sqlx::query(
+            "SELECT version, source, type, payload_json FROM command_log \
+             WHERE broadcaster_id = ? AND version > ? AND version <= ? \
+             ORDER BY version ASC",
+        )
+        .bind(broadcaster_id)
+        .bind(after as i64)
+        .bind(up_to as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(CommandLogError::Database)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let version: i64 = row.get("version");
+                LoggedCommand {
+                    version: version as u64,
+                    source: row.get("source"),
+                    command_type: row.get("type"),
+                    payload_json: row.get("payload_json"),
+                }
+            })
+            .collect())
+    }
+
+    /// Lists the most recent command log entries for a broadcaster, newest first.
+    pub async fn list_recent(
+        &self,
+        broadcaster_id: &str,
+        limit: i64,
+    ) -> Result<Vec<LoggedCommand>, CommandLogError> {
+        let rows = sqlx::query(
+            "SELECT version, source, type, payload_json FROM command_log \
+             WHERE broadcaster_id = ? \
+             ORDER BY version DESC \
+             LIMIT ?",
+        )
+        .bind(broadcaster_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(CommandLogError::Database)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let version: i64 = row.get("version");
+                LoggedCommand {
+                    version: version as u64,
+                    source: row.get("source"),
+                    command_type: row.get("type"),
+                    payload_json: row.get("payload_json"),
+                }
+            })
+            .collect())
+    }
 }
 
 /// Payload required to append a command log record.
 pub struct NewCommandLog<'a> {
     pub broadcaster_id: &'a str,
     pub op_id: Option<&'a str>,
+    pub source: CommandSource,
     pub command_type: &'a str,
     pub payload_json: &'a str,
     pub created_at: DateTime<Utc>,
@@ -478,6 +803,8 @@ pub struct NewCommandLog<'a> {
 #[derive(Debug, Clone)]
 pub struct LoggedCommand {
     pub version: u64,
+    /// `None` for rows written before the `source` column existed.
+    pub source: Option<String>,
     pub command_type: String,
     pub payload_json: String,
 }
@@ -542,8 +869,8 @@ impl QueueRepository {
         let managed = if entry.managed { 1 } else { 0 };
         sqlx::query(
             "INSERT INTO queue_entries \
-             (id, broadcaster_id, user_id, user_login, user_display_name, user_avatar, reward_id, redemption_id, enqueued_at, status, status_reason, managed, last_updated_at) \
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+             (id, broadcaster_id, user_id, user_login, user_display_name, user_avatar, reward_id, reward_title, redemption_id, enqueued_at, status, status_reason, managed, last_updated_at, enqueued_source) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&entry.id)
         .bind(entry.broadcaster_id)
@@ -552,12 +879,14 @@ impl QueueRepository {
         .bind(&entry.user_display_name)
         .bind(&entry.user_avatar)
         .bind(entry.reward_id)
+        .bind(&entry.reward_title)
         .bind(&entry.redemption_id)
         .bind(to_rfc3339(entry.enqueued_at))
         .bind(entry.status.as_str())
         .bind(&entry.status_reason)
         .bind(managed)
         .bind(to_rfc3339(entry.last_updated_at))
+        .bind(entry.enqueued_source.as_str())
         .execute(&mut **tx)
         .await
         .map_err(|err| match err {
@@ -589,11 +918,13 @@ SELECT q.id,
        q.user_display_name,
        q.user_avatar,
        q.reward_id,
+       q.reward_title,
        q.redemption_id,
        q.enqueued_at as "enqueued_at: DateTime<Utc>",
        q.status,
        q.status_reason,
        q.managed,
+       q.enqueued_source,
        q.last_updated_at as "last_updated_at: DateTime<Utc>",
        COALESCE(dc.count, 0) as "today_count"
   FROM queue_entries AS q
@@ -629,11 +960,13 @@ SELECT q.id,
        q.user_display_name,
        q.user_avatar,
        q.reward_id,
+       q.reward_title,
        q.redemption_id,
        q.enqueued_at as "enqueued_at: DateTime<Utc>",
        q.status,
        q.status_reason,
        q.managed,
+       q.enqueued_source,
        q.last_updated_at as "last_updated_at: DateTime<Utc>",
        COALESCE(dc.count, 0) as "today_count"
   FROM queue_entries AS q
@@ -656,7 +989,129 @@ SELECT q.id,
         Ok(rows)
     }
 
+    /// Lists a page of active queue entries using the same ordering as [`Self::list_active_with_counts`].
+    pub async fn list_active_with_counts_page(
+        &self,
+        broadcaster_id: &str,
+        day: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<QueueEntryWithCount>, QueueError> {
+        let rows = sqlx::query_as::<_, QueueEntryWithCount>(
+            r#"
+SELECT q.id,
+       q.broadcaster_id,
+       q.user_id,
+       q.user_login,
+       q.user_display_name,
+       q.user_avatar,
+       q.reward_id,
+       q.reward_title,
+       q.redemption_id,
+       q.enqueued_at as "enqueued_at: DateTime<Utc>",
+       q.status,
+       q.status_reason,
+       q.managed,
+       q.enqueued_source,
+       q.last_updated_at as "last_updated_at: DateTime<Utc>",
+       COALESCE(dc.count, 0) as "today_count"
+  FROM queue_entries AS q
+  LEFT JOIN daily_counters AS dc
+    ON dc.day = ?
+   AND dc.broadcaster_id = q.broadcaster_id
+   AND dc.user_id = q.user_id
+ WHERE q.broadcaster_id = ?
+   AND q.status = 'QUEUED'
+ ORDER BY today_count ASC, q.enqueued_at ASC
+ LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(day)
+        .bind(broadcaster_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Reads a queue entry outside of any transaction.
+    pub async fn find_entry(
+        &self,
+        broadcaster_id: &str,
+        entry_id: &str,
+    ) -> Result<Option<QueueEntry>, QueueError> {
+        let row = sqlx::query_as::<_, QueueEntryRow>(
+            r#"
+SELECT id,
+       broadcaster_id,
+       user_id,
+       user_login,
+       user_display_name,
+       user_avatar,
+       reward_id,
+       reward_title,
+       redemption_id,
+       enqueued_at as "enqueued_at: DateTime<Utc>",
+       status,
+       status_reason,
+       managed,
+       enqueued_source,
+       last_updated_at as "last_updated_at: DateTime<Utc>"
+  FROM queue_entries
+ WHERE broadcaster_id = ?
+   AND id = ?
+            "#,
+        )
+        .bind(broadcaster_id)
+        .bind(entry_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(QueueEntryRow::into_domain))
+    }
+
+    /// Reads a queue entry by redemption identifier outside of any transaction.
+    pub async fn find_entry_by_redemption(
+        &self,
+        broadcaster_id: &str,
+        redemption_id: &str,
+    ) -> Result<Option<QueueEntry>, QueueError> {
+        let row = sqlx::query_as::<_, QueueEntryRow>(
+            r#"
+SELECT id,
+       broadcaster_id,
+       user_id,
+       user_login,
+       user_display_name,
+       user_avatar,
+       reward_id,
+       reward_title,
+       redemption_id,
+       enqueued_at as "enqueued_at: DateTime<Utc>",
+       status,
+       status_reason,
+       managed,
+       enqueued_source,
+       last_updated_at as "last_updated_at: DateTime<Utc>"
+  FROM queue_entries
+ WHERE broadcaster_id = ?
+   AND redemption_id = ?
+            "#,
+        )
+        .bind(broadcaster_id)
+        .bind(redemption_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(QueueEntryRow::into_domain))
+    }
+
     /// Finds a queue entry within an ongoing transaction.
+    ///
+    /// The row is only protected against concurrent writers when the transaction was opened
+    /// with [`CommandLogRepository::begin_immediate`].
     pub async fn find_entry_for_update(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
@@ -672,11 +1127,13 @@ SELECT id,
        user_display_name,
        user_avatar,
        reward_id,
+       reward_title,
        redemption_id,
        enqueued_at as "enqueued_at: DateTime<Utc>",
        status,
        status_reason,
        managed,
+       enqueued_source,
        last_updated_at as "last_updated_at: DateTime<Utc>"
   FROM queue_entries
  WHERE broadcaster_id = ?
@@ -707,11 +1164,13 @@ SELECT id,
        user_display_name,
        user_avatar,
        reward_id,
+       reward_title,
        redemption_id,
        enqueued_at as "enqueued_at: DateTime<Utc>",
        status,
        status_reason,
        managed,
+       enqueued_source,
        last_updated_at as "last_updated_at: DateTime<Utc>"
   FROM queue_entries
  WHERE broadcaster_id = ?
@@ -761,11 +1220,13 @@ UPDATE queue_entries
            user_display_name,
            user_avatar,
            reward_id,
+           reward_title,
            redemption_id,
            enqueued_at as "enqueued_at: DateTime<Utc>",
            status,
            status_reason,
            managed,
+           enqueued_source,
            last_updated_at as "last_updated_at: DateTime<Utc>"
             "#,
         )
@@ -814,11 +1275,13 @@ UPDATE queue_entries
            user_display_name,
            user_avatar,
            reward_id,
+           reward_title,
            redemption_id,
            enqueued_at as "enqueued_at: DateTime<Utc>",
            status,
            status_reason,
            managed,
+           enqueued_source,
            last_updated_at as "last_updated_at: DateTime<Utc>"
             "#,
         )
@@ -832,19 +1295,30 @@ UPDATE queue_entries
         Ok(row.into_domain())
     }
 
-    /// Updates the managed flag for a queue entry, returning the refreshed representation.
-    pub async fn update_managed(
+    /// Moves a queued entry to the back of the line by resetting its enqueue timestamp.
+    pub async fn reenqueue_to_back(
         &self,
         tx: &mut Transaction<'_, Sqlite>,
         broadcaster_id: &str,
         entry_id: &str,
-        managed: bool,
-        updated_at: DateTime<Utc>,
+        now: DateTime<Utc>,
     ) -> Result<QueueEntry, QueueError> {
+        let existing = self
+            .find_entry_for_update(tx, broadcaster_id, entry_id)
+            .await?;
+
+        let Some(entry) = existing else {
+            return Err(QueueError::NotFound);
+        };
+
+        if entry.status != QueueEntryStatus::Queued {
+            return Err(QueueError::InvalidTransition(entry.status));
+        }
+
         let row = sqlx::query_as::<_, QueueEntryRow>(
             r#"
 UPDATE queue_entries
-   SET managed = ?,
+   SET enqueued_at = ?,
        last_updated_at = ?
  WHERE broadcaster_id = ?
    AND id = ?
@@ -855,19 +1329,271 @@ UPDATE queue_entries
            user_display_name,
            user_avatar,
            reward_id,
+           reward_title,
            redemption_id,
            enqueued_at as "enqueued_at: DateTime<Utc>",
            status,
            status_reason,
            managed,
+           enqueued_source,
            last_updated_at as "last_updated_at: DateTime<Utc>"
             "#,
         )
-        .bind(if managed { 1 } else { 0 })
-        .bind(to_rfc3339(updated_at))
+        .bind(to_rfc3339(now))
+        .bind(to_rfc3339(now))
         .bind(broadcaster_id)
         .bind(entry_id)
-        .fetch_optional(&mut **tx)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(row.into_domain())
+    }
+
+    /// Lists ids of `QUEUED` entries enqueued before `threshold`, oldest first.
+    pub async fn list_queued_before(
+        &self,
+        broadcaster_id: &str,
+        threshold: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<String>, QueueError> {
+        let ids = sqlx::query_scalar(
+            r#"
+SELECT id
+  FROM queue_entries
+ WHERE broadcaster_id = ?
+   AND status = 'QUEUED'
+   AND enqueued_at < ?
+ ORDER BY enqueued_at ASC
+ LIMIT ?
+            "#,
+        )
+        .bind(broadcaster_id)
+        .bind(to_rfc3339(threshold))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Counts entries that reached `status` within the `[day_start, day_end)` window.
+    ///
+    /// The window is expected to cover the broadcaster's local day, expressed in UTC.
+    pub async fn count_terminal_today(
+        &self,
+        broadcaster_id: &str,
+        day_start: DateTime<Utc>,
+        day_end: DateTime<Utc>,
+        status: QueueEntryStatus,
+    ) -> Result<u64, QueueError> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+SELECT COUNT(*)
+  FROM queue_entries
+ WHERE broadcaster_id = ?
+   AND status = ?
+   AND last_updated_at >= ?
+   AND last_updated_at < ?
+            "#,
+        )
+        .bind(broadcaster_id)
+        .bind(status.as_str())
+        .bind(to_rfc3339(day_start))
+        .bind(to_rfc3339(day_end))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    /// Computes [`QueueStats`] in a single pass over the broadcaster's entries.
+    ///
+    /// `day` is the broadcaster's local day as UTC bounds `[start, end)`; `serving_size` is the
+    /// head group size (usually `settings.group_size`).
+    pub async fn stats(
+        &self,
+        broadcaster_id: &str,
+        day: (DateTime<Utc>, DateTime<Utc>),
+        serving_size: u32,
+    ) -> Result<QueueStats, QueueError> {
+        let (day_start, day_end) = (to_rfc3339(day.0), to_rfc3339(day.1));
+        let (active, serving, completed_today, removed_today, distinct_users_today): (
+            i64,
+            i64,
+            i64,
+            i64,
+            i64,
+        ) = sqlx::query_as(
+            r#"
+SELECT COALESCE(SUM(CASE WHEN status = 'QUEUED' THEN 1 ELSE 0 END), 0),
+       MIN(COALESCE(SUM(CASE WHEN status = 'QUEUED' THEN 1 ELSE 0 END), 0), ?),
+       COALESCE(SUM(CASE WHEN status = 'COMPLETED'
+                          AND last_updated_at >= ? AND last_updated_at < ? THEN 1 ELSE 0 END), 0),
+       COALESCE(SUM(CASE WHEN status = 'REMOVED'
+                          AND last_updated_at >= ? AND last_updated_at < ? THEN 1 ELSE 0 END), 0),
+       COUNT(DISTINCT CASE WHEN enqueued_at >= ? AND enqueued_at < ? THEN user_id END)
+  FROM queue_entries
+ WHERE broadcaster_id = ?
+            "#,
+        )
+        .bind(i64::from(serving_size))
+        .bind(&day_start)
+        .bind(&day_end)
+        .bind(&day_start)
+        .bind(&day_end)
+        .bind(&day_start)
+        .bind(&day_end)
+        .bind(broadcaster_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(QueueStats {
+            active: active as u64,
+            serving: serving as u64,
+            completed_today: completed_today as u64,
+            removed_today: removed_today as u64,
+            distinct_users_today: distinct_users_today as u64,
+        })
+    }
+
+    /// Lists entries that reached `status` within `[day_start, day_end)`, oldest transition first.
+    pub async fn list_terminal_with_counts(
+        &self,
+        broadcaster_id: &str,
+        day: &str,
+        day_start: DateTime<Utc>,
+        day_end: DateTime<Utc>,
+        status: QueueEntryStatus,
+    ) -> Result<Vec<QueueEntryWithCount>, QueueError> {
+        let rows = sqlx::query_as::<_, QueueEntryWithCount>(
+            r#"
+SELECT q.id,
+       q.broadcaster_id,
+       q.user_id,
+       q.user_login,
+       q.user_display_name,
+       q.user_avatar,
+       q.reward_id,
+       q.reward_title,
+       q.redemption_id,
+       q.enqueued_at as "enqueued_at: DateTime<Utc>",
+       q.status,
+       q.status_reason,
+       q.managed,
+       q.enqueued_source,
+       q.last_updated_at as "last_updated_at: DateTime<Utc>",
+       COALESCE(dc.count, 0) as "today_count"
+  FROM queue_entries AS q
+  LEFT JOIN daily_counters AS dc
+    ON dc.day = ?
+   AND dc.broadcaster_id = q.broadcaster_id
+   AND dc.user_id = q.user_id
+ WHERE q.broadcaster_id = ?
+   AND q.status = ?
+   AND q.last_updated_at >= ?
+   AND q.last_updated_at < ?
+ ORDER BY q.last_updated_at ASC, q.id ASC
+            "#,
+        )
+        .bind(day)
+        .bind(broadcaster_id)
+        .bind(status.as_str())
+        .bind(to_rfc3339(day_start))
+        .bind(to_rfc3339(day_end))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Lists up to `limit` entries that reached `status` at or after `since`, newest transition first.
+    pub async fn list_recent_terminal_with_counts(
+        &self,
+        broadcaster_id: &str,
+        day: &str,
+        since: DateTime<Utc>,
+        status: QueueEntryStatus,
+        limit: usize,
+    ) -> Result<Vec<QueueEntryWithCount>, QueueError> {
+        let rows = sqlx::query_as::<_, QueueEntryWithCount>(
+            r#"
+SELECT q.id,
+       q.broadcaster_id,
+       q.user_id,
+       q.user_login,
+       q.user_display_name,
+       q.user_avatar,
+       q.reward_id,
+       q.reward_title,
+       q.redemption_id,
+       q.enqueued_at as "enqueued_at: DateTime<Utc>",
+       q.status,
+       q.status_reason,
+       q.managed,
+       q.enqueued_source,
+       q.last_updated_at as "last_updated_at: DateTime<Utc>",
+       COALESCE(dc.count, 0) as "today_count"
+  FROM queue_entries AS q
+  LEFT JOIN daily_counters AS dc
+    ON dc.day = ?
+   AND dc.broadcaster_id = q.broadcaster_id
+   AND dc.user_id = q.user_id
+ WHERE q.broadcaster_id = ?
+   AND q.status = ?
+   AND q.last_updated_at >= ?
+ ORDER BY q.last_updated_at DESC, q.id DESC
+ LIMIT ?
+            "#,
+        )
+        .bind(day)
+        .bind(broadcaster_id)
+        .bind(status.as_str())
+        .bind(to_rfc3339(since))
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Updates the managed flag for a queue entry, returning the refreshed representation.
+    pub async fn update_managed(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+        entry_id: &str,
+        managed: bool,
+        updated_at: DateTime<Utc>,
+    ) -> Result<QueueEntry, QueueError> {
+        let row = sqlx::query_as::<_, QueueEntryRow>(
+            r#"
+UPDATE queue_entries
+   SET managed = ?,
+       last_updated_at = ?
+ WHERE broadcaster_id = ?
+   AND id = ?
+ RETURNING id,
+           broadcaster_id,
+           user_id,
+           user_login,
+           user_display_name,
+           user_avatar,
+           reward_id,
+           reward_title,
+           redemption_id,
+           enqueued_at as "enqueued_at: DateTime<Utc>",
+           status,
+           status_reason,
+           managed,
+           enqueued_source,
+           last_updated_at as "last_updated_at: DateTime<Utc>"
+            "#,
+        )
+        .bind(if managed { 1 } else { 0 })
+        .bind(to_rfc3339(updated_at))
+        .bind(broadcaster_id)
+        .bind(entry_id)
+        .fetch_optional(&mut **tx)
         .await?;
 
         let Some(row) = row else {
@@ -887,12 +1613,69 @@ pub struct NewQueueEntry<'a> {
     pub user_display_name: String,
     pub user_avatar: Option<String>,
     pub reward_id: &'a str,
+    pub reward_title: Option<String>,
     pub redemption_id: Option<String>,
     pub enqueued_at: DateTime<Utc>,
     pub status: QueueEntryStatus,
     pub status_reason: Option<String>,
     pub managed: bool,
     pub last_updated_at: DateTime<Utc>,
+    pub enqueued_source: EnqueuedSource,
+}
+
+impl<'a> NewQueueEntry<'a> {
+    /// Maps an enqueue command onto a new `QUEUED` entry stamped at `at`.
+    ///
+    /// Missing login/display names fall back to the next available identifier.
+    pub fn from_enqueue_command(
+        command: &'a EnqueueCommand,
+        id: String,
+        at: DateTime<Utc>,
+    ) -> Self {
+        let user = &command.user;
+        Self {
+            id,
+            broadcaster_id: &command.broadcaster_id,
+            user_id: &user.id,
+            user_login: user.login.clone().unwrap_or_else(|| user.id.clone()),
+            user_display_name: user
+                .display_name
+                .clone()
+                .or_else(|| user.login.clone())
+                .unwrap_or_else(|| user.id.clone()),
+            user_avatar: None,
+            reward_id: &command.reward.id,
+            reward_title: command.reward.title.clone(),
+            redemption_id: Some(command.redemption_id.clone()),
+            enqueued_at: at,
+            status: QueueEntryStatus::Queued,
+            status_reason: None,
+            managed: command.managed.unwrap_or(false),
+            last_updated_at: at,
+            enqueued_source: command.source.into(),
+        }
+    }
+
+    /// Returns the domain representation of the entry as it will be stored.
+    pub fn to_domain(&self) -> QueueEntry {
+        QueueEntry {
+            id: self.id.clone(),
+            broadcaster_id: self.broadcaster_id.to_string(),
+            user_id: self.user_id.to_string(),
+            user_login: self.user_login.clone(),
+            user_display_name: self.user_display_name.clone(),
+            user_avatar: self.user_avatar.clone(),
+            reward_id: self.reward_id.to_string(),
+            reward_title: self.reward_title.clone(),
+            redemption_id: self.redemption_id.clone(),
+            enqueued_at: self.enqueued_at,
+            status: self.status,
+            status_reason: self.status_reason.clone(),
+            managed: self.managed,
+            last_updated_at: self.last_updated_at,
+            enqueued_source: self.enqueued_source,
+        }
+    }
 }
 
 /// Representation of a queue entry joined with the user's daily count.
@@ -905,6 +1688,7 @@ pub struct QueueEntryWithCount {
     pub user_display_name: String,
     pub user_avatar: Option<String>,
     pub reward_id: String,
+    pub reward_title: Option<String>,
     pub redemption_id: Option<String>,
     #[sqlx(rename = "enqueued_at: DateTime<Utc>")]
     pub enqueued_at: DateTime<Utc>,
@@ -913,6 +1697,7 @@ pub struct QueueEntryWithCount {
     pub managed: i64,
     #[sqlx(rename = "last_updated_at: DateTime<Utc>")]
     pub last_updated_at: DateTime<Utc>,
+    pub enqueued_source: String,
     pub today_count: i64,
 }
 
@@ -929,12 +1714,14 @@ impl QueueEntryWithCount {
                 user_display_name: self.user_display_name,
                 user_avatar: self.user_avatar,
                 reward_id: self.reward_id,
+                reward_title: self.reward_title,
                 redemption_id: self.redemption_id,
                 enqueued_at: self.enqueued_at,
                 status,
                 status_reason: self.status_reason,
                 managed: self.managed != 0,
                 last_updated_at: self.last_updated_at,
+                enqueued_source: map_enqueued_source(&self.enqueued_source),
             },
             self.today_count as u32,
         )
@@ -1008,6 +1795,39 @@ impl DailyCounterRepository {
         Ok(rows)
     }
 
+    /// Lists a page of counters for a day, ordered by `user_id` like [`Self::list_for_day`],
+    /// together with the total number of counters for that day.
+    pub async fn list_for_day_page(
+        &self,
+        broadcaster_id: &str,
+        day: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<DailyCounterPage, DailyCounterError> {
+        let counters = sqlx::query_as::<_, DailyCounterValue>(
+            "SELECT user_id, count FROM daily_counters WHERE day = ? AND broadcaster_id = ? ORDER BY user_id LIMIT ? OFFSET ?",
+        )
+        .bind(day)
+        .bind(broadcaster_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM daily_counters WHERE day = ? AND broadcaster_id = ?",
+        )
+        .bind(day)
+        .bind(broadcaster_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(DailyCounterPage {
+            counters,
+            total: total as u64,
+        })
+    }
+
     pub async fn list_updated_since(
         &self,
         broadcaster_id: &str,
@@ -1051,6 +1871,32 @@ impl DailyCounterRepository {
         }))
     }
 
+    /// Overwrites the counter for the given day and user, creating it when missing.
+    pub async fn set(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        record: &NewDailyCounter<'_>,
+        count: u32,
+    ) -> Result<u32, DailyCounterError> {
+        let row = sqlx::query(
+            "INSERT INTO daily_counters(day, broadcaster_id, user_id, count, updated_at) \
+             VALUES (?, ?, ?, ?, ?) \
+             ON CONFLICT(day, broadcaster_id, user_id) DO UPDATE \
+             SET count = excluded.count, updated_at = excluded.updated_at \
+             RETURNING count",
+        )
+        .bind(&record.day)
+        .bind(record.broadcaster_id)
+        .bind(record.user_id)
+        .bind(i64::from(count))
+        .bind(to_rfc3339(record.updated_at))
+        .fetch_one(&mut **tx)
+        .await?;
+
+        let count: i64 = row.get("count");
+        Ok(count as u32)
+    }
+
     /// Fetches the counter value for the provided day and user.
     pub async fn fetch_value(
         &self,
@@ -1090,6 +1936,13 @@ pub struct DailyCounterValue {
     pub count: i64,
 }
 
+/// Page of daily counters with the day's total row count.
+#[derive(Debug)]
+pub struct DailyCounterPage {
+    pub counters: Vec<DailyCounterValue>,
+    pub total: u64,
+}
+
 /// Errors that can occur when mutating counters.
 #[derive(Debug, Error)]
 pub enum DailyCounterError {
@@ -1182,6 +2035,20 @@ DELETE FROM oauth_login_states
         Ok(result.rows_affected())
     }
 
+    /// Deletes every pending login state for the broadcaster so stale callbacks cannot complete.
+    pub async fn purge_for_broadcaster(
+        &self,
+        broadcaster_id: &str,
+    ) -> Result<u64, OauthLoginStateError> {
+        let result = sqlx::query("DELETE FROM oauth_login_states WHERE broadcaster_id = ?")
+            .bind(broadcaster_id)
+            .execute(&self.pool)
+            .await
+            .map_err(OauthLoginStateError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Returns true when a non-expired login state already exists for the broadcaster.
     pub async fn has_active(
         &self,
@@ -1267,6 +2134,75 @@ pub enum OauthStateDecodeError {
     Timestamp(#[from] chrono::ParseError),
 }
 
+/// Repository recording consumed single-use SSE tokens.
+#[derive(Clone)]
+pub struct SseTokenRepository {
+    pool: SqlitePool,
+}
+
+impl SseTokenRepository {
+    /// Records the first use of a token, returning `false` when it was already consumed.
+    pub async fn mark_used(&self, record: &NewSseTokenUse<'_>) -> Result<bool, SseTokenError> {
+        let result = sqlx::query(
+            r#"
+INSERT INTO sse_token_uses(jti, broadcaster_id, audience, used_at, expires_at)
+VALUES(?, ?, ?, ?, ?)
+ON CONFLICT(jti) DO NOTHING
+            "#,
+        )
+        .bind(record.jti)
+        .bind(record.broadcaster_id)
+        .bind(record.audience)
+        .bind(to_rfc3339(record.used_at))
+        .bind(to_rfc3339(record.expires_at))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Deletes markers whose tokens have expired and can no longer be presented.
+    pub async fn purge_expired(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<u64, SseTokenError> {
+        let result = sqlx::query(
+            r#"
+DELETE FROM sse_token_uses
+ WHERE rowid IN (
+    SELECT rowid
+      FROM sse_token_uses
+     WHERE expires_at <= ?
+     ORDER BY expires_at
+     LIMIT ?
+ )
+            "#,
+        )
+        .bind(to_rfc3339(now))
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Marker payload recorded when a single-use SSE token is presented.
+pub struct NewSseTokenUse<'a> {
+    pub jti: &'a str,
+    pub broadcaster_id: &'a str,
+    pub audience: &'a str,
+    pub used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Error)]
+pub enum SseTokenError {
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
 /// Repository managing OAuth link persistence.
 #[derive(Clone)]
 pub struct OauthLinkRepository {
@@ -1505,19 +2441,56 @@ UPDATE oauth_links
         .bind(to_rfc3339(update.expires_at))
         .bind(scopes)
         .bind(managed_scopes)
-        .bind(&refreshed_at)
-        .bind(&validated_at)
-        .bind(to_rfc3339(update.updated_at))
-        .bind(update.broadcaster_id)
-        .bind(&update.twitch_user_id)
-        .fetch_optional(&mut **tx)
+        .bind(&refreshed_at)
+        .bind(&validated_at)
+        .bind(to_rfc3339(update.updated_at))
+        .bind(update.broadcaster_id)
+        .bind(&update.twitch_user_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let Some(row) = row else {
+            return Err(OauthLinkError::NotFound);
+        };
+
+        row.try_into().map_err(OauthLinkError::Decode)
+    }
+
+    /// Replaces the stored scope sets after Twitch reported a different grant.
+    pub async fn update_scopes(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        broadcaster_id: &str,
+        twitch_user_id: &str,
+        scopes: &[String],
+        managed_scopes: &[String],
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), OauthLinkError> {
+        let scopes = serde_json::to_string(scopes)?;
+        let managed_scopes = serde_json::to_string(managed_scopes)?;
+        let rows = sqlx::query(
+            r#"
+UPDATE oauth_links
+   SET scopes_json = ?,
+       managed_scopes_json = ?,
+       updated_at = ?
+ WHERE broadcaster_id = ?
+   AND twitch_user_id = ?
+            "#,
+        )
+        .bind(scopes)
+        .bind(managed_scopes)
+        .bind(to_rfc3339(updated_at))
+        .bind(broadcaster_id)
+        .bind(twitch_user_id)
+        .execute(&mut **tx)
         .await?;
 
-        let Some(row) = row else {
+        if rows.rows_affected() == 0 {
             return Err(OauthLinkError::NotFound);
-        };
+        }
 
-        row.try_into().map_err(OauthLinkError::Decode)
+        Ok(())
     }
 
     /// Records a validation outcome without changing tokens.
@@ -1919,10 +2892,15 @@ fn map_status(value: &str) -> QueueEntryStatus {
     }
 }
 
+fn map_enqueued_source(value: &str) -> EnqueuedSource {
+    EnqueuedSource::parse(value).unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Duration as ChronoDuration;
+    use chrono::{Duration as ChronoDuration, TimeZone};
+    use twi_overlay_core::types::{NormalizedReward, NormalizedUser};
 
     #[tokio::test]
     async fn oauth_login_state_insert_and_consume() {
@@ -1979,6 +2957,39 @@ mod tests {
         assert_eq!(remaining.0, 1);
     }
 
+    #[tokio::test]
+    async fn oauth_login_state_purge_for_broadcaster_keeps_others() {
+        let db = setup_db().await;
+        sqlx::query(
+            "INSERT INTO broadcasters (id, twitch_broadcaster_id, display_name, timezone, settings_json, created_at, updated_at) \
+             VALUES ('b-2', 'twitch-2', 'Other', 'UTC', '{}', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        )
+        .execute(db.pool())
+        .await
+        .expect("insert broadcaster");
+
+        let repo = db.oauth_login_states();
+        let now = Utc::now();
+        for (idx, broadcaster_id) in ["b-1", "b-1", "b-2"].into_iter().enumerate() {
+            repo.insert(&NewOauthLoginState {
+                state: format!("state-{idx}"),
+                broadcaster_id,
+                code_verifier: "verifier".into(),
+                redirect_to: None,
+                created_at: now,
+                expires_at: now + ChronoDuration::minutes(10),
+            })
+            .await
+            .expect("insert state");
+        }
+
+        let purged = repo.purge_for_broadcaster("b-1").await.expect("purge");
+        assert_eq!(purged, 2);
+
+        assert!(!repo.has_active("b-1", now).await.expect("b-1 active"));
+        assert!(repo.has_active("b-2", now).await.expect("b-2 active"));
+    }
+
     #[tokio::test]
     async fn oauth_login_state_has_active_respects_expiration() {
         let db = setup_db().await;
@@ -2297,6 +3308,55 @@ mod tests {
         db
     }
 
+    #[tokio::test]
+    async fn pragma_snapshot_reports_wal_and_numeric_values() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let url = format!(
+            "sqlite://{}?mode=rwc",
+            dir.path().join("pragma.db").display()
+        );
+        let db = Database::connect(&url).await.expect("connect");
+        db.run_migrations().await.expect("migrations");
+
+        let snapshot = db.pragma_snapshot().await.expect("pragma snapshot");
+        assert_eq!(snapshot.journal_mode, "wal");
+        assert!(snapshot.page_size > 0);
+        assert!(snapshot.page_count > 0);
+        assert!(snapshot.freelist_count >= 0);
+        assert!(snapshot.wal_autocheckpoint >= 0);
+        assert!(snapshot.wal_size_bytes.is_some());
+    }
+
+    #[tokio::test]
+    async fn connect_with_applies_wal_autocheckpoint() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let url = format!(
+            "sqlite://{}?mode=rwc",
+            dir.path().join("autocheckpoint.db").display()
+        );
+        let db = Database::connect_with(
+            &url,
+            DatabaseOptions {
+                wal_autocheckpoint: Some(250),
+                ..DatabaseOptions::default()
+            },
+        )
+        .await
+        .expect("connect");
+
+        // Hold several connections so the pragma is checked beyond the first one.
+        let mut conns = Vec::new();
+        for _ in 0..3 {
+            let mut conn = db.pool().acquire().await.expect("acquire");
+            let pages: i64 = sqlx::query_scalar("PRAGMA wal_autocheckpoint;")
+                .fetch_one(&mut *conn)
+                .await
+                .expect("read pragma");
+            assert_eq!(pages, 250);
+            conns.push(conn);
+        }
+    }
+
     #[tokio::test]
     async fn insert_returns_duplicate_on_conflict() {
         let db = setup_db().await;
@@ -2471,6 +3531,46 @@ mod tests {
         assert!(matches!(err, SettingsUpdateError::NotFound));
     }
 
+    #[tokio::test]
+    async fn update_timezone_persists_valid_zone() {
+        let db = setup_db().await;
+        let repo = db.broadcasters();
+        let command_repo = db.command_log();
+        let mut tx = command_repo.begin().await.expect("begin");
+        repo.update_timezone(&mut tx, "b-1", "Asia/Tokyo", Utc::now())
+            .await
+            .expect("update timezone");
+        tx.commit().await.expect("commit");
+
+        let reloaded = repo.fetch_settings("b-1").await.expect("fetch settings");
+        assert_eq!(reloaded.timezone, "Asia/Tokyo");
+    }
+
+    #[tokio::test]
+    async fn update_timezone_rejects_invalid_zone() {
+        let db = setup_db().await;
+        let repo = db.broadcasters();
+        let command_repo = db.command_log();
+        let mut tx = command_repo.begin().await.expect("begin");
+        let err = repo
+            .update_timezone(&mut tx, "b-1", "Mars/Olympus_Mons", Utc::now())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, TimezoneUpdateError::InvalidTimezone(ref zone) if zone == "Mars/Olympus_Mons")
+        );
+
+        let err = repo
+            .update_timezone(&mut tx, "missing", "UTC", Utc::now())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TimezoneUpdateError::NotFound));
+        tx.commit().await.expect("commit");
+
+        let reloaded = repo.fetch_settings("b-1").await.expect("fetch settings");
+        assert_eq!(reloaded.timezone, "UTC");
+    }
+
     #[tokio::test]
     async fn command_log_find_by_op_id_returns_entry() {
         let db = setup_db().await;
@@ -2479,6 +3579,7 @@ mod tests {
         let record = NewCommandLog {
             broadcaster_id: "b-1",
             op_id: Some("op-1"),
+            source: CommandSource::Admin,
             command_type: "queue.complete",
             payload_json: "{}",
             created_at: Utc::now(),
@@ -2494,6 +3595,7 @@ mod tests {
         assert!(found.is_some());
         let entry = found.unwrap();
         assert_eq!(entry.command_type, "queue.complete");
+        assert_eq!(entry.source.as_deref(), Some("admin"));
         assert_eq!(entry.payload_json, "{}");
     }
 
@@ -2510,6 +3612,7 @@ mod tests {
                 NewCommandLog {
                     broadcaster_id: "b-1",
                     op_id: Some(&format!("op-old-{idx}")),
+                    source: CommandSource::Policy,
                     command_type: "queue.enqueue",
                     payload_json: "{}",
                     created_at: now - ChronoDuration::hours(90 - idx as i64),
@@ -2534,6 +3637,21 @@ mod tests {
         assert_eq!(remaining.0, 2);
     }
 
+    #[tokio::test]
+    async fn wal_page_count_tracks_frames_until_truncated() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let url = format!(
+            "sqlite://{}?mode=rwc",
+            dir.path().join("wal-pages.db").display()
+        );
+        let db = Database::connect(&url).await.expect("connect");
+        db.run_migrations().await.expect("migrations");
+
+        assert!(db.wal_page_count().await.expect("wal pages").unwrap() > 0);
+        db.wal_checkpoint_truncate().await.expect("checkpoint");
+        assert_eq!(db.wal_page_count().await.expect("wal pages"), Some(0));
+    }
+
     #[tokio::test]
     async fn wal_checkpoint_returns_counters() {
         let db = setup_db().await;
@@ -2544,6 +3662,67 @@ mod tests {
         assert!(stats.checkpointed_frames >= -1);
     }
 
+    #[tokio::test]
+    async fn connect_reports_unusable_path_with_redacted_url() {
+        let err = Database::connect("sqlite:///nonexistent-dir/twi-overlay/app.db?mode=rwc")
+            .await
+            .err()
+            .expect("connect should fail");
+        match &err {
+            StorageError::Connect { url, .. } => {
+                assert_eq!(
+                    url,
+                    "sqlite:///nonexistent-dir/twi-overlay/app.db?<redacted>"
+                );
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert!(err
+            .to_string()
+            .starts_with("failed to connect to sqlite at sqlite:///nonexistent-dir/twi-overlay/app.db?<redacted>: "));
+    }
+
+    #[test]
+    fn new_queue_entry_from_enqueue_command_maps_fields() {
+        let at = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+        let command = EnqueueCommand {
+            broadcaster_id: "b-1".into(),
+            issued_at: at - ChronoDuration::seconds(1),
+            source: CommandSource::Policy,
+            user: NormalizedUser {
+                id: "user-1".into(),
+                login: Some("alice".into()),
+                display_name: None,
+            },
+            reward: NormalizedReward {
+                id: "reward-1".into(),
+                title: Some("Join".into()),
+                cost: Some(100),
+            },
+            redemption_id: "red-1".into(),
+            managed: Some(true),
+        };
+
+        let entry = NewQueueEntry::from_enqueue_command(&command, "q-1".into(), at);
+        assert_eq!(entry.id, "q-1");
+        assert_eq!(entry.broadcaster_id, "b-1");
+        assert_eq!(entry.user_id, "user-1");
+        assert_eq!(entry.user_login, "alice");
+        assert_eq!(entry.user_display_name, "alice");
+        assert_eq!(entry.reward_id, "reward-1");
+        assert_eq!(entry.redemption_id.as_deref(), Some("red-1"));
+        assert_eq!(entry.enqueued_at, at);
+        assert_eq!(entry.last_updated_at, at);
+        assert_eq!(entry.status, QueueEntryStatus::Queued);
+        assert!(entry.managed);
+
+        let domain = entry.to_domain();
+        assert_eq!(domain.id, "q-1");
+        assert_eq!(domain.user_display_name, "alice");
+        assert_eq!(domain.redemption_id.as_deref(), Some("red-1"));
+        assert!(domain.managed);
+    }
+
     #[tokio::test]
     async fn queue_mark_completed_transitions_entry() {
         let db = setup_db().await;
@@ -2559,12 +3738,14 @@ mod tests {
             user_display_name: "Alice".into(),
             user_avatar: None,
             reward_id: "reward-1",
+            reward_title: None,
             redemption_id: Some("red-1".into()),
             enqueued_at: now,
             status: QueueEntryStatus::Queued,
             status_reason: None,
             managed: true,
             last_updated_at: now,
+            enqueued_source: EnqueuedSource::Policy,
         };
         queue_repo
             .insert_entry(&mut tx, &new_entry)
@@ -2598,12 +3779,14 @@ mod tests {
             user_display_name: "Bob".into(),
             user_avatar: None,
             reward_id: "reward-1",
+            reward_title: None,
             redemption_id: Some("red-2".into()),
             enqueued_at: now,
             status: QueueEntryStatus::Queued,
             status_reason: None,
             managed: false,
             last_updated_at: now,
+            enqueued_source: EnqueuedSource::Policy,
         };
         queue_repo
             .insert_entry(&mut tx, &new_entry)
@@ -2650,12 +3833,14 @@ mod tests {
             user_display_name: "Cara".into(),
             user_avatar: None,
             reward_id: "reward-1",
+            reward_title: None,
             redemption_id: Some("red-3".into()),
             enqueued_at: now,
             status: QueueEntryStatus::Queued,
             status_reason: None,
             managed: false,
             last_updated_at: now,
+            enqueued_source: EnqueuedSource::Policy,
         };
         queue_repo
             .insert_entry(&mut tx, &new_entry)
@@ -2683,6 +3868,77 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn concurrent_completions_with_immediate_transactions_apply_once() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let url = format!(
+            "sqlite://{}?mode=rwc",
+            dir.path().join("immediate.db").display()
+        );
+        let db = Database::connect(&url).await.expect("connect");
+        db.run_migrations().await.expect("migrations");
+        sqlx::query(
+            "INSERT INTO broadcasters (id, twitch_broadcaster_id, display_name, timezone, settings_json, created_at, updated_at) \
+             VALUES ('b-1', 'twitch-1', 'Example', 'UTC', '{}', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        )
+        .execute(db.pool())
+        .await
+        .expect("insert broadcaster");
+
+        let queue_repo = db.queue();
+        let command_repo = db.command_log();
+        let mut tx = command_repo.begin().await.expect("begin");
+        let now = Utc::now();
+        queue_repo
+            .insert_entry(
+                &mut tx,
+                &NewQueueEntry {
+                    id: "q-race".into(),
+                    broadcaster_id: "b-1",
+                    user_id: "user-1",
+                    user_login: "alice".into(),
+                    user_display_name: "Alice".into(),
+                    user_avatar: None,
+                    reward_id: "reward-1",
+                    reward_title: None,
+                    redemption_id: Some("red-race".into()),
+                    enqueued_at: now,
+                    status: QueueEntryStatus::Queued,
+                    status_reason: None,
+                    managed: false,
+                    last_updated_at: now,
+                    enqueued_source: EnqueuedSource::Policy,
+                },
+            )
+            .await
+            .expect("insert entry");
+        tx.commit().await.expect("commit");
+
+        let complete = || async {
+            let command_repo = db.command_log();
+            let mut tx = command_repo
+                .begin_immediate()
+                .await
+                .expect("begin immediate");
+            // Give the other completion a chance to read the entry before this one writes.
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            let result = db
+                .queue()
+                .mark_completed(&mut tx, "b-1", "q-race", Utc::now())
+                .await;
+            tx.commit().await.expect("commit");
+            result
+        };
+        let (first, second) = tokio::join!(complete(), complete());
+
+        let results = [first, second];
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+        assert!(results.iter().any(|result| matches!(
+            result,
+            Err(QueueError::InvalidTransition(QueueEntryStatus::Completed))
+        )));
+    }
+
     #[tokio::test]
     async fn queue_find_by_redemption_within_transaction() {
         let db = setup_db().await;
@@ -2698,12 +3954,14 @@ mod tests {
             user_display_name: "Lookup".into(),
             user_avatar: None,
             reward_id: "reward-lookup",
+            reward_title: None,
             redemption_id: Some("red-lookup".into()),
             enqueued_at: now,
             status: QueueEntryStatus::Queued,
             status_reason: None,
             managed: false,
             last_updated_at: now,
+            enqueued_source: EnqueuedSource::Policy,
         };
         queue_repo
             .insert_entry(&mut tx, &new_entry)
@@ -2734,12 +3992,14 @@ mod tests {
             user_display_name: "Flag".into(),
             user_avatar: None,
             reward_id: "reward-flag",
+            reward_title: None,
             redemption_id: Some("red-flag".into()),
             enqueued_at: now,
             status: QueueEntryStatus::Queued,
             status_reason: None,
             managed: false,
             last_updated_at: now,
+            enqueued_source: EnqueuedSource::Policy,
         };
         queue_repo
             .insert_entry(&mut tx, &new_entry)
@@ -2813,6 +4073,352 @@ mod tests {
         assert_eq!(value, Some(0));
     }
 
+    #[tokio::test]
+    async fn queue_list_active_page_applies_limit_and_offset() {
+        let db = setup_db().await;
+        let queue_repo = db.queue();
+        let command_repo = db.command_log();
+        let mut tx = command_repo.begin().await.expect("begin");
+        let now = Utc::now();
+        for idx in 0..3 {
+            queue_repo
+                .insert_entry(
+                    &mut tx,
+                    &NewQueueEntry {
+                        id: format!("q-{idx}"),
+                        broadcaster_id: "b-1",
+                        user_id: "user-1",
+                        user_login: "alice".into(),
+                        user_display_name: "Alice".into(),
+                        user_avatar: None,
+                        reward_id: "reward-1",
+                        reward_title: None,
+                        redemption_id: Some(format!("red-{idx}")),
+                        enqueued_at: now + ChronoDuration::seconds(idx),
+                        status: QueueEntryStatus::Queued,
+                        status_reason: None,
+                        managed: false,
+                        last_updated_at: now,
+                        enqueued_source: EnqueuedSource::Policy,
+                    },
+                )
+                .await
+                .expect("insert entry");
+        }
+        tx.commit().await.expect("commit");
+
+        let page = queue_repo
+            .list_active_with_counts_page("b-1", "2024-01-01", 2, 1)
+            .await
+            .expect("list page");
+        let ids: Vec<_> = page.into_iter().map(|row| row.id).collect();
+        assert_eq!(ids, vec!["q-1".to_string(), "q-2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn queue_reenqueue_to_back_sorts_after_others() {
+        let db = setup_db().await;
+        let queue_repo = db.queue();
+        let command_repo = db.command_log();
+        let mut tx = command_repo.begin().await.expect("begin");
+        let now = Utc::now();
+        for idx in 0..3 {
+            queue_repo
+                .insert_entry(
+                    &mut tx,
+                    &NewQueueEntry {
+                        id: format!("q-{idx}"),
+                        broadcaster_id: "b-1",
+                        user_id: "user-1",
+                        user_login: "alice".into(),
+                        user_display_name: "Alice".into(),
+                        user_avatar: None,
+                        reward_id: "reward-1",
+                        reward_title: None,
+                        redemption_id: Some(format!("red-{idx}")),
+                        enqueued_at: now - ChronoDuration::minutes(10 - idx),
+                        status: QueueEntryStatus::Queued,
+                        status_reason: None,
+                        managed: false,
+                        last_updated_at: now,
+                        enqueued_source: EnqueuedSource::Policy,
+                    },
+                )
+                .await
+                .expect("insert entry");
+        }
+        tx.commit().await.expect("commit");
+
+        let mut tx = command_repo.begin().await.expect("begin reenqueue");
+        let moved = queue_repo
+            .reenqueue_to_back(&mut tx, "b-1", "q-0", now)
+            .await
+            .expect("reenqueue");
+        assert_eq!(moved.status, QueueEntryStatus::Queued);
+        tx.commit().await.expect("commit reenqueue");
+
+        let ids: Vec<_> = queue_repo
+            .list_active_with_counts("b-1", "2024-01-01")
+            .await
+            .expect("list active")
+            .into_iter()
+            .map(|row| row.id)
+            .collect();
+        assert_eq!(ids, vec!["q-1", "q-2", "q-0"]);
+
+        let mut tx = command_repo.begin().await.expect("begin complete");
+        queue_repo
+            .mark_completed(&mut tx, "b-1", "q-1", now)
+            .await
+            .expect("complete");
+        let err = queue_repo
+            .reenqueue_to_back(&mut tx, "b-1", "q-1", now)
+            .await
+            .expect_err("terminal entry");
+        assert!(matches!(
+            err,
+            QueueError::InvalidTransition(QueueEntryStatus::Completed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn queue_count_terminal_today_respects_day_window() {
+        let db = setup_db().await;
+        let queue_repo = db.queue();
+        let command_repo = db.command_log();
+        let day_start = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        let day_end = day_start + ChronoDuration::days(1);
+        let completions = [
+            ("q-0", day_start - ChronoDuration::seconds(1)),
+            ("q-1", day_start),
+            ("q-2", day_end - ChronoDuration::seconds(1)),
+            ("q-3", day_end),
+        ];
+
+        let mut tx = command_repo.begin().await.expect("begin");
+        for (id, completed_at) in completions {
+            queue_repo
+                .insert_entry(
+                    &mut tx,
+                    &NewQueueEntry {
+                        id: id.to_string(),
+                        broadcaster_id: "b-1",
+                        user_id: "user-1",
+                        user_login: "alice".into(),
+                        user_display_name: "Alice".into(),
+                        user_avatar: None,
+                        reward_id: "reward-1",
+                        reward_title: None,
+                        redemption_id: None,
+                        enqueued_at: completed_at - ChronoDuration::minutes(5),
+                        status: QueueEntryStatus::Queued,
+                        status_reason: None,
+                        managed: false,
+                        last_updated_at: completed_at - ChronoDuration::minutes(5),
+                        enqueued_source: EnqueuedSource::Policy,
+                    },
+                )
+                .await
+                .expect("insert entry");
+            queue_repo
+                .mark_completed(&mut tx, "b-1", id, completed_at)
+                .await
+                .expect("complete");
+        }
+        tx.commit().await.expect("commit");
+
+        let completed = queue_repo
+            .count_terminal_today("b-1", day_start, day_end, QueueEntryStatus::Completed)
+            .await
+            .expect("count completed");
+        assert_eq!(completed, 2);
+
+        let removed = queue_repo
+            .count_terminal_today("b-1", day_start, day_end, QueueEntryStatus::Removed)
+            .await
+            .expect("count removed");
+        assert_eq!(removed, 0);
+
+        let listed = queue_repo
+            .list_terminal_with_counts(
+                "b-1",
+                "2024-01-02",
+                day_start,
+                day_end,
+                QueueEntryStatus::Completed,
+            )
+            .await
+            .expect("list completed");
+        let ids: Vec<_> = listed.iter().map(|row| row.id.as_str()).collect();
+        assert_eq!(ids, vec!["q-1", "q-2"]);
+    }
+
+    #[tokio::test]
+    async fn queue_list_queued_before_skips_fresh_and_terminal_entries() {
+        let db = setup_db().await;
+        let queue_repo = db.queue();
+        let command_repo = db.command_log();
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+        let entries = [
+            ("q-old", now - ChronoDuration::hours(2)),
+            ("q-older", now - ChronoDuration::hours(3)),
+            ("q-done", now - ChronoDuration::hours(4)),
+            ("q-fresh", now - ChronoDuration::minutes(5)),
+        ];
+
+        let mut tx = command_repo.begin().await.expect("begin");
+        for (id, enqueued_at) in entries {
+            queue_repo
+                .insert_entry(
+                    &mut tx,
+                    &NewQueueEntry {
+                        id: id.to_string(),
+                        broadcaster_id: "b-1",
+                        user_id: "user-1",
+                        user_login: "alice".into(),
+                        user_display_name: "Alice".into(),
+                        user_avatar: None,
+                        reward_id: "reward-1",
+                        reward_title: None,
+                        redemption_id: None,
+                        enqueued_at,
+                        status: QueueEntryStatus::Queued,
+                        status_reason: None,
+                        managed: false,
+                        last_updated_at: enqueued_at,
+                        enqueued_source: EnqueuedSource::Policy,
+                    },
+                )
+                .await
+                .expect("insert entry");
+        }
+        queue_repo
+            .mark_completed(&mut tx, "b-1", "q-done", now)
+            .await
+            .expect("complete");
+        tx.commit().await.expect("commit");
+
+        let ids = queue_repo
+            .list_queued_before("b-1", now - ChronoDuration::hours(1), 10)
+            .await
+            .expect("list stale");
+        assert_eq!(ids, vec!["q-older".to_string(), "q-old".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn queue_stats_aggregates_active_and_today_counts() {
+        let db = setup_db().await;
+        let queue_repo = db.queue();
+        let command_repo = db.command_log();
+        let day_start = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        let day_end = day_start + ChronoDuration::days(1);
+        let today = day_start + ChronoDuration::hours(10);
+        let yesterday = day_start - ChronoDuration::hours(2);
+        let entries = [
+            ("q-1", "user-1", today),
+            ("q-2", "user-2", today),
+            ("q-3", "user-1", today),
+            ("q-4", "user-3", yesterday),
+            ("q-done", "user-2", today),
+            ("q-done-old", "user-4", yesterday),
+            ("q-removed", "user-5", today),
+        ];
+
+        let mut tx = command_repo.begin().await.expect("begin");
+        for (id, user_id, enqueued_at) in entries {
+            queue_repo
+                .insert_entry(
+                    &mut tx,
+                    &NewQueueEntry {
+                        id: id.to_string(),
+                        broadcaster_id: "b-1",
+                        user_id,
+                        user_login: user_id.into(),
+                        user_display_name: user_id.into(),
+                        user_avatar: None,
+                        reward_id: "reward-1",
+                        reward_title: None,
+                        redemption_id: None,
+                        enqueued_at,
+                        status: QueueEntryStatus::Queued,
+                        status_reason: None,
+                        managed: false,
+                        last_updated_at: enqueued_at,
+                        enqueued_source: EnqueuedSource::Policy,
+                    },
+                )
+                .await
+                .expect("insert entry");
+        }
+        queue_repo
+            .mark_completed(&mut tx, "b-1", "q-done", today)
+            .await
+            .expect("complete");
+        queue_repo
+            .mark_completed(&mut tx, "b-1", "q-done-old", yesterday)
+            .await
+            .expect("complete old");
+        queue_repo
+            .mark_removed(
+                &mut tx,
+                "b-1",
+                "q-removed",
+                QueueRemovalReason::ExplicitRemove,
+                today,
+            )
+            .await
+            .expect("remove");
+        tx.commit().await.expect("commit");
+
+        let stats = queue_repo
+            .stats("b-1", (day_start, day_end), 2)
+            .await
+            .expect("stats");
+        assert_eq!(
+            stats,
+            QueueStats {
+                active: 4,
+                serving: 2,
+                completed_today: 1,
+                removed_today: 1,
+                distinct_users_today: 3,
+            }
+        );
+
+        let empty = queue_repo
+            .stats("b-unknown", (day_start, day_end), 2)
+            .await
+            .expect("stats");
+        assert_eq!(empty, QueueStats::default());
+    }
+
+    #[tokio::test]
+    async fn sse_token_mark_used_rejects_reuse_until_purged() {
+        let db = setup_db().await;
+        let repo = db.sse_tokens();
+        let now = Utc::now();
+        let record = NewSseTokenUse {
+            jti: "jti-1",
+            broadcaster_id: "b-1",
+            audience: "overlay",
+            used_at: now,
+            expires_at: now + ChronoDuration::minutes(5),
+        };
+
+        assert!(repo.mark_used(&record).await.expect("first use"));
+        assert!(!repo.mark_used(&record).await.expect("second use"));
+
+        let purged = repo
+            .purge_expired(now + ChronoDuration::minutes(1), 100)
+            .await
+            .expect("purge before expiry");
+        assert_eq!(purged, 0);
+        let purged = repo
+            .purge_expired(now + ChronoDuration::minutes(5), 100)
+            .await
+            .expect("purge after expiry");
+        assert_eq!(purged, 1);
+    }
     #[tokio::test]
     async fn counter_fetch_value_reads_current_count() {
         let db = setup_db().await;
@@ -2839,6 +4445,47 @@ mod tests {
             .expect("fetch missing");
         assert_eq!(missing, None);
     }
+
+    #[tokio::test]
+    async fn counter_list_for_day_page_slices_and_counts() {
+        let db = setup_db().await;
+        for index in 0..25 {
+            sqlx::query(
+                "INSERT INTO daily_counters(day, broadcaster_id, user_id, count, updated_at) VALUES ('2024-01-01','b-1', ?, ?, '2024-01-01T00:00:00Z')",
+            )
+            .bind(format!("user-{index:02}"))
+            .bind(index)
+            .execute(db.pool())
+            .await
+            .expect("insert counter");
+        }
+        sqlx::query(
+            "INSERT INTO daily_counters(day, broadcaster_id, user_id, count, updated_at) VALUES ('2024-01-02','b-1','user-other', 1, '2024-01-02T00:00:00Z')",
+        )
+        .execute(db.pool())
+        .await
+        .expect("insert other day");
+
+        let counter_repo = db.daily_counters();
+        let page = counter_repo
+            .list_for_day_page("b-1", "2024-01-01", 10, 10)
+            .await
+            .expect("page");
+        assert_eq!(page.total, 25);
+        let users: Vec<_> = page.counters.iter().map(|c| c.user_id.as_str()).collect();
+        assert_eq!(users.len(), 10);
+        assert_eq!(users.first(), Some(&"user-10"));
+        assert_eq!(users.last(), Some(&"user-19"));
+        assert_eq!(page.counters[0].count, 10);
+
+        let tail = counter_repo
+            .list_for_day_page("b-1", "2024-01-01", 10, 20)
+            .await
+            .expect("tail");
+        assert_eq!(tail.total, 25);
+        assert_eq!(tail.counters.len(), 5);
+        assert_eq!(tail.counters[4].user_id, "user-24");
+    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -2850,6 +4497,7 @@ struct QueueEntryRow {
     user_display_name: String,
     user_avatar: Option<String>,
     reward_id: String,
+    reward_title: Option<String>,
     redemption_id: Option<String>,
     #[sqlx(rename = "enqueued_at: DateTime<Utc>")]
     enqueued_at: DateTime<Utc>,
@@ -2858,6 +4506,7 @@ struct QueueEntryRow {
     managed: i64,
     #[sqlx(rename = "last_updated_at: DateTime<Utc>")]
     last_updated_at: DateTime<Utc>,
+    enqueued_source: String,
 }
 
 impl QueueEntryRow {
@@ -2870,12 +4519,14 @@ impl QueueEntryRow {
             user_display_name: self.user_display_name,
             user_avatar: self.user_avatar,
             reward_id: self.reward_id,
+            reward_title: self.reward_title,
             redemption_id: self.redemption_id,
             enqueued_at: self.enqueued_at,
             status: map_status(&self.status),
             status_reason: self.status_reason,
             managed: self.managed != 0,
             last_updated_at: self.last_updated_at,
+            enqueued_source: map_enqueued_source(&self.enqueued_source),
         }
     }
 }
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use reqwest::{Client, Method, Response, StatusCode};
-use serde::{de::DeserializeOwned, Deserialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
 use thiserror::Error;
 use url::Url;
 
@@ -38,7 +38,7 @@ impl HelixClient {
             query.append_pair("id", request.redemption_id);
         }
 
-        let body = serde_json::json!({ "status": request.status.as_str() });
+        let body = serde_json::json!({ "status": request.status.request_value()? });
         let response = self
             .authorized_request(Method::PATCH, url, access_token)
             .json(&body)
@@ -54,32 +54,44 @@ impl HelixClient {
         access_token: &str,
         params: &ListRedemptionsParams<'_>,
     ) -> Result<HelixRedemptionPage, HelixError> {
+        let url = self.list_redemptions_url(params)?;
+        let response = self
+            .authorized_request(Method::GET, url, access_token)
+            .send()
+            .await?;
+
+        parse_json::<HelixRedemptionListResponse>(response)
+            .await
+            .map(HelixRedemptionPage::from)
+    }
+
+    fn list_redemptions_url(&self, params: &ListRedemptionsParams<'_>) -> Result<Url, HelixError> {
         let mut url = self
             .base_url
             .join("channel_points/custom_rewards/redemptions")?;
         {
             let mut query = url.query_pairs_mut();
             query.append_pair("broadcaster_id", params.broadcaster_id);
-            query.append_pair("status", params.status.as_str());
+            query.append_pair("status", params.status.request_value()?);
             if let Some(reward_id) = params.reward_id {
                 query.append_pair("reward_id", reward_id);
             }
-            if let Some(after) = params.after {
+            for id in params.ids {
+                query.append_pair("id", id);
+            }
+            // Helix rejects an empty cursor instead of treating it as the first page.
+            if let Some(after) = params.after.filter(|after| !after.is_empty()) {
                 query.append_pair("after", after);
             }
             if let Some(first) = params.first {
+                let first = first.clamp(1, MAX_REDEMPTIONS_PAGE_SIZE);
                 query.append_pair("first", &first.to_string());
             }
+            if let Some(sort) = params.sort {
+                query.append_pair("sort", sort.as_str());
+            }
         }
-
-        let response = self
-            .authorized_request(Method::GET, url, access_token)
-            .send()
-            .await?;
-
-        parse_json::<HelixRedemptionListResponse>(response)
-            .await
-            .map(HelixRedemptionPage::from)
+        Ok(url)
     }
 
     fn authorized_request(
@@ -103,34 +115,90 @@ pub struct UpdateRedemptionRequest<'a> {
     pub status: HelixRedemptionStatus,
 }
 
+/// Largest `first` Helix accepts for redemption listings.
+pub const MAX_REDEMPTIONS_PAGE_SIZE: u32 = 50;
+
 /// Parameters when listing redemptions.
 pub struct ListRedemptionsParams<'a> {
     pub broadcaster_id: &'a str,
     pub reward_id: Option<&'a str>,
+    /// Specific redemptions to fetch, sent as repeated `id` parameters.
+    pub ids: &'a [&'a str],
     pub status: HelixRedemptionStatus,
+    /// Pagination cursor; an empty cursor is omitted.
     pub after: Option<&'a str>,
+    /// Page size, clamped to `1..=MAX_REDEMPTIONS_PAGE_SIZE`.
     pub first: Option<u32>,
+    pub sort: Option<HelixRedemptionSort>,
+}
+
+/// Ordering of listed redemptions; Helix defaults to oldest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelixRedemptionSort {
+    Oldest,
+    Newest,
+}
+
+impl HelixRedemptionSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Oldest => "OLDEST",
+            Self::Newest => "NEWEST",
+        }
+    }
 }
 
 /// Possible redemption statuses.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+///
+/// Statuses Twitch may add later deserialize into `Unknown` so a single record does not fail
+/// the whole page; they are never sent back in requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HelixRedemptionStatus {
     Unfulfilled,
     Fulfilled,
     Canceled,
+    Unknown(String),
 }
 
 impl HelixRedemptionStatus {
-    pub fn as_str(self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Self::Unfulfilled => "UNFULFILLED",
             Self::Fulfilled => "FULFILLED",
             Self::Canceled => "CANCELED",
+            Self::Unknown(value) => value,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+
+    fn request_value(&self) -> Result<&'static str, HelixError> {
+        match self {
+            Self::Unfulfilled => Ok("UNFULFILLED"),
+            Self::Fulfilled => Ok("FULFILLED"),
+            Self::Canceled => Ok("CANCELED"),
+            Self::Unknown(value) => Err(HelixError::UnsupportedStatus(value.clone())),
         }
     }
 }
 
+impl<'de> Deserialize<'de> for HelixRedemptionStatus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Ok(match value.as_str() {
+            "UNFULFILLED" => Self::Unfulfilled,
+            "FULFILLED" => Self::Fulfilled,
+            "CANCELED" => Self::Canceled,
+            _ => Self::Unknown(value),
+        })
+    }
+}
+
 /// Page of redemption results.
 #[derive(Debug, Clone, PartialEq)]
 pub struct HelixRedemptionPage {
@@ -142,7 +210,10 @@ impl From<HelixRedemptionListResponse> for HelixRedemptionPage {
     fn from(value: HelixRedemptionListResponse) -> Self {
         Self {
             data: value.data,
-            cursor: value.pagination.and_then(|p| p.cursor),
+            cursor: value
+                .pagination
+                .and_then(|p| p.cursor)
+                .filter(|cursor| !cursor.is_empty()),
         }
     }
 }
@@ -192,6 +263,20 @@ pub enum HelixError {
     Http(#[from] reqwest::Error),
     #[error("unexpected status {status}: {body}")]
     Status { status: StatusCode, body: String },
+    #[error("redemption status {0} cannot be sent to Helix")]
+    UnsupportedStatus(String),
+}
+
+impl HelixError {
+    /// Whether repeating the same request later may succeed (timeouts, connection
+    /// failures, 429 and 5xx). Auth and other 4xx failures are not retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Http(err) => crate::is_retryable_http(err),
+            Self::Status { status, .. } => crate::is_retryable_status(*status),
+            Self::Url(_) | Self::UnsupportedStatus(_) => false,
+        }
+    }
 }
 
 async fn ensure_success(response: Response) -> Result<(), HelixError> {
@@ -282,9 +367,11 @@ mod tests {
                 &ListRedemptionsParams {
                     broadcaster_id: "b-1",
                     reward_id: None,
+                    ids: &[],
                     status: HelixRedemptionStatus::Unfulfilled,
                     after: None,
                     first: Some(50),
+                    sort: None,
                 },
             )
             .await
@@ -296,6 +383,141 @@ mod tests {
         assert_eq!(result.data[0].reward.title, "Wave");
     }
 
+    fn list_params<'a>(
+        reward_id: Option<&'a str>,
+        ids: &'a [&'a str],
+        after: Option<&'a str>,
+        first: Option<u32>,
+        sort: Option<HelixRedemptionSort>,
+    ) -> ListRedemptionsParams<'a> {
+        ListRedemptionsParams {
+            broadcaster_id: "b-1",
+            reward_id,
+            ids,
+            status: HelixRedemptionStatus::Unfulfilled,
+            after,
+            first,
+            sort,
+        }
+    }
+
+    #[test]
+    fn list_redemptions_url_encodes_each_parameter_combination() {
+        let client = client(&Url::parse("https://api.twitch.tv/helix/").expect("url"));
+        let cases = [
+            (
+                list_params(None, &[], None, None, None),
+                "broadcaster_id=b-1&status=UNFULFILLED",
+            ),
+            (
+                list_params(Some("reward-1"), &[], None, None, None),
+                "broadcaster_id=b-1&status=UNFULFILLED&reward_id=reward-1",
+            ),
+            (
+                list_params(None, &["red-1"], None, None, None),
+                "broadcaster_id=b-1&status=UNFULFILLED&id=red-1",
+            ),
+            (
+                list_params(Some("reward-1"), &["red-1", "red-2"], None, None, None),
+                "broadcaster_id=b-1&status=UNFULFILLED&reward_id=reward-1&id=red-1&id=red-2",
+            ),
+            (
+                list_params(None, &[], Some("eyJiIjpudWxsfQ=="), Some(20), None),
+                "broadcaster_id=b-1&status=UNFULFILLED&after=eyJiIjpudWxsfQ%3D%3D&first=20",
+            ),
+            (
+                list_params(None, &[], Some(""), Some(0), None),
+                "broadcaster_id=b-1&status=UNFULFILLED&first=1",
+            ),
+            (
+                list_params(None, &[], None, Some(100), Some(HelixRedemptionSort::Newest)),
+                "broadcaster_id=b-1&status=UNFULFILLED&first=50&sort=NEWEST",
+            ),
+            (
+                list_params(
+                    Some("reward-1"),
+                    &["red-1"],
+                    Some("cursor"),
+                    Some(50),
+                    Some(HelixRedemptionSort::Oldest),
+                ),
+                "broadcaster_id=b-1&status=UNFULFILLED&reward_id=reward-1&id=red-1&after=cursor&first=50&sort=OLDEST",
+            ),
+        ];
+
+        for (params, expected) in cases {
+            let url = client.list_redemptions_url(&params).expect("url");
+            assert_eq!(
+                url.path(),
+                "/helix/channel_points/custom_rewards/redemptions"
+            );
+            assert_eq!(url.query(), Some(expected));
+        }
+
+        let mut params = list_params(None, &[], None, None, None);
+        for status in [
+            HelixRedemptionStatus::Fulfilled,
+            HelixRedemptionStatus::Canceled,
+        ] {
+            let expected = format!("broadcaster_id=b-1&status={}", status.as_str());
+            params.status = status;
+            let url = client.list_redemptions_url(&params).expect("url");
+            assert_eq!(url.query(), Some(expected.as_str()));
+        }
+
+        params.status = HelixRedemptionStatus::Unknown("PENDING".to_string());
+        assert!(matches!(
+            client.list_redemptions_url(&params),
+            Err(HelixError::UnsupportedStatus(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn list_redemptions_sends_every_parameter() {
+        let server = MockServer::start_async().await;
+        let base = Url::parse(&server.url("/helix/")).expect("url");
+        let client = client(&base);
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/helix/channel_points/custom_rewards/redemptions")
+                    .query_param("broadcaster_id", "b-1")
+                    .query_param("reward_id", "reward-1")
+                    .query_param("id", "red-1")
+                    .query_param("id", "red-2")
+                    .query_param("status", "CANCELED")
+                    .query_param("after", "a+b/c=")
+                    .query_param("first", "25")
+                    .query_param("sort", "NEWEST")
+                    .header("Authorization", "Bearer token")
+                    .header("Client-Id", "client-id");
+                then.status(200)
+                    .json_body(json!({ "data": [], "pagination": { "cursor": "" } }));
+            })
+            .await;
+
+        let page = client
+            .list_redemptions(
+                "token",
+                &ListRedemptionsParams {
+                    broadcaster_id: "b-1",
+                    reward_id: Some("reward-1"),
+                    ids: &["red-1", "red-2"],
+                    status: HelixRedemptionStatus::Canceled,
+                    after: Some("a+b/c="),
+                    first: Some(25),
+                    sort: Some(HelixRedemptionSort::Newest),
+                },
+            )
+            .await
+            .expect("list redemptions");
+        mock.assert_async().await;
+
+        assert!(page.data.is_empty());
+        assert_eq!(page.cursor, None, "empty cursor marks the last page");
+    }
+
     #[tokio::test]
     async fn update_redemption_sends_patch() {
         let server = MockServer::start_async().await;
@@ -351,9 +573,11 @@ mod tests {
                 &ListRedemptionsParams {
                     broadcaster_id: "b-1",
                     reward_id: None,
+                    ids: &[],
                     status: HelixRedemptionStatus::Unfulfilled,
                     after: None,
                     first: None,
+                    sort: None,
                 },
             )
             .await
@@ -366,4 +590,68 @@ mod tests {
             other => panic!("unexpected error: {:?}", other),
         }
     }
+
+    #[test]
+    fn redemption_status_round_trips_known_values() {
+        for status in [
+            HelixRedemptionStatus::Unfulfilled,
+            HelixRedemptionStatus::Fulfilled,
+            HelixRedemptionStatus::Canceled,
+        ] {
+            let parsed: HelixRedemptionStatus =
+                serde_json::from_value(json!(status.as_str())).expect("known status");
+            assert_eq!(parsed, status);
+            assert_eq!(status.request_value().expect("sendable"), status.as_str());
+        }
+    }
+
+    #[test]
+    fn redemption_status_falls_back_to_unknown() {
+        let parsed: HelixRedemptionStatus =
+            serde_json::from_value(json!("PENDING_REVIEW")).expect("unknown status");
+        assert_eq!(
+            parsed,
+            HelixRedemptionStatus::Unknown("PENDING_REVIEW".to_string())
+        );
+        assert!(parsed.is_unknown());
+        assert!(matches!(
+            parsed.request_value(),
+            Err(HelixError::UnsupportedStatus(value)) if value == "PENDING_REVIEW"
+        ));
+    }
+
+    fn status_error(status: StatusCode) -> HelixError {
+        HelixError::Status {
+            status,
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn status_errors_retry_only_on_rate_limit_and_server_errors() {
+        assert!(status_error(StatusCode::TOO_MANY_REQUESTS).is_retryable());
+        assert!(status_error(StatusCode::INTERNAL_SERVER_ERROR).is_retryable());
+        assert!(status_error(StatusCode::SERVICE_UNAVAILABLE).is_retryable());
+        assert!(!status_error(StatusCode::BAD_REQUEST).is_retryable());
+        assert!(!status_error(StatusCode::UNAUTHORIZED).is_retryable());
+        assert!(!status_error(StatusCode::FORBIDDEN).is_retryable());
+        assert!(!status_error(StatusCode::NOT_FOUND).is_retryable());
+    }
+
+    #[test]
+    fn url_errors_are_not_retryable() {
+        let err = HelixError::from(Url::parse("not a url").expect_err("invalid url"));
+        assert!(!err.is_retryable());
+        assert!(!HelixError::UnsupportedStatus("PENDING_REVIEW".into()).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn connection_failures_are_retryable() {
+        let err = Client::new()
+            .get("http://127.0.0.1:1/")
+            .send()
+            .await
+            .expect_err("connection refused");
+        assert!(HelixError::from(err).is_retryable());
+    }
 }
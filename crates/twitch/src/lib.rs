@@ -2,9 +2,22 @@ pub mod helix;
 pub mod oauth;
 
 pub use helix::{
-    HelixClient, HelixError, HelixRedemption, HelixRedemptionPage, HelixRedemptionStatus,
-    ListRedemptionsParams, UpdateRedemptionRequest,
+    HelixClient, HelixError, HelixRedemption, HelixRedemptionPage, HelixRedemptionSort,
+    HelixRedemptionStatus, ListRedemptionsParams, UpdateRedemptionRequest,
 };
 pub use oauth::{
     AuthorizeUrlParams, OAuthError, TokenResponse, TwitchOAuthClient, ValidateTokenResponse,
 };
+
+use reqwest::StatusCode;
+
+/// Statuses worth retrying later: rate limiting and server-side failures.
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Transport failures worth retrying later: timeouts, refused connections and
+/// retryable statuses surfaced through `error_for_status`.
+pub(crate) fn is_retryable_http(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_connect() || err.status().is_some_and(is_retryable_status)
+}
@@ -159,6 +159,18 @@ pub enum OAuthError {
     Status { status: StatusCode, body: String },
 }
 
+impl OAuthError {
+    /// Whether repeating the same request later may succeed (timeouts, connection
+    /// failures, 429 and 5xx). Rejected credentials and other 4xx are final.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Http(err) => crate::is_retryable_http(err),
+            Self::Status { status, .. } => crate::is_retryable_status(*status),
+            Self::Url(_) => false,
+        }
+    }
+}
+
 async fn parse_json<T>(response: Response) -> Result<T, OAuthError>
 where
     T: DeserializeOwned,
@@ -246,6 +258,7 @@ mod tests {
         mock.assert_async().await;
         assert_eq!(response.access_token, "access");
         assert_eq!(response.refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(response.scope, vec!["scope".to_string()]);
     }
 
     #[tokio::test]
@@ -264,7 +277,7 @@ mod tests {
                     "access_token": "new-access",
                     "refresh_token": "new-refresh",
                     "expires_in": 4000,
-                    "scope": [],
+                    "scope": ["channel:read:redemptions", "moderator:read:chatters"],
                     "token_type": "bearer"
                 }));
             })
@@ -274,6 +287,13 @@ mod tests {
         mock.assert_async().await;
         assert_eq!(response.access_token, "new-access");
         assert_eq!(response.refresh_token.as_deref(), Some("new-refresh"));
+        assert_eq!(
+            response.scope,
+            vec![
+                "channel:read:redemptions".to_string(),
+                "moderator:read:chatters".to_string()
+            ]
+        );
     }
 
     #[tokio::test]
@@ -328,4 +348,38 @@ mod tests {
             other => panic!("unexpected error: {:?}", other),
         }
     }
+
+    fn status_error(status: StatusCode) -> OAuthError {
+        OAuthError::Status {
+            status,
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn status_errors_retry_only_on_rate_limit_and_server_errors() {
+        assert!(status_error(StatusCode::TOO_MANY_REQUESTS).is_retryable());
+        assert!(status_error(StatusCode::INTERNAL_SERVER_ERROR).is_retryable());
+        assert!(status_error(StatusCode::SERVICE_UNAVAILABLE).is_retryable());
+        assert!(!status_error(StatusCode::BAD_REQUEST).is_retryable());
+        assert!(!status_error(StatusCode::UNAUTHORIZED).is_retryable());
+        assert!(!status_error(StatusCode::FORBIDDEN).is_retryable());
+        assert!(!status_error(StatusCode::NOT_FOUND).is_retryable());
+    }
+
+    #[test]
+    fn url_errors_are_not_retryable() {
+        let err = OAuthError::from(Url::parse("not a url").expect_err("invalid url"));
+        assert!(!err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn connection_failures_are_retryable() {
+        let err = Client::new()
+            .get("http://127.0.0.1:1/")
+            .send()
+            .await
+            .expect_err("connection refused");
+        assert!(OAuthError::from(err).is_retryable());
+    }
 }
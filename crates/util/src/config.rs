@@ -1,8 +1,8 @@
-use std::{env, fmt, net::SocketAddr};
+use std::{collections::HashMap, env, fmt, net::SocketAddr};
 
 const DEV_SSE_TOKEN_HEX: &str = "6465762d7373652d7365637265742d6368616e67652d6d65";
 
-use super::server_bind_address;
+use super::DEFAULT_BIND_ADDR;
 
 /// Application runtime environment.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -42,6 +42,149 @@ impl Environment {
     }
 }
 
+/// EventSub delivery transport the webhook endpoint is subscribed with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventSubTransport {
+    /// One notification per request.
+    #[default]
+    Webhook,
+    /// Conduit shard delivery; notifications may arrive batched under `events`.
+    Conduit,
+}
+
+impl EventSubTransport {
+    fn from_str(value: &str) -> Result<Self, ConfigError> {
+        match value {
+            "webhook" => Ok(Self::Webhook),
+            "conduit" => Ok(Self::Conduit),
+            other => Err(ConfigError::InvalidValue(
+                "EVENTSUB_TRANSPORT".to_string(),
+                other.to_string(),
+            )),
+        }
+    }
+
+    /// Returns the canonical name used in configuration and logs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Webhook => "webhook",
+            Self::Conduit => "conduit",
+        }
+    }
+}
+
+/// Group of HTTP routes that can be switched off via `ENABLED_ENDPOINTS`.
+///
+/// `/healthz` and `/metrics` are not part of any group and are always mounted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointGroup {
+    /// `/_debug/*`.
+    Debug,
+    /// `/overlay/sse` and `/admin/sse`.
+    Sse,
+    /// `/api/state` and `/api/state/diff`.
+    State,
+    /// Read-only queue views: `/api/queue`, `/api/queue/served-today`, `/api/queue/export.csv`.
+    Queue,
+    /// `/api/queue/add` and `/api/queue/dequeue`.
+    QueueMutations,
+    /// `/api/settings/update` and `/api/settings/timezone`.
+    Settings,
+    /// `/api/counters/correct`.
+    Counters,
+    /// `/api/commands/noop`.
+    Commands,
+    /// `/eventsub/webhook`.
+    Eventsub,
+    /// `/oauth/login`, `/oauth/callback`, `/oauth2/validate` and `/api/oauth/validate-all`.
+    Oauth,
+}
+
+impl EndpointGroup {
+    /// Every group, in the order routes are mounted.
+    pub const ALL: [Self; 10] = [
+        Self::Debug,
+        Self::Sse,
+        Self::State,
+        Self::Queue,
+        Self::QueueMutations,
+        Self::Settings,
+        Self::Counters,
+        Self::Commands,
+        Self::Eventsub,
+        Self::Oauth,
+    ];
+
+    fn from_str(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|group| group.as_str() == value)
+    }
+
+    /// Returns the canonical name used in configuration and logs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Sse => "sse",
+            Self::State => "state",
+            Self::Queue => "queue",
+            Self::QueueMutations => "queue_mutations",
+            Self::Settings => "settings",
+            Self::Counters => "counters",
+            Self::Commands => "commands",
+            Self::Eventsub => "eventsub",
+            Self::Oauth => "oauth",
+        }
+    }
+}
+
+/// Twitch deployment the OAuth and Helix clients talk to by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TwitchEnv {
+    #[default]
+    Production,
+    /// Twitch CLI mock API (`twitch mock-api start`).
+    Mock,
+}
+
+impl TwitchEnv {
+    fn from_str(value: &str) -> Result<Self, ConfigError> {
+        match value {
+            "production" | "prod" => Ok(Self::Production),
+            "mock" => Ok(Self::Mock),
+            other => Err(ConfigError::InvalidValue(
+                "TWITCH_ENV".to_string(),
+                other.to_string(),
+            )),
+        }
+    }
+
+    /// Returns the canonical name used in configuration and logs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Production => "production",
+            Self::Mock => "mock",
+        }
+    }
+
+    /// Default OAuth base URL when `TWITCH_OAUTH_BASE_URL` is unset.
+    ///
+    /// The mock defaults expect `twitch mock-api start -p 8090`; the CLI's own default port
+    /// (8080) is taken by the app itself under `DEFAULT_BIND_ADDR`.
+    pub fn default_oauth_base_url(self) -> &'static str {
+        match self {
+            Self::Production => "https://id.twitch.tv/oauth2",
+            Self::Mock => "http://localhost:8090/auth",
+        }
+    }
+
+    /// Default Helix base URL when `TWITCH_API_BASE_URL` is unset.
+    pub fn default_api_base_url(self) -> &'static str {
+        match self {
+            Self::Production => "https://api.twitch.tv/helix",
+            Self::Mock => "http://localhost:8090/mock",
+        }
+    }
+}
+
 /// Runtime configuration resolved from environment variables.
 #[derive(Debug, Clone)]
 pub struct AppConfig {
@@ -51,28 +194,62 @@ pub struct AppConfig {
     pub webhook_secret: String,
     pub sse_token_signing_key: Vec<u8>,
     pub sse_heartbeat_secs: u64,
+    pub sse_heartbeat_text: String,
+    pub sse_heartbeat_event: Option<String>,
     pub sse_ring_max: usize,
     pub sse_ring_ttl_secs: u64,
     pub twitch_client_id: String,
     pub twitch_client_secret: String,
     pub oauth_redirect_uri: String,
+    pub twitch_env: TwitchEnv,
     pub twitch_oauth_base_url: String,
     pub twitch_api_base_url: String,
     pub oauth_state_ttl_secs: u64,
+    pub oauth_login_cooldown_secs: u64,
     pub helix_backfill_interval_secs: u64,
     pub helix_backfill_page_size: u32,
+    pub state_snapshot_max_queue: usize,
+    pub patch_coalesce_threshold: usize,
+    pub sse_single_use_audiences: Vec<String>,
+    pub sqlite_wal_autocheckpoint: Option<u32>,
+    pub sqlite_wal_checkpoint_pages: Option<u64>,
+    pub eventsub_transport: EventSubTransport,
+    pub eventsub_persist_types: Vec<String>,
+    pub policy_trace_capacity: usize,
+    pub sse_max_filter_types: usize,
+    pub sse_max_lag: usize,
+    pub enabled_endpoints: Vec<EndpointGroup>,
 }
 
 impl AppConfig {
     /// Constructs the configuration by reading and validating environment variables.
     pub fn from_env() -> Result<Self, ConfigError> {
-        let env_value = env::var("APP_ENV").unwrap_or_else(|_| "development".to_string());
+        Self::from_lookup(|key| env::var(key))
+    }
+
+    /// Same as [`Self::from_env`], with `overrides` taking precedence over the process
+    /// environment. The process environment itself is left untouched.
+    pub fn from_env_with_overrides(
+        overrides: &HashMap<String, String>,
+    ) -> Result<Self, ConfigError> {
+        Self::from_lookup(|key| match overrides.get(key) {
+            Some(value) => Ok(value.clone()),
+            None => env::var(key),
+        })
+    }
+
+    fn from_lookup(
+        var: impl Fn(&str) -> Result<String, env::VarError>,
+    ) -> Result<Self, ConfigError> {
+        let env_value = var("APP_ENV").unwrap_or_else(|_| "development".to_string());
         let environment = Environment::from_str(&env_value)?;
-        let bind_addr = server_bind_address().map_err(ConfigError::BindAddress)?;
-        let database_url =
-            env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://./dev.db".to_string());
+        let bind_addr = var("APP_BIND_ADDR")
+            .unwrap_or_else(|_| DEFAULT_BIND_ADDR.to_string())
+            .parse::<SocketAddr>()
+            .map_err(ConfigError::BindAddress)?;
+        let database_url = var("DATABASE_URL").unwrap_or_else(|_| "sqlite://./dev.db".to_string());
 
-        let webhook_secret = match env::var("WEBHOOK_SECRET") {
+        let webhook_secret = match var("WEBHOOK_SECRET") {
             Ok(value) if !value.is_empty() => value,
             Ok(_) => {
                 return Err(ConfigError::MissingEnvVar(
@@ -89,7 +266,7 @@ impl AppConfig {
             }
         };
 
-        let sse_token_signing_key = match env::var("SSE_TOKEN_SIGNING_KEY") {
+        let sse_token_signing_key = match var("SSE_TOKEN_SIGNING_KEY") {
             Ok(value) if !value.is_empty() => decode_hex(&value)?,
             Ok(_) => {
                 return Err(ConfigError::MissingEnvVar(
@@ -106,21 +283,43 @@ impl AppConfig {
             }
         };
 
-        let sse_heartbeat_secs = match env::var("SSE_HEARTBEAT_SECS") {
+        let sse_heartbeat_secs = match var("SSE_HEARTBEAT_SECS") {
             Ok(value) => value
                 .parse::<u64>()
                 .map_err(|_| ConfigError::InvalidNumber("SSE_HEARTBEAT_SECS".to_string(), value))?,
             Err(_) => 25,
         };
 
-        let sse_ring_max = match env::var("SSE_RING_MAX") {
+        let sse_heartbeat_text = match var("SSE_HEARTBEAT_TEXT") {
+            Ok(value) if value.contains(['\r', '\n']) => {
+                return Err(ConfigError::InvalidValue(
+                    "SSE_HEARTBEAT_TEXT".to_string(),
+                    value,
+                ));
+            }
+            Ok(value) => value,
+            Err(_) => "heartbeat".to_string(),
+        };
+
+        let sse_heartbeat_event = match var("SSE_HEARTBEAT_EVENT") {
+            Ok(value) if value.contains(['\r', '\n']) => {
+                return Err(ConfigError::InvalidValue(
+                    "SSE_HEARTBEAT_EVENT".to_string(),
+                    value,
+                ));
+            }
+            Ok(value) if !value.trim().is_empty() => Some(value.trim().to_string()),
+            _ => None,
+        };
+
+        let sse_ring_max = match var("SSE_RING_MAX") {
             Ok(value) => value
                 .parse::<usize>()
                 .map_err(|_| ConfigError::InvalidNumber("SSE_RING_MAX".to_string(), value))?,
             Err(_) => 1000,
         };
 
-        let sse_ring_ttl_secs = match env::var("SSE_RING_TTL_SECS") {
+        let sse_ring_ttl_secs = match var("SSE_RING_TTL_SECS") {
             Ok(value) => value
                 .parse::<u64>()
                 .map_err(|_| ConfigError::InvalidNumber("SSE_RING_TTL_SECS".to_string(), value))?,
@@ -128,10 +327,14 @@ impl AppConfig {
         };
 
         let twitch_client_id =
-            read_required_secret("TWITCH_CLIENT_ID", environment, "local-client-id")?;
-        let twitch_client_secret =
-            read_required_secret("TWITCH_CLIENT_SECRET", environment, "local-client-secret")?;
-        let oauth_redirect_uri = match env::var("OAUTH_REDIRECT_URI") {
+            read_required_secret(&var, "TWITCH_CLIENT_ID", environment, "local-client-id")?;
+        let twitch_client_secret = read_required_secret(
+            &var,
+            "TWITCH_CLIENT_SECRET",
+            environment,
+            "local-client-secret",
+        )?;
+        let oauth_redirect_uri = match var("OAUTH_REDIRECT_URI") {
             Ok(value) if !value.is_empty() => value,
             Ok(_) => {
                 return Err(ConfigError::MissingEnvVar(
@@ -148,32 +351,112 @@ impl AppConfig {
             }
         };
 
-        let twitch_oauth_base_url = env::var("TWITCH_OAUTH_BASE_URL")
-            .unwrap_or_else(|_| "https://id.twitch.tv/oauth2".to_string());
-        let twitch_api_base_url = env::var("TWITCH_API_BASE_URL")
-            .unwrap_or_else(|_| "https://api.twitch.tv/helix".to_string());
+        let twitch_env = match var("TWITCH_ENV") {
+            Ok(value) => TwitchEnv::from_str(&value)?,
+            Err(_) => TwitchEnv::default(),
+        };
+        let twitch_oauth_base_url = var("TWITCH_OAUTH_BASE_URL")
+            .unwrap_or_else(|_| twitch_env.default_oauth_base_url().to_string());
+        let twitch_api_base_url = var("TWITCH_API_BASE_URL")
+            .unwrap_or_else(|_| twitch_env.default_api_base_url().to_string());
 
-        let oauth_state_ttl_secs = match env::var("OAUTH_STATE_TTL_SECS") {
+        let oauth_state_ttl_secs = match var("OAUTH_STATE_TTL_SECS") {
             Ok(value) => value.parse::<u64>().map_err(|_| {
                 ConfigError::InvalidNumber("OAUTH_STATE_TTL_SECS".to_string(), value)
             })?,
             Err(_) => 600,
         };
 
-        let helix_backfill_interval_secs = match env::var("HELIX_BACKFILL_INTERVAL_SECS") {
+        let oauth_login_cooldown_secs = match var("OAUTH_LOGIN_COOLDOWN_SECS") {
+            Ok(value) => value.parse::<u64>().map_err(|_| {
+                ConfigError::InvalidNumber("OAUTH_LOGIN_COOLDOWN_SECS".to_string(), value)
+            })?,
+            Err(_) => 30,
+        };
+
+        let helix_backfill_interval_secs = match var("HELIX_BACKFILL_INTERVAL_SECS") {
             Ok(value) => value.parse::<u64>().map_err(|_| {
                 ConfigError::InvalidNumber("HELIX_BACKFILL_INTERVAL_SECS".to_string(), value)
             })?,
             Err(_) => 300,
         };
 
-        let helix_backfill_page_size = match env::var("HELIX_BACKFILL_PAGE_SIZE") {
+        let helix_backfill_page_size = match var("HELIX_BACKFILL_PAGE_SIZE") {
             Ok(value) => value.parse::<u32>().map_err(|_| {
                 ConfigError::InvalidNumber("HELIX_BACKFILL_PAGE_SIZE".to_string(), value)
             })?,
             Err(_) => 50,
         };
 
+        let state_snapshot_max_queue = match var("STATE_SNAPSHOT_MAX_QUEUE") {
+            Ok(value) => value.parse::<usize>().map_err(|_| {
+                ConfigError::InvalidNumber("STATE_SNAPSHOT_MAX_QUEUE".to_string(), value)
+            })?,
+            Err(_) => 500,
+        };
+
+        let patch_coalesce_threshold = match var("PATCH_COALESCE_THRESHOLD") {
+            Ok(value) => value.parse::<usize>().map_err(|_| {
+                ConfigError::InvalidNumber("PATCH_COALESCE_THRESHOLD".to_string(), value)
+            })?,
+            Err(_) => 0,
+        };
+
+        let sse_single_use_audiences = match var("SSE_SINGLE_USE_AUDIENCES") {
+            Ok(value) => parse_audience_list("SSE_SINGLE_USE_AUDIENCES", &value)?,
+            Err(_) => Vec::new(),
+        };
+
+        let sqlite_wal_autocheckpoint = match var("SQLITE_WAL_AUTOCHECKPOINT") {
+            Ok(value) => Some(value.parse::<u32>().map_err(|_| {
+                ConfigError::InvalidNumber("SQLITE_WAL_AUTOCHECKPOINT".to_string(), value)
+            })?),
+            Err(_) => None,
+        };
+
+        let sqlite_wal_checkpoint_pages = match var("SQLITE_WAL_CHECKPOINT_PAGES") {
+            Ok(value) => Some(value.parse::<u64>().map_err(|_| {
+                ConfigError::InvalidNumber("SQLITE_WAL_CHECKPOINT_PAGES".to_string(), value)
+            })?),
+            Err(_) => None,
+        };
+
+        let eventsub_transport = match var("EVENTSUB_TRANSPORT") {
+            Ok(value) => EventSubTransport::from_str(&value)?,
+            Err(_) => EventSubTransport::Webhook,
+        };
+
+        let eventsub_persist_types = match var("EVENTSUB_PERSIST_TYPES") {
+            Ok(value) => parse_list(&value),
+            Err(_) => Vec::new(),
+        };
+
+        let policy_trace_capacity = match var("POLICY_TRACE_CAPACITY") {
+            Ok(value) => value.parse::<usize>().map_err(|_| {
+                ConfigError::InvalidNumber("POLICY_TRACE_CAPACITY".to_string(), value)
+            })?,
+            Err(_) => 50,
+        };
+
+        let sse_max_filter_types = match var("SSE_MAX_FILTER_TYPES") {
+            Ok(value) => value.parse::<usize>().map_err(|_| {
+                ConfigError::InvalidNumber("SSE_MAX_FILTER_TYPES".to_string(), value)
+            })?,
+            Err(_) => 64,
+        };
+
+        let sse_max_lag = match var("SSE_MAX_LAG") {
+            Ok(value) => value
+                .parse::<usize>()
+                .map_err(|_| ConfigError::InvalidNumber("SSE_MAX_LAG".to_string(), value))?,
+            Err(_) => 128,
+        };
+
+        let enabled_endpoints = match var("ENABLED_ENDPOINTS") {
+            Ok(value) => parse_endpoint_groups("ENABLED_ENDPOINTS", &value)?,
+            Err(_) => EndpointGroup::ALL.to_vec(),
+        };
+
         Ok(Self {
             bind_addr,
             environment,
@@ -181,16 +464,31 @@ impl AppConfig {
             webhook_secret,
             sse_token_signing_key,
             sse_heartbeat_secs,
+            sse_heartbeat_text,
+            sse_heartbeat_event,
             sse_ring_max,
             sse_ring_ttl_secs,
             twitch_client_id,
             twitch_client_secret,
             oauth_redirect_uri,
+            twitch_env,
             twitch_oauth_base_url,
             twitch_api_base_url,
             oauth_state_ttl_secs,
+            oauth_login_cooldown_secs,
             helix_backfill_interval_secs,
             helix_backfill_page_size,
+            state_snapshot_max_queue,
+            patch_coalesce_threshold,
+            sse_single_use_audiences,
+            sqlite_wal_autocheckpoint,
+            sqlite_wal_checkpoint_pages,
+            eventsub_transport,
+            eventsub_persist_types,
+            policy_trace_capacity,
+            sse_max_filter_types,
+            sse_max_lag,
+            enabled_endpoints,
         })
     }
 }
@@ -203,6 +501,7 @@ pub enum ConfigError {
     MissingEnvVar(String),
     InvalidHex(String),
     InvalidNumber(String, String),
+    InvalidValue(String, String),
 }
 
 impl fmt::Display for ConfigError {
@@ -218,6 +517,7 @@ impl fmt::Display for ConfigError {
             Self::InvalidNumber(var, value) => {
                 write!(f, "{var} must be a valid number (got {value})")
             }
+            Self::InvalidValue(var, value) => write!(f, "{var} has an invalid value (got {value})"),
         }
     }
 }
@@ -228,12 +528,58 @@ fn decode_hex(value: &str) -> Result<Vec<u8>, ConfigError> {
     hex::decode(value).map_err(|_| ConfigError::InvalidHex(value.to_string()))
 }
 
+fn parse_list(value: &str) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for item in value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        if !items.iter().any(|existing| existing == item) {
+            items.push(item.to_string());
+        }
+    }
+    items
+}
+
+fn parse_audience_list(var: &str, value: &str) -> Result<Vec<String>, ConfigError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| match item {
+            "overlay" | "admin" => Ok(item.to_string()),
+            other => Err(ConfigError::InvalidValue(
+                var.to_string(),
+                other.to_string(),
+            )),
+        })
+        .collect()
+}
+
+fn parse_endpoint_groups(var: &str, value: &str) -> Result<Vec<EndpointGroup>, ConfigError> {
+    let mut groups = Vec::new();
+    for item in value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let group = EndpointGroup::from_str(item)
+            .ok_or_else(|| ConfigError::InvalidValue(var.to_string(), item.to_string()))?;
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    Ok(groups)
+}
+
 fn read_required_secret(
+    lookup: impl Fn(&str) -> Result<String, env::VarError>,
     var: &str,
     environment: Environment,
     dev_default: &str,
 ) -> Result<String, ConfigError> {
-    match env::var(var) {
+    match lookup(var) {
         Ok(value) if !value.is_empty() => Ok(value),
         Ok(_) => Err(ConfigError::MissingEnvVar(format!(
             "{var} must not be empty"
@@ -271,6 +617,8 @@ mod tests {
             decode_hex(DEV_SSE_TOKEN_HEX).unwrap()
         );
         assert_eq!(config.sse_heartbeat_secs, 25);
+        assert_eq!(config.sse_heartbeat_text, "heartbeat");
+        assert_eq!(config.sse_heartbeat_event, None);
         assert_eq!(config.sse_ring_max, 1000);
         assert_eq!(config.sse_ring_ttl_secs, 120);
         assert_eq!(config.twitch_client_id, "local-client-id");
@@ -279,11 +627,85 @@ mod tests {
             config.oauth_redirect_uri,
             "http://127.0.0.1:8080/oauth/callback"
         );
+        assert_eq!(config.twitch_env, TwitchEnv::Production);
         assert_eq!(config.twitch_oauth_base_url, "https://id.twitch.tv/oauth2");
         assert_eq!(config.twitch_api_base_url, "https://api.twitch.tv/helix");
         assert_eq!(config.oauth_state_ttl_secs, 600);
+        assert_eq!(config.oauth_login_cooldown_secs, 30);
         assert_eq!(config.helix_backfill_interval_secs, 300);
         assert_eq!(config.helix_backfill_page_size, 50);
+        assert_eq!(config.state_snapshot_max_queue, 500);
+        assert_eq!(config.patch_coalesce_threshold, 0);
+        assert!(config.sse_single_use_audiences.is_empty());
+        assert_eq!(config.sqlite_wal_autocheckpoint, None);
+        assert_eq!(config.sqlite_wal_checkpoint_pages, None);
+        assert_eq!(config.eventsub_transport, EventSubTransport::Webhook);
+        assert!(config.eventsub_persist_types.is_empty());
+        assert_eq!(config.policy_trace_capacity, 50);
+        assert_eq!(config.sse_max_filter_types, 64);
+        assert_eq!(config.sse_max_lag, 128);
+        assert_eq!(config.enabled_endpoints, EndpointGroup::ALL.to_vec());
+    }
+
+    #[test]
+    fn twitch_env_mock_sets_defaults_and_overrides_win() {
+        let _guard = test_support::env_vars_lock();
+        env::remove_var("APP_ENV");
+        env::set_var("TWITCH_ENV", "mock");
+        env::remove_var("TWITCH_OAUTH_BASE_URL");
+        env::remove_var("TWITCH_API_BASE_URL");
+
+        let config = AppConfig::from_env().expect("config should load");
+        assert_eq!(config.twitch_env, TwitchEnv::Mock);
+        assert_eq!(config.twitch_oauth_base_url, "http://localhost:8090/auth");
+        assert_eq!(config.twitch_api_base_url, "http://localhost:8090/mock");
+
+        let bind_port = format!(":{}/", DEFAULT_BIND_ADDR.rsplit(':').next().unwrap());
+        assert!(!config.twitch_oauth_base_url.contains(&bind_port));
+        assert!(!config.twitch_api_base_url.contains(&bind_port));
+
+        env::set_var("TWITCH_API_BASE_URL", "http://127.0.0.1:9999/mock");
+        let config = AppConfig::from_env().expect("config should load");
+        assert_eq!(config.twitch_oauth_base_url, "http://localhost:8090/auth");
+        assert_eq!(config.twitch_api_base_url, "http://127.0.0.1:9999/mock");
+
+        env::set_var("TWITCH_ENV", "staging");
+        let err = AppConfig::from_env().expect_err("unknown twitch env should error");
+        assert!(matches!(
+            err,
+            ConfigError::InvalidValue(var, value) if var == "TWITCH_ENV" && value == "staging"
+        ));
+
+        env::remove_var("TWITCH_ENV");
+        env::remove_var("TWITCH_API_BASE_URL");
+    }
+
+    #[test]
+    fn overrides_take_precedence_without_touching_process_env() {
+        let _guard = test_support::env_vars_lock();
+        env::remove_var("APP_ENV");
+        env::set_var("PATCH_COALESCE_THRESHOLD", "5");
+        env::remove_var("POLICY_TRACE_CAPACITY");
+
+        let overrides = HashMap::from([
+            ("PATCH_COALESCE_THRESHOLD".to_string(), "12".to_string()),
+            ("POLICY_TRACE_CAPACITY".to_string(), "7".to_string()),
+        ]);
+        let config = AppConfig::from_env_with_overrides(&overrides).expect("config should load");
+        assert_eq!(config.patch_coalesce_threshold, 12);
+        assert_eq!(config.policy_trace_capacity, 7);
+        assert_eq!(env::var("PATCH_COALESCE_THRESHOLD").as_deref(), Ok("5"));
+        assert!(env::var("POLICY_TRACE_CAPACITY").is_err());
+
+        let overrides = HashMap::from([("POLICY_TRACE_CAPACITY".to_string(), "x".to_string())]);
+        let err = AppConfig::from_env_with_overrides(&overrides)
+            .expect_err("invalid override should error");
+        assert!(matches!(
+            err,
+            ConfigError::InvalidNumber(var, value) if var == "POLICY_TRACE_CAPACITY" && value == "x"
+        ));
+
+        env::remove_var("PATCH_COALESCE_THRESHOLD");
     }
 
     #[test]
@@ -306,14 +728,33 @@ mod tests {
         env::set_var("WEBHOOK_SECRET", "prod-secret");
         env::set_var("SSE_TOKEN_SIGNING_KEY", "abcdef");
         env::set_var("SSE_HEARTBEAT_SECS", "30");
+        env::set_var("SSE_HEARTBEAT_TEXT", "ping");
+        env::set_var("SSE_HEARTBEAT_EVENT", "keepalive");
         env::set_var("SSE_RING_MAX", "512");
         env::set_var("SSE_RING_TTL_SECS", "180");
         env::set_var("TWITCH_CLIENT_ID", "prod-client");
         env::set_var("TWITCH_CLIENT_SECRET", "prod-secret");
         env::set_var("OAUTH_REDIRECT_URI", "https://example.com/oauth/callback");
+        env::set_var("TWITCH_ENV", "production");
+        env::set_var("TWITCH_OAUTH_BASE_URL", "https://id.example.test/oauth2");
         env::set_var("OAUTH_STATE_TTL_SECS", "900");
+        env::set_var("OAUTH_LOGIN_COOLDOWN_SECS", "45");
         env::set_var("HELIX_BACKFILL_INTERVAL_SECS", "120");
         env::set_var("HELIX_BACKFILL_PAGE_SIZE", "75");
+        env::set_var("STATE_SNAPSHOT_MAX_QUEUE", "250");
+        env::set_var("PATCH_COALESCE_THRESHOLD", "20");
+        env::set_var("SSE_SINGLE_USE_AUDIENCES", "overlay, admin");
+        env::set_var("SQLITE_WAL_AUTOCHECKPOINT", "2000");
+        env::set_var("SQLITE_WAL_CHECKPOINT_PAGES", "4000");
+        env::set_var("EVENTSUB_TRANSPORT", "conduit");
+        env::set_var(
+            "EVENTSUB_PERSIST_TYPES",
+            "channel.channel_points_custom_reward_redemption.add, stream.online,stream.online",
+        );
+        env::set_var("POLICY_TRACE_CAPACITY", "10");
+        env::set_var("SSE_MAX_FILTER_TYPES", "8");
+        env::set_var("SSE_MAX_LAG", "32");
+        env::set_var("ENABLED_ENDPOINTS", "sse, state,sse");
 
         let config = AppConfig::from_env().expect("config should load");
         assert_eq!(config.environment, Environment::Production);
@@ -322,6 +763,8 @@ mod tests {
         assert_eq!(config.webhook_secret, "prod-secret");
         assert_eq!(config.sse_token_signing_key, decode_hex("abcdef").unwrap());
         assert_eq!(config.sse_heartbeat_secs, 30);
+        assert_eq!(config.sse_heartbeat_text, "ping");
+        assert_eq!(config.sse_heartbeat_event.as_deref(), Some("keepalive"));
         assert_eq!(config.sse_ring_max, 512);
         assert_eq!(config.sse_ring_ttl_secs, 180);
         assert_eq!(config.twitch_client_id, "prod-client");
@@ -330,9 +773,36 @@ mod tests {
             config.oauth_redirect_uri,
             "https://example.com/oauth/callback"
         );
+        assert_eq!(config.twitch_env, TwitchEnv::Production);
+        assert_eq!(
+            config.twitch_oauth_base_url,
+            "https://id.example.test/oauth2"
+        );
+        assert_eq!(config.twitch_api_base_url, "https://api.twitch.tv/helix");
         assert_eq!(config.oauth_state_ttl_secs, 900);
+        assert_eq!(config.oauth_login_cooldown_secs, 45);
         assert_eq!(config.helix_backfill_interval_secs, 120);
         assert_eq!(config.helix_backfill_page_size, 75);
+        assert_eq!(config.state_snapshot_max_queue, 250);
+        assert_eq!(config.patch_coalesce_threshold, 20);
+        assert_eq!(config.sse_single_use_audiences, vec!["overlay", "admin"]);
+        assert_eq!(config.sqlite_wal_autocheckpoint, Some(2000));
+        assert_eq!(config.sqlite_wal_checkpoint_pages, Some(4000));
+        assert_eq!(config.eventsub_transport, EventSubTransport::Conduit);
+        assert_eq!(
+            config.eventsub_persist_types,
+            vec![
+                "channel.channel_points_custom_reward_redemption.add".to_string(),
+                "stream.online".to_string(),
+            ]
+        );
+        assert_eq!(config.policy_trace_capacity, 10);
+        assert_eq!(config.sse_max_filter_types, 8);
+        assert_eq!(config.sse_max_lag, 32);
+        assert_eq!(
+            config.enabled_endpoints,
+            vec![EndpointGroup::Sse, EndpointGroup::State]
+        );
 
         env::remove_var("APP_ENV");
         env::remove_var("APP_BIND_ADDR");
@@ -340,14 +810,30 @@ mod tests {
         env::remove_var("WEBHOOK_SECRET");
         env::remove_var("SSE_TOKEN_SIGNING_KEY");
         env::remove_var("SSE_HEARTBEAT_SECS");
+        env::remove_var("SSE_HEARTBEAT_TEXT");
+        env::remove_var("SSE_HEARTBEAT_EVENT");
         env::remove_var("SSE_RING_MAX");
         env::remove_var("SSE_RING_TTL_SECS");
         env::remove_var("TWITCH_CLIENT_ID");
         env::remove_var("TWITCH_CLIENT_SECRET");
         env::remove_var("OAUTH_REDIRECT_URI");
+        env::remove_var("TWITCH_ENV");
+        env::remove_var("TWITCH_OAUTH_BASE_URL");
         env::remove_var("OAUTH_STATE_TTL_SECS");
+        env::remove_var("OAUTH_LOGIN_COOLDOWN_SECS");
         env::remove_var("HELIX_BACKFILL_INTERVAL_SECS");
         env::remove_var("HELIX_BACKFILL_PAGE_SIZE");
+        env::remove_var("STATE_SNAPSHOT_MAX_QUEUE");
+        env::remove_var("PATCH_COALESCE_THRESHOLD");
+        env::remove_var("SSE_SINGLE_USE_AUDIENCES");
+        env::remove_var("SQLITE_WAL_AUTOCHECKPOINT");
+        env::remove_var("SQLITE_WAL_CHECKPOINT_PAGES");
+        env::remove_var("EVENTSUB_TRANSPORT");
+        env::remove_var("EVENTSUB_PERSIST_TYPES");
+        env::remove_var("POLICY_TRACE_CAPACITY");
+        env::remove_var("SSE_MAX_FILTER_TYPES");
+        env::remove_var("SSE_MAX_LAG");
+        env::remove_var("ENABLED_ENDPOINTS");
     }
 
     #[test]
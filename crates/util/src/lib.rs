@@ -1,8 +1,10 @@
 pub mod config;
 
-use std::{env, net::SocketAddr};
+use std::{collections::HashMap, env, net::SocketAddr, path::Path};
 
-pub use config::{AppConfig, ConfigError, Environment};
+pub use config::{
+    AppConfig, ConfigError, EndpointGroup, Environment, EventSubTransport, TwitchEnv,
+};
 
 pub const DEFAULT_BIND_ADDR: &str = "127.0.0.1:8080";
 
@@ -25,6 +27,23 @@ pub fn load_env_file() {
     let _ = dotenvy::dotenv();
 }
 
+/// Re-reads `.env` into a map without modifying the process environment.
+///
+/// Used on SIGHUP so edits to the dotenv file take effect without a restart; feed the map to
+/// [`AppConfig::from_env_with_overrides`]. Missing or unreadable files yield an empty map.
+pub fn read_env_file() -> HashMap<String, String> {
+    dotenvy::dotenv_iter()
+        .map(|iter| iter.filter_map(Result::ok).collect())
+        .unwrap_or_default()
+}
+
+/// Same as [`read_env_file`] for an explicit dotenv path.
+pub fn read_env_path(path: impl AsRef<Path>) -> HashMap<String, String> {
+    dotenvy::from_path_iter(path)
+        .map(|iter| iter.filter_map(Result::ok).collect())
+        .unwrap_or_default()
+}
+
 /// Returns the address the HTTP server should bind to.
 ///
 /// The value is resolved from the `APP_BIND_ADDR` environment variable and
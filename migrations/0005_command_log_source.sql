@@ -0,0 +1,2 @@
+-- 0005_command_log_source.sql -- Record which CommandSource produced each command log row
+ALTER TABLE command_log ADD COLUMN source TEXT CHECK(source IN ('policy','admin'));
@@ -0,0 +1,10 @@
+-- 0006_sse_token_uses.sql -- Markers for single-use SSE tokens, kept until the token expires
+CREATE TABLE sse_token_uses (
+  jti TEXT PRIMARY KEY,
+  broadcaster_id TEXT NOT NULL REFERENCES broadcasters(id) ON DELETE CASCADE,
+  audience TEXT NOT NULL CHECK(audience IN ('overlay','admin')),
+  used_at TEXT NOT NULL,
+  expires_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_sse_token_uses_expires ON sse_token_uses(expires_at);
@@ -0,0 +1,28 @@
+-- 0007_queue_enqueued_source.sql -- Record how each queue entry was enqueued; allow backfill as a command source
+ALTER TABLE queue_entries ADD COLUMN enqueued_source TEXT NOT NULL DEFAULT 'policy'
+  CHECK(enqueued_source IN ('policy','backfill','manual'));
+
+UPDATE queue_entries SET enqueued_source = 'manual' WHERE reward_id = 'manual';
+
+-- SQLite cannot alter a CHECK constraint in place, so command_log is rebuilt.
+CREATE TABLE command_log_new (
+  broadcaster_id TEXT NOT NULL REFERENCES broadcasters(id) ON DELETE CASCADE,
+  version INTEGER NOT NULL,
+  op_id TEXT NULL,
+  type TEXT NOT NULL,
+  payload_json TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  source TEXT CHECK(source IN ('policy','admin','backfill')),
+  PRIMARY KEY (broadcaster_id, version)
+);
+
+INSERT INTO command_log_new (broadcaster_id, version, op_id, type, payload_json, created_at, source)
+  SELECT broadcaster_id, version, op_id, type, payload_json, created_at, source FROM command_log;
+
+DROP TABLE command_log;
+ALTER TABLE command_log_new RENAME TO command_log;
+
+CREATE UNIQUE INDEX ux_command_op_id
+  ON command_log(broadcaster_id, op_id)
+  WHERE op_id IS NOT NULL;
+CREATE INDEX ix_command_created_at ON command_log(created_at);
@@ -0,0 +1,2 @@
+-- 0008_queue_reward_title.sql -- Keep the reward title on queue entries for title-targeted ownership checks
+ALTER TABLE queue_entries ADD COLUMN reward_title TEXT;
@@ -98,6 +98,61 @@ describe('shared state helpers', () => {
     expect(next.queue[0].id).toBe('entry-9');
   });
 
+  it('unrolls patch.batch across consecutive versions', () => {
+    const state = createClientState(baseSnapshot);
+    const patch: Patch = {
+      type: 'patch.batch',
+      version: 12,
+      at: '2024-01-01T10:10:00Z',
+      data: {
+        from_version: 11,
+        patches: [
+          {
+            type: 'queue.enqueued',
+            version: 11,
+            at: '2024-01-01T10:10:00Z',
+            data: {
+              entry: makeEntry('entry-3', 'user-3', '2024-01-01T10:10:00Z'),
+              user_today_count: 0,
+            },
+          },
+          {
+            type: 'queue.completed',
+            version: 12,
+            at: '2024-01-01T10:10:00Z',
+            data: { entry_id: 'entry-1' },
+          },
+        ],
+      },
+    };
+
+    const next = applyPatch(state, patch);
+    expect(next.version).toBe(12);
+    expect(next.queue.map((entry) => entry.id)).toEqual(['entry-3', 'entry-2']);
+  });
+
+  it('rejects a batch that does not continue from the current version', () => {
+    const state = createClientState(baseSnapshot);
+    const patch: Patch = {
+      type: 'patch.batch',
+      version: 14,
+      at: '2024-01-01T10:10:00Z',
+      data: {
+        from_version: 13,
+        patches: [
+          {
+            type: 'queue.completed',
+            version: 13,
+            at: '2024-01-01T10:10:00Z',
+            data: { entry_id: 'entry-1' },
+          },
+        ],
+      },
+    };
+
+    expect(() => applyPatch(state, patch)).toThrow(VersionMismatchError);
+  });
+
   it('merges nested policy settings on settings.updated', () => {
     const state = createClientState(baseSnapshot);
     const patchPayload: SettingsPatch = {
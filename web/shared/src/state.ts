@@ -37,6 +37,11 @@ export function applyPatch(state: ClientState, patch: Patch): ClientState {
     return createClientState(patch.data.state);
   }
 
+  // Coalesced patches carry consecutive versions; apply them one by one.
+  if (patch.type === 'patch.batch') {
+    return patch.data.patches.reduce(applyPatch, state);
+  }
+
   const expected = state.version + 1;
   if (patch.version !== expected) {
     throw new VersionMismatchError(expected, patch.version);
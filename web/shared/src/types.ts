@@ -8,6 +8,7 @@ export interface QueueEntry {
   user_display_name: string;
   user_avatar?: string;
   reward_id: string;
+  reward_title?: string;
   redemption_id?: string;
   enqueued_at: string;
   status: QueueEntryStatus;
@@ -122,6 +123,16 @@ export interface StateReplacePatch {
   };
 }
 
+export interface BatchPatch {
+  type: 'patch.batch';
+  version: number;
+  at: string;
+  data: {
+    from_version: number;
+    patches: Patch[];
+  };
+}
+
 export type Patch =
   | QueueEnqueuedPatch
   | QueueRemovedPatch
@@ -129,4 +140,5 @@ export type Patch =
   | CounterUpdatedPatch
   | SettingsUpdatedPatch
   | RedemptionUpdatedPatch
-  | StateReplacePatch;
+  | StateReplacePatch
+  | BatchPatch;